@@ -0,0 +1,16 @@
+//! Code generation from aski schemas.
+//!
+//! [`generate_rust`] emits one serde-derived Rust type per declaration, reproducing the
+//! type universe aski schemas describe, and one Rust impl per impl of a known trait;
+//! [`generate_rust_item`] and [`rust_type`] spell out a single declaration or type
+//! reference the same way. [`generate_rust_modules`] emits a schema spread over several
+//! modules as one Rust module each. [`check_names`] reports schema names that cannot be
+//! spelled faithfully in Rust and should be consulted before generating.
+
+mod naming;
+mod rust;
+
+pub use crate::naming::{
+    check_names, from_rust_field, from_rust_type, to_rust_field, to_rust_type,
+};
+pub use crate::rust::{generate_rust, generate_rust_item, generate_rust_modules, rust_type};
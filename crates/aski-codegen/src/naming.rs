@@ -0,0 +1,233 @@
+// Spelling schema names as Rust identifiers, and back.
+//
+// Schema names are kebab-case: lowercase words of letters and digits joined by single
+// hyphens. On those the mapping is reversible:
+//
+//   fields       `bool-value`      <-> `bool_value`
+//   types        `user-id`         <-> `UserId`
+//   variants     `not-found`       <-> `NotFound`
+//
+// A word starting with a digit has no capital letter to mark where it begins, so it is
+// joined with an underscore instead: `len-3` <-> `Len_3`. Fields that are Rust keywords
+// become raw identifiers: `type` <-> `r#type`.
+//
+// Two different schema names can still map to the same identifier (`user-id` and
+// `user_id`), and some identifiers cannot be written at all (`self`); `check_names`
+// reports both.
+
+use std::collections::{HashMap, HashSet};
+
+use aski_sema::{Item, ItemKind, Name, Schema, VariantBody};
+use aski_syntax::{kebab_case, pascal_case, Diagnostic};
+
+/// Rust keywords, strict and reserved, as of edition 2024.
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "try", "type",
+    "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Keywords that cannot be raw identifiers either.
+const UNESCAPABLE: &[&str] = &["crate", "self", "Self", "super"];
+
+/// Type names the generated code itself refers to; a schema type must not shadow them.
+const GENERATED_CODE_NAMES: &[&str] = &[
+    "BTreeMap",
+    "BTreeSet",
+    "Box",
+    "Default",
+    "Deserialize",
+    "Option",
+    "Result",
+    "Serialize",
+    "String",
+    "TryFrom",
+    "Uuid",
+    "Vec",
+];
+
+fn is_rust_keyword(ident: &str) -> bool {
+    KEYWORDS.contains(&ident)
+}
+
+/// Whether `name` is canonical kebab-case, the form the mapping round-trips.
+fn is_kebab_case(name: &str) -> bool {
+    !name.is_empty()
+        && name.split('-').all(|word| {
+            !word.is_empty()
+                && word
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+        })
+}
+
+/// `user-id` -> `UserId`, `len-3` -> `Len_3`.
+pub fn to_rust_type(name: &str) -> String {
+    pascal_case(name)
+}
+
+/// `bool-value` -> `bool_value`, `type` -> `r#type`.
+pub fn to_rust_field(name: &str) -> String {
+    let ident = name.replace('-', "_");
+    if is_rust_keyword(&ident) && !UNESCAPABLE.contains(&ident.as_str()) {
+        format!("r#{ident}")
+    } else {
+        ident
+    }
+}
+
+/// `UserId` -> `user-id`, `Len_3` -> `len-3`, `HTTPServer` -> `http-server`.
+pub fn from_rust_type(ident: &str) -> String {
+    kebab_case(ident)
+}
+
+/// `bool_value` -> `bool-value`, `r#type` -> `type`.
+pub fn from_rust_field(ident: &str) -> String {
+    ident.trim_start_matches("r#").replace('_', "-")
+}
+
+/// Reports schema names that have no faithful Rust spelling.
+///
+/// Names are checked within the scope Rust sees them in: type names across the whole
+/// schema, variant names per enum and field names per record or struct variant.
+pub fn check_names(schema: &Schema) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    for name in all_names(schema) {
+        if !is_kebab_case(&name.text) {
+            diagnostics.push(Diagnostic::warning(
+                format!("`{name}` is not kebab-case and will not round-trip through Rust"),
+                name.range,
+            ));
+        }
+    }
+
+    let mut types = Scope::new("type", to_rust_type);
+    for reserved in GENERATED_CODE_NAMES {
+        types.reserve(reserved);
+    }
+    for item in &schema.items {
+        types.declare(&item.name, &mut diagnostics);
+    }
+
+    for item in &schema.items {
+        check_params(item, &types, &mut diagnostics);
+        match &item.kind {
+            ItemKind::Record(fields) => {
+                check_fields(fields.iter().map(|f| &f.name), &mut diagnostics)
+            }
+            ItemKind::Enum(variants) => {
+                let mut scope = Scope::new("variant", to_rust_type);
+                for variant in variants {
+                    scope.declare(&variant.name, &mut diagnostics);
+                    if let VariantBody::Struct(fields) = &variant.body {
+                        check_fields(fields.iter().map(|f| &f.name), &mut diagnostics);
+                    }
+                }
+            }
+            ItemKind::Newtype(_) | ItemKind::Tuple(_) => {}
+        }
+    }
+
+    diagnostics
+}
+
+fn all_names(schema: &Schema) -> Vec<&Name> {
+    let mut names = Vec::new();
+    for item in &schema.items {
+        names.push(&item.name);
+        match &item.kind {
+            ItemKind::Record(fields) => names.extend(fields.iter().map(|field| &field.name)),
+            ItemKind::Enum(variants) => {
+                for variant in variants {
+                    names.push(&variant.name);
+                    if let VariantBody::Struct(fields) = &variant.body {
+                        names.extend(fields.iter().map(|field| &field.name));
+                    }
+                }
+            }
+            ItemKind::Newtype(_) | ItemKind::Tuple(_) => {}
+        }
+    }
+    names
+}
+
+fn check_fields<'a>(names: impl Iterator<Item = &'a Name>, diagnostics: &mut Vec<Diagnostic>) {
+    let mut scope = Scope::new("field", to_rust_field);
+    for name in names {
+        scope.declare(name, diagnostics);
+    }
+}
+
+/// Type parameters are emitted verbatim, so they only clash with declared types.
+fn check_params(item: &Item, types: &Scope, diagnostics: &mut Vec<Diagnostic>) {
+    for param in &item.params {
+        if let Some(declared) = types.seen.get(&param.text) {
+            diagnostics.push(Diagnostic::error(
+                format!("type parameter `{param}` collides with {declared} in Rust"),
+                param.range,
+            ));
+        }
+    }
+}
+
+/// One Rust namespace, remembering which schema name claimed each identifier.
+struct Scope {
+    what: &'static str,
+    convert: fn(&str) -> String,
+    /// Rust identifier -> description of its first claimant.
+    seen: HashMap<String, String>,
+    /// Schema names declared so far; exact duplicates are the resolver's to report.
+    declared: HashSet<String>,
+}
+
+impl Scope {
+    fn new(what: &'static str, convert: fn(&str) -> String) -> Scope {
+        Scope {
+            what,
+            convert,
+            seen: HashMap::new(),
+            declared: HashSet::new(),
+        }
+    }
+
+    fn reserve(&mut self, ident: &str) {
+        self.seen.insert(
+            ident.to_string(),
+            format!("the `{ident}` used by generated code"),
+        );
+    }
+
+    fn declare(&mut self, name: &Name, diagnostics: &mut Vec<Diagnostic>) {
+        if !self.declared.insert(name.text.clone()) {
+            return;
+        }
+        let what = self.what;
+        let ident = (self.convert)(&name.text);
+        let bare = ident.trim_start_matches("r#");
+        if UNESCAPABLE.contains(&bare) {
+            diagnostics.push(Diagnostic::error(
+                format!(
+                    "{what} `{name}` would become `{bare}`, which Rust does not allow as a name"
+                ),
+                name.range,
+            ));
+        } else if ident.starts_with("r#") {
+            diagnostics.push(Diagnostic::warning(
+                format!("{what} `{name}` is a Rust keyword and is generated as `{ident}`"),
+                name.range,
+            ));
+        }
+        match self.seen.get(&ident) {
+            Some(first) => diagnostics.push(Diagnostic::error(
+                format!("{what} `{name}` becomes `{ident}` in Rust, colliding with {first}"),
+                name.range,
+            )),
+            None => {
+                self.seen.insert(ident, format!("{what} `{name}`"));
+            }
+        }
+    }
+}
@@ -0,0 +1,758 @@
+// Rust source generation.
+//
+// Every declaration becomes one Rust item deriving serde's traits. Which comparison
+// traits an item derives is decided by the schema, not per declaration: everything gets
+// `PartialEq`, `Eq` unless a float is reachable from it, and `PartialOrd + Ord + Hash` if
+// it is reachable from a map key or set element.
+//
+// Generic items derive the same way; the derives bound each parameter by the trait being
+// derived. Serde infers `T: Deserialize<'de>` for each parameter, which is not enough when
+// a parameter ends up inside a map key or set element: deserializing a `BTreeSet<T>`
+// needs `T: Ord` too. Such items get an explicit deserialize bound.
+//
+// A schema spread over several modules becomes one Rust module per aski module, side by
+// side, so a qualified reference, `stuff/user`, is spelled `super::stuff::User`. The
+// traits are decided across all of them, since a type can reach a float or a map key
+// through another module's declarations.
+//
+// Impls follow the declarations, one Rust impl each, with the function body written
+// inside the method the trait calls for: `from-str` gets the text as `s`, `try-from` its
+// argument as `value`, and a `display` body makes the text `fmt` writes out. A body still
+// `TODO` becomes `todo!()`, and the parameter it leaves unused is named `_s` or `_value`.
+//
+// A record field with a default is filled in by a function returning it when the field
+// is missing from the input, named after the record and the field. A record whose every
+// field has a default also implements `Default` with them, unless the document impls
+// `default` for it itself.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt::Write;
+
+use aski_sema::{
+    hex_bytes, ordered_params, Field, Item, ItemKind, KnownTrait, ModuleFile, Modules, Name,
+    Primitive, Schema, TypeKind, TypeRef, ValueExpr, ValueKind, ValueTag, VariantBody,
+    ENUM_CONTENT, ENUM_TAG,
+};
+
+use crate::naming::{to_rust_field, to_rust_type};
+
+/// Generates a Rust module declaring every type of `schema`.
+pub fn generate_rust(schema: &Schema) -> String {
+    let traits = TraitAnalysis::new(schema);
+    let mut out = String::new();
+    write_imports(&mut out, schema);
+    for item in &schema.items {
+        out.push('\n');
+        write_item(&mut out, item, &traits, &item.name.text);
+    }
+    write_defaults(&mut out, schema);
+    write_impls(&mut out, schema);
+    out
+}
+
+/// Generates a Rust module for each module of `modules`, declaring every type of its
+/// document, in the order they are loaded.
+pub fn generate_rust_modules(modules: &Modules) -> String {
+    let everything = Schema {
+        items: modules.files.iter().flat_map(qualified_items).collect(),
+        ..Schema::default()
+    };
+    let traits = TraitAnalysis::new(&everything);
+    let mut out = String::new();
+    for (index, file) in modules.files.iter().enumerate() {
+        let mut body = String::new();
+        write_imports(&mut body, &file.schema);
+        for item in &file.schema.items {
+            body.push('\n');
+            let key = format!("{}/{}", file.name, item.name);
+            write_item(&mut body, item, &traits, &key);
+        }
+        write_defaults(&mut body, &file.schema);
+        write_impls(&mut body, &file.schema);
+        if index > 0 {
+            out.push('\n');
+        }
+        writeln!(out, "pub mod {} {{", to_rust_field(&file.name)).unwrap();
+        for line in body.lines() {
+            if line.is_empty() {
+                out.push('\n');
+            } else {
+                writeln!(out, "    {line}").unwrap();
+            }
+        }
+        out.push_str("}\n");
+    }
+    out
+}
+
+/// The declarations of `file` under their qualified names, referring to each other by
+/// them too, so the declarations of every module can be analysed together.
+fn qualified_items(file: &ModuleFile) -> Vec<Item> {
+    let qualify = |name: &mut String, params: &[Name]| {
+        let is_param = params.iter().any(|param| &param.text == name);
+        if !is_param && file.schema.item(name).is_some() {
+            *name = format!("{}/{name}", file.name);
+        }
+    };
+    file.schema
+        .items
+        .iter()
+        .map(|item| {
+            let mut item = item.clone();
+            let params = item.params.clone();
+            qualify(&mut item.name.text, &[]);
+            let mut stack = item.type_refs_mut();
+            while let Some(ty) = stack.pop() {
+                if let TypeKind::Named { name, .. } = &mut ty.kind {
+                    qualify(name, &params);
+                }
+                stack.extend(ty.children_mut());
+            }
+            item
+        })
+        .collect()
+}
+
+/// The Rust item [`generate_rust`] emits for the declaration `name` of `schema`.
+pub fn generate_rust_item(schema: &Schema, name: &str) -> Option<String> {
+    let item = schema.item(name)?;
+    let mut out = String::new();
+    write_item(&mut out, item, &TraitAnalysis::new(schema), name);
+    Some(out)
+}
+
+fn write_imports(out: &mut String, schema: &Schema) {
+    let mut collections = BTreeSet::new();
+    let mut uses_uuid = false;
+    for ty in schema
+        .items
+        .iter()
+        .flat_map(Item::type_refs)
+        .flat_map(TypeRef::walk)
+    {
+        match &ty.kind {
+            TypeKind::Map(..) => {
+                collections.insert("BTreeMap");
+            }
+            TypeKind::Set(_) => {
+                collections.insert("BTreeSet");
+            }
+            TypeKind::Named { name, .. } if Primitive::from_name(name) == Some(Primitive::Uuid) => {
+                uses_uuid = true;
+            }
+            _ => {}
+        }
+    }
+
+    out.push_str("use serde::{Deserialize, Serialize};\n");
+    match collections.len() {
+        0 => {}
+        1 => writeln!(
+            out,
+            "use std::collections::{};",
+            collections.first().unwrap()
+        )
+        .unwrap(),
+        _ => {
+            let list: Vec<_> = collections.into_iter().collect();
+            writeln!(out, "use std::collections::{{{}}};", list.join(", ")).unwrap();
+        }
+    }
+    if uses_uuid {
+        out.push_str("use uuid::Uuid;\n");
+    }
+}
+
+/// Writes `item`, known to `traits` as `key`.
+fn write_item(out: &mut String, item: &Item, traits: &TraitAnalysis, key: &str) {
+    let mut derives = vec!["Debug", "Clone", "PartialEq"];
+    if !traits.has_float(key) {
+        derives.push("Eq");
+        if traits.is_ordered(key) {
+            derives.extend(["PartialOrd", "Ord", "Hash"]);
+        }
+    }
+    derives.extend(["Serialize", "Deserialize"]);
+    let name = to_rust_type(&item.name.text);
+    // Words starting with a digit are joined with an underscore, see `naming`.
+    let variant_names: Vec<String> = match &item.kind {
+        ItemKind::Enum(variants) => variants
+            .iter()
+            .map(|v| to_rust_type(&v.name.text))
+            .collect(),
+        _ => Vec::new(),
+    };
+    if name.contains('_') || variant_names.iter().any(|variant| variant.contains('_')) {
+        out.push_str("#[allow(non_camel_case_types)]\n");
+    }
+    writeln!(out, "#[derive({})]", derives.join(", ")).unwrap();
+    if let Some(bound) = traits.deserialize_bound(item, key) {
+        writeln!(out, r#"#[serde(bound(deserialize = "{bound}"))]"#).unwrap();
+    }
+
+    let generics = if item.params.is_empty() {
+        String::new()
+    } else {
+        let params: Vec<_> = item
+            .params
+            .iter()
+            .map(|param| param.text.as_str())
+            .collect();
+        format!("<{}>", params.join(", "))
+    };
+    let ty = |ty: &TypeRef| rust_type(ty, item);
+
+    match &item.kind {
+        ItemKind::Newtype(inner) => {
+            writeln!(out, "pub struct {name}{generics}(pub {});", ty(inner)).unwrap();
+        }
+        ItemKind::Record(fields) if fields.is_empty() => {
+            writeln!(out, "pub struct {name}{generics};").unwrap();
+        }
+        ItemKind::Record(fields) => {
+            writeln!(out, "pub struct {name}{generics} {{").unwrap();
+            for field in fields {
+                if field.default.is_some() {
+                    writeln!(
+                        out,
+                        r#"    #[serde(default = "{}")]"#,
+                        default_fn(item, field)
+                    )
+                    .unwrap();
+                }
+                writeln!(
+                    out,
+                    "    pub {}: {},",
+                    to_rust_field(&field.name.text),
+                    ty(&field.ty)
+                )
+                .unwrap();
+            }
+            out.push_str("}\n");
+        }
+        ItemKind::Tuple(types) => {
+            let fields: Vec<_> = types.iter().map(|t| format!("pub {}", ty(t))).collect();
+            writeln!(out, "pub struct {name}{generics}({});", fields.join(", ")).unwrap();
+        }
+        ItemKind::Enum(variants) => {
+            // The canonical envelope; no other enum representation is ever generated.
+            writeln!(
+                out,
+                r#"#[serde(tag = "{ENUM_TAG}", content = "{ENUM_CONTENT}")]"#
+            )
+            .unwrap();
+            writeln!(out, "pub enum {name}{generics} {{").unwrap();
+            for variant in variants {
+                let variant_name = to_rust_type(&variant.name.text);
+                match &variant.body {
+                    VariantBody::Unit => writeln!(out, "    {variant_name},"),
+                    VariantBody::Newtype(inner) => {
+                        writeln!(out, "    {variant_name}({}),", ty(inner))
+                    }
+                    VariantBody::Tuple(types) => {
+                        let types: Vec<_> = types.iter().map(ty).collect();
+                        writeln!(out, "    {variant_name}({}),", types.join(", "))
+                    }
+                    VariantBody::Struct(fields) => {
+                        let fields: Vec<_> = fields
+                            .iter()
+                            .map(|field| {
+                                format!("{}: {}", to_rust_field(&field.name.text), ty(&field.ty))
+                            })
+                            .collect();
+                        writeln!(out, "    {variant_name} {{ {} }},", fields.join(", "))
+                    }
+                }
+                .unwrap();
+            }
+            out.push_str("}\n");
+        }
+    }
+}
+
+/// Writes the functions returning the defaults of record fields, and `Default` for each
+/// record with a default for every field.
+fn write_defaults(out: &mut String, schema: &Schema) {
+    for item in &schema.items {
+        let ItemKind::Record(fields) = &item.kind else {
+            continue;
+        };
+        let generics = if item.params.is_empty() {
+            String::new()
+        } else {
+            let params: Vec<_> = item
+                .params
+                .iter()
+                .map(|param| param.text.as_str())
+                .collect();
+            format!("<{}>", params.join(", "))
+        };
+        for field in fields {
+            let Some(default) = &field.default else {
+                continue;
+            };
+            writeln!(
+                out,
+                "\nfn {}{generics}() -> {} {{\n    {}\n}}",
+                default_fn(item, field),
+                rust_type(&field.ty, item),
+                rust_value(schema, default, &field.ty)
+            )
+            .unwrap();
+        }
+        let implemented = schema.impls.iter().any(|imp| {
+            imp.trait_name.text == "default"
+                && matches!(&imp.self_ty.kind, TypeKind::Named { name, .. } if *name == item.name.text)
+        });
+        if fields.is_empty() || implemented || fields.iter().any(|f| f.default.is_none()) {
+            continue;
+        }
+        let name = to_rust_type(&item.name.text);
+        writeln!(
+            out,
+            "\nimpl{generics} Default for {name}{generics} {{\n    fn default() -> Self {{\n        {name} {{"
+        )
+        .unwrap();
+        for field in fields {
+            writeln!(
+                out,
+                "            {}: {}(),",
+                to_rust_field(&field.name.text),
+                default_fn(item, field)
+            )
+            .unwrap();
+        }
+        out.push_str("        }\n    }\n}\n");
+    }
+}
+
+/// The name of the function returning the default of `field` of `item`.
+fn default_fn(item: &Item, field: &Field) -> String {
+    to_rust_field(&format!("default-{}-{}", item.name, field.name))
+}
+
+/// The Rust expression for `value`, a value of `ty` as `check_defaults` accepts it.
+fn rust_value(schema: &Schema, value: &ValueExpr, ty: &TypeRef) -> String {
+    let each = |values: &[ValueExpr], ty: &TypeRef| -> Vec<String> {
+        values
+            .iter()
+            .map(|value| rust_value(schema, value, ty))
+            .collect()
+    };
+    let zipped = |values: &[ValueExpr], types: &[TypeRef]| -> Vec<String> {
+        values
+            .iter()
+            .zip(types)
+            .map(|(value, ty)| rust_value(schema, value, ty))
+            .collect()
+    };
+    match (&ty.kind, &value.kind) {
+        (TypeKind::Option(_), ValueKind::None) => "None".to_string(),
+        (TypeKind::Option(inner), _) => format!("Some({})", rust_value(schema, value, inner)),
+        (TypeKind::Box(inner), _) => {
+            format!("Box::new({})", rust_value(schema, value, inner))
+        }
+        (TypeKind::Result(ok, err), ValueKind::Tagged { tag, value }) => match tag {
+            ValueTag::Err => format!("Err({})", rust_value(schema, value, err)),
+            _ => format!("Ok({})", rust_value(schema, value, ok)),
+        },
+        (TypeKind::Vec(_), ValueKind::List(elements)) if elements.is_empty() => {
+            "Vec::new()".to_string()
+        }
+        (TypeKind::Vec(element), ValueKind::List(elements)) => {
+            format!("vec![{}]", each(elements, element).join(", "))
+        }
+        (TypeKind::Set(_), ValueKind::List(elements)) if elements.is_empty() => {
+            "BTreeSet::new()".to_string()
+        }
+        (TypeKind::Set(element), ValueKind::List(elements)) => {
+            format!("BTreeSet::from([{}])", each(elements, element).join(", "))
+        }
+        (TypeKind::Array(_, element), ValueKind::List(elements)) => {
+            format!("[{}]", each(elements, element).join(", "))
+        }
+        (TypeKind::Tuple(types), ValueKind::List(elements)) => {
+            match zipped(elements, types).as_slice() {
+                [single] => format!("({single},)"),
+                elements => format!("({})", elements.join(", ")),
+            }
+        }
+        (TypeKind::Map(..), ValueKind::Map(entries)) if entries.is_empty() => {
+            "BTreeMap::new()".to_string()
+        }
+        (TypeKind::Map(key, val), ValueKind::Map(entries)) => {
+            let entries: Vec<_> = entries
+                .iter()
+                .map(|(k, v)| {
+                    format!(
+                        "({}, {})",
+                        rust_value(schema, k, key),
+                        rust_value(schema, v, val)
+                    )
+                })
+                .collect();
+            format!("BTreeMap::from([{}])", entries.join(", "))
+        }
+        (TypeKind::Named { name, args }, _) => {
+            if let Some(primitive) = Primitive::from_name(name) {
+                return rust_literal(primitive, value);
+            }
+            let Some(declared) = schema.item(name) else {
+                return "Default::default()".to_string();
+            };
+            let substituted = |ty: &TypeRef| ty.substituted(&declared.params, args);
+            let name = to_rust_type(name);
+            let fields = |fields: &[Field], given: &[(Name, ValueExpr)]| -> String {
+                let given: Vec<_> = given
+                    .iter()
+                    .filter_map(|(field, value)| {
+                        let declared = fields.iter().find(|f| f.name.text == field.text)?;
+                        Some(format!(
+                            "{}: {}",
+                            to_rust_field(&field.text),
+                            rust_value(schema, value, &substituted(&declared.ty))
+                        ))
+                    })
+                    .collect();
+                format!("{{ {} }}", given.join(", "))
+            };
+            match (&declared.kind, &value.kind) {
+                (ItemKind::Newtype(inner), _) => {
+                    format!("{name}({})", rust_value(schema, value, &substituted(inner)))
+                }
+                (ItemKind::Record(declared_fields), ValueKind::Record(given)) => {
+                    format!("{name} {}", fields(declared_fields, given))
+                }
+                (ItemKind::Record(_), _) => name,
+                (ItemKind::Tuple(types), ValueKind::List(elements)) => {
+                    let types: Vec<_> = types.iter().map(substituted).collect();
+                    format!("{name}({})", zipped(elements, &types).join(", "))
+                }
+                (
+                    ItemKind::Enum(variants),
+                    ValueKind::Variant {
+                        variant, payload, ..
+                    },
+                ) => {
+                    let declared = variants.iter().find(|v| v.name.text == variant.text);
+                    let variant = format!("{name}::{}", to_rust_type(&variant.text));
+                    match (declared.map(|v| &v.body), payload.as_deref()) {
+                        (Some(VariantBody::Newtype(inner)), Some(payload)) => format!(
+                            "{variant}({})",
+                            rust_value(schema, payload, &substituted(inner))
+                        ),
+                        (
+                            Some(VariantBody::Tuple(types)),
+                            Some(ValueExpr {
+                                kind: ValueKind::List(elements),
+                                ..
+                            }),
+                        ) => {
+                            let types: Vec<_> = types.iter().map(substituted).collect();
+                            format!("{variant}({})", zipped(elements, &types).join(", "))
+                        }
+                        (
+                            Some(VariantBody::Struct(declared_fields)),
+                            Some(ValueExpr {
+                                kind: ValueKind::Record(given),
+                                ..
+                            }),
+                        ) => format!("{variant} {}", fields(declared_fields, given)),
+                        (Some(VariantBody::Struct(_)), _) => format!("{variant} {{}}"),
+                        _ => variant,
+                    }
+                }
+                _ => "Default::default()".to_string(),
+            }
+        }
+        _ => "Default::default()".to_string(),
+    }
+}
+
+/// The Rust literal for `value`, a value of `primitive`.
+fn rust_literal(primitive: Primitive, value: &ValueExpr) -> String {
+    match &value.kind {
+        ValueKind::Bool(b) => b.to_string(),
+        ValueKind::Int(text) if primitive.is_float() => format!("{text}.0"),
+        ValueKind::Float(text) if text == "inf" => format!("{primitive}::INFINITY"),
+        ValueKind::Float(text) if text == "-inf" => format!("{primitive}::NEG_INFINITY"),
+        ValueKind::Float(text) if text == "nan" => format!("{primitive}::NAN"),
+        ValueKind::Int(text) | ValueKind::Float(text) => text.clone(),
+        ValueKind::String(text) => match primitive {
+            Primitive::Char => format!("{:?}", text.chars().next().unwrap_or_default()),
+            Primitive::Uuid => format!("Uuid::from_u128(0x{})", text.replace('-', "")),
+            _ => format!("{text:?}.to_string()"),
+        },
+        ValueKind::List(elements) if primitive == Primitive::Bytes => {
+            if elements.is_empty() {
+                "Vec::new()".to_string()
+            } else {
+                let bytes: Vec<_> = elements
+                    .iter()
+                    .map(|byte| rust_literal(Primitive::U8, byte))
+                    .collect();
+                format!("vec![{}]", bytes.join(", "))
+            }
+        }
+        ValueKind::List(_) => "()".to_string(),
+        ValueKind::Tagged { tag, value } => match (tag, &value.kind) {
+            (ValueTag::Bytes, ValueKind::String(text)) => {
+                let bytes: Vec<_> = hex_bytes(text)
+                    .unwrap_or_default()
+                    .iter()
+                    .map(u8::to_string)
+                    .collect();
+                if bytes.is_empty() {
+                    "Vec::new()".to_string()
+                } else {
+                    format!("vec![{}]", bytes.join(", "))
+                }
+            }
+            _ => rust_literal(primitive, value),
+        },
+        _ => "Default::default()".to_string(),
+    }
+}
+
+/// Writes the impls of `schema` whose member has a signature of the shape its trait
+/// gives it. The others are left out: `check_impls`, which `load_modules` and the
+/// database both run, reports an error for each, or a warning for a `TODO` signature.
+fn write_impls(out: &mut String, schema: &Schema) {
+    for imp in &schema.impls {
+        let Some(known) = KnownTrait::from_name(&imp.trait_name.text) else {
+            continue;
+        };
+        let TypeKind::Named { name, .. } = &imp.self_ty.kind else {
+            continue;
+        };
+        let Some(item) = schema.item(name).filter(|item| item.params.is_empty()) else {
+            continue;
+        };
+        let Some((signature, body)) = imp
+            .members
+            .iter()
+            .filter(|member| member.name.text == known.member)
+            .find_map(|member| Some((member.signature.as_ref()?, member.body.as_deref())))
+            .filter(|(signature, _)| known.accepts(signature))
+        else {
+            continue;
+        };
+        let ty = |ty: &TypeRef| rust_type(ty, item);
+        let error = |ret: &TypeRef| match &ret.kind {
+            TypeKind::Result(_, err) => ty(err),
+            _ => unreachable!("the signature is accepted"),
+        };
+        let name = to_rust_type(&item.name.text);
+        let unused = if body.is_none() { "_" } else { "" };
+        out.push('\n');
+        let header = match known.name {
+            "from-str" => format!(
+                "impl std::str::FromStr for {name} {{\n    type Err = {};\n\n    \
+                 fn from_str({unused}s: &str) -> Result<Self, Self::Err> {{",
+                error(&signature.ret)
+            ),
+            "try-from" => format!(
+                "impl TryFrom<{}> for {name} {{\n    type Error = {};\n\n    \
+                 fn try_from({unused}value: {0}) -> Result<Self, Self::Error> {{",
+                ty(&signature.params[0]),
+                error(&signature.ret)
+            ),
+            "display" => format!(
+                "impl std::fmt::Display for {name} {{\n    \
+                 fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {{"
+            ),
+            _ => format!("impl Default for {name} {{\n    fn default() -> Self {{"),
+        };
+        writeln!(out, "{header}").unwrap();
+        let body = body.map_or("todo!()", str::trim);
+        if known.name == "display" {
+            // The body makes the text; writing it out is the trait's business.
+            out.push_str("        let text: String = {\n");
+            write_body(out, body, 12);
+            out.push_str("        };\n        f.write_str(&text)\n");
+        } else {
+            write_body(out, body, 8);
+        }
+        out.push_str("    }\n}\n");
+    }
+}
+
+/// Writes the lines of a function body, indented by `indent`.
+fn write_body(out: &mut String, body: &str, indent: usize) {
+    for line in body.lines() {
+        if line.trim().is_empty() {
+            out.push('\n');
+        } else {
+            writeln!(out, "{:indent$}{line}", "").unwrap();
+        }
+    }
+}
+
+/// The Rust spelling of a type reference inside `item`.
+pub fn rust_type(ty: &TypeRef, item: &Item) -> String {
+    match &ty.kind {
+        TypeKind::Named { name, args } => {
+            if item.params.iter().any(|param| &param.text == name) {
+                return name.clone();
+            }
+            if let Some(primitive) = Primitive::from_name(name) {
+                return rust_primitive(primitive).to_string();
+            }
+            let name = match name.split_once('/') {
+                Some((module, local)) => {
+                    format!("super::{}::{}", to_rust_field(module), to_rust_type(local))
+                }
+                None => to_rust_type(name),
+            };
+            if args.is_empty() {
+                name
+            } else {
+                let args: Vec<_> = args.iter().map(|arg| rust_type(arg, item)).collect();
+                format!("{name}<{}>", args.join(", "))
+            }
+        }
+        TypeKind::Option(inner) => format!("Option<{}>", rust_type(inner, item)),
+        TypeKind::Result(ok, err) => {
+            format!("Result<{}, {}>", rust_type(ok, item), rust_type(err, item))
+        }
+        TypeKind::Vec(element) => format!("Vec<{}>", rust_type(element, item)),
+        TypeKind::Set(element) => format!("BTreeSet<{}>", rust_type(element, item)),
+        TypeKind::Map(key, value) => {
+            format!(
+                "BTreeMap<{}, {}>",
+                rust_type(key, item),
+                rust_type(value, item)
+            )
+        }
+        TypeKind::Array(length, element) => format!("[{}; {length}]", rust_type(element, item)),
+        TypeKind::Box(inner) => format!("Box<{}>", rust_type(inner, item)),
+        TypeKind::Tuple(elements) => match elements.as_slice() {
+            [single] => format!("({},)", rust_type(single, item)),
+            elements => {
+                let elements: Vec<_> = elements.iter().map(|e| rust_type(e, item)).collect();
+                format!("({})", elements.join(", "))
+            }
+        },
+    }
+}
+
+fn rust_primitive(primitive: Primitive) -> &'static str {
+    match primitive {
+        Primitive::Bool => "bool",
+        Primitive::Char => "char",
+        Primitive::String => "String",
+        Primitive::I8 => "i8",
+        Primitive::I16 => "i16",
+        Primitive::I32 => "i32",
+        Primitive::I64 => "i64",
+        Primitive::I128 => "i128",
+        Primitive::Isize => "isize",
+        Primitive::U8 => "u8",
+        Primitive::U16 => "u16",
+        Primitive::U32 => "u32",
+        Primitive::U64 => "u64",
+        Primitive::U128 => "u128",
+        Primitive::Usize => "usize",
+        Primitive::F32 => "f32",
+        Primitive::F64 => "f64",
+        Primitive::Unit => "()",
+        Primitive::Uuid => "Uuid",
+        Primitive::Bytes => "Vec<u8>",
+    }
+}
+
+/// Schema-wide facts deciding which traits each item can and must derive.
+struct TraitAnalysis {
+    with_float: HashSet<String>,
+    ordered: HashSet<String>,
+    /// For each generic item, which of its parameters must be `Ord` to deserialize it.
+    ord_params: HashMap<String, Vec<bool>>,
+}
+
+impl TraitAnalysis {
+    fn new(schema: &Schema) -> TraitAnalysis {
+        let with_float = schema
+            .items
+            .iter()
+            .filter(|item| {
+                reachable(schema, item.type_refs()).iter().any(|ty| {
+                    matches!(&ty.kind, TypeKind::Named { name, .. }
+                        if Primitive::from_name(name).is_some_and(Primitive::is_float))
+                })
+            })
+            .map(|item| item.name.text.clone())
+            .collect();
+
+        let ordering_roots = schema
+            .items
+            .iter()
+            .flat_map(Item::type_refs)
+            .flat_map(TypeRef::walk)
+            .flat_map(|ty| match &ty.kind {
+                TypeKind::Map(key, _) => vec![&**key],
+                TypeKind::Set(element) => vec![&**element],
+                _ => Vec::new(),
+            })
+            .collect();
+        let ordered = reachable(schema, ordering_roots)
+            .into_iter()
+            .filter_map(|ty| match &ty.kind {
+                TypeKind::Named { name, .. } if schema.item(name).is_some() => Some(name.clone()),
+                _ => None,
+            })
+            .collect();
+
+        TraitAnalysis {
+            with_float,
+            ordered,
+            ord_params: ordered_params(schema),
+        }
+    }
+
+    fn has_float(&self, item: &str) -> bool {
+        self.with_float.contains(item)
+    }
+
+    fn is_ordered(&self, item: &str) -> bool {
+        self.ordered.contains(item)
+    }
+
+    /// The where-clause for `Deserialize`, if serde's inferred one is not enough.
+    fn deserialize_bound(&self, item: &Item, key: &str) -> Option<String> {
+        let needs_ord = self.ord_params.get(key)?;
+        if !needs_ord.contains(&true) {
+            return None;
+        }
+        let bounds: Vec<_> = item
+            .params
+            .iter()
+            .zip(needs_ord)
+            .map(|(param, &ord)| {
+                let extra = if ord { " + Ord" } else { "" };
+                format!("{param}: Deserialize<'de>{extra}")
+            })
+            .collect();
+        Some(bounds.join(", "))
+    }
+}
+
+/// Every type reference reachable from `roots`, following named types into their
+/// declarations.
+fn reachable<'a>(schema: &'a Schema, roots: Vec<&'a TypeRef>) -> Vec<&'a TypeRef> {
+    let mut seen_items = HashSet::new();
+    let mut out = Vec::new();
+    let mut stack = roots;
+    while let Some(ty) = stack.pop() {
+        for nested in ty.walk() {
+            out.push(nested);
+            if let TypeKind::Named { name, .. } = &nested.kind {
+                if let Some(item) = schema.item(name) {
+                    if seen_items.insert(name.as_str()) {
+                        stack.extend(item.type_refs());
+                    }
+                }
+            }
+        }
+    }
+    out
+}
@@ -0,0 +1,18 @@
+//! `(box T)` is the schema's explicit opt-in to indirection and becomes a Rust `Box`.
+
+use aski_codegen::generate_rust;
+use aski_sema::lower_source_file;
+use aski_syntax::parse;
+
+#[test]
+fn boxed_references_become_boxes() {
+    let parse = parse("(aski/v1 (record node {value: i64 next: (? (box node))}))");
+    assert_eq!(parse.errors(), &[]);
+    let (schema, diagnostics) = lower_source_file(&parse.tree());
+    assert_eq!(diagnostics, &[]);
+    let generated = generate_rust(&schema);
+    assert!(
+        generated.contains("    pub next: Option<Box<Node>>,\n"),
+        "{generated}"
+    );
+}
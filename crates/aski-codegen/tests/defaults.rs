@@ -0,0 +1,188 @@
+//! Field defaults become serde defaults, and `Default` for records defaulting every field.
+
+use aski_codegen::generate_rust;
+use aski_sema::{check_defaults, lower_source_file};
+use aski_syntax::parse;
+
+fn generate(text: &str) -> String {
+    let parse = parse(text);
+    assert_eq!(parse.errors(), &[]);
+    let (schema, diagnostics) = lower_source_file(&parse.tree());
+    assert_eq!(diagnostics, &[]);
+    assert_eq!(check_defaults(&schema), &[]);
+    generate_rust(&schema)
+}
+
+#[test]
+fn defaults_are_generated() {
+    let generated = generate(
+        r#"(aski/v1
+  (record point {x: f64 y: f64})
+  (enum shape (dot) (circle f64) (poly {points: (vec point)}))
+  (record settings {
+    port: u16 (default 8080)
+    name: string (default "local \"host\"")
+    ratio: f64 (default 1)
+    limit: f32 (default -inf)
+    id: uuid (default "67e55044-10b1-426f-9247-bb680e5fe0c8")
+    parent: (? string) (default none)
+    seen: (set u8) (default [1 2])
+    pair: [i32 string] (default [-1 "a"])
+    names: (map u8 string) (default {1 "one"})
+    origin: (box point) (default {x: 0 y: 0.5})
+    shape: shape (default (shape poly {points: []}))
+    blob: bytes (default #bytes "00ff")
+    last: (result u8 string) (default #err "never run")})
+  (record (page T) {items: (vec T) (default []) total: u32})
+  (record age {years: u8 (default 18)})
+  (impl default age {default [(type-signature [] self) (function-body "Age { years: 0 }")]}))"#,
+    );
+    let expected = r#"use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "variant", content = "data")]
+pub enum Shape {
+    Dot,
+    Circle(f64),
+    Poly { points: Vec<Point> },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Settings {
+    #[serde(default = "default_settings_port")]
+    pub port: u16,
+    #[serde(default = "default_settings_name")]
+    pub name: String,
+    #[serde(default = "default_settings_ratio")]
+    pub ratio: f64,
+    #[serde(default = "default_settings_limit")]
+    pub limit: f32,
+    #[serde(default = "default_settings_id")]
+    pub id: Uuid,
+    #[serde(default = "default_settings_parent")]
+    pub parent: Option<String>,
+    #[serde(default = "default_settings_seen")]
+    pub seen: BTreeSet<u8>,
+    #[serde(default = "default_settings_pair")]
+    pub pair: (i32, String),
+    #[serde(default = "default_settings_names")]
+    pub names: BTreeMap<u8, String>,
+    #[serde(default = "default_settings_origin")]
+    pub origin: Box<Point>,
+    #[serde(default = "default_settings_shape")]
+    pub shape: Shape,
+    #[serde(default = "default_settings_blob")]
+    pub blob: Vec<u8>,
+    #[serde(default = "default_settings_last")]
+    pub last: Result<u8, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Page<T> {
+    #[serde(default = "default_page_items")]
+    pub items: Vec<T>,
+    pub total: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Age {
+    #[serde(default = "default_age_years")]
+    pub years: u8,
+}
+
+fn default_settings_port() -> u16 {
+    8080
+}
+
+fn default_settings_name() -> String {
+    "local \"host\"".to_string()
+}
+
+fn default_settings_ratio() -> f64 {
+    1.0
+}
+
+fn default_settings_limit() -> f32 {
+    f32::NEG_INFINITY
+}
+
+fn default_settings_id() -> Uuid {
+    Uuid::from_u128(0x67e5504410b1426f9247bb680e5fe0c8)
+}
+
+fn default_settings_parent() -> Option<String> {
+    None
+}
+
+fn default_settings_seen() -> BTreeSet<u8> {
+    BTreeSet::from([1, 2])
+}
+
+fn default_settings_pair() -> (i32, String) {
+    (-1, "a".to_string())
+}
+
+fn default_settings_names() -> BTreeMap<u8, String> {
+    BTreeMap::from([(1, "one".to_string())])
+}
+
+fn default_settings_origin() -> Box<Point> {
+    Box::new(Point { x: 0.0, y: 0.5 })
+}
+
+fn default_settings_shape() -> Shape {
+    Shape::Poly { points: Vec::new() }
+}
+
+fn default_settings_blob() -> Vec<u8> {
+    vec![0, 255]
+}
+
+fn default_settings_last() -> Result<u8, String> {
+    Err("never run".to_string())
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            port: default_settings_port(),
+            name: default_settings_name(),
+            ratio: default_settings_ratio(),
+            limit: default_settings_limit(),
+            id: default_settings_id(),
+            parent: default_settings_parent(),
+            seen: default_settings_seen(),
+            pair: default_settings_pair(),
+            names: default_settings_names(),
+            origin: default_settings_origin(),
+            shape: default_settings_shape(),
+            blob: default_settings_blob(),
+            last: default_settings_last(),
+        }
+    }
+}
+
+fn default_page_items<T>() -> Vec<T> {
+    Vec::new()
+}
+
+fn default_age_years() -> u8 {
+    18
+}
+
+impl Default for Age {
+    fn default() -> Self {
+        Age { years: 0 }
+    }
+}
+"#;
+    assert_eq!(generated, expected);
+}
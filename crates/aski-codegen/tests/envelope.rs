@@ -0,0 +1,131 @@
+//! Conformance: every enum the generator emits uses the canonical `{variant, data}`
+//! envelope, including `ErrorCode`, which the draft tags externally, and nothing else
+//! changes serde's representation. Trait bounds on generic items are not part of the
+//! representation and are allowed.
+
+use aski_codegen::generate_rust;
+use aski_sema::{lower_source_file, ENUM_CONTENT, ENUM_TAG};
+use aski_syntax::parse;
+
+const ALL_TYPES_ASKI: &str = include_str!("../../../encoder/drafts/all-types.aski");
+const ALL_TYPES_RS: &str = include_str!("../../../encoder/drafts/all-types.rs");
+
+fn generate(text: &str) -> String {
+    let parse = parse(text);
+    assert_eq!(parse.errors(), &[]);
+    let (schema, diagnostics) = lower_source_file(&parse.tree());
+    assert_eq!(diagnostics, &[]);
+    generate_rust(&schema)
+}
+
+/// Every violation of the envelope rule in `source`, one line each.
+fn envelope_violations(source: &str) -> Vec<String> {
+    let envelope = format!(r#"#[serde(tag = "{ENUM_TAG}", content = "{ENUM_CONTENT}")]"#);
+    let mut violations = Vec::new();
+    let mut attrs: Vec<&str> = Vec::new();
+    for line in source.lines().map(str::trim) {
+        if line.starts_with("#[") {
+            attrs.push(line);
+            continue;
+        }
+        if let Some(rest) = line.strip_prefix("pub enum ") {
+            let name = rest.split(['<', ' ', '{']).next().unwrap_or(rest);
+            if !attrs.contains(&envelope.as_str()) {
+                violations.push(format!("enum `{name}` lacks the envelope"));
+            }
+        }
+        for attr in attrs.drain(..) {
+            let representation = attr.starts_with("#[serde") && !attr.starts_with("#[serde(bound");
+            if representation && attr != envelope {
+                violations.push(format!("unexpected serde attribute `{attr}`"));
+            }
+        }
+    }
+    violations
+}
+
+#[test]
+fn all_types_enums_use_the_envelope() {
+    let generated = generate(ALL_TYPES_ASKI);
+    assert_eq!(generated.matches("pub enum ").count(), 3);
+    assert_eq!(envelope_violations(&generated), Vec::<String>::new());
+}
+
+#[test]
+fn every_variant_shape_uses_the_envelope() {
+    let generated = generate(
+        "(aski/v1
+  (enum switch (on) (off))
+  (enum payload
+    (empty)
+    (single (vec switch))
+    (pair [u8 string])
+    (named {inner: switch flag: (? bool)})))",
+    );
+    assert_eq!(envelope_violations(&generated), Vec::<String>::new());
+}
+
+#[test]
+fn hand_written_draft_tags_only_error_code_externally() {
+    assert_eq!(
+        envelope_violations(ALL_TYPES_RS),
+        ["enum `ErrorCode` lacks the envelope"]
+    );
+}
+
+#[test]
+fn error_code_is_brought_under_the_envelope() {
+    // The draft tags `ErrorCode` externally; the generated enum is the same but for the
+    // envelope, and every other enum keeps the one the draft gives it.
+    let generated = generate(ALL_TYPES_ASKI);
+    let envelope = format!(r#"#[serde(tag = "{ENUM_TAG}", content = "{ENUM_CONTENT}")]"#);
+    let attrs_of = |source: &str, name: &str| -> Vec<String> {
+        let lines: Vec<&str> = source.lines().map(str::trim).collect();
+        let at = lines
+            .iter()
+            .position(|line| *line == format!("pub enum {name} {{"))
+            .unwrap();
+        lines[..at]
+            .iter()
+            .rev()
+            .take_while(|line| line.starts_with("#["))
+            .filter(|line| line.starts_with("#[serde"))
+            .map(|line| line.to_string())
+            .collect()
+    };
+    assert_eq!(attrs_of(ALL_TYPES_RS, "ErrorCode"), Vec::<String>::new());
+    for name in ["ErrorCode", "Shape", "Message"] {
+        assert_eq!(attrs_of(&generated, name), vec![envelope.clone()], "{name}");
+    }
+    for name in ["Shape", "Message"] {
+        assert_eq!(
+            attrs_of(ALL_TYPES_RS, name),
+            vec![envelope.clone()],
+            "{name}"
+        );
+    }
+}
+
+#[test]
+fn other_representations_are_rejected() {
+    let source = r#"
+#[derive(Debug, Serialize, Deserialize)]
+pub enum ExternallyTagged {
+    A,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum InternallyTagged {
+    A,
+}
+"#;
+    assert_eq!(
+        envelope_violations(source),
+        [
+            "enum `ExternallyTagged` lacks the envelope",
+            "enum `InternallyTagged` lacks the envelope",
+            r#"unexpected serde attribute `#[serde(tag = "type")]`"#,
+        ]
+    );
+}
@@ -0,0 +1,71 @@
+//! Generic declarations become Rust generics, with serde bounds where inference falls
+//! short.
+
+use aski_codegen::generate_rust;
+use aski_sema::lower_source_file;
+use aski_syntax::parse;
+
+fn generate(text: &str) -> String {
+    let parse = parse(text);
+    assert_eq!(parse.errors(), &[]);
+    let (schema, diagnostics) = lower_source_file(&parse.tree());
+    assert_eq!(diagnostics, &[]);
+    generate_rust(&schema)
+}
+
+#[test]
+fn generic_declarations_become_rust_generics() {
+    let generated = generate(
+        "(aski/v1
+  (record (page T) {items: (vec T) next: (? string)})
+  (tuple (pair A B) [A B])
+  (enum (either L R) (left L) (right R))
+  (record listing {page: (page (pair u8 string))}))",
+    );
+    let expected = r#"use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Pair<A, B>(pub A, pub B);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "variant", content = "data")]
+pub enum Either<L, R> {
+    Left(L),
+    Right(R),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Listing {
+    pub page: Page<Pair<u8, String>>,
+}
+"#;
+    assert_eq!(generated, expected);
+}
+
+#[test]
+fn parameters_in_ordered_positions_get_an_ord_bound() {
+    let generated = generate(
+        "(aski/v1
+  (record (index K V) {entries: (map K V)})
+  (newtype (tags T) (set T))
+  (record (catalog K) {by-key: (index K string) plain: (vec K)}))",
+    );
+    let bounds: Vec<_> = generated
+        .lines()
+        .filter(|line| line.starts_with("#[serde(bound"))
+        .collect();
+    assert_eq!(
+        bounds,
+        [
+            r#"#[serde(bound(deserialize = "K: Deserialize<'de> + Ord, V: Deserialize<'de>"))]"#,
+            r#"#[serde(bound(deserialize = "T: Deserialize<'de> + Ord"))]"#,
+            r#"#[serde(bound(deserialize = "K: Deserialize<'de> + Ord"))]"#,
+        ]
+    );
+}
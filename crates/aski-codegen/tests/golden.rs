@@ -0,0 +1,78 @@
+//! The generator must reproduce the hand-written draft of the type universe.
+
+use aski_codegen::{generate_rust, generate_rust_item};
+use aski_sema::lower_source_file;
+use aski_syntax::{parse, parse_capitalized};
+
+mod draft;
+
+const ALL_TYPES_ASKI: &str = include_str!("../../../encoder/drafts/all-types.aski");
+const ALL_TYPES_RS: &str = include_str!("../../../encoder/drafts/all-types.rs");
+
+#[test]
+fn all_types_matches_draft() {
+    let parse = parse(ALL_TYPES_ASKI);
+    assert_eq!(parse.errors(), &[]);
+    let (schema, diagnostics) = lower_source_file(&parse.tree());
+    assert_eq!(diagnostics, &[]);
+
+    let generated = generate_rust(&schema);
+    draft::assert_matches_draft(&generated, ALL_TYPES_RS);
+}
+
+#[test]
+fn items_are_generated_as_in_the_whole_module() {
+    let (schema, _) = lower_source_file(&parse(ALL_TYPES_ASKI).tree());
+    let generated = generate_rust(&schema);
+    for item in &schema.items {
+        let single = generate_rust_item(&schema, &item.name.text).unwrap();
+        assert!(generated.contains(&single), "{single}");
+    }
+    assert_eq!(generate_rust_item(&schema, "missing"), None);
+}
+
+/// all-types.aski written in the capitalized dialect.
+const ALL_TYPES_CAPITALIZED: &str = "\
+UserId (Uuid)
+Blob (Bytes)
+(Wrapped T) (T)
+UnitStruct {}
+Pair (I32 I32)
+ErrorCode [NotFound PermissionDenied Invalid (String)]
+Shape [Unit Circle {r F64} Rect (F64 F64) Named (String)]
+Message [Ping Text (String) Batch (Vec Message) Kv (Map String String)]
+Status {ok Bool code (Option U32) note (Option String)}
+AllTypes {
+  boolValue Bool charValue Char stringValue String
+  i8Value I8 i16Value I16 i32Value I32 i64Value I64 i128Value I128 isizeValue Isize
+  u8Value U8 u16Value U16 u32Value U32 u64Value U64 u128Value U128 usizeValue Usize
+  f32Value F32 f64Value F64
+  maybeI64Value (Option I64)
+  outcomeStringOrErrorCode (Result String ErrorCode)
+  stringVector (Vec String)
+  mixedTuple [I32 String Bool]
+  u16ArrayLen_3 (Array 3 U16)
+  stringSet (Set String)
+  stringToU32Map (Map String U32)
+  userIdToI64Map (Map UserId I64)
+  userId UserId
+  blobBytes Blob
+  wrappedPair (Wrapped Pair)
+  shape Shape
+  message Message
+  status Status
+  unitValue Unit
+  unitStructValue UnitStruct
+}
+";
+
+#[test]
+fn both_dialects_describe_the_same_types() {
+    let capitalized = parse_capitalized(ALL_TYPES_CAPITALIZED);
+    assert_eq!(capitalized.errors(), &[]);
+    let (capitalized, diagnostics) = lower_source_file(&capitalized.tree());
+    assert_eq!(diagnostics, &[]);
+
+    let (schema, _) = lower_source_file(&parse(ALL_TYPES_ASKI).tree());
+    assert_eq!(generate_rust(&capitalized), generate_rust(&schema));
+}
@@ -0,0 +1,92 @@
+//! Impls of known traits become Rust impls on the generated types.
+
+use aski_codegen::generate_rust;
+use aski_sema::{check_impls, lower_source_file};
+use aski_syntax::{parse, Severity};
+
+fn generate(text: &str) -> String {
+    let parse = parse(text);
+    assert_eq!(parse.errors(), &[]);
+    let (schema, diagnostics) = lower_source_file(&parse.tree());
+    assert_eq!(diagnostics, &[]);
+    generate_rust(&schema)
+}
+
+#[test]
+fn known_traits_are_implemented_after_the_types() {
+    let generated = generate(
+        r#"(aski/v1
+  (newtype age u16)
+  (impl from-str age {from-str [(type-signature [string] (result self string))
+                                (function-body "let years = s.parse().map_err(|_| format!(\"bad age {s}\"))?;
+Ok(Age(years))")]})
+  (impl try-from age {try-from [(type-signature [(vec u8)] (result self unit)) (function-body TODO)]})
+  (impl display age {fmt [(type-signature [self] string) (function-body "format!(\"{} years\", self.0)")]})
+  (impl default age {default [(type-signature [] self) (function-body "Age(18)")]}))"#,
+    );
+    let expected = r#"use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Age(pub u16);
+
+impl std::str::FromStr for Age {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let years = s.parse().map_err(|_| format!("bad age {s}"))?;
+        Ok(Age(years))
+    }
+}
+
+impl TryFrom<Vec<u8>> for Age {
+    type Error = ();
+
+    fn try_from(_value: Vec<u8>) -> Result<Self, Self::Error> {
+        todo!()
+    }
+}
+
+impl std::fmt::Display for Age {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text: String = {
+            format!("{} years", self.0)
+        };
+        f.write_str(&text)
+    }
+}
+
+impl Default for Age {
+    fn default() -> Self {
+        Age(18)
+    }
+}
+"#;
+    assert_eq!(generated, expected);
+}
+
+#[test]
+fn impls_check_impls_rejects_are_left_out() {
+    let text = "(aski/v1
+  (newtype age u16)
+  (impl display age {fmt [(type-signature TODO) (function-body TODO)]})
+  (impl default age {default [(type-signature [string] self)]})
+  (impl hash age {hash [(type-signature [self] u64)]}))";
+    let generated = generate(text);
+    assert!(!generated.contains("impl "), "{generated}");
+
+    let (schema, _) = lower_source_file(&parse(text).tree());
+    let reported: Vec<_> = check_impls(&schema)
+        .into_iter()
+        .map(|d| (&text[d.range.start()..d.range.end()], d.severity))
+        .collect();
+    assert_eq!(
+        reported,
+        [
+            ("fmt", Severity::Warning),
+            ("fmt", Severity::Warning),
+            ("default", Severity::Warning),
+            ("(type-signature [string] self)", Severity::Error),
+            ("hash", Severity::Error),
+        ]
+    );
+}
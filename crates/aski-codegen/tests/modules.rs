@@ -0,0 +1,78 @@
+//! A schema spread over several modules becomes one Rust module each.
+
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use aski_codegen::generate_rust_modules;
+use aski_sema::load_modules;
+
+fn generate(root: &str, files: &[(&str, &str)]) -> String {
+    let files: HashMap<PathBuf, String> = files
+        .iter()
+        .map(|(path, text)| (PathBuf::from(path), text.to_string()))
+        .collect();
+    let modules = load_modules(Path::new(root), |path| {
+        files
+            .get(path)
+            .cloned()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "not found"))
+    })
+    .unwrap();
+    assert!(!modules.has_errors(), "{modules:?}");
+    generate_rust_modules(&modules)
+}
+
+#[test]
+fn modules_become_sibling_rust_modules() {
+    let generated = generate(
+        "shop.aski",
+        &[
+            (
+                "shop.aski",
+                "module shop {require (geo-data)}\n\
+                 Store {id Uuid location geo-data/Point sites (Set geo-data/Region)}\n",
+            ),
+            (
+                "geo-data.aski",
+                "(aski/v1
+  (module geo-data {})
+  (record point {lat: f64 lon: f64})
+  (record region {name: string})
+  (newtype (tagged T) T))",
+            ),
+        ],
+    );
+    let expected = r#"pub mod geo_data {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct Point {
+        pub lat: f64,
+        pub lon: f64,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+    pub struct Region {
+        pub name: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct Tagged<T>(pub T);
+}
+
+pub mod shop {
+    use serde::{Deserialize, Serialize};
+    use std::collections::BTreeSet;
+    use uuid::Uuid;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct Store {
+        pub id: Uuid,
+        pub location: super::geo_data::Point,
+        pub sites: BTreeSet<super::geo_data::Region>,
+    }
+}
+"#;
+    assert_eq!(generated, expected);
+}
@@ -0,0 +1,175 @@
+//! The kebab-case to Rust mapping must round-trip, and names it cannot spell faithfully
+//! must be reported.
+
+use aski_codegen::{check_names, from_rust_field, from_rust_type, to_rust_field, to_rust_type};
+use aski_sema::{lower_source_file, ItemKind, Schema, VariantBody};
+use aski_syntax::{parse, Diagnostic, Severity};
+
+const ALL_TYPES_ASKI: &str = include_str!("../../../encoder/drafts/all-types.aski");
+
+fn lower(text: &str) -> Schema {
+    let parse = parse(text);
+    assert_eq!(parse.errors(), &[]);
+    let (schema, diagnostics) = lower_source_file(&parse.tree());
+    assert_eq!(diagnostics, &[]);
+    schema
+}
+
+/// `(severity, source text at the range, message)` for compact assertions.
+fn describe<'a>(text: &'a str, diagnostics: &[Diagnostic]) -> Vec<(Severity, &'a str, String)> {
+    diagnostics
+        .iter()
+        .map(|d| {
+            (
+                d.severity,
+                &text[d.range.start()..d.range.end()],
+                d.message.clone(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn mapping_round_trips() {
+    for (kebab, rust) in [
+        ("user-id", "UserId"),
+        ("u16-array", "U16Array"),
+        ("len-3", "Len_3"),
+        ("not-found", "NotFound"),
+    ] {
+        assert_eq!(to_rust_type(kebab), rust);
+        assert_eq!(from_rust_type(rust), kebab);
+    }
+    for (kebab, rust) in [
+        ("bool-value", "bool_value"),
+        ("u16-array-len-3", "u16_array_len_3"),
+        ("type", "r#type"),
+    ] {
+        assert_eq!(to_rust_field(kebab), rust);
+        assert_eq!(from_rust_field(rust), kebab);
+    }
+}
+
+#[test]
+fn all_types_names_round_trip_cleanly() {
+    let schema = lower(ALL_TYPES_ASKI);
+    assert_eq!(check_names(&schema), &[]);
+    for item in &schema.items {
+        assert_eq!(
+            from_rust_type(&to_rust_type(&item.name.text)),
+            item.name.text
+        );
+        let fields = match &item.kind {
+            ItemKind::Record(fields) => fields.iter().collect(),
+            ItemKind::Enum(variants) => variants
+                .iter()
+                .flat_map(|variant| match &variant.body {
+                    VariantBody::Struct(fields) => fields.iter().collect(),
+                    _ => Vec::new(),
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        for field in fields {
+            assert_eq!(
+                from_rust_field(&to_rust_field(&field.name.text)),
+                field.name.text
+            );
+        }
+    }
+}
+
+#[test]
+fn collisions_are_errors() {
+    let text = "(aski/v1
+  (record user-id {})
+  (record user_id {})
+  (record login {user-name: string user_name: string})
+  (enum state (on-hold) (onHold))
+  (record vec {})
+  (newtype default u8)
+  (record try-from {}))";
+    let diagnostics = check_names(&lower(text));
+    let errors: Vec<_> = describe(text, &diagnostics)
+        .into_iter()
+        .filter(|(severity, ..)| *severity == Severity::Error)
+        .collect();
+    assert_eq!(
+        errors,
+        [
+            (
+                Severity::Error,
+                "user_id",
+                "type `user_id` becomes `UserId` in Rust, colliding with type `user-id`"
+                    .to_string()
+            ),
+            (
+                Severity::Error,
+                "vec",
+                "type `vec` becomes `Vec` in Rust, colliding with the `Vec` used by generated code"
+                    .to_string()
+            ),
+            (
+                Severity::Error,
+                "default",
+                "type `default` becomes `Default` in Rust, colliding with the `Default` used by \
+                 generated code"
+                    .to_string()
+            ),
+            (
+                Severity::Error,
+                "try-from",
+                "type `try-from` becomes `TryFrom` in Rust, colliding with the `TryFrom` used by \
+                 generated code"
+                    .to_string()
+            ),
+            (
+                Severity::Error,
+                "user_name",
+                "field `user_name` becomes `user_name` in Rust, colliding with field `user-name`"
+                    .to_string()
+            ),
+            (
+                Severity::Error,
+                "onHold",
+                "variant `onHold` becomes `OnHold` in Rust, colliding with variant `on-hold`"
+                    .to_string()
+            ),
+        ]
+    );
+    // The non-kebab spellings are flagged on their own as well.
+    let warned: Vec<_> = describe(text, &diagnostics)
+        .into_iter()
+        .filter(|(severity, ..)| *severity == Severity::Warning)
+        .map(|(_, name, _)| name)
+        .collect();
+    assert_eq!(warned, ["user_id", "user_name", "onHold"]);
+}
+
+#[test]
+fn keywords_are_escaped_or_rejected() {
+    let text = "(aski/v1
+  (record token {type: string match: bool})
+  (enum this (self)))";
+    assert_eq!(
+        describe(text, &check_names(&lower(text))),
+        [
+            (
+                Severity::Warning,
+                "type",
+                "field `type` is a Rust keyword and is generated as `r#type`".to_string()
+            ),
+            (
+                Severity::Warning,
+                "match",
+                "field `match` is a Rust keyword and is generated as `r#match`".to_string()
+            ),
+            (
+                Severity::Error,
+                "self",
+                "variant `self` would become `Self`, which Rust does not allow as a name"
+                    .to_string()
+            ),
+        ]
+    );
+}
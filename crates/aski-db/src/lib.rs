@@ -0,0 +1,269 @@
+//! Incremental semantics for aski documents.
+//!
+//! [`AskiDatabase`] holds each document as a salsa input and derives everything else on
+//! demand: the parse, the declarations it contains, each declaration's semantic model and
+//! resolution, and the diagnostics of the whole file.
+//!
+//! Each declaration is lowered and resolved from its own subtree, with ranges relative to
+//! where it starts. A green subtree carries no absolute offsets, and where a declaration
+//! starts is kept out of it, in [`item_offsets`], so editing one declaration leaves every
+//! other declaration's results valid even when their position moves; only the file-level
+//! queries, which place declarations back at their offsets, run again.
+//!
+//! Impls are lowered together, from the parse of the whole file, in [`file_impls`]: they
+//! name the declarations they are for rather than being named themselves, and they are
+//! few and small.
+//!
+//! [`expected_at`] is deliberately not a query. It asks about a byte offset, which is
+//! different at almost every keystroke, so memoizing it would keep one result per
+//! position that is rarely asked about again; what it costs beyond the parse, which is
+//! memoized, is finding one token.
+
+use std::sync::{Arc, Mutex};
+
+use aski_sema::{
+    check_defaults, check_impls, check_ordering, check_recursion, lower_decl, lower_impl,
+    resolve_impl, resolve_item, Binding, Declarations, Expected, Field, Impl, Item, ItemKind,
+    Resolution, Schema, TypeRef, VariantBody,
+};
+use aski_syntax::ast::{self, AstNode, HasName};
+use aski_syntax::{parse, Diagnostic, GreenNode, Parse, SyntaxNode};
+
+/// The text of one aski document.
+#[salsa::input]
+pub struct SourceFile {
+    #[return_ref]
+    pub text: String,
+}
+
+/// One top-level declaration of a file, identified by its name.
+#[salsa::tracked]
+pub struct ItemSyntax<'db> {
+    #[id]
+    #[return_ref]
+    pub name: String,
+    pub file: SourceFile,
+    /// The declaration's subtree. It carries no absolute offsets, so it compares equal
+    /// across edits that only move the declaration.
+    #[return_ref]
+    pub green: GreenNode,
+}
+
+/// The type of one field, as declared and as resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldType {
+    /// The enum variant declaring the field, for fields of struct variants.
+    pub variant: Option<String>,
+    pub name: String,
+    pub ty: TypeRef,
+    /// What the field's outermost type names, if it names something and that resolved.
+    pub binding: Option<Binding>,
+}
+
+#[salsa::tracked(return_ref)]
+pub fn parse_file(db: &dyn salsa::Database, file: SourceFile) -> Parse {
+    parse(file.text(db))
+}
+
+/// The named top-level declarations of `file`, in source order.
+#[salsa::tracked(return_ref)]
+pub fn items(db: &dyn salsa::Database, file: SourceFile) -> Vec<ItemSyntax<'_>> {
+    let Some(schema) = parse_file(db, file).tree().schema() else {
+        return Vec::new();
+    };
+    schema
+        .decls()
+        .filter_map(|decl| {
+            let name = decl.name()?.text();
+            Some(ItemSyntax::new(
+                db,
+                name,
+                file,
+                decl.syntax().green().clone(),
+            ))
+        })
+        .collect()
+}
+
+/// Where each of the [`items`] of `file` starts, in the same order. Only the file-level
+/// queries read it: a declaration's own results never depend on where it is.
+#[salsa::tracked(return_ref)]
+pub fn item_offsets(db: &dyn salsa::Database, file: SourceFile) -> Vec<usize> {
+    let Some(schema) = parse_file(db, file).tree().schema() else {
+        return Vec::new();
+    };
+    schema
+        .decls()
+        .filter(|decl| decl.name().is_some())
+        .map(|decl| decl.syntax().text_range().start())
+        .collect()
+}
+
+/// The semantic model of one declaration, with ranges relative to its start.
+#[salsa::tracked(return_ref)]
+pub fn lower_item<'db>(
+    db: &'db dyn salsa::Database,
+    item: ItemSyntax<'db>,
+) -> (Option<Item>, Vec<Diagnostic>) {
+    let root = SyntaxNode::new_root(item.green(db).clone());
+    let decl = ast::Decl::cast(root).expect("items are made from declarations");
+    lower_decl(&decl)
+}
+
+/// The names `file` declares and their arities. Edits inside declaration bodies leave
+/// this unchanged, which is what spares the other declarations from resolving again.
+#[salsa::tracked(return_ref)]
+pub fn declarations(db: &dyn salsa::Database, file: SourceFile) -> Declarations {
+    let lowered = items(db, file)
+        .iter()
+        .filter_map(|item| lower_item(db, *item).0.as_ref());
+    Declarations::new(lowered).0
+}
+
+/// The bindings of one declaration's type references, with ranges relative to its start.
+#[salsa::tracked(return_ref)]
+pub fn item_resolution<'db>(
+    db: &'db dyn salsa::Database,
+    item: ItemSyntax<'db>,
+) -> (Resolution, Vec<Diagnostic>) {
+    match &lower_item(db, item).0 {
+        Some(lowered) => resolve_item(declarations(db, item.file(db)), lowered),
+        None => Default::default(),
+    }
+}
+
+/// The resolved type of every field of one declaration, including fields of struct
+/// variants.
+#[salsa::tracked(return_ref)]
+pub fn field_types<'db>(db: &'db dyn salsa::Database, item: ItemSyntax<'db>) -> Vec<FieldType> {
+    let Some(lowered) = &lower_item(db, item).0 else {
+        return Vec::new();
+    };
+    let resolution = &item_resolution(db, item).0;
+    let field_type = |variant: Option<&str>, field: &Field| FieldType {
+        variant: variant.map(str::to_string),
+        name: field.name.text.clone(),
+        ty: field.ty.clone(),
+        binding: resolution.binding(&field.ty).cloned(),
+    };
+    match &lowered.kind {
+        ItemKind::Record(fields) => fields.iter().map(|field| field_type(None, field)).collect(),
+        ItemKind::Enum(variants) => variants
+            .iter()
+            .flat_map(|variant| match &variant.body {
+                VariantBody::Struct(fields) => fields
+                    .iter()
+                    .map(|field| field_type(Some(&variant.name.text), field))
+                    .collect(),
+                _ => Vec::new(),
+            })
+            .collect(),
+        ItemKind::Newtype(_) | ItemKind::Tuple(_) => Vec::new(),
+    }
+}
+
+/// The impls of `file`, in source order, with ranges relative to the file.
+#[salsa::tracked(return_ref)]
+pub fn file_impls(db: &dyn salsa::Database, file: SourceFile) -> (Vec<Impl>, Vec<Diagnostic>) {
+    let Some(schema) = parse_file(db, file).tree().schema() else {
+        return Default::default();
+    };
+    let mut impls = Vec::new();
+    let mut diagnostics = Vec::new();
+    for decl in schema.impls() {
+        let (imp, impl_diagnostics) = lower_impl(&decl);
+        impls.extend(imp);
+        diagnostics.extend(impl_diagnostics);
+    }
+    (impls, diagnostics)
+}
+
+/// The semantic model of the whole file, with ranges relative to the file.
+#[salsa::tracked(return_ref)]
+pub fn file_schema(db: &dyn salsa::Database, file: SourceFile) -> Schema {
+    let items = items(db, file)
+        .iter()
+        .zip(item_offsets(db, file))
+        .filter_map(|(item, &offset)| {
+            let mut lowered = lower_item(db, *item).0.clone()?;
+            lowered.shift(offset);
+            Some(lowered)
+        })
+        .collect();
+    Schema {
+        items,
+        module: None,
+        impls: file_impls(db, file).0.clone(),
+    }
+}
+
+/// Every diagnostic of `file`: syntax, lowering, resolution, recursion, ordering,
+/// defaults and impls, in source order.
+#[salsa::tracked(return_ref)]
+pub fn file_diagnostics(db: &dyn salsa::Database, file: SourceFile) -> Vec<Diagnostic> {
+    let mut diagnostics = parse_file(db, file).errors().to_vec();
+    for (item, &offset) in items(db, file).iter().zip(item_offsets(db, file)) {
+        let shift = |diagnostic: &Diagnostic| Diagnostic {
+            range: diagnostic.range.shifted(offset),
+            ..diagnostic.clone()
+        };
+        diagnostics.extend(lower_item(db, *item).1.iter().map(shift));
+        diagnostics.extend(item_resolution(db, *item).1.iter().map(shift));
+    }
+    diagnostics.extend(file_impls(db, file).1.iter().cloned());
+    let schema = file_schema(db, file);
+    for imp in &schema.impls {
+        diagnostics.extend(resolve_impl(declarations(db, file), imp).1);
+    }
+    diagnostics.extend(Declarations::new(&schema.items).1);
+    diagnostics.extend(check_recursion(schema));
+    diagnostics.extend(check_ordering(schema));
+    diagnostics.extend(check_defaults(schema));
+    diagnostics.extend(check_impls(schema));
+    diagnostics.sort_by_key(|diagnostic| diagnostic.range.start());
+    diagnostics
+}
+
+/// What `file` expects at the byte `offset`. It only needs the parse, so it is cheap to
+/// ask at every keystroke; see the crate documentation for why it is not a query.
+pub fn expected_at(db: &dyn salsa::Database, file: SourceFile, offset: usize) -> Expected {
+    aski_sema::expected_at(&parse_file(db, file).tree(), offset)
+}
+
+/// The database holding aski documents and everything derived from them.
+#[salsa::db]
+#[derive(Default, Clone)]
+pub struct AskiDatabase {
+    storage: salsa::Storage<Self>,
+    /// The queries executed since logging was enabled, when it is.
+    executed: Arc<Mutex<Option<Vec<String>>>>,
+}
+
+impl AskiDatabase {
+    /// Starts recording which queries execute, for observing incrementality.
+    pub fn enable_logging(&self) {
+        *self.executed.lock().unwrap() = Some(Vec::new());
+    }
+
+    /// The queries executed since the previous call, like `lower_item(Id(3))`, in order.
+    pub fn take_executed(&self) -> Vec<String> {
+        self.executed
+            .lock()
+            .unwrap()
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+}
+
+#[salsa::db]
+impl salsa::Database for AskiDatabase {
+    fn salsa_event(&self, event: &dyn Fn() -> salsa::Event) {
+        let mut executed = self.executed.lock().unwrap();
+        if let Some(executed) = &mut *executed {
+            if let salsa::EventKind::WillExecute { database_key } = event().kind {
+                executed.push(format!("{database_key:?}"));
+            }
+        }
+    }
+}
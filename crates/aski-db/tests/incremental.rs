@@ -0,0 +1,208 @@
+//! Edits re-execute only the queries whose inputs they touch.
+
+use std::collections::BTreeMap;
+
+use aski_db::{
+    expected_at, field_types, file_diagnostics, file_schema, item_resolution, items, lower_item,
+    AskiDatabase, SourceFile,
+};
+use aski_edit::{Edit, History, Transaction};
+use aski_sema::{lower_source_file, resolve, Binding, Expected, Primitive};
+use aski_syntax::{parse, TextRange};
+use ropey::Rope;
+use salsa::Setter;
+
+const ALL_TYPES_ASKI: &str = include_str!("../../../encoder/drafts/all-types.aski");
+
+/// Runs every per-item query plus the file diagnostics, as an editor would after a change.
+fn analyze(db: &AskiDatabase, file: SourceFile) {
+    file_diagnostics(db, file);
+    for item in items(db, file) {
+        field_types(db, *item);
+    }
+}
+
+/// How many times each query executed, by query name.
+fn executions(db: &AskiDatabase) -> BTreeMap<String, usize> {
+    let mut counts = BTreeMap::new();
+    for key in db.take_executed() {
+        let query = key.split('(').next().unwrap_or(&key).to_string();
+        *counts.entry(query).or_default() += 1;
+    }
+    counts
+}
+
+#[test]
+fn editing_one_field_recomputes_only_its_record() {
+    let mut db = AskiDatabase::default();
+    let file = SourceFile::new(&db, ALL_TYPES_ASKI.to_string());
+    db.enable_logging();
+    analyze(&db, file);
+    assert_eq!(file_diagnostics(&db, file), &[]);
+    let initial = executions(&db);
+    assert_eq!(initial["lower_item"], 10);
+    assert_eq!(initial["item_resolution"], 10);
+
+    // Growing a field of `status` moves `all-types`, which follows it, but changes
+    // nothing inside it.
+    let edited = ALL_TYPES_ASKI.replace("code: (? u32)", "code: (? (vec u32))");
+    file.set_text(&mut db).to(edited.clone());
+    analyze(&db, file);
+    let after_edit = executions(&db);
+    assert_eq!(after_edit["parse_file"], 1);
+    assert_eq!(after_edit["items"], 1);
+    assert_eq!(after_edit["lower_item"], 1);
+    assert_eq!(after_edit["item_resolution"], 1);
+    assert_eq!(after_edit["field_types"], 1);
+
+    let status = items(&db, file)
+        .iter()
+        .find(|item| item.name(&db) == "status")
+        .copied()
+        .unwrap();
+    let code = &field_types(&db, status)[1];
+    assert_eq!(code.name, "code");
+    assert_eq!(code.binding, None);
+    assert_eq!(file_diagnostics(&db, file), &[]);
+    // `all-types` was not lowered again, yet the file's schema places it where it moved.
+    let (schema, _) = lower_source_file(&parse(&edited).tree());
+    assert_eq!(file_schema(&db, file), &schema);
+}
+
+#[test]
+fn renaming_a_declaration_re_resolves_its_users() {
+    let mut db = AskiDatabase::default();
+    let file = SourceFile::new(&db, ALL_TYPES_ASKI.to_string());
+    db.enable_logging();
+    analyze(&db, file);
+    executions(&db);
+
+    let edited = ALL_TYPES_ASKI.replace("(newtype blob bytes)", "(newtype blob-data bytes)");
+    file.set_text(&mut db).to(edited.clone());
+    analyze(&db, file);
+    let after_edit = executions(&db);
+    assert_eq!(after_edit["lower_item"], 1);
+    assert_eq!(after_edit["item_resolution"], 10);
+
+    let unresolved: Vec<_> = file_diagnostics(&db, file)
+        .iter()
+        .map(|d| (&edited[d.range.start()..d.range.end()], d.message.as_str()))
+        .collect();
+    assert_eq!(unresolved, [("blob", "cannot find type `blob`")]);
+}
+
+#[test]
+fn expected_at_only_needs_the_parse() {
+    let mut db = AskiDatabase::default();
+    let file = SourceFile::new(&db, ALL_TYPES_ASKI.to_string());
+    db.enable_logging();
+    analyze(&db, file);
+    executions(&db);
+
+    // It is not a query: asking reuses the memoized parse and executes nothing.
+    let offset = ALL_TYPES_ASKI.find("code: (? u32)").unwrap() + "code: (? ".len();
+    assert_eq!(expected_at(&db, file, offset), Expected::Type);
+    assert_eq!(executions(&db), BTreeMap::new());
+
+    // After an edit, it parses again and lowers nothing.
+    let edited = ALL_TYPES_ASKI.replace("code: (? u32)", "code: (? u64)");
+    file.set_text(&mut db).to(edited);
+    assert_eq!(expected_at(&db, file, offset), Expected::Type);
+    assert_eq!(
+        executions(&db),
+        BTreeMap::from([("parse_file".to_string(), 1)])
+    );
+}
+
+#[test]
+fn undoing_edits_restores_every_result() {
+    let mut db = AskiDatabase::default();
+    let mut rope = Rope::from_str(ALL_TYPES_ASKI);
+    let file = SourceFile::new(&db, rope.to_string());
+    db.enable_logging();
+    analyze(&db, file);
+    let schema = file_schema(&db, file).clone();
+    executions(&db);
+
+    let mut history = History::new();
+    let change = Transaction::new([Edit::ChangeFieldType {
+        field: "status/fields/code".parse().unwrap(),
+        ty: "(? (vec u32))".into(),
+    }]);
+    history.begin_group();
+    history.apply(&mut rope, &change).unwrap();
+    history.replace(&mut rope, TextRange::empty(0), ";; draft\n");
+    history.end_group();
+    file.set_text(&mut db).to(rope.to_string());
+    analyze(&db, file);
+    let edited = file_schema(&db, file).clone();
+    assert_ne!(edited, schema);
+    executions(&db);
+
+    // The undone text parses to the same trees as before, so only the declaration the
+    // edit touched is lowered again and everything derived matches the original.
+    history.undo(&mut rope).unwrap();
+    file.set_text(&mut db).to(rope.to_string());
+    analyze(&db, file);
+    assert_eq!(executions(&db)["lower_item"], 1);
+    assert_eq!(file_schema(&db, file), &schema);
+    assert_eq!(file_diagnostics(&db, file), &[]);
+
+    // Redoing the group brings back the edited results the same way.
+    history.redo(&mut rope).unwrap();
+    file.set_text(&mut db).to(rope.to_string());
+    analyze(&db, file);
+    assert_eq!(executions(&db)["lower_item"], 1);
+    assert_eq!(file_schema(&db, file), &edited);
+}
+
+#[test]
+fn results_match_the_batch_pipeline() {
+    let text = "(aski/v1
+  (record account {owner: user roles: (set role) home: (? account)})
+  (newtype user u64)
+  (newtype user string)
+  (enum role (admin) (guest {since: u64 since: u64})))";
+    let db = AskiDatabase::default();
+    let file = SourceFile::new(&db, text.to_string());
+
+    let parse = parse(text);
+    let (schema, mut expected) = lower_source_file(&parse.tree());
+    assert_eq!(file_schema(&db, file), &schema);
+    expected.extend(resolve(&schema).1);
+    expected.extend(aski_sema::check_recursion(&schema));
+    expected.extend(aski_sema::check_ordering(&schema));
+    expected.sort_by_key(|d| d.range.start());
+    assert_eq!(file_diagnostics(&db, file), &expected);
+
+    let account = items(&db, file)[0];
+    let owner = &field_types(&db, account)[0];
+    assert_eq!(owner.binding, Some(Binding::Item("user".to_string())));
+    let role = items(&db, file)[3];
+    let since = &field_types(&db, role)[0];
+    assert_eq!(since.variant.as_deref(), Some("guest"));
+    assert_eq!(since.binding, Some(Binding::Primitive(Primitive::U64)));
+    assert!(lower_item(&db, role).0.is_some());
+    assert!(item_resolution(&db, role).0.binding(&since.ty).is_some());
+}
+
+#[test]
+fn impls_match_the_batch_pipeline() {
+    let text = r#"(aski/v1
+  (newtype age u16)
+  (impl display age {fmt [(type-signature [self] string) (function-body "self.0.to_string()")]})
+  (impl display age {fmt [(type-signature [self] (vec string)) (function-body "")]})
+  (impl hash age {hash [(type-signature [self] u64)]})
+  (impl from-str age {from-str [(type-signature [string] (result self nope)) (function-body "")]}))"#;
+    let db = AskiDatabase::default();
+    let file = SourceFile::new(&db, text.to_string());
+
+    let parse = parse(text);
+    let (schema, mut expected) = lower_source_file(&parse.tree());
+    assert_eq!(file_schema(&db, file), &schema);
+    expected.extend(resolve(&schema).1);
+    expected.extend(aski_sema::check_impls(&schema));
+    expected.sort_by_key(|d| d.range.start());
+    assert_eq!(expected.len(), 4);
+    assert_eq!(file_diagnostics(&db, file), &expected);
+}
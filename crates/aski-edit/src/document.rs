@@ -0,0 +1,137 @@
+// A document kept parsed as it is edited.
+//
+// The rope holds the text and the green tree is always the parse of it. A replacement
+// reparses only the smallest balanced `( )`, `[ ]` or `{ }` form around it that still
+// parses on its own, and splices the new subtree into the tree, so everything outside that
+// form keeps its green nodes, and with them whatever was derived from them. A change that
+// touches a delimiter of every enclosing form, or leaves one unbalanced, reparses the
+// whole text.
+
+use aski_syntax::ast::{AstNode, SourceFile};
+use aski_syntax::{
+    parse, reparse_form, Diagnostic, GreenNode, SyntaxElement, SyntaxNode, TextRange,
+};
+use ropey::Rope;
+
+use crate::{Buffer, Patch};
+
+/// The text of a document together with its syntax tree.
+#[derive(Debug, Clone)]
+pub struct Document {
+    rope: Rope,
+    green: GreenNode,
+    errors: Vec<Diagnostic>,
+}
+
+impl Document {
+    pub fn new(text: &str) -> Document {
+        let parse = parse(text);
+        Document {
+            rope: Rope::from_str(text),
+            green: parse.green().clone(),
+            errors: parse.errors().to_vec(),
+        }
+    }
+
+    pub fn rope(&self) -> &Rope {
+        &self.rope
+    }
+
+    pub fn green(&self) -> &GreenNode {
+        &self.green
+    }
+
+    pub fn syntax(&self) -> SyntaxNode {
+        SyntaxNode::new_root(self.green.clone())
+    }
+
+    /// The typed root of the tree.
+    pub fn tree(&self) -> SourceFile {
+        SourceFile::cast(self.syntax()).expect("documents always have a SOURCE_FILE root")
+    }
+
+    pub fn errors(&self) -> &[Diagnostic] {
+        &self.errors
+    }
+
+    /// Replaces `range` with `text` and brings the tree up to date. Returns the range of
+    /// the text that was reparsed.
+    pub fn replace(&mut self, range: TextRange, text: &str) -> TextRange {
+        self.edit(&Patch::new(range, text)).1
+    }
+
+    /// Applies `patch`, returning the patch that undoes it and the range reparsed.
+    fn edit(&mut self, patch: &Patch) -> (Patch, TextRange) {
+        let inverse = patch.apply_to_rope(&mut self.rope);
+        let (range, text) = (patch.range, patch.replacement.as_str());
+        let innermost = match self.syntax().covering_element(range) {
+            SyntaxElement::Node(node) => node,
+            SyntaxElement::Token(token) => token.parent(),
+        };
+        for form in innermost.ancestors() {
+            if let Some(reparsed) = self.reparse(&form, range, text) {
+                return (inverse, reparsed);
+            }
+        }
+        let parse = parse(&self.rope.to_string());
+        self.green = parse.green().clone();
+        self.errors = parse.errors().to_vec();
+        (inverse, TextRange::at(0, self.rope.len_bytes()))
+    }
+
+    /// Reparses `form` with `range` replaced by `text`, if the change lies between its
+    /// delimiters and the result still parses on its own.
+    fn reparse(&mut self, form: &SyntaxNode, range: TextRange, text: &str) -> Option<TextRange> {
+        let (open, close) = (form.first_token()?, form.last_token()?);
+        if open.kind().closing() != Some(close.kind())
+            || range.start() < open.text_range().end()
+            || range.end() > close.text_range().start()
+        {
+            return None;
+        }
+        let old = form.text_range();
+        let mut new_text = form.text();
+        let relative = TextRange::new(range.start() - old.start(), range.end() - old.start());
+        Patch::new(relative, text).apply(&mut new_text);
+        let (green, errors) = reparse_form(form, &new_text)?;
+        self.green = form.replace_with(green);
+
+        // Errors reported inside the old form were its own: an enclosing form reports at
+        // the form's opening delimiter at most, and at an empty range only at the end.
+        let new = TextRange::at(old.start(), new_text.len());
+        let mut kept = Vec::with_capacity(self.errors.len() + errors.len());
+        for error in self.errors.drain(..) {
+            let own = !error.range.is_empty()
+                && error.range.start() > old.start()
+                && error.range.end() <= old.end();
+            if own {
+                continue;
+            }
+            let mut error = error;
+            if error.range.start() >= old.end() {
+                error.range = TextRange::at(
+                    error.range.start() - old.end() + new.end(),
+                    error.range.len(),
+                );
+            }
+            kept.push(error);
+        }
+        kept.extend(errors.into_iter().map(|mut error| {
+            error.range = error.range.shifted(old.start());
+            error
+        }));
+        kept.sort_by_key(|error| error.range.start());
+        self.errors = kept;
+        Some(new)
+    }
+}
+
+impl Buffer for Document {
+    fn text(&self) -> String {
+        self.rope.to_string()
+    }
+
+    fn apply(&mut self, patch: &Patch) -> Patch {
+        self.edit(patch).0
+    }
+}
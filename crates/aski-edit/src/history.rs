@@ -0,0 +1,126 @@
+// Undo and redo.
+//
+// Every change made through a history is logged as the patches that made it together with
+// their inverses, which hold the text each patch replaced. Undoing applies the inverses in
+// reverse order and gives back the previous text byte for byte, so whatever is derived from
+// the text, the syntax tree or a database's results, comes back as it was too. Textual and
+// structural edits share the log, and changes made between `begin_group` and `end_group`
+// count as one step.
+
+use aski_syntax::TextRange;
+
+use crate::{Buffer, EditError, Patch, Transaction};
+
+/// The undo and redo stacks of one document.
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    undo: Vec<Change>,
+    redo: Vec<Change>,
+    /// The change collecting the edits of the open group, if there is one.
+    group: Option<Change>,
+    depth: usize,
+}
+
+/// One step of the history: the patches that made it and the ones that revert it, both in
+/// the order they apply.
+#[derive(Debug, Clone, Default)]
+struct Change {
+    forward: Vec<Patch>,
+    backward: Vec<Patch>,
+}
+
+impl History {
+    pub fn new() -> History {
+        History::default()
+    }
+
+    /// Applies `transaction` to `buffer` as one step.
+    pub fn apply(
+        &mut self,
+        buffer: &mut impl Buffer,
+        transaction: &Transaction,
+    ) -> Result<Vec<Patch>, EditError> {
+        let patches = transaction.patches(&buffer.text())?;
+        self.record(buffer, &patches);
+        Ok(patches)
+    }
+
+    /// Replaces `range` of `buffer` with `text` as one step.
+    pub fn replace(&mut self, buffer: &mut impl Buffer, range: TextRange, text: &str) -> Patch {
+        let patch = Patch::new(range, text);
+        self.record(buffer, std::slice::from_ref(&patch));
+        patch
+    }
+
+    /// Starts a group: everything up to the matching [`end_group`](History::end_group) is
+    /// undone and redone as one step. Groups nest, and only the outermost one counts.
+    pub fn begin_group(&mut self) {
+        self.depth += 1;
+        self.group.get_or_insert_with(Change::default);
+    }
+
+    pub fn end_group(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+        if self.depth == 0 {
+            self.close_group();
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty() || self.group.as_ref().is_some_and(|g| !g.forward.is_empty())
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Reverts the latest step and returns the patches that did so, in the order they
+    /// applied. An open group is closed first, then undone as a whole.
+    pub fn undo(&mut self, buffer: &mut impl Buffer) -> Option<Vec<Patch>> {
+        self.depth = 0;
+        self.close_group();
+        let change = self.undo.pop()?;
+        for patch in &change.backward {
+            buffer.apply(patch);
+        }
+        let patches = change.backward.clone();
+        self.redo.push(change);
+        Some(patches)
+    }
+
+    /// Makes the latest undone step again and returns the patches that did so.
+    pub fn redo(&mut self, buffer: &mut impl Buffer) -> Option<Vec<Patch>> {
+        let change = self.redo.pop()?;
+        for patch in &change.forward {
+            buffer.apply(patch);
+        }
+        let patches = change.forward.clone();
+        self.undo.push(change);
+        Some(patches)
+    }
+
+    fn record(&mut self, buffer: &mut impl Buffer, patches: &[Patch]) {
+        if patches.is_empty() {
+            return;
+        }
+        let mut change = self.group.take().unwrap_or_default();
+        for patch in patches {
+            change.backward.insert(0, buffer.apply(patch));
+            change.forward.push(patch.clone());
+        }
+        self.redo.clear();
+        if self.depth > 0 {
+            self.group = Some(change);
+        } else {
+            self.undo.push(change);
+        }
+    }
+
+    fn close_group(&mut self) {
+        if let Some(change) = self.group.take() {
+            if !change.forward.is_empty() {
+                self.undo.push(change);
+            }
+        }
+    }
+}
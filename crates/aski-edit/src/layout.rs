@@ -0,0 +1,93 @@
+// Where structural edits put text and which text they take away.
+//
+// Edits keep the layout around them as it was. An element removed from a list takes its
+// line with it when it has one to itself, along with any comment trailing it there, and
+// otherwise the whitespace separating it from a neighbour. An element added to a list
+// goes after the last one: on a line of its own, indented the same, if the last one has a
+// line of its own, and next to it otherwise.
+
+use aski_syntax::{SyntaxKind, SyntaxNode, TextRange};
+
+use crate::Patch;
+
+/// The text to delete to take `element` out of its list, whose elements are the siblings
+/// of the kinds `is_element` accepts.
+pub(crate) fn removal(
+    text: &str,
+    element: &SyntaxNode,
+    is_element: fn(SyntaxKind) -> bool,
+) -> TextRange {
+    let range = element.text_range();
+    let line_start = line_start(text, range.start());
+    let rest = rest_of_line(text, range.end());
+    if text[line_start..range.start()].trim().is_empty() && text[rest..].starts_with('\n') {
+        return TextRange::new(line_start, rest + 1);
+    }
+    let parent = element.parent();
+    let siblings = parent
+        .iter()
+        .flat_map(|parent| parent.children())
+        .filter(|sibling| is_element(sibling.kind()));
+    let (mut before, mut after) = (false, None);
+    for sibling in siblings {
+        if sibling.text_range().end() <= range.start() {
+            before = true;
+        } else if sibling.text_range().start() >= range.end() {
+            after = after.or(Some(sibling));
+        }
+    }
+    match after {
+        Some(next) => TextRange::new(range.start(), next.text_range().start()),
+        None if before => TextRange::new(text[..range.start()].trim_end().len(), range.end()),
+        None => {
+            let rest = &text[range.end()..];
+            TextRange::new(range.start(), text.len() - rest.trim_start().len())
+        }
+    }
+}
+
+/// Adds `new` after `last`, the last element of a list, or at `empty_at` after
+/// `empty_separator` when the list has no elements.
+pub(crate) fn insertion(
+    text: &str,
+    last: Option<&SyntaxNode>,
+    empty_at: usize,
+    empty_separator: &str,
+    new: &str,
+) -> Patch {
+    let Some(last) = last else {
+        return Patch::insert(empty_at, format!("{empty_separator}{new}"));
+    };
+    let range = last.text_range();
+    let indent = &text[line_start(text, range.start())..range.start()];
+    if indent.trim().is_empty() {
+        // After the comment trailing the last element, if there is one.
+        let at = rest_of_line(text, range.end());
+        let at = if text[range.end()..at].trim().is_empty() {
+            range.end()
+        } else {
+            at
+        };
+        Patch::insert(at, format!("\n{indent}{new}"))
+    } else {
+        Patch::insert(range.end(), format!(" {new}"))
+    }
+}
+
+/// Where the line holding `offset` starts.
+fn line_start(text: &str, offset: usize) -> usize {
+    text[..offset].rfind('\n').map_or(0, |newline| newline + 1)
+}
+
+/// The end of the blanks and comment following `offset` on its line, which is the
+/// newline ending the line if nothing else follows on it.
+fn rest_of_line(text: &str, offset: usize) -> usize {
+    let rest = &text[offset..];
+    let blanks = rest.len() - rest.trim_start_matches([' ', '\t']).len();
+    let end = offset + blanks;
+    if text[end..].starts_with(";;") {
+        end + text[end..].find('\n').unwrap_or(text.len() - end)
+    } else {
+        end
+    }
+}
@@ -0,0 +1,24 @@
+//! Structural editing of aski documents.
+//!
+//! A [`Transaction`] is a list of [`Edit`]s, each finding the part of the document it
+//! changes by [`NodePath`](aski_syntax::NodePath). Applied to a rope, a transaction either
+//! makes all of its edits and returns the byte [`Patch`]es that did so, or fails and leaves
+//! the text as it was. Patches replace only what the edits change, so comments and layout
+//! around the nodes they touch stay as they are.
+//!
+//! A [`History`] logs transactions and plain text replacements alike with the patches that
+//! revert them, for undo and redo.
+//!
+//! Both work on any [`Buffer`]: a bare rope, or a [`Document`], which keeps the syntax tree
+//! of its text up to date by reparsing only the smallest form around each change.
+
+mod document;
+mod history;
+mod layout;
+mod patch;
+mod transaction;
+
+pub use crate::document::Document;
+pub use crate::history::History;
+pub use crate::patch::{Buffer, Patch};
+pub use crate::transaction::{Edit, EditError, Transaction};
@@ -0,0 +1,85 @@
+use aski_syntax::TextRange;
+use ropey::Rope;
+
+/// A replacement of one byte range of a document's text.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Patch {
+    pub range: TextRange,
+    pub replacement: String,
+}
+
+impl Patch {
+    pub fn new(range: TextRange, replacement: impl Into<String>) -> Patch {
+        Patch {
+            range,
+            replacement: replacement.into(),
+        }
+    }
+
+    pub fn insert(offset: usize, text: impl Into<String>) -> Patch {
+        Patch::new(TextRange::empty(offset), text)
+    }
+
+    pub fn delete(range: TextRange) -> Patch {
+        Patch::new(range, "")
+    }
+
+    pub fn apply(&self, text: &mut String) {
+        text.replace_range(self.range.start()..self.range.end(), &self.replacement);
+    }
+
+    /// Applies the patch to `rope` and returns the patch that undoes it.
+    pub fn apply_to_rope(&self, rope: &mut Rope) -> Patch {
+        let start = rope.byte_to_char(self.range.start());
+        let end = rope.byte_to_char(self.range.end());
+        let replaced = rope.slice(start..end).to_string();
+        rope.remove(start..end);
+        rope.insert(start, &self.replacement);
+        Patch::new(
+            TextRange::at(self.range.start(), self.replacement.len()),
+            replaced,
+        )
+    }
+
+    /// The same replacement without the bytes it would leave as they are in `text`: its
+    /// common prefix and suffix with the text it replaces.
+    pub fn minimized(&self, text: &str) -> Patch {
+        let old = &text[self.range.start()..self.range.end()];
+        let new = self.replacement.as_str();
+        let prefix = common_len(old.chars(), new.chars());
+        let (old, new) = (&old[prefix..], &new[prefix..]);
+        let suffix = common_len(old.chars().rev(), new.chars().rev());
+        let start = self.range.start() + prefix;
+        Patch::new(
+            TextRange::new(start, start + old.len() - suffix),
+            &new[..new.len() - suffix],
+        )
+    }
+}
+
+/// Text that patches apply to: a bare rope, or a [`Document`](crate::Document) keeping its
+/// syntax tree up to date as well.
+pub trait Buffer {
+    fn text(&self) -> String;
+
+    /// Applies `patch` and returns the patch that undoes it.
+    fn apply(&mut self, patch: &Patch) -> Patch;
+}
+
+impl Buffer for Rope {
+    fn text(&self) -> String {
+        self.to_string()
+    }
+
+    fn apply(&mut self, patch: &Patch) -> Patch {
+        patch.apply_to_rope(self)
+    }
+}
+
+/// How many bytes two walks over characters go through before they differ.
+fn common_len(a: impl Iterator<Item = char>, b: impl Iterator<Item = char>) -> usize {
+    a.zip(b)
+        .take_while(|(a, b)| a == b)
+        .map(|(c, _)| c.len_utf8())
+        .sum()
+}
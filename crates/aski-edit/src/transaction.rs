@@ -0,0 +1,386 @@
+// Transactions of structural edits.
+//
+// An edit says what to change in terms of the schema, like adding a field or renaming a
+// type, and finds what it changes by node path. A transaction turns its edits into byte
+// patches one after the other, each against the text the ones before it left, so later
+// edits see what earlier ones did. The patches only reach the text once every edit has
+// succeeded, so a transaction applies entirely or not at all.
+//
+// Renames follow names into default values: renaming a type renames it at the head of
+// `(type variant ...)` values, and renaming a field renames the keys that name it in
+// `{field: value ...}` values of its record or struct variant. Names of variants, and of
+// fields of other records, are left alone even when they are spelled the same.
+
+use std::cmp::Reverse;
+use std::fmt;
+
+use aski_sema::{field_keys, lower_source_file, Primitive};
+use aski_syntax::ast::{self, AstNode, HasName, HasTypeParams};
+use aski_syntax::{
+    is_symbol_char, is_symbol_start, parse, NodePath, SyntaxKind, SyntaxNode, SyntaxToken,
+};
+
+use crate::layout::{insertion, removal};
+use crate::{Buffer, Patch};
+
+use SyntaxKind::*;
+
+/// One structural change to a document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Edit {
+    /// Adds `name: ty` after the last field of the record or struct variant at `target`.
+    AddField {
+        target: NodePath,
+        name: String,
+        ty: String,
+    },
+    /// Removes a named field, or a positional field of a tuple.
+    RemoveField {
+        field: NodePath,
+    },
+    /// Renames a named field along with the keys naming it in default values.
+    RenameField {
+        field: NodePath,
+        name: String,
+    },
+    /// Replaces the type of a named field, or the type `field` addresses, like a
+    /// positional field or `user-id/type`.
+    ChangeFieldType {
+        field: NodePath,
+        ty: String,
+    },
+    /// Adds `(name body)` after the last variant of the enum at `target`. An empty `body`
+    /// adds a unit variant.
+    AddVariant {
+        target: NodePath,
+        name: String,
+        body: String,
+    },
+    RemoveVariant {
+        variant: NodePath,
+    },
+    /// Renames the declaration `decl` along with every reference to it, in type position or
+    /// heading a variant value.
+    RenameType {
+        decl: String,
+        name: String,
+    },
+}
+
+/// Edits applied together, in order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Transaction {
+    pub edits: Vec<Edit>,
+}
+
+/// Why a transaction could not be applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EditError {
+    /// The index of the edit that failed.
+    pub edit: usize,
+    pub message: String,
+}
+
+impl fmt::Display for EditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for EditError {}
+
+impl Transaction {
+    pub fn new(edits: impl IntoIterator<Item = Edit>) -> Transaction {
+        Transaction {
+            edits: edits.into_iter().collect(),
+        }
+    }
+
+    /// The patches that apply the transaction to `text`, in the order they apply. Each is
+    /// relative to the text left by the ones before it.
+    pub fn patches(&self, text: &str) -> Result<Vec<Patch>, EditError> {
+        let mut text = text.to_string();
+        let mut patches = Vec::new();
+        for (index, edit) in self.edits.iter().enumerate() {
+            let file = parse(&text).tree();
+            let mut edit_patches = edit.patches(&file, &text).map_err(|message| EditError {
+                edit: index,
+                message,
+            })?;
+            // Back to front, so applying one leaves the ranges of the rest in place.
+            edit_patches.sort_by_key(|patch| Reverse(patch.range.start()));
+            for patch in edit_patches {
+                let patch = patch.minimized(&text);
+                if patch.range.is_empty() && patch.replacement.is_empty() {
+                    continue;
+                }
+                patch.apply(&mut text);
+                patches.push(patch);
+            }
+        }
+        Ok(patches)
+    }
+
+    /// Applies the transaction to `buffer` and returns the patches it made, or leaves
+    /// `buffer` as it was if any edit fails.
+    pub fn apply(&self, buffer: &mut impl Buffer) -> Result<Vec<Patch>, EditError> {
+        let patches = self.patches(&buffer.text())?;
+        for patch in &patches {
+            buffer.apply(patch);
+        }
+        Ok(patches)
+    }
+}
+
+impl Edit {
+    fn patches(&self, file: &ast::SourceFile, text: &str) -> Result<Vec<Patch>, String> {
+        match self {
+            Edit::AddField { target, name, ty } => {
+                check_name(name)?;
+                check_type(ty)?;
+                let node = resolve(file, target)?;
+                let list = match node.kind() {
+                    RECORD_DECL => ast::RecordDecl::cast(node).and_then(|r| r.field_list()),
+                    STRUCT_VARIANT => ast::StructVariant::cast(node).and_then(|v| v.field_list()),
+                    _ => return Err(format!("`{target}` is not a record or struct variant")),
+                };
+                let list = list.ok_or_else(|| format!("`{target}` has no field list"))?;
+                if list.fields().any(|field| has_name(&field, name)) {
+                    return Err(format!("`{target}` already has a field `{name}`"));
+                }
+                let open = list
+                    .syntax()
+                    .first_token()
+                    .expect("field lists start with `{`");
+                let last = list.fields().last();
+                Ok(vec![insertion(
+                    text,
+                    last.as_ref().map(AstNode::syntax),
+                    open.text_range().end(),
+                    "",
+                    &format!("{name}: {ty}"),
+                )])
+            }
+            Edit::RemoveField { field } => {
+                let node = resolve(file, field)?;
+                let range = if node.kind() == FIELD {
+                    removal(text, &node, |kind| kind == FIELD)
+                } else if is_positional(&node) {
+                    removal(text, &node, ast::TypeExpr::can_cast)
+                } else {
+                    return Err(format!("`{field}` is not a field"));
+                };
+                Ok(vec![Patch::delete(range)])
+            }
+            Edit::RenameField { field, name } => {
+                check_name(name)?;
+                let node = resolve(file, field)?;
+                let renamed = ast::Field::cast(node.clone())
+                    .and_then(|field| field.name()?.token())
+                    .ok_or_else(|| format!("`{field}` is not a named field"))?;
+                let taken = node
+                    .parent()
+                    .into_iter()
+                    .flat_map(|list| list.children())
+                    .filter_map(ast::Field::cast)
+                    .any(|sibling| sibling.syntax() != &node && has_name(&sibling, name));
+                if taken {
+                    return Err(format!("a field `{name}` already exists next to `{field}`"));
+                }
+                let mut ranges = vec![renamed.text_range()];
+                if let Some((owner, variant)) = field_owner(&node) {
+                    let schema = lower_source_file(file).0;
+                    ranges.extend(field_keys(
+                        &schema,
+                        &owner,
+                        variant.as_deref(),
+                        renamed.text(),
+                    ));
+                }
+                Ok(ranges
+                    .into_iter()
+                    .map(|range| Patch::new(range, name))
+                    .collect())
+            }
+            Edit::ChangeFieldType { field, ty } => {
+                check_type(ty)?;
+                let node = resolve(file, field)?;
+                let old = if node.kind() == FIELD {
+                    ast::Field::cast(node)
+                        .and_then(|field| field.ty())
+                        .ok_or_else(|| format!("`{field}` has no type"))?
+                        .syntax()
+                        .clone()
+                } else if ast::TypeExpr::can_cast(node.kind()) {
+                    node
+                } else {
+                    return Err(format!("`{field}` is not a field or type"));
+                };
+                Ok(vec![Patch::new(old.text_range(), ty)])
+            }
+            Edit::AddVariant { target, name, body } => {
+                check_name(name)?;
+                check_variant_body(body)?;
+                let node = resolve(file, target)?;
+                let decl = ast::EnumDecl::cast(node)
+                    .ok_or_else(|| format!("`{target}` is not an enum"))?;
+                if decl.variants().any(|variant| has_name(&variant, name)) {
+                    return Err(format!("`{target}` already has a variant `{name}`"));
+                }
+                let head = decl
+                    .syntax()
+                    .children()
+                    .find(|child| matches!(child.kind(), NAME | GENERIC_NAME))
+                    .ok_or_else(|| format!("`{target}` has no name"))?;
+                let last = decl.variants().last();
+                let variant = match body.trim() {
+                    "" => format!("({name})"),
+                    body => format!("({name} {body})"),
+                };
+                Ok(vec![insertion(
+                    text,
+                    last.as_ref().map(AstNode::syntax),
+                    head.text_range().end(),
+                    " ",
+                    &variant,
+                )])
+            }
+            Edit::RemoveVariant { variant } => {
+                let node = resolve(file, variant)?;
+                if !ast::Variant::can_cast(node.kind()) {
+                    return Err(format!("`{variant}` is not a variant"));
+                }
+                Ok(vec![Patch::delete(removal(
+                    text,
+                    &node,
+                    ast::Variant::can_cast,
+                ))])
+            }
+            Edit::RenameType { decl, name } => {
+                check_name(name)?;
+                if Primitive::from_name(name).is_some() {
+                    return Err(format!("`{name}` is a primitive type"));
+                }
+                let schema = file.schema().ok_or("the document has no schema")?;
+                if name != decl && schema.decls().any(|other| has_name(&other, name)) {
+                    return Err(format!("type `{name}` is already declared"));
+                }
+                let renamed = resolve(file, &NodePath::decl(decl.as_str()))
+                    .ok()
+                    .and_then(ast::Decl::cast)
+                    .and_then(|decl| decl.name()?.token())
+                    .ok_or_else(|| format!("there is no declaration `{decl}`"))?;
+                let mut tokens = vec![renamed];
+                tokens.extend(references(&schema, decl));
+                Ok(tokens
+                    .into_iter()
+                    .map(|token| Patch::new(token.text_range(), name))
+                    .collect())
+            }
+        }
+    }
+}
+
+fn resolve(file: &ast::SourceFile, path: &NodePath) -> Result<SyntaxNode, String> {
+    path.resolve(file)
+        .ok_or_else(|| format!("`{path}` does not exist"))
+}
+
+/// Whether `node` is a positional field of a tuple declaration, variant or type.
+fn is_positional(node: &SyntaxNode) -> bool {
+    ast::TypeExpr::can_cast(node.kind())
+        && node
+            .parent()
+            .is_some_and(|parent| matches!(parent.kind(), TUPLE_FIELD_LIST | TUPLE_TYPE))
+}
+
+fn has_name(node: &impl HasName, name: &str) -> bool {
+    node.name().is_some_and(|found| found.text() == name)
+}
+
+/// The name tokens of every reference to the declaration `decl`, in type position or
+/// heading a variant value, leaving out names of type parameters that shadow it. The
+/// types of impls count, both the implementing type and those of member signatures; the
+/// traits they implement are not types of the schema.
+fn references(schema: &ast::Schema, decl: &str) -> Vec<SyntaxToken> {
+    let decls = schema
+        .decls()
+        .filter(|item| item.type_params().all(|param| param.text() != decl))
+        .map(|item| item.syntax().clone());
+    let impls = schema.impls().map(|imp| imp.syntax().clone());
+    decls
+        .chain(impls)
+        .flat_map(|node| {
+            node.descendants()
+                .filter_map(|node| match node.kind() {
+                    NAMED_TYPE => ast::NamedType::cast(node)?.name_ref(),
+                    GENERIC_TYPE => ast::GenericType::cast(node)?.name_ref(),
+                    VARIANT_VALUE => ast::VariantValue::cast(node)?.type_ref(),
+                    _ => None,
+                })
+                .filter_map(|name| name.token())
+                .filter(|token| token.text() == decl)
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// The name of the declaration a field belongs to, and of its struct variant if it is a
+/// variant's field.
+fn field_owner(field: &SyntaxNode) -> Option<(String, Option<String>)> {
+    let parent = field.parent()?.parent()?;
+    if let Some(record) = ast::RecordDecl::cast(parent.clone()) {
+        return Some((record.name()?.text().to_string(), None));
+    }
+    let variant = ast::StructVariant::cast(parent)?;
+    let decl = ast::EnumDecl::cast(variant.syntax().parent()?)?;
+    Some((
+        decl.name()?.text().to_string(),
+        Some(variant.name()?.text().to_string()),
+    ))
+}
+
+fn check_name(name: &str) -> Result<(), String> {
+    let mut chars = name.chars();
+    if chars.next().is_some_and(is_symbol_start) && chars.all(is_symbol_char) {
+        Ok(())
+    } else {
+        Err(format!("`{name}` is not a valid name"))
+    }
+}
+
+fn check_type(ty: &str) -> Result<(), String> {
+    let probe = parse(&format!("(aski/v1 (newtype probe {ty}))"));
+    let parsed = probe
+        .tree()
+        .schema()
+        .and_then(|schema| schema.decls().next())
+        .and_then(|decl| match decl {
+            ast::Decl::Newtype(newtype) => newtype.ty(),
+            _ => None,
+        });
+    match parsed {
+        Some(parsed) if probe.errors().is_empty() && parsed.syntax().text() == ty.trim() => Ok(()),
+        _ => Err(format!("`{ty}` is not a type expression")),
+    }
+}
+
+fn check_variant_body(body: &str) -> Result<(), String> {
+    let variant = format!("(probe {body})");
+    let probe = parse(&format!("(aski/v1 (enum probe {variant}))"));
+    let parsed: Vec<_> = probe
+        .tree()
+        .schema()
+        .and_then(|schema| schema.decls().next())
+        .into_iter()
+        .filter_map(|decl| match decl {
+            ast::Decl::Enum(decl) => Some(decl),
+            _ => None,
+        })
+        .flat_map(|decl| decl.variants())
+        .collect();
+    match parsed.as_slice() {
+        [parsed] if probe.errors().is_empty() && parsed.syntax().text() == variant => Ok(()),
+        _ => Err(format!("`{body}` is not a variant body")),
+    }
+}
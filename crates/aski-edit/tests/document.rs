@@ -0,0 +1,156 @@
+use aski_edit::{Buffer, Document, Edit, History, Patch, Transaction};
+use aski_syntax::ast::AstNode;
+use aski_syntax::{parse, GreenNode, TextRange};
+
+const ALL_TYPES: &str = include_str!("../../../encoder/drafts/all-types.aski");
+
+/// Checks that the tree and errors of `document` are those of a full parse of its text.
+fn assert_up_to_date(document: &Document) {
+    let parse = parse(&document.rope().to_string());
+    assert_eq!(document.green(), parse.green());
+    assert_eq!(document.errors(), parse.errors());
+}
+
+fn decls(document: &Document) -> Vec<(String, GreenNode)> {
+    let schema = document.tree().schema().unwrap();
+    schema
+        .syntax()
+        .children()
+        .map(|decl| {
+            let name = decl.children().next().map(|name| name.text());
+            (name.unwrap_or_default(), decl.green().clone())
+        })
+        .collect()
+}
+
+#[test]
+fn editing_a_field_leaves_the_other_declarations_alone() {
+    let mut document = Document::new(ALL_TYPES);
+    let before = decls(&document);
+    let u8_type = ALL_TYPES.find("u8-value: u8").unwrap() + "u8-value: ".len();
+    let reparsed = document.replace(TextRange::at(u8_type, "u8".len()), "(? u8)");
+    assert_up_to_date(&document);
+
+    let text = document.rope().to_string();
+    let list = &text[reparsed.start()..reparsed.end()];
+    assert!(
+        list.starts_with("{ bool-value: bool")
+            && list.ends_with("unit-struct-value: unit-struct }")
+    );
+    assert!(list.contains("u8-value: (? u8)"));
+
+    let after = decls(&document);
+    assert_eq!(after.len(), before.len());
+    for ((name, old), (_, new)) in before.iter().zip(&after) {
+        assert_eq!(old.ptr_eq(new), name != "all-types", "{name}");
+    }
+}
+
+#[test]
+fn reparsing_stays_in_the_innermost_form() {
+    let mut document = Document::new(ALL_TYPES);
+    let digits = ALL_TYPES.find("code: (? u32)").unwrap() + "code: (? u".len();
+    let reparsed = document.replace(TextRange::at(digits, 2), "64");
+    assert_eq!(
+        document.rope().to_string()[reparsed.start()..reparsed.end()],
+        *"(? u64)"
+    );
+    assert_up_to_date(&document);
+
+    // A change reaching over the delimiters of a form reparses the one around it, and one
+    // leaving every form around it unbalanced reparses everything.
+    let option = document.rope().to_string().find("(? u64)").unwrap();
+    let reparsed = document.replace(TextRange::at(option, "(? u64)".len()), "u64");
+    let text = document.rope().to_string();
+    assert!(text[reparsed.start()..reparsed.end()].starts_with("{ ok: bool"));
+    assert_up_to_date(&document);
+    let close = text.find("code: u64").unwrap() + "code: u64\n      note: (? string) }".len();
+    assert_eq!(&text[close..close + 1], ")");
+    let reparsed = document.replace(TextRange::at(close, 1), "");
+    assert_eq!(reparsed, TextRange::at(0, document.rope().len_bytes()));
+    assert_up_to_date(&document);
+}
+
+#[test]
+fn every_edit_matches_a_full_parse() {
+    let mut document = Document::new(ALL_TYPES);
+    let original = document.green().clone();
+    let replacements = [
+        "(",
+        ")",
+        "]",
+        "}",
+        "x",
+        " ",
+        ";; note\n",
+        "\"",
+        "(vec u8)",
+        "{a: u8}",
+    ];
+    for offset in (0..ALL_TYPES.len()).step_by(7) {
+        if !ALL_TYPES.is_char_boundary(offset) {
+            continue;
+        }
+        let deleted = ALL_TYPES[offset..].chars().next().map_or(0, char::len_utf8);
+        let patches = replacements
+            .iter()
+            .map(|text| Patch::insert(offset, *text))
+            .chain([Patch::delete(TextRange::at(offset, deleted))]);
+        for patch in patches {
+            let inverse = document.apply(&patch);
+            assert_up_to_date(&document);
+            document.apply(&inverse);
+            assert_eq!(document.rope().to_string(), ALL_TYPES);
+        }
+    }
+    assert_eq!(document.green(), &original);
+    assert_eq!(document.errors(), &[]);
+}
+
+#[test]
+fn transactions_and_histories_keep_documents_parsed() {
+    let mut document = Document::new(ALL_TYPES);
+    let original = document.green().clone();
+    let mut history = History::new();
+    let add_tags = Transaction::new([Edit::AddField {
+        target: "all-types".parse().unwrap(),
+        name: "tags".into(),
+        ty: "(set string)".into(),
+    }]);
+    history.apply(&mut document, &add_tags).unwrap();
+    assert!(document.text().contains("tags: (set string)"));
+    assert_up_to_date(&document);
+
+    history.undo(&mut document).unwrap();
+    assert_eq!(document.text(), ALL_TYPES);
+    assert_eq!(document.green(), &original);
+}
+
+#[test]
+fn half_typed_documents_match_a_full_parse() {
+    let text = "\
+(aski/v1
+  (record status { ok: bool code: (?
+  (enum e
+    (a {x: u8})
+    (b [u8 u8]))
+  (newtype id uuid))
+";
+    let mut document = Document::new(text);
+    let replacements = [
+        "\n  (newtype x y)",
+        "\n(record r {})",
+        ")",
+        "}",
+        "(enum f (g))",
+    ];
+    for offset in 0..text.len() {
+        for replacement in replacements {
+            let inverse = document.apply(&Patch::insert(offset, replacement));
+            assert_up_to_date(&document);
+            document.apply(&inverse);
+            assert_eq!(document.rope().to_string(), text);
+        }
+    }
+    assert_up_to_date(&document);
+}
@@ -0,0 +1,109 @@
+use aski_edit::{Edit, History, Transaction};
+use aski_syntax::{parse, GreenNode, NodePath, TextRange};
+use ropey::Rope;
+
+const ALL_TYPES: &str = include_str!("../../../encoder/drafts/all-types.aski");
+
+fn path(text: &str) -> NodePath {
+    text.parse().unwrap()
+}
+
+fn green(rope: &Rope) -> GreenNode {
+    parse(&rope.to_string()).green().clone()
+}
+
+fn add_tags() -> Transaction {
+    Transaction::new([Edit::AddField {
+        target: path("all-types"),
+        name: "tags".into(),
+        ty: "(set string)".into(),
+    }])
+}
+
+fn rename_pair() -> Transaction {
+    Transaction::new([Edit::RenameType {
+        decl: "pair".into(),
+        name: "couple".into(),
+    }])
+}
+
+#[test]
+fn textual_and_structural_edits_undo_in_order() {
+    let mut rope = Rope::from_str(ALL_TYPES);
+    let original = green(&rope);
+    let mut history = History::new();
+
+    history.apply(&mut rope, &add_tags()).unwrap();
+    let added = rope.to_string();
+    let ok = ALL_TYPES.find("{ ok: bool").unwrap() + "{ ".len();
+    history.replace(&mut rope, TextRange::at(ok, "ok".len()), "fine");
+    let replaced = rope.to_string();
+    assert!(replaced.contains("{ fine: bool") && replaced.contains("tags: (set string)"));
+
+    history.undo(&mut rope).unwrap();
+    assert_eq!(rope.to_string(), added);
+    history.undo(&mut rope).unwrap();
+    assert_eq!(rope.to_string(), ALL_TYPES);
+    assert_eq!(green(&rope), original);
+    assert_eq!(history.undo(&mut rope), None);
+    assert!(!history.can_undo());
+
+    history.redo(&mut rope).unwrap();
+    history.redo(&mut rope).unwrap();
+    assert_eq!(rope.to_string(), replaced);
+    assert_eq!(history.redo(&mut rope), None);
+}
+
+#[test]
+fn undo_applies_the_inverse_patches() {
+    let mut rope = Rope::from_str(ALL_TYPES);
+    let mut history = History::new();
+    let patches = history.apply(&mut rope, &rename_pair()).unwrap();
+    let inverses = history.undo(&mut rope).unwrap();
+    assert_eq!(inverses.len(), patches.len());
+    for (patch, inverse) in patches.iter().rev().zip(&inverses) {
+        assert_eq!(inverse.range.start(), patch.range.start());
+        assert_eq!(inverse.range.len(), patch.replacement.len());
+    }
+    assert_eq!(history.redo(&mut rope).unwrap(), patches);
+}
+
+#[test]
+fn groups_undo_as_one_step() {
+    let mut rope = Rope::from_str(ALL_TYPES);
+    let mut history = History::new();
+    history.begin_group();
+    history.apply(&mut rope, &add_tags()).unwrap();
+    history.begin_group();
+    history.apply(&mut rope, &rename_pair()).unwrap();
+    history.end_group();
+    history.replace(&mut rope, TextRange::empty(0), ";; edited\n");
+    history.end_group();
+    let edited = rope.to_string();
+
+    history.undo(&mut rope).unwrap();
+    assert_eq!(rope.to_string(), ALL_TYPES);
+    assert!(!history.can_undo());
+    history.redo(&mut rope).unwrap();
+    assert_eq!(rope.to_string(), edited);
+}
+
+#[test]
+fn new_edits_drop_what_was_undone() {
+    let mut rope = Rope::from_str(ALL_TYPES);
+    let mut history = History::new();
+    history.apply(&mut rope, &add_tags()).unwrap();
+    history.undo(&mut rope).unwrap();
+    assert!(history.can_redo());
+    history.apply(&mut rope, &rename_pair()).unwrap();
+    assert!(!history.can_redo());
+
+    // Failed transactions are not steps.
+    let failing = Transaction::new([Edit::RemoveVariant {
+        variant: path("shape/variants/missing"),
+    }]);
+    assert!(history.apply(&mut rope, &failing).is_err());
+    history.undo(&mut rope).unwrap();
+    assert_eq!(rope.to_string(), ALL_TYPES);
+    assert!(!history.can_undo());
+}
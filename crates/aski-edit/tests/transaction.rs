@@ -0,0 +1,355 @@
+use aski_edit::{Edit, Patch, Transaction};
+use aski_syntax::{parse, NodePath, TextRange};
+use ropey::Rope;
+
+const ALL_TYPES: &str = include_str!("../../../encoder/drafts/all-types.aski");
+
+fn path(text: &str) -> NodePath {
+    text.parse().unwrap()
+}
+
+/// Applies `edits` to the draft, checking that the patches describe the change and that
+/// the result still parses cleanly.
+fn edit(edits: impl IntoIterator<Item = Edit>) -> (String, Vec<Patch>) {
+    let mut rope = Rope::from_str(ALL_TYPES);
+    let patches = Transaction::new(edits).apply(&mut rope).unwrap();
+    let mut text = ALL_TYPES.to_string();
+    for patch in &patches {
+        patch.apply(&mut text);
+    }
+    assert_eq!(rope.to_string(), text);
+    assert_eq!(parse(&text).errors(), &[]);
+    (text, patches)
+}
+
+fn offset_of(needle: &str) -> usize {
+    ALL_TYPES.find(needle).unwrap()
+}
+
+#[test]
+fn added_fields_follow_the_layout_of_the_last_one() {
+    let (text, patches) = edit([Edit::AddField {
+        target: path("all-types"),
+        name: "tags".into(),
+        ty: "(set string)".into(),
+    }]);
+    let end = offset_of("unit-struct-value: unit-struct") + "unit-struct-value: unit-struct".len();
+    assert_eq!(patches, [Patch::insert(end, "\n      tags: (set string)")]);
+    assert!(text.contains("unit-struct-value: unit-struct\n      tags: (set string) } )"));
+
+    let (text, _) = edit([
+        Edit::AddField {
+            target: path("unit-struct"),
+            name: "id".into(),
+            ty: "u64".into(),
+        },
+        Edit::AddField {
+            target: path("shape/variants/circle"),
+            name: "label".into(),
+            ty: "string".into(),
+        },
+    ]);
+    assert!(text.contains("(record unit-struct {id: u64})"));
+    assert!(text.contains("(circle {r: f64 label: string})"));
+}
+
+#[test]
+fn changed_types_patch_only_what_differs() {
+    let (text, patches) = edit([Edit::ChangeFieldType {
+        field: path("status/fields/code"),
+        ty: "(? u64)".into(),
+    }]);
+    let digits = offset_of("code: (? u32)") + "code: (? u".len();
+    assert_eq!(patches, [Patch::new(TextRange::at(digits, 2), "64")]);
+    assert!(text.contains("      code: (? u64)\n"));
+
+    let (text, _) = edit([
+        Edit::ChangeFieldType {
+            field: path("pair/fields/1"),
+            ty: "i64".into(),
+        },
+        Edit::ChangeFieldType {
+            field: path("user-id/type"),
+            ty: "string".into(),
+        },
+    ]);
+    assert!(text.contains("(tuple pair [i32 i64])"));
+    assert!(text.contains("(newtype user-id string)"));
+}
+
+#[test]
+fn removals_take_their_line_and_keep_the_comments_around_them() {
+    let (text, patches) = edit([Edit::RemoveVariant {
+        variant: path("shape/variants/rect"),
+    }]);
+    let line = offset_of("    (rect [f64 f64])\n");
+    assert_eq!(
+        patches,
+        [Patch::delete(TextRange::at(
+            line,
+            "    (rect [f64 f64])\n".len()
+        ))]
+    );
+    assert!(text.contains(
+        "  ;; The declaration is still compact; the runtime knows the envelope rule globally.\n  (enum shape\n    (unit)\n    (circle {r: f64})\n    (named string))"
+    ));
+
+    let (text, _) = edit([
+        Edit::RemoveField {
+            field: path("all-types/fields/mixed-tuple"),
+        },
+        Edit::RemoveField {
+            field: path("status/fields/ok"),
+        },
+        Edit::RemoveField {
+            field: path("status/fields/note"),
+        },
+        Edit::RemoveField {
+            field: path("pair/fields/0"),
+        },
+    ]);
+    assert!(!text.contains("sugar: bare vector"));
+    assert!(
+        text.contains("      string-vector: (vec string)\n      u16-array-len-3: (array 3 u16)\n")
+    );
+    assert!(text.contains("(record status\n    { code: (? u32) })"));
+    assert!(text.contains("(tuple pair [i32])"));
+    assert!(text.contains(";; Coverage record that forces the full Serde-compatible surface area."));
+}
+
+#[test]
+fn renamed_types_rename_their_references() {
+    let (text, patches) = edit([Edit::RenameType {
+        decl: "error-code".into(),
+        name: "failure-code".into(),
+    }]);
+    assert_eq!(patches.len(), 2);
+    assert!(patches
+        .iter()
+        .all(|patch| patch.replacement == "failure" && patch.range.len() == "error".len()));
+    let expected = ALL_TYPES
+        .replace("(enum error-code", "(enum failure-code")
+        .replace("(result string error-code)", "(result string failure-code)");
+    assert_eq!(text, expected);
+
+    // A type parameter named like the type shadows it.
+    let source = "(aski/v1 (newtype t u8) (newtype (box-of t) (box t)) (record r {a: t}))";
+    let mut rope = Rope::from_str(source);
+    Transaction::new([Edit::RenameType {
+        decl: "t".into(),
+        name: "tee".into(),
+    }])
+    .apply(&mut rope)
+    .unwrap();
+    assert_eq!(
+        rope.to_string(),
+        "(aski/v1 (newtype tee u8) (newtype (box-of t) (box t)) (record r {a: tee}))"
+    );
+}
+
+#[test]
+fn renamed_types_rename_their_impls() {
+    let source = r#"(aski/v1
+  (newtype age u16)
+  (newtype display string)
+  (impl display age {fmt [(type-signature [self] display) (function-body "age")]})
+  (impl try-from age {try-from [(type-signature [(vec age)] (result self age))]}))"#;
+    let rename = |decl: &str, name: &str| {
+        let mut rope = Rope::from_str(source);
+        Transaction::new([Edit::RenameType {
+            decl: decl.into(),
+            name: name.into(),
+        }])
+        .apply(&mut rope)
+        .unwrap();
+        rope.to_string()
+    };
+    assert_eq!(
+        rename("age", "years"),
+        source
+            .replace("(newtype age", "(newtype years")
+            .replace("display age", "display years")
+            .replace("try-from age", "try-from years")
+            .replace(
+                "[(vec age)] (result self age)",
+                "[(vec years)] (result self years)"
+            )
+    );
+
+    // The trait an impl implements is not a type, even when a type shares its name.
+    assert_eq!(
+        rename("display", "label"),
+        source
+            .replace("(newtype display", "(newtype label")
+            .replace("[self] display)", "[self] label)")
+    );
+}
+
+/// A schema whose defaults spell type, variant and field names alike.
+const DEFAULTS: &str = "(aski/v1
+  (record point {x: f64 y: f64})
+  (enum mode (fast) (point) (moved {x: i64}))
+  (record settings
+    { origin: point (default {x: 0 y: 1})
+      mode: mode (default (mode point))
+      moves: (vec mode) (default [(mode moved {x: 2})])
+      x: (? point) (default {x: 3 y: 4}) })
+)";
+
+fn edit_defaults(edit: Edit) -> String {
+    let mut rope = Rope::from_str(DEFAULTS);
+    Transaction::new([edit]).apply(&mut rope).unwrap();
+    let text = rope.to_string();
+    assert_eq!(parse(&text).errors(), &[]);
+    text
+}
+
+#[test]
+fn renames_reach_into_default_values() {
+    // The variant `point` and the field `x` of `settings` keep their names.
+    assert_eq!(
+        edit_defaults(Edit::RenameType {
+            decl: "point".into(),
+            name: "spot".into(),
+        }),
+        DEFAULTS
+            .replace("(record point", "(record spot")
+            .replace("origin: point", "origin: spot")
+            .replace("(? point)", "(? spot)")
+    );
+    assert_eq!(
+        edit_defaults(Edit::RenameType {
+            decl: "mode".into(),
+            name: "speed".into(),
+        }),
+        DEFAULTS
+            .replace("(enum mode", "(enum speed")
+            .replace(
+                "mode: mode (default (mode point))",
+                "mode: speed (default (speed point))"
+            )
+            .replace(
+                "(vec mode) (default [(mode moved",
+                "(vec speed) (default [(speed moved"
+            )
+    );
+
+    // Only the keys of values of `point` name its field.
+    assert_eq!(
+        edit_defaults(Edit::RenameField {
+            field: path("point/fields/x"),
+            name: "across".into(),
+        }),
+        DEFAULTS
+            .replace("{x: f64", "{across: f64")
+            .replace("{x: 0", "{across: 0")
+            .replace("{x: 3", "{across: 3")
+    );
+    assert_eq!(
+        edit_defaults(Edit::RenameField {
+            field: path("mode/variants/moved/fields/x"),
+            name: "by".into(),
+        }),
+        DEFAULTS
+            .replace("{x: i64}", "{by: i64}")
+            .replace("{x: 2}", "{by: 2}")
+    );
+}
+
+#[test]
+fn later_edits_see_earlier_ones() {
+    let (text, _) = edit([
+        Edit::AddVariant {
+            target: path("shape"),
+            name: "square".into(),
+            body: "{side: f64}".into(),
+        },
+        Edit::RenameField {
+            field: path("shape/variants/square/fields/side"),
+            name: "edge".into(),
+        },
+        Edit::AddVariant {
+            target: path("shape"),
+            name: "empty".into(),
+            body: String::new(),
+        },
+    ]);
+    assert!(text.contains("    (named string)\n    (square {edge: f64})\n    (empty))"));
+}
+
+#[test]
+fn failing_transactions_change_nothing() {
+    let failures = [
+        (
+            Edit::AddField {
+                target: path("status"),
+                name: "ok".into(),
+                ty: "bool".into(),
+            },
+            "`status` already has a field `ok`",
+        ),
+        (
+            Edit::AddField {
+                target: path("shape"),
+                name: "x".into(),
+                ty: "u8".into(),
+            },
+            "`shape` is not a record or struct variant",
+        ),
+        (
+            Edit::ChangeFieldType {
+                field: path("status/fields/code"),
+                ty: "(vec".into(),
+            },
+            "`(vec` is not a type expression",
+        ),
+        (
+            Edit::RenameField {
+                field: path("status/fields/missing"),
+                name: "x".into(),
+            },
+            "`status/fields/missing` does not exist",
+        ),
+        (
+            Edit::RenameField {
+                field: path("status/fields/ok"),
+                name: "has space".into(),
+            },
+            "`has space` is not a valid name",
+        ),
+        (
+            Edit::RenameType {
+                decl: "blob".into(),
+                name: "shape".into(),
+            },
+            "type `shape` is already declared",
+        ),
+        (
+            Edit::RenameType {
+                decl: "blob".into(),
+                name: "bytes".into(),
+            },
+            "`bytes` is a primitive type",
+        ),
+        (
+            Edit::AddVariant {
+                target: path("message"),
+                name: "ping".into(),
+                body: String::new(),
+            },
+            "`message` already has a variant `ping`",
+        ),
+    ];
+    for (failure, message) in failures {
+        let mut rope = Rope::from_str(ALL_TYPES);
+        let renamed = Edit::RenameType {
+            decl: "pair".into(),
+            name: "couple".into(),
+        };
+        let error = Transaction::new([renamed, failure])
+            .apply(&mut rope)
+            .unwrap_err();
+        assert_eq!((error.edit, error.message.as_str()), (1, message));
+        assert_eq!(rope.to_string(), ALL_TYPES);
+    }
+}
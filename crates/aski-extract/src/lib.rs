@@ -0,0 +1,553 @@
+//! Derive aski schemas from existing Rust types.
+//!
+//! [`extract`] reads a Rust source file, picks out every struct and enum deriving serde's
+//! `Serialize` or `Deserialize`, and translates it into the equivalent aski/v1
+//! declaration. It is the reverse of `aski-codegen`: running the generated schema back
+//! through the code generator yields the original types.
+//!
+//! Anything aski cannot express, like references, lifetimes or serde attributes other
+//! than the canonical enum envelope, is an error rather than a silent approximation.
+
+mod print;
+
+use std::fmt;
+
+use aski_codegen::{from_rust_field, from_rust_type, to_rust_field, to_rust_type};
+use aski_sema::{
+    Field, Item, ItemKind, Name, Primitive, Schema, TypeKind, TypeRef, Variant, VariantBody,
+    ENUM_CONTENT, ENUM_TAG,
+};
+use aski_syntax::TextRange;
+use proc_macro2::Span;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+
+pub use crate::print::print_schema;
+
+/// Something worth telling the user about a Rust item, with where it is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    pub message: String,
+    /// 1-based line of the offending construct.
+    pub line: usize,
+    /// 0-based column, in characters.
+    pub column: usize,
+}
+
+impl Issue {
+    fn new(span: Span, message: impl Into<String>) -> Issue {
+        let start = span.start();
+        Issue {
+            message: message.into(),
+            line: start.line,
+            column: start.column,
+        }
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column + 1, self.message)
+    }
+}
+
+impl std::error::Error for Issue {}
+
+impl From<syn::Error> for Issue {
+    fn from(error: syn::Error) -> Issue {
+        Issue::new(error.span(), error.to_string())
+    }
+}
+
+/// The schema recovered from a Rust file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Extraction {
+    pub schema: Schema,
+    /// Things that were translated but change meaning on the way, like an enum that
+    /// moves from serde's external tagging to the aski envelope.
+    pub warnings: Vec<Issue>,
+}
+
+impl Extraction {
+    /// The schema as aski/v1 source text.
+    pub fn to_aski(&self) -> String {
+        print_schema(&self.schema)
+    }
+}
+
+/// Extracts the aski schema of every serde type in a Rust source file.
+pub fn extract(source: &str) -> Result<Extraction, Issue> {
+    let file = syn::parse_file(source)?;
+    let mut extractor = Extractor::default();
+    let mut items = Vec::new();
+    for item in &file.items {
+        let extracted = match item {
+            syn::Item::Struct(item) if derives_serde(&item.attrs)? => {
+                extractor.item_struct(item)?
+            }
+            syn::Item::Enum(item) if derives_serde(&item.attrs)? => extractor.item_enum(item)?,
+            _ => continue,
+        };
+        items.push(extracted);
+    }
+    Ok(Extraction {
+        schema: Schema {
+            items,
+            module: None,
+            impls: Vec::new(),
+        },
+        warnings: extractor.warnings,
+    })
+}
+
+/// Whether the attributes include `#[derive(Serialize)]` or `#[derive(Deserialize)]`.
+fn derives_serde(attrs: &[syn::Attribute]) -> Result<bool, Issue> {
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("derive")) {
+        let paths =
+            attr.parse_args_with(Punctuated::<syn::Path, syn::Token![,]>::parse_terminated)?;
+        let is_serde = paths.iter().any(|path| {
+            path.segments.last().is_some_and(|segment| {
+                segment.ident == "Serialize" || segment.ident == "Deserialize"
+            })
+        });
+        if is_serde {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Rejects `#[serde(...)]` on anything but enums: aski has no way to spell it.
+fn reject_serde_attrs(attrs: &[syn::Attribute]) -> Result<(), Issue> {
+    match attrs.iter().find(|attr| attr.path().is_ident("serde")) {
+        Some(attr) => Err(Issue::new(
+            attr.span(),
+            "serde attributes have no aski equivalent",
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Like [`reject_serde_attrs`], but allows the `bound` attribute generated for generic
+/// items: it follows from the schema, so there is nothing to extract from it.
+fn reject_item_serde_attrs(attrs: &[syn::Attribute]) -> Result<(), Issue> {
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+        attr.parse_nested_meta(|meta| {
+            if skip_bound(&meta)? {
+                Ok(())
+            } else {
+                Err(meta.error("serde attributes have no aski equivalent"))
+            }
+        })?;
+    }
+    Ok(())
+}
+
+/// Consumes `meta` if it is `bound = "..."` or `bound(serialize = "...", ...)`.
+fn skip_bound(meta: &syn::meta::ParseNestedMeta) -> syn::Result<bool> {
+    if !meta.path.is_ident("bound") {
+        return Ok(false);
+    }
+    if meta.input.peek(syn::Token![=]) {
+        meta.value()?.parse::<syn::LitStr>()?;
+    } else {
+        meta.parse_nested_meta(|inner| {
+            inner.value()?.parse::<syn::LitStr>()?;
+            Ok(())
+        })?;
+    }
+    Ok(true)
+}
+
+/// A name with no source range: extracted items have no aski text behind them yet.
+fn name(text: String) -> Name {
+    Name {
+        text,
+        range: TextRange::default(),
+    }
+}
+
+fn type_ref(kind: TypeKind) -> TypeRef {
+    TypeRef {
+        kind,
+        range: TextRange::default(),
+    }
+}
+
+fn named(name: &str) -> TypeRef {
+    type_ref(TypeKind::Named {
+        name: name.to_string(),
+        args: Vec::new(),
+    })
+}
+
+#[derive(Default)]
+struct Extractor {
+    warnings: Vec<Issue>,
+    /// Type parameters of the item being extracted.
+    params: Vec<String>,
+}
+
+impl Extractor {
+    fn item_struct(&mut self, item: &syn::ItemStruct) -> Result<Item, Issue> {
+        reject_item_serde_attrs(&item.attrs)?;
+        let params = self.generics(&item.generics)?;
+        let kind = match &item.fields {
+            syn::Fields::Named(fields) => ItemKind::Record(self.named_fields(&fields.named)?),
+            syn::Fields::Unit => ItemKind::Record(Vec::new()),
+            syn::Fields::Unnamed(fields) => {
+                let mut types = self.unnamed_fields(&fields.unnamed)?;
+                if types.len() == 1 {
+                    ItemKind::Newtype(types.remove(0))
+                } else {
+                    ItemKind::Tuple(types)
+                }
+            }
+        };
+        Ok(Item {
+            name: self.type_name(&item.ident),
+            params,
+            kind,
+            range: TextRange::default(),
+        })
+    }
+
+    fn item_enum(&mut self, item: &syn::ItemEnum) -> Result<Item, Issue> {
+        self.check_envelope(item)?;
+        let params = self.generics(&item.generics)?;
+        let variants = item
+            .variants
+            .iter()
+            .map(|variant| self.variant(variant))
+            .collect::<Result<_, _>>()?;
+        Ok(Item {
+            name: self.type_name(&item.ident),
+            params,
+            kind: ItemKind::Enum(variants),
+            range: TextRange::default(),
+        })
+    }
+
+    /// Accepts the canonical `#[serde(tag = "variant", content = "data")]` envelope and
+    /// nothing else; an enum without any serde attribute is accepted with a warning.
+    fn check_envelope(&mut self, item: &syn::ItemEnum) -> Result<(), Issue> {
+        let mut tag = None;
+        let mut content = None;
+        let serde_attrs = item
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("serde"));
+        for attr in serde_attrs.clone() {
+            attr.parse_nested_meta(|meta| {
+                if skip_bound(&meta)? {
+                    return Ok(());
+                }
+                let slot = if meta.path.is_ident("tag") {
+                    &mut tag
+                } else if meta.path.is_ident("content") {
+                    &mut content
+                } else {
+                    return Err(meta.error("only the {variant, data} envelope is supported"));
+                };
+                *slot = Some(meta.value()?.parse::<syn::LitStr>()?.value());
+                Ok(())
+            })?;
+        }
+        match (tag.as_deref(), content.as_deref()) {
+            (Some(tag), Some(content)) if tag == ENUM_TAG && content == ENUM_CONTENT => Ok(()),
+            (None, None) => {
+                self.warnings.push(Issue::new(
+                    item.ident.span(),
+                    format!(
+                        "enum `{}` is externally tagged; aski always uses the {{variant, data}} envelope",
+                        item.ident
+                    ),
+                ));
+                Ok(())
+            }
+            _ => Err(Issue::new(
+                serde_attrs.map(|attr| attr.span()).next().unwrap_or(item.ident.span()),
+                "enums must use the {variant, data} envelope: #[serde(tag = \"variant\", content = \"data\")]",
+            )),
+        }
+    }
+
+    fn type_name(&mut self, ident: &syn::Ident) -> Name {
+        let text = from_rust_type(&ident.to_string());
+        self.check_round_trip(ident, &to_rust_type(&text));
+        name(text)
+    }
+
+    fn field_name(&mut self, ident: &syn::Ident) -> Name {
+        let text = from_rust_field(&ident.to_string());
+        self.check_round_trip(ident, &to_rust_field(&text));
+        name(text)
+    }
+
+    /// Warns when generating from the extracted name would not spell `ident` again.
+    fn check_round_trip(&mut self, ident: &syn::Ident, generated: &str) {
+        if *ident != generated {
+            self.warnings.push(Issue::new(
+                ident.span(),
+                format!("`{ident}` will be generated back as `{generated}`"),
+            ));
+        }
+    }
+
+    fn generics(&mut self, generics: &syn::Generics) -> Result<Vec<Name>, Issue> {
+        if let Some(clause) = &generics.where_clause {
+            return Err(Issue::new(
+                clause.span(),
+                "where clauses have no aski equivalent",
+            ));
+        }
+        let mut params = Vec::new();
+        for param in &generics.params {
+            match param {
+                syn::GenericParam::Type(param) if param.bounds.is_empty() => {
+                    params.push(name(param.ident.to_string()));
+                }
+                syn::GenericParam::Type(param) => {
+                    return Err(Issue::new(
+                        param.bounds.span(),
+                        "type parameter bounds have no aski equivalent",
+                    ));
+                }
+                param => {
+                    return Err(Issue::new(
+                        param.span(),
+                        "only type parameters are supported",
+                    ));
+                }
+            }
+        }
+        self.params = params.iter().map(|param| param.text.clone()).collect();
+        Ok(params)
+    }
+
+    fn variant(&mut self, variant: &syn::Variant) -> Result<Variant, Issue> {
+        reject_serde_attrs(&variant.attrs)?;
+        if let Some((_, discriminant)) = &variant.discriminant {
+            return Err(Issue::new(
+                discriminant.span(),
+                "explicit discriminants have no aski equivalent",
+            ));
+        }
+        let body = match &variant.fields {
+            syn::Fields::Unit => VariantBody::Unit,
+            syn::Fields::Named(fields) => VariantBody::Struct(self.named_fields(&fields.named)?),
+            syn::Fields::Unnamed(fields) => {
+                let mut types = self.unnamed_fields(&fields.unnamed)?;
+                if types.len() == 1 {
+                    VariantBody::Newtype(types.remove(0))
+                } else {
+                    VariantBody::Tuple(types)
+                }
+            }
+        };
+        Ok(Variant {
+            name: self.type_name(&variant.ident),
+            body,
+        })
+    }
+
+    fn named_fields<'a>(
+        &mut self,
+        fields: impl IntoIterator<Item = &'a syn::Field>,
+    ) -> Result<Vec<Field>, Issue> {
+        fields
+            .into_iter()
+            .map(|field| {
+                reject_serde_attrs(&field.attrs)?;
+                let ident = field.ident.as_ref().expect("named fields have identifiers");
+                Ok(Field {
+                    name: self.field_name(ident),
+                    ty: self.ty(&field.ty)?,
+                    default: None,
+                })
+            })
+            .collect()
+    }
+
+    fn unnamed_fields<'a>(
+        &mut self,
+        fields: impl IntoIterator<Item = &'a syn::Field>,
+    ) -> Result<Vec<TypeRef>, Issue> {
+        fields
+            .into_iter()
+            .map(|field| {
+                reject_serde_attrs(&field.attrs)?;
+                self.ty(&field.ty)
+            })
+            .collect()
+    }
+
+    fn ty(&mut self, ty: &syn::Type) -> Result<TypeRef, Issue> {
+        match ty {
+            syn::Type::Path(path) if path.qself.is_none() => self.path_type(&path.path),
+            syn::Type::Tuple(tuple) if tuple.elems.is_empty() => Ok(named(Primitive::Unit.name())),
+            syn::Type::Tuple(tuple) => {
+                let elements = tuple
+                    .elems
+                    .iter()
+                    .map(|element| self.ty(element))
+                    .collect::<Result<_, _>>()?;
+                Ok(type_ref(TypeKind::Tuple(elements)))
+            }
+            syn::Type::Array(array) => {
+                let length = match &array.len {
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Int(int),
+                        ..
+                    }) => int.base10_parse::<u64>()?,
+                    len => {
+                        return Err(Issue::new(
+                            len.span(),
+                            "array lengths must be integer literals",
+                        ))
+                    }
+                };
+                let element = self.ty(&array.elem)?;
+                Ok(type_ref(TypeKind::Array(length, Box::new(element))))
+            }
+            syn::Type::Paren(paren) => self.ty(&paren.elem),
+            ty => Err(Issue::new(ty.span(), "this type has no aski equivalent")),
+        }
+    }
+
+    fn path_type(&mut self, path: &syn::Path) -> Result<TypeRef, Issue> {
+        let segment = path
+            .segments
+            .last()
+            .expect("paths have at least one segment");
+        let ident = segment.ident.to_string();
+        check_qualified(path, &ident)?;
+        let args = self.type_args(&segment.arguments)?;
+
+        if args.is_empty() {
+            if self.params.contains(&ident) {
+                return Ok(named(&ident));
+            }
+            if let Some(primitive) = rust_primitive(&ident) {
+                return Ok(named(primitive.name()));
+            }
+        }
+
+        let mut args = args.into_iter();
+        let kind = match (ident.as_str(), args.len()) {
+            ("Option", 1) => TypeKind::Option(Box::new(args.next().unwrap())),
+            ("Result", 2) => TypeKind::Result(
+                Box::new(args.next().unwrap()),
+                Box::new(args.next().unwrap()),
+            ),
+            ("Vec", 1) => {
+                let element = args.next().unwrap();
+                if matches!(&element.kind, TypeKind::Named { name, .. } if name == "u8") {
+                    return Ok(named(Primitive::Bytes.name()));
+                }
+                TypeKind::Vec(Box::new(element))
+            }
+            ("BTreeSet" | "HashSet", 1) => {
+                self.check_ordered(segment, "BTreeSet");
+                TypeKind::Set(Box::new(args.next().unwrap()))
+            }
+            ("BTreeMap" | "HashMap", 2) => {
+                self.check_ordered(segment, "BTreeMap");
+                TypeKind::Map(
+                    Box::new(args.next().unwrap()),
+                    Box::new(args.next().unwrap()),
+                )
+            }
+            ("Box", 1) => TypeKind::Box(Box::new(args.next().unwrap())),
+            _ => TypeKind::Named {
+                name: from_rust_type(&ident),
+                args: args.collect(),
+            },
+        };
+        Ok(type_ref(kind))
+    }
+
+    /// Warns that a hash collection comes back as the ordered one aski generates.
+    fn check_ordered(&mut self, segment: &syn::PathSegment, ordered: &str) {
+        if segment.ident != ordered {
+            self.warnings.push(Issue::new(
+                segment.ident.span(),
+                format!(
+                    "`{}` will be generated back as `{ordered}`, which keeps its contents in order",
+                    segment.ident
+                ),
+            ));
+        }
+    }
+
+    fn type_args(&mut self, arguments: &syn::PathArguments) -> Result<Vec<TypeRef>, Issue> {
+        match arguments {
+            syn::PathArguments::None => Ok(Vec::new()),
+            syn::PathArguments::AngleBracketed(arguments) => arguments
+                .args
+                .iter()
+                .map(|arg| match arg {
+                    syn::GenericArgument::Type(ty) => self.ty(ty),
+                    arg => Err(Issue::new(arg.span(), "only type arguments are supported")),
+                })
+                .collect(),
+            syn::PathArguments::Parenthesized(arguments) => Err(Issue::new(
+                arguments.span(),
+                "function types have no aski equivalent",
+            )),
+        }
+    }
+}
+
+/// Accepts a path qualified with a module only when it is where the standard type of that
+/// name lives; `std::rc::Rc` is not the `rc` a schema would declare.
+fn check_qualified(path: &syn::Path, ident: &str) -> Result<(), Issue> {
+    if path.segments.len() == 1 && path.leading_colon.is_none() {
+        return Ok(());
+    }
+    let module: Vec<_> = path
+        .segments
+        .iter()
+        .take(path.segments.len() - 1)
+        .map(|segment| segment.ident.to_string())
+        .collect();
+    let module = module.join("::");
+    if std_modules(ident).contains(&module.as_str()) {
+        return Ok(());
+    }
+    let qualified = if module.is_empty() {
+        ident.to_string()
+    } else {
+        format!("{module}::{ident}")
+    };
+    Err(Issue::new(
+        path.span(),
+        format!("`{qualified}` has no aski equivalent; only types of this file and standard ones may be named"),
+    ))
+}
+
+/// The modules the standard types extraction understands live in.
+fn std_modules(ident: &str) -> &'static [&'static str] {
+    match ident {
+        "Option" => &["std::option", "core::option"],
+        "Result" => &["std::result", "core::result"],
+        "Vec" => &["std::vec", "alloc::vec"],
+        "Box" => &["std::boxed", "alloc::boxed"],
+        "String" => &["std::string", "alloc::string"],
+        "BTreeSet" | "BTreeMap" => &["std::collections", "alloc::collections"],
+        "HashSet" | "HashMap" => &["std::collections"],
+        "Uuid" => &["uuid"],
+        _ => &[],
+    }
+}
+
+/// The aski primitive a Rust type name stands for.
+fn rust_primitive(ident: &str) -> Option<Primitive> {
+    match ident {
+        "String" => Some(Primitive::String),
+        "Uuid" => Some(Primitive::Uuid),
+        // aski spellings that are not Rust types.
+        "string" | "unit" | "uuid" | "bytes" => None,
+        ident => Primitive::from_name(ident),
+    }
+}
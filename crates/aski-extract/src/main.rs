@@ -0,0 +1,32 @@
+//! `aski-extract <file.rs>` prints the aski schema of the serde types in a Rust file.
+
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let Some(path) = std::env::args().nth(1) else {
+        eprintln!("usage: aski-extract <file.rs>");
+        return ExitCode::FAILURE;
+    };
+    let source = match std::fs::read_to_string(&path) {
+        Ok(source) => source,
+        Err(error) => {
+            eprintln!("{path}: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
+    match aski_extract::extract(&source) {
+        Ok(extraction) => {
+            for warning in &extraction.warnings {
+                let (line, column) = (warning.line, warning.column + 1);
+                eprintln!("{path}:{line}:{column}: warning: {}", warning.message);
+            }
+            print!("{}", extraction.to_aski());
+            ExitCode::SUCCESS
+        }
+        Err(issue) => {
+            let (line, column) = (issue.line, issue.column + 1);
+            eprintln!("{path}:{line}:{column}: error: {}", issue.message);
+            ExitCode::FAILURE
+        }
+    }
+}
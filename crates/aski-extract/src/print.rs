@@ -0,0 +1,97 @@
+// Printing a semantic model back out as aski/v1 source, in the layout all-types.aski uses:
+// one declaration per paragraph, record fields one per line, enum variants one per line.
+
+use std::fmt::Write;
+
+use aski_sema::{Field, Item, ItemKind, Schema, TypeKind, TypeRef, VariantBody};
+use aski_syntax::SCHEMA_VERSION;
+
+/// Renders `schema` as an aski/v1 document.
+pub fn print_schema(schema: &Schema) -> String {
+    let mut out = format!("({SCHEMA_VERSION}\n");
+    for item in &schema.items {
+        out.push('\n');
+        print_item(&mut out, item);
+    }
+    out.push_str(")\n");
+    out
+}
+
+fn print_item(out: &mut String, item: &Item) {
+    let name = if item.params.is_empty() {
+        item.name.text.clone()
+    } else {
+        let params: Vec<_> = item
+            .params
+            .iter()
+            .map(|param| param.text.as_str())
+            .collect();
+        format!("({} {})", item.name.text, params.join(" "))
+    };
+    match &item.kind {
+        ItemKind::Newtype(ty) => writeln!(out, "  (newtype {name} {})", print_type(ty)),
+        ItemKind::Record(fields) if fields.is_empty() => writeln!(out, "  (record {name} {{}})"),
+        ItemKind::Record(fields) => {
+            writeln!(
+                out,
+                "  (record {name}\n    {})",
+                print_fields(fields, "      ")
+            )
+        }
+        ItemKind::Tuple(types) => writeln!(out, "  (tuple {name} {})", print_tuple(types)),
+        ItemKind::Enum(variants) => {
+            write!(out, "  (enum {name}").unwrap();
+            for variant in variants {
+                let body = match &variant.body {
+                    VariantBody::Unit => String::new(),
+                    VariantBody::Newtype(ty) => format!(" {}", print_type(ty)),
+                    VariantBody::Tuple(types) => format!(" {}", print_tuple(types)),
+                    VariantBody::Struct(fields) => format!(" {}", print_inline_fields(fields)),
+                };
+                write!(out, "\n    ({}{body})", variant.name.text).unwrap();
+            }
+            writeln!(out, ")")
+        }
+    }
+    .unwrap();
+}
+
+/// `{ a: T` / `b: U }` with one field per line, continuation lines indented by `indent`.
+fn print_fields(fields: &[Field], indent: &str) -> String {
+    let lines: Vec<_> = fields
+        .iter()
+        .map(|field| format!("{}: {}", field.name.text, print_type(&field.ty)))
+        .collect();
+    format!("{{ {} }}", lines.join(&format!("\n{indent}")))
+}
+
+fn print_inline_fields(fields: &[Field]) -> String {
+    let fields: Vec<_> = fields
+        .iter()
+        .map(|field| format!("{}: {}", field.name.text, print_type(&field.ty)))
+        .collect();
+    format!("{{{}}}", fields.join(" "))
+}
+
+fn print_tuple(types: &[TypeRef]) -> String {
+    let types: Vec<_> = types.iter().map(print_type).collect();
+    format!("[{}]", types.join(" "))
+}
+
+fn print_type(ty: &TypeRef) -> String {
+    match &ty.kind {
+        TypeKind::Named { name, args } if args.is_empty() => name.clone(),
+        TypeKind::Named { name, args } => {
+            let args: Vec<_> = args.iter().map(print_type).collect();
+            format!("({name} {})", args.join(" "))
+        }
+        TypeKind::Option(inner) => format!("(? {})", print_type(inner)),
+        TypeKind::Result(ok, err) => format!("(result {} {})", print_type(ok), print_type(err)),
+        TypeKind::Vec(element) => format!("(vec {})", print_type(element)),
+        TypeKind::Set(element) => format!("(set {})", print_type(element)),
+        TypeKind::Map(key, value) => format!("(map {} {})", print_type(key), print_type(value)),
+        TypeKind::Array(length, element) => format!("(array {length} {})", print_type(element)),
+        TypeKind::Box(inner) => format!("(box {})", print_type(inner)),
+        TypeKind::Tuple(types) => print_tuple(types),
+    }
+}
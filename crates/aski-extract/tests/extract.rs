@@ -0,0 +1,179 @@
+use aski_codegen::generate_rust;
+use aski_extract::extract;
+use aski_sema::lower_source_file;
+use aski_syntax::{lex, parse};
+
+#[path = "../../aski-codegen/tests/draft/mod.rs"]
+mod draft;
+
+const ALL_TYPES_ASKI: &str = include_str!("../../../encoder/drafts/all-types.aski");
+const ALL_TYPES_RS: &str = include_str!("../../../encoder/drafts/all-types.rs");
+
+/// Significant tokens only, so layout and comments do not matter.
+fn tokens(source: &str) -> Vec<&str> {
+    let (tokens, errors) = lex(source);
+    assert_eq!(errors, &[]);
+    tokens
+        .into_iter()
+        .filter(|token| !token.kind.is_trivia())
+        .map(|token| token.text)
+        .collect()
+}
+
+#[test]
+fn draft_extracts_to_all_types_schema() {
+    let extraction = extract(ALL_TYPES_RS).unwrap();
+    let warnings: Vec<_> = extraction.warnings.iter().map(|w| &w.message).collect();
+    assert_eq!(
+        warnings,
+        ["enum `ErrorCode` is externally tagged; aski always uses the {variant, data} envelope"]
+    );
+    assert_eq!(tokens(&extraction.to_aski()), tokens(ALL_TYPES_ASKI));
+}
+
+#[test]
+fn extracted_schema_generates_the_draft_back() {
+    let aski = extract(ALL_TYPES_RS).unwrap().to_aski();
+    let parse = parse(&aski);
+    assert_eq!(parse.errors(), &[]);
+    let (schema, diagnostics) = lower_source_file(&parse.tree());
+    assert_eq!(diagnostics, &[]);
+    let generated = generate_rust(&schema);
+    draft::assert_matches_draft(&generated, ALL_TYPES_RS);
+}
+
+#[test]
+fn unsupported_constructs_are_errors() {
+    let source = "
+        #[derive(Serialize)]
+        struct Borrowed<'a> {
+            name: &'a str,
+        }
+    ";
+    let issue = extract(source).unwrap_err();
+    assert_eq!(issue.line, 3);
+}
+
+#[test]
+fn externally_tagged_enums_warn() {
+    let source = "
+        #[derive(Serialize, Deserialize)]
+        enum Light { Red, Green }
+    ";
+    let extraction = extract(source).unwrap();
+    assert_eq!(extraction.warnings.len(), 1);
+    assert_eq!(
+        tokens(&extraction.to_aski()),
+        tokens("(aski/v1 (enum light (red) (green)))")
+    );
+}
+
+#[test]
+fn generic_items_extract_and_ignore_serde_bounds() {
+    let source = r#"
+        #[derive(Serialize, Deserialize)]
+        #[serde(bound(deserialize = "K: Deserialize<'de> + Ord"))]
+        pub struct Index<K> {
+            pub keys: BTreeSet<K>,
+        }
+
+        #[derive(Serialize, Deserialize)]
+        #[serde(tag = "variant", content = "data")]
+        pub enum Either<L, R> {
+            Left(L),
+            Right(R),
+        }
+    "#;
+    let extraction = extract(source).unwrap();
+    assert_eq!(extraction.warnings, &[]);
+    assert_eq!(
+        tokens(&extraction.to_aski()),
+        tokens(
+            "(aski/v1
+               (record (index K) {keys: (set K)})
+               (enum (either L R) (left L) (right R)))"
+        )
+    );
+}
+
+#[test]
+fn boxes_extract_as_box_types() {
+    let source = "
+        #[derive(Serialize, Deserialize)]
+        pub struct Node {
+            pub next: Option<Box<Node>>,
+        }
+    ";
+    let extraction = extract(source).unwrap();
+    assert_eq!(extraction.warnings, &[]);
+    assert_eq!(
+        tokens(&extraction.to_aski()),
+        tokens("(aski/v1 (record node {next: (? (box node))}))")
+    );
+}
+
+#[test]
+fn hash_collections_warn_that_they_come_back_ordered() {
+    let source = "
+        #[derive(Serialize, Deserialize)]
+        pub struct Index {
+            pub names: HashSet<String>,
+            pub ages: std::collections::HashMap<String, u16>,
+            pub sorted: BTreeSet<u8>,
+        }
+    ";
+    let extraction = extract(source).unwrap();
+    let warnings: Vec<_> = extraction
+        .warnings
+        .iter()
+        .map(|w| (w.line, w.message.as_str()))
+        .collect();
+    assert_eq!(
+        warnings,
+        [
+            (
+                4,
+                "`HashSet` will be generated back as `BTreeSet`, which keeps its contents in order"
+            ),
+            (
+                5,
+                "`HashMap` will be generated back as `BTreeMap`, which keeps its contents in order"
+            ),
+        ]
+    );
+    assert_eq!(
+        tokens(&extraction.to_aski()),
+        tokens("(aski/v1 (record index {names: (set string) ages: (map string u16) sorted: (set u8)}))")
+    );
+}
+
+#[test]
+fn qualified_paths_must_name_standard_types() {
+    let source = "
+        #[derive(Serialize, Deserialize)]
+        pub struct Config {
+            pub name: std::string::String,
+            pub id: uuid::Uuid,
+            pub tags: std::collections::BTreeSet<String>,
+        }
+    ";
+    let extraction = extract(source).unwrap();
+    assert_eq!(extraction.warnings, &[]);
+    assert_eq!(
+        tokens(&extraction.to_aski()),
+        tokens("(aski/v1 (record config {name: string id: uuid tags: (set string)}))")
+    );
+
+    let source = "
+        #[derive(Serialize, Deserialize)]
+        pub struct Shared<T> {
+            pub value: std::rc::Rc<T>,
+        }
+    ";
+    let issue = extract(source).unwrap_err();
+    assert_eq!(issue.line, 4);
+    assert_eq!(
+        issue.message,
+        "`std::rc::Rc` has no aski equivalent; only types of this file and standard ones may be named"
+    );
+}
@@ -0,0 +1,262 @@
+// What the name under the cursor refers to.
+//
+// Navigation starts from a name token: the name of a declaration or a field, a type
+// parameter, or a type reference. References are looked up in the resolver's bindings, so
+// a name means here what it means to the checker and the code generator: a parameter
+// shadows a declaration of the same name, and the first of two declarations of a name
+// is the one it refers to.
+
+use aski_codegen::{generate_rust_item, rust_type, to_rust_field};
+use aski_edit::{Edit, Patch, Transaction};
+use aski_sema::{
+    resolve, Binding, Field, Item, ItemKind, Primitive, Resolution, Schema, TypeRef, VariantBody,
+};
+use aski_syntax::ast::{self, AstNode, HasName};
+use aski_syntax::{
+    NodePath, SyntaxElement, SyntaxKind, SyntaxNode, SyntaxToken, TextRange, TokenAtOffset,
+};
+
+use SyntaxKind::*;
+
+/// What a name token names.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Symbol {
+    /// The declaration with this name.
+    Decl(String),
+    /// Type parameter `param` of the declaration at index `item` of the schema.
+    Param {
+        item: usize,
+        param: usize,
+    },
+    Primitive(Primitive),
+    /// A named field, by node path.
+    Field(NodePath),
+}
+
+/// One document's syntax and semantic model, with the bindings of its type references.
+pub(crate) struct Analysis<'a> {
+    root: SyntaxNode,
+    schema: &'a Schema,
+    resolution: Resolution,
+}
+
+impl<'a> Analysis<'a> {
+    pub(crate) fn new(root: SyntaxNode, schema: &'a Schema) -> Analysis<'a> {
+        Analysis {
+            root,
+            schema,
+            resolution: resolve(schema).0,
+        }
+    }
+
+    /// The name token at `offset` and what it names.
+    pub(crate) fn symbol_at(&self, offset: usize) -> Option<(SyntaxToken, Symbol)> {
+        self.tokens_at(offset)
+            .into_iter()
+            .find_map(|token| Some((token.clone(), self.symbol(&token)?)))
+    }
+
+    /// The range of the name `symbol` is declared by, for symbols declared in the document.
+    pub(crate) fn definition(&self, symbol: &Symbol) -> Option<TextRange> {
+        match symbol {
+            Symbol::Decl(name) => Some(self.schema.item(name)?.name.range),
+            Symbol::Param { item, param } => Some(self.schema.items[*item].params[*param].range),
+            Symbol::Primitive(_) => None,
+            Symbol::Field(path) => {
+                let file = ast::SourceFile::cast(self.root.clone())?;
+                let field = ast::Field::cast(path.resolve(&file)?)?;
+                Some(field.name()?.token()?.text_range())
+            }
+        }
+    }
+
+    /// The name tokens of every type reference to `symbol`, in source order.
+    pub(crate) fn references(&self, symbol: &Symbol) -> Vec<TextRange> {
+        let mut ranges: Vec<_> = self
+            .schema
+            .items
+            .iter()
+            .enumerate()
+            .flat_map(|(index, item)| {
+                item.type_refs()
+                    .into_iter()
+                    .flat_map(TypeRef::walk)
+                    .map(move |ty| (index, ty))
+            })
+            .filter(|(index, ty)| self.refers_to(*index, ty, symbol))
+            .filter_map(|(_, ty)| self.name_of(ty))
+            .collect();
+        ranges.sort();
+        ranges
+    }
+
+    /// What to show for the token at `offset`: the Rust item generated for a declaration,
+    /// the Rust field generated for a field, or the Rust spelling of a type expression.
+    pub(crate) fn hover(&self, offset: usize) -> Option<(TextRange, String)> {
+        if let Some((token, symbol)) = self.symbol_at(offset) {
+            let shown = match &symbol {
+                Symbol::Decl(name) => generate_rust_item(self.schema, name),
+                Symbol::Field(_) => self.field_named(token.text_range()).map(|(item, field)| {
+                    let name = to_rust_field(&field.name.text);
+                    format!("pub {name}: {}", rust_type(&field.ty, item))
+                }),
+                Symbol::Param { .. } | Symbol::Primitive(_) => None,
+            };
+            if let Some(shown) = shown {
+                return Some((token.text_range(), rust_block(&shown)));
+            }
+        }
+        let token = self
+            .tokens_at(offset)
+            .into_iter()
+            .find(|token| !token.kind().is_trivia())?;
+        let item = self.item_at(token.text_range().start())?;
+        let item = &self.schema.items[item];
+        let ty = token
+            .ancestors()
+            .find_map(|node| type_ref(item, node.text_range()))?;
+        Some((token.text_range(), rust_block(&rust_type(ty, item))))
+    }
+
+    /// The patches renaming `symbol` to `name` in `text`, the document's text.
+    pub(crate) fn rename(
+        &self,
+        symbol: &Symbol,
+        name: &str,
+        text: &str,
+    ) -> Result<Vec<Patch>, String> {
+        let edit = match symbol {
+            Symbol::Decl(decl) => Edit::RenameType {
+                decl: decl.clone(),
+                name: name.to_string(),
+            },
+            Symbol::Field(field) => Edit::RenameField {
+                field: field.clone(),
+                name: name.to_string(),
+            },
+            Symbol::Param { .. } | Symbol::Primitive(_) => {
+                return Err("only declarations and fields can be renamed".to_string())
+            }
+        };
+        Transaction::new([edit])
+            .patches(text)
+            .map_err(|error| error.message)
+    }
+
+    /// The tokens touching `offset`, the one starting there first.
+    fn tokens_at(&self, offset: usize) -> Vec<SyntaxToken> {
+        match self.root.token_at_offset(offset) {
+            TokenAtOffset::None => Vec::new(),
+            TokenAtOffset::Single(token) => vec![token],
+            TokenAtOffset::Between(left, right) => vec![right, left],
+        }
+    }
+
+    fn symbol(&self, token: &SyntaxToken) -> Option<Symbol> {
+        if token.kind() != SYMBOL {
+            return None;
+        }
+        let parent = token.parent();
+        match parent.kind() {
+            NAME => {
+                let owner = parent.parent()?;
+                match owner.kind() {
+                    FIELD => NodePath::of(&owner).map(Symbol::Field),
+                    kind if kind == GENERIC_NAME || ast::Decl::can_cast(kind) => {
+                        Some(Symbol::Decl(token.text().to_string()))
+                    }
+                    _ => None,
+                }
+            }
+            TYPE_PARAM => {
+                let item = self.item_at(token.text_range().start())?;
+                let param = self.schema.items[item]
+                    .params
+                    .iter()
+                    .position(|param| param.range == token.text_range())?;
+                Some(Symbol::Param { item, param })
+            }
+            NAME_REF => {
+                let ty = parent.parent()?;
+                let index = self.item_at(ty.text_range().start())?;
+                let ty = type_ref(&self.schema.items[index], ty.text_range())?;
+                Some(match self.resolution.binding(ty)? {
+                    Binding::Item(name) => Symbol::Decl(name.clone()),
+                    Binding::Param(param) => Symbol::Param {
+                        item: index,
+                        param: *param,
+                    },
+                    Binding::Primitive(primitive) => Symbol::Primitive(*primitive),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether `ty`, a type reference of the declaration at index `item`, names `symbol`.
+    fn refers_to(&self, item: usize, ty: &TypeRef, symbol: &Symbol) -> bool {
+        match (self.resolution.binding(ty), symbol) {
+            (Some(Binding::Item(bound)), Symbol::Decl(name)) => bound == name,
+            (Some(Binding::Param(bound)), Symbol::Param { item: owner, param }) => {
+                *owner == item && bound == param
+            }
+            (Some(Binding::Primitive(bound)), Symbol::Primitive(primitive)) => bound == primitive,
+            _ => false,
+        }
+    }
+
+    /// The range of the name a named type reference is written with.
+    fn name_of(&self, ty: &TypeRef) -> Option<TextRange> {
+        let node = match self.root.covering_element(ty.range) {
+            SyntaxElement::Node(node) => node,
+            SyntaxElement::Token(token) => token.parent(),
+        };
+        if node.kind() == NAME_REF {
+            return Some(node.text_range());
+        }
+        let name = node.children().find(|child| child.kind() == NAME_REF)?;
+        Some(name.text_range())
+    }
+
+    /// The index of the declaration containing `offset`.
+    fn item_at(&self, offset: usize) -> Option<usize> {
+        self.schema
+            .items
+            .iter()
+            .position(|item| item.range.contains(offset))
+    }
+
+    /// The field whose name is at `range`, with its declaration.
+    fn field_named(&self, range: TextRange) -> Option<(&'a Item, &'a Field)> {
+        self.schema.items.iter().find_map(|item| {
+            let field = fields(item).find(|field| field.name.range == range)?;
+            Some((item, field))
+        })
+    }
+}
+
+/// Every named field of `item`, including the fields of its struct variants.
+fn fields(item: &Item) -> Box<dyn Iterator<Item = &Field> + '_> {
+    match &item.kind {
+        ItemKind::Record(fields) => Box::new(fields.iter()),
+        ItemKind::Enum(variants) => {
+            Box::new(variants.iter().flat_map(|variant| match &variant.body {
+                VariantBody::Struct(fields) => fields.as_slice(),
+                _ => &[],
+            }))
+        }
+        ItemKind::Newtype(_) | ItemKind::Tuple(_) => Box::new(std::iter::empty()),
+    }
+}
+
+/// The type reference of `item` written at `range`.
+fn type_ref(item: &Item, range: TextRange) -> Option<&TypeRef> {
+    item.type_refs()
+        .into_iter()
+        .flat_map(TypeRef::walk)
+        .find(|ty| ty.range == range)
+}
+
+fn rust_block(code: &str) -> String {
+    format!("```rust\n{}\n```", code.trim_end())
+}
@@ -0,0 +1,14 @@
+//! A language server for aski schemas.
+//!
+//! [`run`] speaks the Language Server Protocol over a connection, answering from an
+//! [`AskiDatabase`](aski_db::AskiDatabase) that holds every open document. It publishes
+//! each document's diagnostics as it changes, goes to the declaration a type reference
+//! names, finds every reference to a declaration, shows the Rust a declaration, field or
+//! type expression generates on hover, and renames fields, and declarations along with
+//! every use.
+
+mod analysis;
+mod lines;
+mod server;
+
+pub use crate::server::run;
@@ -0,0 +1,12 @@
+//! `aski-lsp` serves the Language Server Protocol for aski schemas over stdin and stdout.
+
+use std::error::Error;
+
+use lsp_server::Connection;
+
+fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
+    let (connection, threads) = Connection::stdio();
+    aski_lsp::run(&connection)?;
+    threads.join()?;
+    Ok(())
+}
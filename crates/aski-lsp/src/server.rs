@@ -0,0 +1,285 @@
+// The server loop: LSP messages in, answers from the database out.
+//
+// Every open document is a salsa input holding its current text. Changes arrive as
+// ranges in protocol positions and are applied to that text before the input is updated,
+// and the document's diagnostics are published after every change. Requests are answered
+// from whatever the database derives from the text at that point, so nothing is computed
+// again that an edit did not invalidate.
+
+use std::collections::HashMap;
+use std::error::Error;
+
+use aski_codegen::check_names;
+use aski_db::{file_diagnostics, file_schema, parse_file, AskiDatabase, SourceFile};
+use aski_syntax::{Severity, TextRange};
+use lsp_server::{Connection, ErrorCode, ExtractError, Message, Notification, Request, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, Notification as _,
+    PublishDiagnostics,
+};
+use lsp_types::request::{GotoDefinition, HoverRequest, References, Rename, Request as _};
+use lsp_types::{
+    DiagnosticSeverity, DidChangeTextDocumentParams, DidCloseTextDocumentParams,
+    DidOpenTextDocumentParams, GotoDefinitionParams, GotoDefinitionResponse, Hover, HoverContents,
+    HoverParams, HoverProviderCapability, Location, MarkupContent, MarkupKind, OneOf,
+    PublishDiagnosticsParams, ReferenceParams, RenameParams, ServerCapabilities,
+    TextDocumentSyncCapability, TextDocumentSyncKind, TextEdit, Url, WorkspaceEdit,
+};
+use salsa::Setter;
+
+use crate::analysis::Analysis;
+use crate::lines::LineIndex;
+
+/// Serves `connection` until the client shuts the server down.
+pub fn run(connection: &Connection) -> Result<(), Box<dyn Error + Send + Sync>> {
+    connection.initialize(serde_json::to_value(capabilities())?)?;
+    let mut server = Server::default();
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                if connection.handle_shutdown(&request)? {
+                    return Ok(());
+                }
+                connection
+                    .sender
+                    .send(Message::Response(server.request(request)))?;
+            }
+            Message::Notification(notification) => {
+                if let Some(published) = server.notification(notification) {
+                    connection.sender.send(Message::Notification(published))?;
+                }
+            }
+            Message::Response(_) => {}
+        }
+    }
+    Ok(())
+}
+
+fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(
+            TextDocumentSyncKind::INCREMENTAL,
+        )),
+        definition_provider: Some(OneOf::Left(true)),
+        references_provider: Some(OneOf::Left(true)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        rename_provider: Some(OneOf::Left(true)),
+        ..ServerCapabilities::default()
+    }
+}
+
+#[derive(Default)]
+struct Server {
+    db: AskiDatabase,
+    documents: HashMap<Url, Open>,
+}
+
+/// An open document.
+struct Open {
+    file: SourceFile,
+    version: i32,
+}
+
+/// An open document, ready to answer a request about it.
+struct View<'a> {
+    uri: &'a Url,
+    text: &'a str,
+    lines: LineIndex<'a>,
+    analysis: Analysis<'a>,
+}
+
+impl View<'_> {
+    fn location(&self, range: TextRange) -> Location {
+        Location::new(self.uri.clone(), self.lines.range(range))
+    }
+}
+
+impl Server {
+    /// Handles a notification, returning the diagnostics to publish if it changed them.
+    fn notification(&mut self, notification: Notification) -> Option<Notification> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params: DidOpenTextDocumentParams =
+                    notification.extract(DidOpenTextDocument::METHOD).ok()?;
+                let document = params.text_document;
+                let open = Open {
+                    file: SourceFile::new(&self.db, document.text),
+                    version: document.version,
+                };
+                self.documents.insert(document.uri.clone(), open);
+                Some(self.publish(document.uri))
+            }
+            DidChangeTextDocument::METHOD => {
+                let params: DidChangeTextDocumentParams =
+                    notification.extract(DidChangeTextDocument::METHOD).ok()?;
+                let uri = params.text_document.uri;
+                let open = self.documents.get_mut(&uri)?;
+                let mut text = open.file.text(&self.db).clone();
+                for change in params.content_changes {
+                    match change.range {
+                        Some(range) => {
+                            let range = LineIndex::new(&text).text_range(range);
+                            text.replace_range(range.start()..range.end(), &change.text);
+                        }
+                        None => text = change.text,
+                    }
+                }
+                open.version = params.text_document.version;
+                open.file.set_text(&mut self.db).to(text);
+                Some(self.publish(uri))
+            }
+            DidCloseTextDocument::METHOD => {
+                let params: DidCloseTextDocumentParams =
+                    notification.extract(DidCloseTextDocument::METHOD).ok()?;
+                let uri = params.text_document.uri;
+                self.documents.remove(&uri)?;
+                let cleared = PublishDiagnosticsParams::new(uri, Vec::new(), None);
+                Some(Notification::new(
+                    PublishDiagnostics::METHOD.to_string(),
+                    cleared,
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    fn publish(&self, uri: Url) -> Notification {
+        let open = &self.documents[&uri];
+        let lines = LineIndex::new(open.file.text(&self.db));
+        let mut diagnostics = file_diagnostics(&self.db, open.file).clone();
+        diagnostics.extend(check_names(file_schema(&self.db, open.file)));
+        let diagnostics = diagnostics
+            .into_iter()
+            .map(|diagnostic| lsp_types::Diagnostic {
+                range: lines.range(diagnostic.range),
+                severity: Some(match diagnostic.severity {
+                    Severity::Error => DiagnosticSeverity::ERROR,
+                    Severity::Warning => DiagnosticSeverity::WARNING,
+                }),
+                source: Some("aski".to_string()),
+                message: diagnostic.message,
+                ..lsp_types::Diagnostic::default()
+            })
+            .collect();
+        let params = PublishDiagnosticsParams::new(uri, diagnostics, Some(open.version));
+        Notification::new(PublishDiagnostics::METHOD.to_string(), params)
+    }
+
+    fn request(&self, request: Request) -> Response {
+        match request.method.as_str() {
+            GotoDefinition::METHOD => self.dispatch::<GotoDefinition>(request, Server::definition),
+            References::METHOD => self.dispatch::<References>(request, Server::references),
+            HoverRequest::METHOD => self.dispatch::<HoverRequest>(request, Server::hover),
+            Rename::METHOD => self.dispatch::<Rename>(request, Server::rename),
+            method => {
+                let message = format!("unknown request `{method}`");
+                Response::new_err(request.id, ErrorCode::MethodNotFound as i32, message)
+            }
+        }
+    }
+
+    /// Answers `request` with `handler`, whose errors are reported as failed requests.
+    fn dispatch<R: lsp_types::request::Request>(
+        &self,
+        request: Request,
+        handler: fn(&Server, R::Params) -> Result<R::Result, String>,
+    ) -> Response {
+        let id = request.id.clone();
+        let params = match request.extract::<R::Params>(R::METHOD) {
+            Ok((_, params)) => params,
+            Err(ExtractError::JsonError { error, .. }) => {
+                return Response::new_err(id, ErrorCode::InvalidParams as i32, error.to_string())
+            }
+            Err(ExtractError::MethodMismatch(request)) => {
+                let message = format!("unknown request `{}`", request.method);
+                return Response::new_err(id, ErrorCode::MethodNotFound as i32, message);
+            }
+        };
+        match handler(self, params) {
+            Ok(result) => Response::new_ok(id, result),
+            Err(message) => Response::new_err(id, ErrorCode::RequestFailed as i32, message),
+        }
+    }
+
+    fn view<'a>(&'a self, uri: &'a Url) -> Result<View<'a>, String> {
+        let open = self
+            .documents
+            .get(uri)
+            .ok_or_else(|| format!("{uri} is not open"))?;
+        let text = open.file.text(&self.db);
+        let root = parse_file(&self.db, open.file).syntax_node();
+        Ok(View {
+            uri,
+            text,
+            lines: LineIndex::new(text),
+            analysis: Analysis::new(root, file_schema(&self.db, open.file)),
+        })
+    }
+
+    fn definition(
+        &self,
+        params: GotoDefinitionParams,
+    ) -> Result<Option<GotoDefinitionResponse>, String> {
+        let position = params.text_document_position_params;
+        let view = self.view(&position.text_document.uri)?;
+        let offset = view.lines.offset(position.position);
+        Ok(view
+            .analysis
+            .symbol_at(offset)
+            .and_then(|(_, symbol)| view.analysis.definition(&symbol))
+            .map(|range| GotoDefinitionResponse::Scalar(view.location(range))))
+    }
+
+    fn references(&self, params: ReferenceParams) -> Result<Option<Vec<Location>>, String> {
+        let position = params.text_document_position;
+        let view = self.view(&position.text_document.uri)?;
+        let offset = view.lines.offset(position.position);
+        let Some((_, symbol)) = view.analysis.symbol_at(offset) else {
+            return Ok(None);
+        };
+        let mut ranges = view.analysis.references(&symbol);
+        if params.context.include_declaration {
+            ranges.extend(view.analysis.definition(&symbol));
+            ranges.sort();
+        }
+        Ok(Some(
+            ranges
+                .into_iter()
+                .map(|range| view.location(range))
+                .collect(),
+        ))
+    }
+
+    fn hover(&self, params: HoverParams) -> Result<Option<Hover>, String> {
+        let position = params.text_document_position_params;
+        let view = self.view(&position.text_document.uri)?;
+        let offset = view.lines.offset(position.position);
+        Ok(view.analysis.hover(offset).map(|(range, value)| Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value,
+            }),
+            range: Some(view.lines.range(range)),
+        }))
+    }
+
+    fn rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>, String> {
+        let position = params.text_document_position;
+        let view = self.view(&position.text_document.uri)?;
+        let offset = view.lines.offset(position.position);
+        let (_, symbol) = view
+            .analysis
+            .symbol_at(offset)
+            .ok_or("there is no name to rename here")?;
+        let edits = view
+            .analysis
+            .rename(&symbol, &params.new_name, view.text)?
+            .into_iter()
+            .map(|patch| TextEdit::new(view.lines.range(patch.range), patch.replacement))
+            .collect();
+        Ok(Some(WorkspaceEdit::new(HashMap::from([(
+            view.uri.clone(),
+            edits,
+        )]))))
+    }
+}
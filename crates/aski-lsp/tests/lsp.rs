@@ -0,0 +1,341 @@
+//! The server, driven over an in-process connection the way an editor drives it.
+
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use lsp_server::{Connection, Message, Notification, Request, RequestId};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidOpenTextDocument, Exit, Initialized, Notification as _,
+    PublishDiagnostics,
+};
+use lsp_types::request::{GotoDefinition, HoverRequest, Initialize, References, Rename, Shutdown};
+use lsp_types::{
+    DidChangeTextDocumentParams, DidOpenTextDocumentParams, GotoDefinitionParams,
+    GotoDefinitionResponse, HoverContents, HoverParams, InitializeParams, InitializedParams,
+    Location, Position, PublishDiagnosticsParams, Range, ReferenceContext, ReferenceParams,
+    RenameParams, TextDocumentContentChangeEvent, TextDocumentIdentifier, TextDocumentItem,
+    TextDocumentPositionParams, TextEdit, Url, VersionedTextDocumentIdentifier,
+};
+
+const ALL_TYPES: &str = include_str!("../../../encoder/drafts/all-types.aski");
+
+struct Client {
+    connection: Connection,
+    server: JoinHandle<()>,
+    uri: Url,
+    next_id: i32,
+    /// Notifications that arrived while waiting for a response.
+    notifications: Vec<Notification>,
+}
+
+impl Client {
+    /// Starts a server and opens the draft in it.
+    fn open() -> Client {
+        Client::open_text(ALL_TYPES)
+    }
+
+    /// Starts a server and opens `text` in it.
+    fn open_text(text: &str) -> Client {
+        let (server, connection) = Connection::memory();
+        let server = thread::spawn(move || aski_lsp::run(&server).unwrap());
+        let mut client = Client {
+            connection,
+            server,
+            uri: Url::parse("file:///all-types.aski").unwrap(),
+            next_id: 0,
+            notifications: Vec::new(),
+        };
+        client
+            .request::<Initialize>(InitializeParams::default())
+            .unwrap();
+        client.notify::<Initialized>(InitializedParams {});
+        let item = TextDocumentItem::new(client.uri.clone(), "aski".into(), 1, text.into());
+        client.notify::<DidOpenTextDocument>(DidOpenTextDocumentParams {
+            text_document: item,
+        });
+        client
+    }
+
+    fn shutdown(mut self) {
+        self.request::<Shutdown>(()).unwrap();
+        self.notify::<Exit>(());
+        self.server.join().unwrap();
+    }
+
+    fn request<R: lsp_types::request::Request>(
+        &mut self,
+        params: R::Params,
+    ) -> Result<R::Result, String> {
+        self.next_id += 1;
+        let id = RequestId::from(self.next_id);
+        let request = Request::new(id.clone(), R::METHOD.to_string(), params);
+        self.connection.sender.send(request.into()).unwrap();
+        loop {
+            match self.receive() {
+                Message::Response(response) if response.id == id => {
+                    return match response.error {
+                        Some(error) => Err(error.message),
+                        None => Ok(
+                            serde_json::from_value(response.result.unwrap_or_default()).unwrap()
+                        ),
+                    };
+                }
+                Message::Notification(notification) => self.notifications.push(notification),
+                message => panic!("unexpected {message:?}"),
+            }
+        }
+    }
+
+    fn notify<N: lsp_types::notification::Notification>(&mut self, params: N::Params) {
+        let notification = Notification::new(N::METHOD.to_string(), params);
+        self.connection.sender.send(notification.into()).unwrap();
+    }
+
+    /// The next diagnostics the server publishes.
+    fn diagnostics(&mut self) -> PublishDiagnosticsParams {
+        loop {
+            let published = self
+                .notifications
+                .iter()
+                .position(|notification| notification.method == PublishDiagnostics::METHOD);
+            if let Some(index) = published {
+                let notification = self.notifications.remove(index);
+                return serde_json::from_value(notification.params).unwrap();
+            }
+            match self.receive() {
+                Message::Notification(notification) => self.notifications.push(notification),
+                message => panic!("unexpected {message:?}"),
+            }
+        }
+    }
+
+    fn receive(&self) -> Message {
+        self.connection
+            .receiver
+            .recv_timeout(Duration::from_secs(10))
+            .expect("the server answers")
+    }
+
+    fn change(&mut self, version: i32, range: Range, text: &str) {
+        self.notify::<DidChangeTextDocument>(DidChangeTextDocumentParams {
+            text_document: VersionedTextDocumentIdentifier::new(self.uri.clone(), version),
+            content_changes: vec![TextDocumentContentChangeEvent {
+                range: Some(range),
+                range_length: None,
+                text: text.into(),
+            }],
+        });
+    }
+
+    fn at(&self, position: Position) -> TextDocumentPositionParams {
+        TextDocumentPositionParams::new(TextDocumentIdentifier::new(self.uri.clone()), position)
+    }
+
+    fn definition(&mut self, position: Position) -> Option<GotoDefinitionResponse> {
+        let params = GotoDefinitionParams {
+            text_document_position_params: self.at(position),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        };
+        self.request::<GotoDefinition>(params).unwrap()
+    }
+
+    fn hover(&mut self, position: Position) -> String {
+        let params = HoverParams {
+            text_document_position_params: self.at(position),
+            work_done_progress_params: Default::default(),
+        };
+        match self
+            .request::<HoverRequest>(params)
+            .unwrap()
+            .unwrap()
+            .contents
+        {
+            HoverContents::Markup(markup) => markup.value,
+            contents => panic!("unexpected {contents:?}"),
+        }
+    }
+
+    fn rename(&mut self, position: Position, name: &str) -> Result<Vec<TextEdit>, String> {
+        let params = RenameParams {
+            text_document_position: self.at(position),
+            new_name: name.into(),
+            work_done_progress_params: Default::default(),
+        };
+        let edit = self.request::<Rename>(params)?.unwrap();
+        Ok(edit.changes.unwrap().remove(&self.uri).unwrap())
+    }
+}
+
+/// The position of the byte `delta` bytes into the first occurrence of `needle` in the
+/// draft.
+fn position(needle: &str, delta: usize) -> Position {
+    position_in(ALL_TYPES, needle, delta)
+}
+
+fn position_in(text: &str, needle: &str, delta: usize) -> Position {
+    let offset = text.find(needle).unwrap() + delta;
+    let line_start = text[..offset].rfind('\n').map_or(0, |newline| newline + 1);
+    let line = text[..offset].matches('\n').count();
+    let character = text[line_start..offset].encode_utf16().count();
+    Position::new(line as u32, character as u32)
+}
+
+/// The range of `name`, found right after the first occurrence of `prefix`.
+fn range_after(prefix: &str, name: &str) -> Range {
+    let start = position(prefix, prefix.len());
+    Range::new(
+        start,
+        Position::new(start.line, start.character + name.len() as u32),
+    )
+}
+
+fn apply(text: &str, mut edits: Vec<TextEdit>) -> String {
+    let offset = |position: Position| {
+        let line_start: usize = text
+            .split_inclusive('\n')
+            .take(position.line as usize)
+            .map(str::len)
+            .sum();
+        let mut units = 0;
+        let column = text[line_start..]
+            .char_indices()
+            .find(|(_, c)| {
+                let reached = units >= position.character as usize;
+                units += c.len_utf16();
+                reached
+            })
+            .map_or(text.len() - line_start, |(index, _)| index);
+        line_start + column
+    };
+    edits.sort_by_key(|edit| (edit.range.start.line, edit.range.start.character));
+    let mut text = text.to_string();
+    for edit in edits.into_iter().rev() {
+        let (start, end) = (offset(edit.range.start), offset(edit.range.end));
+        text.replace_range(start..end, &edit.new_text);
+    }
+    text
+}
+
+#[test]
+fn diagnostics_follow_changes() {
+    let mut client = Client::open();
+    let opened = client.diagnostics();
+    assert_eq!((opened.diagnostics, opened.version), (Vec::new(), Some(1)));
+
+    let reference = range_after("(result string ", "error-code");
+    client.change(2, reference, "error-kind");
+    let changed = client.diagnostics();
+    assert_eq!(changed.version, Some(2));
+    let messages: Vec<_> = changed
+        .diagnostics
+        .iter()
+        .map(|diagnostic| (diagnostic.range, diagnostic.message.as_str()))
+        .collect();
+    assert_eq!(messages, [(reference, "cannot find type `error-kind`")]);
+
+    client.change(3, reference, "error-code");
+    assert_eq!(client.diagnostics().diagnostics, Vec::new());
+    client.shutdown();
+}
+
+#[test]
+fn references_lead_to_their_declaration_and_back() {
+    let mut client = Client::open();
+    let declaration = Location::new(client.uri.clone(), range_after("\n  (enum ", "error-code"));
+    let reference = Location::new(
+        client.uri.clone(),
+        range_after("(result string ", "error-code"),
+    );
+    let at_reference = position("(result string error-code", "(result string err".len());
+    assert_eq!(
+        client.definition(at_reference),
+        Some(GotoDefinitionResponse::Scalar(declaration.clone()))
+    );
+    assert_eq!(client.definition(position("code: (? u32)", 9)), None);
+
+    let params = ReferenceParams {
+        text_document_position: client.at(declaration.range.start),
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+        context: ReferenceContext {
+            include_declaration: true,
+        },
+    };
+    let found = client.request::<References>(params).unwrap();
+    assert_eq!(found, Some(vec![declaration, reference]));
+    client.shutdown();
+}
+
+#[test]
+fn hovers_show_the_generated_rust() {
+    let mut client = Client::open();
+    let item = client.hover(position(
+        "(result string error-code",
+        "(result string ".len(),
+    ));
+    assert!(
+        item.starts_with("```rust\n") && item.contains("pub enum ErrorCode {"),
+        "{item}"
+    );
+    let field = client.hover(position("code: (? u32)", 0));
+    assert_eq!(field, "```rust\npub code: Option<u32>\n```");
+    let ty = client.hover(position("(vec string)", 1));
+    assert_eq!(ty, "```rust\nVec<String>\n```");
+    client.shutdown();
+}
+
+#[test]
+fn renames_update_every_use() {
+    let mut client = Client::open();
+    let at_declaration = position("(enum error-code", "(enum ".len());
+    let edits = client.rename(at_declaration, "failure-code").unwrap();
+    let expected = ALL_TYPES
+        .replace("(enum error-code", "(enum failure-code")
+        .replace("(result string error-code)", "(result string failure-code)");
+    assert_eq!(apply(ALL_TYPES, edits), expected);
+
+    let edits = client
+        .rename(position("code: (? u32)", 0), "status-code")
+        .unwrap();
+    assert!(apply(ALL_TYPES, edits).contains("status-code: (? u32)"));
+
+    assert_eq!(
+        client.rename(at_declaration, "bytes"),
+        Err("`bytes` is a primitive type".to_string())
+    );
+    assert_eq!(
+        client.rename(position("code: (? u32)", "code: (? ".len()), "u64"),
+        Err("only declarations and fields can be renamed".to_string())
+    );
+    client.shutdown();
+}
+
+#[test]
+fn renames_leave_names_in_values_that_mean_something_else() {
+    let text = "(aski/v1
+  (record point {x: f64 y: f64})
+  (enum mode (fast) (point))
+  (record settings
+    { origin: point (default {x: 0 y: 1})
+      mode: mode (default (mode point))
+      x: u8 })
+)";
+    let mut client = Client::open_text(text);
+    let edits = client
+        .rename(position_in(text, "(record point", "(record ".len()), "spot")
+        .unwrap();
+    let expected = text
+        .replace("(record point", "(record spot")
+        .replace("origin: point", "origin: spot");
+    assert_eq!(apply(text, edits), expected);
+
+    let edits = client
+        .rename(position_in(text, "x: f64", 0), "across")
+        .unwrap();
+    let expected = text
+        .replace("{x: f64", "{across: f64")
+        .replace("{x: 0", "{across: 0");
+    assert_eq!(apply(text, edits), expected);
+    client.shutdown();
+}
@@ -0,0 +1,426 @@
+// Default values of record fields.
+//
+// A default must be a value of its field's type. Values are checked structurally, with
+// the arguments of a generic declaration standing in for its parameters:
+//
+//   bool                  true, false
+//   integers, floats      numbers in the type's range; floats also take integers,
+//                         inf, -inf and nan
+//   string, char          a string, of exactly one character for a char
+//   uuid                  #uuid "..." or a string, in the hyphenated form
+//   unit, bytes           [] and, for bytes, #bytes "00ff" or a list of u8
+//   (? T)                 none, or a value of T
+//   (result a b)          #ok a, #err b
+//   vec, set, array       [element ...], as many as an array holds
+//   map                   {key value ...}
+//   tuple types           [element ...], one per element type
+//   newtypes, box         the value they wrap
+//   records               {field: value ...}, every field once; {} without fields
+//   tuple declarations    [field ...]
+//   enums                 (enum variant payload), the payload matching the variant
+//
+// A field typed by a parameter of its own declaration has no values to write, though
+// an empty container of it does. Types declared in other modules are not checked here.
+//
+// The same walk finds the keys that name a given field in `{field: value ...}` values,
+// which are the ones renaming the field has to rename along with it.
+
+use std::collections::HashSet;
+
+use aski_syntax::{Diagnostic, TextRange};
+
+use crate::model::{
+    hex_bytes, Field, Item, ItemKind, Name, Primitive, Schema, TypeKind, TypeRef, ValueExpr,
+    ValueKind, ValueTag, VariantBody,
+};
+
+/// Checks the default of every record field of `schema` against the field's type.
+pub fn check_defaults(schema: &Schema) -> Vec<Diagnostic> {
+    let mut checker = Checker::new(schema, None);
+    checker.defaults();
+    checker.diagnostics
+}
+
+/// The keys naming `field` in the default values of `schema`, where `field` is a field
+/// of the record `owner`, or of its struct variant `variant`.
+pub fn field_keys(
+    schema: &Schema,
+    owner: &str,
+    variant: Option<&str>,
+    field: &str,
+) -> Vec<TextRange> {
+    let mut checker = Checker::new(
+        schema,
+        Some(FieldKey {
+            owner,
+            variant,
+            field,
+        }),
+    );
+    checker.defaults();
+    checker.keys
+}
+
+/// A field whose keys are being looked for.
+#[derive(Clone, Copy)]
+struct FieldKey<'a> {
+    owner: &'a str,
+    variant: Option<&'a str>,
+    field: &'a str,
+}
+
+struct Checker<'a> {
+    schema: &'a Schema,
+    /// The type parameters of the record whose defaults are being checked.
+    params: &'a [Name],
+    diagnostics: Vec<Diagnostic>,
+    /// The field whose keys are looked for, if any.
+    key: Option<FieldKey<'a>>,
+    /// The keys found naming `key`.
+    keys: Vec<TextRange>,
+}
+
+impl<'a> Checker<'a> {
+    fn new(schema: &'a Schema, key: Option<FieldKey<'a>>) -> Checker<'a> {
+        Checker {
+            schema,
+            params: &[],
+            diagnostics: Vec::new(),
+            key,
+            keys: Vec::new(),
+        }
+    }
+
+    fn defaults(&mut self) {
+        let schema = self.schema;
+        for item in &schema.items {
+            let ItemKind::Record(fields) = &item.kind else {
+                continue;
+            };
+            self.params = &item.params;
+            for field in fields {
+                if let Some(default) = &field.default {
+                    self.value(default, &field.ty);
+                }
+            }
+        }
+    }
+
+    fn value(&mut self, value: &ValueExpr, ty: &TypeRef) {
+        match (&ty.kind, &value.kind) {
+            (TypeKind::Option(_), ValueKind::None) => {}
+            (TypeKind::Option(inner) | TypeKind::Box(inner), _) => self.value(value, inner),
+            (TypeKind::Vec(element) | TypeKind::Set(element), ValueKind::List(elements)) => {
+                for each in elements {
+                    self.value(each, element);
+                }
+            }
+            (TypeKind::Array(len, element), ValueKind::List(elements)) => {
+                if self.count(value, ty, *len as usize, elements.len()) {
+                    for each in elements {
+                        self.value(each, element);
+                    }
+                }
+            }
+            (TypeKind::Tuple(types), ValueKind::List(_)) => self.list(value, ty, types),
+            (TypeKind::Map(key, val), ValueKind::Map(entries)) => {
+                for (k, v) in entries {
+                    self.value(k, key);
+                    self.value(v, val);
+                }
+            }
+            (
+                TypeKind::Result(ok, err),
+                ValueKind::Tagged {
+                    tag: tag @ (ValueTag::Ok | ValueTag::Err),
+                    value,
+                },
+            ) => self.value(value, if *tag == ValueTag::Ok { ok } else { err }),
+            (TypeKind::Named { name, args }, _) => self.named(value, ty, name, args),
+            _ => self.mismatch(value, ty),
+        }
+    }
+
+    fn named(&mut self, value: &ValueExpr, ty: &TypeRef, name: &str, args: &[TypeRef]) {
+        if self.params.iter().any(|param| param.text == name) {
+            return self.error(
+                value,
+                format!("`{name}` is a type parameter, so its values cannot be written"),
+            );
+        }
+        if let Some(primitive) = Primitive::from_name(name) {
+            return self.primitive(value, ty, primitive);
+        }
+        if name.contains('/') {
+            return self.error(
+                value,
+                format!("cannot check a value of `{name}`, which another module declares"),
+            );
+        }
+        // Unknown names are reported by resolution.
+        let Some(item) = self.schema.item(name) else {
+            return;
+        };
+        let substituted = |ty: &TypeRef| ty.substituted(&item.params, args);
+        match (&item.kind, &value.kind) {
+            (ItemKind::Newtype(inner), _) => self.value(value, &substituted(inner)),
+            (ItemKind::Record(fields), ValueKind::Record(given)) => {
+                self.fields(value, item, None, fields, given, args)
+            }
+            (ItemKind::Record(fields), ValueKind::Map(entries))
+                if fields.is_empty() && entries.is_empty() => {}
+            (ItemKind::Tuple(types), ValueKind::List(_)) => {
+                let types: Vec<_> = types.iter().map(substituted).collect();
+                self.list(value, ty, &types)
+            }
+            (
+                ItemKind::Enum(variants),
+                ValueKind::Variant {
+                    ty: enum_name,
+                    variant,
+                    payload,
+                },
+            ) if enum_name.text == item.name.text => {
+                let Some(declared) = variants.iter().find(|v| v.name.text == variant.text) else {
+                    return self.diagnostics.push(Diagnostic::error(
+                        format!("`{name}` has no variant `{variant}`"),
+                        variant.range,
+                    ));
+                };
+                match (&declared.body, payload) {
+                    (VariantBody::Unit, None) => {}
+                    (VariantBody::Unit, Some(payload)) => self.error(
+                        payload,
+                        format!("variant `{variant}` of `{name}` takes no payload"),
+                    ),
+                    (_, None) => self.error(
+                        value,
+                        format!("variant `{variant}` of `{name}` takes a payload"),
+                    ),
+                    (VariantBody::Newtype(inner), Some(payload)) => {
+                        self.value(payload, &substituted(inner))
+                    }
+                    (VariantBody::Tuple(types), Some(payload)) => {
+                        let types: Vec<_> = types.iter().map(substituted).collect();
+                        match &payload.kind {
+                            ValueKind::List(_) => self.list(payload, ty, &types),
+                            _ => self.error(
+                                payload,
+                                format!(
+                                    "variant `{variant}` of `{name}` takes a list of {} values",
+                                    types.len()
+                                ),
+                            ),
+                        }
+                    }
+                    (VariantBody::Struct(fields), Some(payload)) => match &payload.kind {
+                        ValueKind::Record(given) => {
+                            self.fields(payload, item, Some(variant), fields, given, args)
+                        }
+                        ValueKind::Map(entries) if fields.is_empty() && entries.is_empty() => {}
+                        _ => self.error(
+                            payload,
+                            format!("variant `{variant}` of `{name}` takes `{{field: value ...}}`"),
+                        ),
+                    },
+                }
+            }
+            _ => self.mismatch(value, ty),
+        }
+    }
+
+    fn primitive(&mut self, value: &ValueExpr, ty: &TypeRef, primitive: Primitive) {
+        match (primitive, &value.kind) {
+            (Primitive::Bool, ValueKind::Bool(_)) | (Primitive::String, ValueKind::String(_)) => {}
+            (Primitive::Char, ValueKind::String(text)) => {
+                if text.chars().count() != 1 {
+                    self.error(
+                        value,
+                        "a `char` is written as a string of exactly one character".to_string(),
+                    );
+                }
+            }
+            (
+                Primitive::Uuid,
+                ValueKind::Tagged {
+                    tag: ValueTag::Uuid,
+                    value: tagged,
+                },
+            ) => match &tagged.kind {
+                ValueKind::String(_) => self.primitive(tagged, ty, primitive),
+                _ => self.mismatch(tagged, ty),
+            },
+            (
+                Primitive::Bytes,
+                ValueKind::Tagged {
+                    tag: ValueTag::Bytes,
+                    value: tagged,
+                },
+            ) => match &tagged.kind {
+                ValueKind::String(text) if hex_bytes(text).is_some() => {}
+                _ => self.error(
+                    tagged,
+                    "`#bytes` takes a string of hex digits, two per byte, like \"00ff\""
+                        .to_string(),
+                ),
+            },
+            (Primitive::Uuid, ValueKind::String(text)) => {
+                if !is_uuid(text) {
+                    self.error(
+                        value,
+                        "a `uuid` is written as a string like \
+                         \"67e55044-10b1-426f-9247-bb680e5fe0c8\""
+                            .to_string(),
+                    );
+                }
+            }
+            (Primitive::Unit, ValueKind::List(elements)) if elements.is_empty() => {}
+            (Primitive::Bytes, ValueKind::List(elements)) => {
+                let byte = TypeRef {
+                    kind: TypeKind::Named {
+                        name: Primitive::U8.name().to_string(),
+                        args: Vec::new(),
+                    },
+                    range: ty.range,
+                };
+                for each in elements {
+                    self.value(each, &byte);
+                }
+            }
+            (_, ValueKind::Int(_) | ValueKind::Float(_)) if primitive.is_float() => {}
+            (_, ValueKind::Int(text)) if int_range(primitive).is_some() => {
+                let (min, max) = int_range(primitive).expect("an integer primitive");
+                let in_range = match text.parse::<i128>() {
+                    Ok(n) => n >= min && (n < 0 || n as u128 <= max),
+                    Err(_) => text.parse::<u128>().is_ok_and(|n| n <= max),
+                };
+                if !in_range {
+                    self.error(value, format!("`{text}` is out of range for `{primitive}`"));
+                }
+            }
+            _ => self.mismatch(value, ty),
+        }
+    }
+
+    /// Checks `{field: value ...}` against the fields of `item` or of its struct variant
+    /// `variant`.
+    fn fields(
+        &mut self,
+        value: &ValueExpr,
+        item: &Item,
+        variant: Option<&Name>,
+        fields: &[Field],
+        given: &[(Name, ValueExpr)],
+        args: &[TypeRef],
+    ) {
+        let mut seen = HashSet::new();
+        for (name, field_value) in given {
+            let Some(field) = fields.iter().find(|field| field.name.text == name.text) else {
+                self.diagnostics.push(Diagnostic::error(
+                    format!("`{}` has no field `{name}`", item.name),
+                    name.range,
+                ));
+                continue;
+            };
+            if self.key.is_some_and(|key| {
+                key.owner == item.name.text
+                    && key.variant == variant.map(|variant| variant.text.as_str())
+                    && key.field == name.text
+            }) {
+                self.keys.push(name.range);
+            }
+            if !seen.insert(name.text.as_str()) {
+                self.diagnostics.push(Diagnostic::error(
+                    format!("field `{name}` is given more than once"),
+                    name.range,
+                ));
+                continue;
+            }
+            self.value(field_value, &field.ty.substituted(&item.params, args));
+        }
+        for field in fields {
+            if !seen.contains(field.name.text.as_str()) {
+                self.error(value, format!("missing field `{}`", field.name));
+            }
+        }
+    }
+
+    /// Checks `[element ...]` against one type per element.
+    fn list(&mut self, value: &ValueExpr, ty: &TypeRef, types: &[TypeRef]) {
+        let ValueKind::List(elements) = &value.kind else {
+            return self.mismatch(value, ty);
+        };
+        if self.count(value, ty, types.len(), elements.len()) {
+            for (each, ty) in elements.iter().zip(types) {
+                self.value(each, ty);
+            }
+        }
+    }
+
+    /// Whether a list of `given` elements has the `expected` number, reporting it if not.
+    fn count(&mut self, value: &ValueExpr, ty: &TypeRef, expected: usize, given: usize) -> bool {
+        if expected != given {
+            self.error(
+                value,
+                format!("a value of `{ty}` has {expected} elements, not {given}"),
+            );
+        }
+        expected == given
+    }
+
+    fn mismatch(&mut self, value: &ValueExpr, ty: &TypeRef) {
+        self.error(
+            value,
+            format!("expected a value of `{ty}`, found {}", described(value)),
+        );
+    }
+
+    fn error(&mut self, value: &ValueExpr, message: String) {
+        self.diagnostics
+            .push(Diagnostic::error(message, value.range));
+    }
+}
+
+/// The smallest and largest value of an integer primitive. `isize` and `usize` are taken
+/// to be 64 bits wide.
+fn int_range(primitive: Primitive) -> Option<(i128, u128)> {
+    let range = match primitive {
+        Primitive::I8 => (i8::MIN as i128, i8::MAX as u128),
+        Primitive::I16 => (i16::MIN as i128, i16::MAX as u128),
+        Primitive::I32 => (i32::MIN as i128, i32::MAX as u128),
+        Primitive::I64 | Primitive::Isize => (i64::MIN as i128, i64::MAX as u128),
+        Primitive::I128 => (i128::MIN, i128::MAX as u128),
+        Primitive::U8 => (0, u8::MAX as u128),
+        Primitive::U16 => (0, u16::MAX as u128),
+        Primitive::U32 => (0, u32::MAX as u128),
+        Primitive::U64 | Primitive::Usize => (0, u64::MAX as u128),
+        Primitive::U128 => (0, u128::MAX),
+        _ => return None,
+    };
+    Some(range)
+}
+
+/// Whether `text` is a uuid in its hyphenated form.
+fn is_uuid(text: &str) -> bool {
+    let groups: Vec<&str> = text.split('-').collect();
+    groups.len() == 5
+        && groups
+            .iter()
+            .zip([8, 4, 4, 4, 12])
+            .all(|(group, len)| group.len() == len && group.bytes().all(|b| b.is_ascii_hexdigit()))
+}
+
+fn described(value: &ValueExpr) -> String {
+    match &value.kind {
+        ValueKind::Bool(b) => format!("`{b}`"),
+        ValueKind::Int(_) => "an integer".to_string(),
+        ValueKind::Float(_) => "a float".to_string(),
+        ValueKind::String(_) => "a string".to_string(),
+        ValueKind::None => "`none`".to_string(),
+        ValueKind::List(_) => "a list".to_string(),
+        ValueKind::Map(_) => "a map".to_string(),
+        ValueKind::Record(_) => "a record".to_string(),
+        ValueKind::Variant { ty, .. } => format!("a `{ty}` variant"),
+        ValueKind::Tagged { tag, .. } => format!("a `{tag}` value"),
+    }
+}
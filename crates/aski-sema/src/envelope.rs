@@ -0,0 +1,11 @@
+// The canonical representation of enum values.
+//
+// There is exactly one: a value of any enum is a map holding the variant name under
+// `variant` and the payload, if the variant has one, under `data`. Schemas never spell it
+// out; code generators, extractors and runtimes all take it from here.
+
+/// Key holding the variant name.
+pub const ENUM_TAG: &str = "variant";
+
+/// Key holding the variant's payload.
+pub const ENUM_CONTENT: &str = "data";
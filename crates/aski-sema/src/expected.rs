@@ -0,0 +1,226 @@
+// What belongs at a position of a document.
+//
+// Editors complete and validate by asking what the schema expects at the cursor: a
+// declaration, a field key, a type, the body of a variant, an array length. The answer
+// comes from the concrete syntax tree alone, so incomplete input gets one too. On a word,
+// or just after one, it is what that word stands for; between words it is what the
+// enclosing form takes next, counting the parts that come before the position.
+
+use aski_syntax::ast::{self, AstNode};
+use aski_syntax::{NodePath, SyntaxElement, SyntaxKind, SyntaxNode, SyntaxToken, TokenAtOffset};
+
+use SyntaxKind::*;
+
+/// What a position of a document holds, or may hold next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Expected {
+    /// Nothing to fill in: comments, strings, slots only a delimiter can follow, and
+    /// anything outside the schema form.
+    Nothing,
+    /// The `aski/v1` head of the schema form.
+    Version,
+    /// A declaration, or the keyword starting one.
+    Declaration,
+    /// The name a declaration declares.
+    DeclName,
+    /// A type parameter of a generic declaration.
+    TypeParam,
+    /// The key of a field of a record or struct variant.
+    FieldKey,
+    /// A variant of an enum, or its name.
+    Variant,
+    /// What follows a variant's name: a type, `[T ...]`, `{field: T ...}` or nothing.
+    VariantBody,
+    /// A type expression.
+    Type,
+    /// The head of a type form: a constructor like `vec`, or a generic declaration.
+    TypeConstructor,
+    /// The length of an array, an integer literal.
+    ArrayLength,
+}
+
+/// What `file` expects at the byte `offset`.
+pub fn expected_at(file: &ast::SourceFile, offset: usize) -> Expected {
+    match file.syntax().token_at_offset(offset) {
+        TokenAtOffset::None => Expected::Nothing,
+        TokenAtOffset::Single(token) => {
+            let kind = token.kind();
+            if is_word(kind) {
+                return word(&token);
+            }
+            let parent = token.parent();
+            let at_start = offset == token.text_range().start();
+            let container = match kind {
+                WHITESPACE => Some(parent),
+                _ if kind.is_opening_delimiter() && at_start => parent.parent(),
+                _ if kind.is_closing_delimiter() && !at_start => parent.parent(),
+                _ if kind.is_opening_delimiter() || kind.is_closing_delimiter() => Some(parent),
+                _ => None,
+            };
+            container.map_or(Expected::Nothing, |node| {
+                gap(&open_form(node, offset), offset)
+            })
+        }
+        TokenAtOffset::Between(left, right) => {
+            if is_word(left.kind()) {
+                return word(&left);
+            }
+            if is_word(right.kind()) {
+                return word(&right);
+            }
+            if is_opaque(left.kind()) || matches!(right.kind(), STRING | ERROR_TOKEN) {
+                return Expected::Nothing;
+            }
+            let container = if left.kind().is_opening_delimiter() {
+                Some(left.parent())
+            } else if right.kind().is_closing_delimiter() {
+                Some(right.parent())
+            } else if left.kind().is_closing_delimiter() {
+                left.parent().parent()
+            } else {
+                Some(left.parent())
+            };
+            container.map_or(Expected::Nothing, |node| {
+                gap(&open_form(node, offset), offset)
+            })
+        }
+    }
+}
+
+/// What `file` expects where the node at `path` starts, if `path` resolves.
+pub fn expected_at_path(file: &ast::SourceFile, path: &NodePath) -> Option<Expected> {
+    let node = path.resolve(file)?;
+    Some(expected_at(file, node.text_range().start()))
+}
+
+/// Tokens that stand for something on their own.
+fn is_word(kind: SyntaxKind) -> bool {
+    matches!(kind, SYMBOL | INT) || kind.is_keyword()
+}
+
+/// Tokens whose insides hold nothing the schema expects.
+fn is_opaque(kind: SyntaxKind) -> bool {
+    matches!(kind, COMMENT | STRING | ERROR_TOKEN)
+}
+
+/// What a word stands for, by where it sits.
+fn word(token: &SyntaxToken) -> Expected {
+    let parent = token.parent();
+    match token.kind() {
+        NEWTYPE_KW | RECORD_KW | TUPLE_KW | ENUM_KW => Expected::Declaration,
+        kind if kind.is_keyword() => Expected::TypeConstructor,
+        INT if parent.kind() == ARRAY_TYPE => Expected::ArrayLength,
+        SYMBOL => match parent.kind() {
+            SCHEMA => Expected::Version,
+            TYPE_PARAM => Expected::TypeParam,
+            NAME => match parent.parent().map(|node| node.kind()) {
+                Some(NEWTYPE_DECL | RECORD_DECL | TUPLE_DECL | ENUM_DECL | GENERIC_NAME) => {
+                    Expected::DeclName
+                }
+                Some(FIELD) => Expected::FieldKey,
+                Some(UNIT_VARIANT | NEWTYPE_VARIANT | TUPLE_VARIANT | STRUCT_VARIANT) => {
+                    Expected::Variant
+                }
+                _ => Expected::Nothing,
+            },
+            NAME_REF => {
+                let ty = parent.parent();
+                match ty.as_ref().map(|node| node.kind()) {
+                    Some(GENERIC_TYPE) => Expected::TypeConstructor,
+                    _ if ty.and_then(|ty| ty.parent()).map(|node| node.kind())
+                        == Some(NEWTYPE_VARIANT) =>
+                    {
+                        Expected::VariantBody
+                    }
+                    _ => Expected::Type,
+                }
+            }
+            _ => Expected::Nothing,
+        },
+        _ => Expected::Nothing,
+    }
+}
+
+/// The innermost form still open at `offset`. Forms left unclosed at the end of the
+/// input end at their last part, so the trivia after them lands in whatever encloses
+/// them, yet that is where typing would continue them.
+fn open_form(mut node: SyntaxNode, offset: usize) -> SyntaxNode {
+    while let Some(last) = node
+        .children()
+        .filter(|child| child.text_range().end() <= offset)
+        .last()
+    {
+        let open = match last.first_token().map(|token| token.kind()) {
+            _ if last.kind() == FIELD => {
+                ast::Field::cast(last.clone()).is_some_and(|f| f.ty().is_none())
+            }
+            Some(first) if first.is_opening_delimiter() => {
+                last.last_token().map(|token| token.kind()) != first.closing()
+            }
+            _ => false,
+        };
+        if !open {
+            break;
+        }
+        node = last;
+    }
+    node
+}
+
+/// What the form `node` takes at `offset`, between its parts.
+fn gap(node: &SyntaxNode, offset: usize) -> Expected {
+    let before: Vec<SyntaxElement> = node
+        .children_with_tokens()
+        .filter(|element| !element.kind().is_trivia() && !element.kind().is_opening_delimiter())
+        .filter(|element| element.text_range().end() <= offset)
+        .collect();
+    match (node.kind(), before.len()) {
+        (SCHEMA, 0) => Expected::Version,
+        (SCHEMA, _) => Expected::Declaration,
+        (NEWTYPE_DECL | RECORD_DECL | TUPLE_DECL | ENUM_DECL, 0) => Expected::Declaration,
+        (NEWTYPE_DECL | RECORD_DECL | TUPLE_DECL | ENUM_DECL, 1) => Expected::DeclName,
+        (NEWTYPE_DECL, 2) => Expected::Type,
+        (ENUM_DECL, _) => Expected::Variant,
+        (GENERIC_NAME, 0) => Expected::DeclName,
+        (GENERIC_NAME, _) => Expected::TypeParam,
+        (FIELD_LIST, _) => match before.last().and_then(|last| last.clone().into_node()) {
+            Some(field) => after_field(&field),
+            None => Expected::FieldKey,
+        },
+        (FIELD, _) if before.last().map(|last| last.kind()) == Some(COLON) => Expected::Type,
+        (TUPLE_FIELD_LIST | TUPLE_TYPE, _) => Expected::Type,
+        (UNIT_VARIANT | NEWTYPE_VARIANT | TUPLE_VARIANT | STRUCT_VARIANT, 0) => Expected::Variant,
+        (UNIT_VARIANT | NEWTYPE_VARIANT | TUPLE_VARIANT | STRUCT_VARIANT, 1) => {
+            Expected::VariantBody
+        }
+        (
+            OPTION_TYPE | RESULT_TYPE | VEC_TYPE | SET_TYPE | MAP_TYPE | ARRAY_TYPE | BOX_TYPE
+            | GENERIC_TYPE,
+            0,
+        ) => Expected::TypeConstructor,
+        (OPTION_TYPE | VEC_TYPE | SET_TYPE | BOX_TYPE, 1) => Expected::Type,
+        (RESULT_TYPE | MAP_TYPE, 1 | 2) => Expected::Type,
+        (ARRAY_TYPE, 1) => Expected::ArrayLength,
+        (ARRAY_TYPE, 2) => Expected::Type,
+        (GENERIC_TYPE, _) => Expected::Type,
+        // Input the parser could not make sense of stands where it starts, as far as the
+        // enclosing form is concerned: `(aski/v1 (` is starting a declaration.
+        (ERROR, _) => node.parent().map_or(Expected::Nothing, |parent| {
+            gap(&parent, node.text_range().start())
+        }),
+        _ => Expected::Nothing,
+    }
+}
+
+/// What a field list takes after `field`: its type if the field stops at the colon,
+/// otherwise the next field.
+fn after_field(field: &SyntaxNode) -> Expected {
+    let Some(field) = ast::Field::cast(field.clone()) else {
+        return Expected::FieldKey;
+    };
+    match (field.colon(), field.ty()) {
+        (Some(_), None) => Expected::Type,
+        (None, _) => Expected::Nothing,
+        (Some(_), Some(_)) => Expected::FieldKey,
+    }
+}
@@ -0,0 +1,68 @@
+// Well-formedness of generic declarations and their uses.
+//
+// A declaration's parameters must be distinct and each must be used, since Rust rejects
+// unused type parameters. Every use of a declared type must supply exactly as many
+// arguments as it has parameters; primitives and type parameters take none. The resolver
+// runs both checks, since counting arguments needs to know what a name refers to.
+
+use std::collections::HashSet;
+
+use aski_syntax::{Diagnostic, TextRange};
+
+use crate::model::{Item, TypeKind, TypeRef};
+
+pub(crate) fn check_params(item: &Item, diagnostics: &mut Vec<Diagnostic>) {
+    let used: HashSet<&str> = item
+        .type_refs()
+        .into_iter()
+        .flat_map(TypeRef::walk)
+        .filter_map(|ty| match &ty.kind {
+            TypeKind::Named { name, .. } => Some(name.as_str()),
+            _ => None,
+        })
+        .collect();
+    let mut seen = HashSet::new();
+    for param in &item.params {
+        if !seen.insert(param.text.as_str()) {
+            diagnostics.push(Diagnostic::error(
+                format!("type parameter `{param}` is declared twice"),
+                param.range,
+            ));
+        } else if !used.contains(param.text.as_str()) {
+            diagnostics.push(Diagnostic::error(
+                format!("type parameter `{param}` of `{}` is never used", item.name),
+                param.range,
+            ));
+        }
+    }
+}
+
+/// The error for using `name`, which takes `expected` arguments, with `given` of them.
+pub(crate) fn check_arity(
+    name: &str,
+    expected: usize,
+    given: usize,
+    range: TextRange,
+) -> Option<Diagnostic> {
+    (given != expected).then(|| {
+        Diagnostic::error(
+            format!(
+                "`{name}` takes {} but {} given",
+                count(expected, "type argument"),
+                match given {
+                    1 => "1 was".to_string(),
+                    n => format!("{n} were"),
+                }
+            ),
+            range,
+        )
+    })
+}
+
+fn count(n: usize, what: &str) -> String {
+    match n {
+        0 => format!("no {what}s"),
+        1 => format!("1 {what}"),
+        n => format!("{n} {what}s"),
+    }
+}
@@ -0,0 +1,228 @@
+// Trait implementations.
+//
+// An impl implements one of a fixed table of traits, the ones generated code knows how
+// to implement, for a non-generic type the document declares. Each trait has one member,
+// whose signature must have the shape the table gives it, `self` standing for the
+// implementing type and `E` and `T` for any type:
+//
+//   from-str  from-str  [string] (result self E)
+//   try-from  try-from  [T] (result self E)
+//   display   fmt       [self] string
+//   default   default   [] self
+//
+// The prototype design sketched `from-str` with a second member, `try-from-str`. Rust's
+// `FromStr` has only `from_str`, and it is already fallible, so there is nothing for such
+// a member to generate; converting from any other type is `try-from`, as its own impl.
+//
+// A member may be left `TODO` while it is being written. That is only a warning, but an
+// impl whose signature is still `TODO` is left out of generated code, and a body that is
+// still `TODO` panics when it runs.
+
+use std::collections::HashSet;
+
+use aski_syntax::Diagnostic;
+
+use crate::model::{Impl, Primitive, Schema, Signature, TypeKind, TypeRef, SELF_TYPE};
+
+/// A trait impls may implement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KnownTrait {
+    /// The trait's name, `from-str`.
+    pub name: &'static str,
+    /// The name of its one member, `from-str`.
+    pub member: &'static str,
+    /// The shape of the member's signature, as written in messages.
+    pub signature: &'static str,
+    params: &'static [Shape],
+    ret: Shape,
+}
+
+/// What a type in a known trait's signature may be.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Shape {
+    /// `self`.
+    Itself,
+    /// `string`.
+    Text,
+    /// Any type.
+    Any,
+    /// `(result self E)`.
+    Fallible,
+}
+
+/// The traits impls may implement.
+pub const KNOWN_TRAITS: [KnownTrait; 4] = [
+    KnownTrait {
+        name: "from-str",
+        member: "from-str",
+        signature: "[string] (result self E)",
+        params: &[Shape::Text],
+        ret: Shape::Fallible,
+    },
+    KnownTrait {
+        name: "try-from",
+        member: "try-from",
+        signature: "[T] (result self E)",
+        params: &[Shape::Any],
+        ret: Shape::Fallible,
+    },
+    KnownTrait {
+        name: "display",
+        member: "fmt",
+        signature: "[self] string",
+        params: &[Shape::Itself],
+        ret: Shape::Text,
+    },
+    KnownTrait {
+        name: "default",
+        member: "default",
+        signature: "[] self",
+        params: &[],
+        ret: Shape::Itself,
+    },
+];
+
+impl KnownTrait {
+    pub fn from_name(name: &str) -> Option<&'static KnownTrait> {
+        KNOWN_TRAITS.iter().find(|known| known.name == name)
+    }
+
+    /// Whether `signature` has the shape this trait gives its member.
+    pub fn accepts(&self, signature: &Signature) -> bool {
+        signature.params.len() == self.params.len()
+            && self
+                .params
+                .iter()
+                .zip(&signature.params)
+                .all(|(shape, ty)| shape.accepts(ty))
+            && self.ret.accepts(&signature.ret)
+    }
+}
+
+impl Shape {
+    fn accepts(self, ty: &TypeRef) -> bool {
+        match self {
+            Shape::Itself => is_named(ty, SELF_TYPE),
+            Shape::Text => is_named(ty, Primitive::String.name()),
+            Shape::Any => true,
+            Shape::Fallible => {
+                matches!(&ty.kind, TypeKind::Result(ok, _) if is_named(ok, SELF_TYPE))
+            }
+        }
+    }
+}
+
+fn is_named(ty: &TypeRef, expected: &str) -> bool {
+    matches!(&ty.kind, TypeKind::Named { name, args } if name == expected && args.is_empty())
+}
+
+/// Checks every impl of `schema` against the trait it implements.
+pub fn check_impls(schema: &Schema) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut implemented = HashSet::new();
+    for imp in &schema.impls {
+        check_impl(schema, imp, &mut implemented, &mut diagnostics);
+    }
+    diagnostics.sort_by_key(|diagnostic| diagnostic.range.start());
+    diagnostics
+}
+
+fn check_impl<'s>(
+    schema: &Schema,
+    imp: &'s Impl,
+    implemented: &mut HashSet<(&'s str, &'s str)>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let trait_name = &imp.trait_name;
+    let Some(known) = KnownTrait::from_name(&trait_name.text) else {
+        diagnostics.push(Diagnostic::error(
+            format!(
+                "cannot find trait `{trait_name}`; impls may implement {}",
+                known_names()
+            ),
+            trait_name.range,
+        ));
+        return;
+    };
+    let self_name = match &imp.self_ty.kind {
+        TypeKind::Named { name, args } if args.is_empty() => match schema.item(name) {
+            Some(item) if item.params.is_empty() => Some(name.as_str()),
+            Some(_) => None,
+            // Resolution reports names that do not resolve.
+            None if Primitive::from_name(name).is_none() && !name.contains('/') => return,
+            None => None,
+        },
+        _ => None,
+    };
+    let Some(self_name) = self_name else {
+        diagnostics.push(Diagnostic::error(
+            "traits are implemented for the non-generic types the document declares",
+            imp.self_ty.range,
+        ));
+        return;
+    };
+    if !implemented.insert((known.name, self_name)) {
+        diagnostics.push(Diagnostic::error(
+            format!("`{trait_name}` is implemented for `{self_name}` more than once"),
+            trait_name.range,
+        ));
+    }
+
+    let mut members = HashSet::new();
+    for member in &imp.members {
+        let name = &member.name;
+        if name.text != known.member {
+            diagnostics.push(Diagnostic::error(
+                format!(
+                    "trait `{trait_name}` has no member `{name}`; its member is `{}`",
+                    known.member
+                ),
+                name.range,
+            ));
+            continue;
+        }
+        if !members.insert(name.text.as_str()) {
+            diagnostics.push(Diagnostic::error(
+                format!("member `{name}` is declared more than once"),
+                name.range,
+            ));
+            continue;
+        }
+        match &member.signature {
+            None => diagnostics.push(Diagnostic::warning(
+                format!("the signature of `{name}` is still `TODO`, so the impl is not generated"),
+                name.range,
+            )),
+            Some(signature) if !known.accepts(signature) => diagnostics.push(Diagnostic::error(
+                format!("`{name}` takes the signature `{}`", known.signature),
+                signature.range,
+            )),
+            Some(_) => {}
+        }
+        if member.body.is_none() {
+            diagnostics.push(Diagnostic::warning(
+                format!("the body of `{name}` is still `TODO`, so it panics when called"),
+                name.range,
+            ));
+        }
+    }
+    if members.is_empty() {
+        diagnostics.push(Diagnostic::error(
+            format!(
+                "the impl of `{trait_name}` for `{self_name}` is missing its member `{}`",
+                known.member
+            ),
+            trait_name.range,
+        ));
+    }
+}
+
+/// The names of the known traits, as a list in a sentence.
+fn known_names() -> String {
+    let names: Vec<_> = KNOWN_TRAITS
+        .iter()
+        .map(|known| format!("`{}`", known.name))
+        .collect();
+    let (last, rest) = names.split_last().expect("there are known traits");
+    format!("{} or {last}", rest.join(", "))
+}
@@ -0,0 +1,43 @@
+//! Semantics of aski schemas.
+//!
+//! [`lower_source_file`] turns a parsed document into a [`Schema`]: the declarations it
+//! makes, independent of how they were spelled. Enum values always use the envelope
+//! described by [`ENUM_TAG`] and [`ENUM_CONTENT`]. [`resolve`] then binds every type
+//! reference to what it names, checking names and generic arity along the way, and
+//! [`check_recursion`] rejects types that would contain themselves without indirection,
+//! and [`check_ordering`] map keys and set elements that cannot be ordered.
+//! [`check_impls`] checks impls against the [`KNOWN_TRAITS`] they may implement, and
+//! [`check_defaults`] checks the default values of record fields against their types,
+//! and [`field_keys`] finds the keys in them that name a given field.
+//!
+//! [`load_modules`] loads a schema spread over several documents, following the modules
+//! each one requires, and checks every document against the modules it requires.
+//!
+//! For editors, [`expected_at`] tells what a position of a document holds or takes next.
+
+mod defaults;
+mod envelope;
+mod expected;
+mod generics;
+mod impls;
+mod lower;
+mod model;
+mod modules;
+mod ordering;
+mod recursion;
+mod resolve;
+
+pub use crate::defaults::{check_defaults, field_keys};
+pub use crate::envelope::{ENUM_CONTENT, ENUM_TAG};
+pub use crate::expected::{expected_at, expected_at_path, Expected};
+pub use crate::impls::{check_impls, KnownTrait, KNOWN_TRAITS};
+pub use crate::lower::{lower_decl, lower_impl, lower_source_file, lower_value};
+pub use crate::model::{
+    hex_bytes, Field, Impl, ImplMember, Item, ItemKind, Module, ModuleOption, Name, Primitive,
+    Schema, Signature, TypeKind, TypeRef, ValueExpr, ValueKind, ValueTag, Variant, VariantBody,
+    REQUIRE, SELF_TYPE,
+};
+pub use crate::modules::{load_modules, ModuleFile, Modules};
+pub use crate::ordering::{check_ordering, ordered_params};
+pub use crate::recursion::check_recursion;
+pub use crate::resolve::{resolve, resolve_impl, resolve_item, Binding, Declarations, Resolution};
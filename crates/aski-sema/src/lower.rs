@@ -0,0 +1,452 @@
+// Lowering from the typed syntax tree to the semantic model.
+//
+// Lowering is forgiving: a declaration or field whose name is missing is dropped, and a
+// missing type is dropped with it. The parser has already reported those holes, so they
+// produce no diagnostics here. What lowering does report is input that parses fine but
+// means nothing.
+//
+// Definitions in the capitalized dialect are lowered like the aski/v1 declarations they
+// parse into, with their names spelled in kebab-case: `UserId` becomes `user-id` and
+// `firstName` becomes `first-name`, so both dialects describe the same types the same
+// way. Type parameters and module names keep their spelling in either dialect. A string
+// literal in type position is an example value, and stands for `string`.
+//
+// A document declares at most one module. Its options are kept as written, apart from
+// `require`, whose values must name modules. Impl members are named in kebab-case like
+// fields, `from_str` becoming `from-str`, and their function bodies are kept as written.
+//
+// Only record fields take a `(default value)`. Values are lowered as written, with their
+// names respelled like any other; whether a value fits its type is checked later. Only
+// the tags of `ValueTag` mean anything.
+
+use aski_syntax::ast::{self, AstNode, HasName, HasTypeParams};
+use aski_syntax::{kebab_case, string_value, Diagnostic, SyntaxKind, SyntaxToken};
+
+use crate::model::{
+    Field, Impl, ImplMember, Item, ItemKind, Module, ModuleOption, Name, Primitive, Schema,
+    Signature, TypeKind, TypeRef, ValueExpr, ValueKind, ValueTag, Variant, VariantBody, REQUIRE,
+};
+
+/// Lowers a parsed document, in either dialect, into its semantic model.
+pub fn lower_source_file(file: &ast::SourceFile) -> (Schema, Vec<Diagnostic>) {
+    let mut lowerer = Lowerer::default();
+    let schema = file.schema();
+    let items = schema
+        .iter()
+        .flat_map(|schema| schema.decls())
+        .chain(file.decls())
+        .filter_map(|decl| lowerer.decl(&decl))
+        .collect();
+    let mut module: Option<Module> = None;
+    for decl in schema
+        .iter()
+        .flat_map(|schema| schema.modules())
+        .chain(file.modules())
+    {
+        let Some(lowered) = lowerer.module(&decl) else {
+            continue;
+        };
+        match &module {
+            Some(first) => lowerer.diagnostics.push(Diagnostic::error(
+                format!(
+                    "module `{}` is declared after `{}`; a document is one module",
+                    lowered.name, first.name
+                ),
+                lowered.name.range,
+            )),
+            None => module = Some(lowered),
+        }
+    }
+    let impls = schema
+        .iter()
+        .flat_map(|schema| schema.impls())
+        .chain(file.impls())
+        .filter_map(|decl| lowerer.impl_decl(&decl))
+        .collect();
+    (
+        Schema {
+            items,
+            module,
+            impls,
+        },
+        lowerer.diagnostics,
+    )
+}
+
+/// Lowers a single declaration.
+pub fn lower_decl(decl: &ast::Decl) -> (Option<Item>, Vec<Diagnostic>) {
+    let mut lowerer = Lowerer::default();
+    let item = lowerer.decl(decl);
+    (item, lowerer.diagnostics)
+}
+
+/// Lowers a single impl.
+pub fn lower_impl(decl: &ast::ImplDecl) -> (Option<Impl>, Vec<Diagnostic>) {
+    let mut lowerer = Lowerer::default();
+    let imp = lowerer.impl_decl(decl);
+    (imp, lowerer.diagnostics)
+}
+
+/// Lowers a value, like the one of a document holding a single value.
+pub fn lower_value(value: &ast::Value) -> (Option<ValueExpr>, Vec<Diagnostic>) {
+    let mut lowerer = Lowerer::default();
+    let value = lowerer.value(value);
+    (value, lowerer.diagnostics)
+}
+
+#[derive(Default)]
+struct Lowerer {
+    diagnostics: Vec<Diagnostic>,
+    /// Whether the declaration being lowered is a capitalized definition.
+    capitalized: bool,
+    /// The type parameters of the declaration being lowered.
+    params: Vec<String>,
+}
+
+impl Lowerer {
+    fn decl(&mut self, decl: &ast::Decl) -> Option<Item> {
+        self.capitalized = decl
+            .syntax()
+            .parent()
+            .is_some_and(|parent| parent.kind() == SyntaxKind::SOURCE_FILE);
+        let params: Vec<Name> = decl.type_params().filter_map(type_param).collect();
+        self.params = params.iter().map(|param| param.text.clone()).collect();
+        let name = self.name(decl.name()?)?;
+        let kind = match decl {
+            ast::Decl::Newtype(newtype) => ItemKind::Newtype(self.type_ref(&newtype.ty()?)?),
+            ast::Decl::Record(record) => ItemKind::Record(self.fields(record.fields(), true)),
+            ast::Decl::Tuple(tuple) => ItemKind::Tuple(self.type_refs(tuple.fields())),
+            ast::Decl::Enum(enum_decl) => ItemKind::Enum(
+                enum_decl
+                    .variants()
+                    .filter_map(|variant| self.variant(&variant))
+                    .collect(),
+            ),
+        };
+        Some(Item {
+            name,
+            params,
+            kind,
+            range: decl.syntax().text_range(),
+        })
+    }
+
+    fn module(&mut self, module: &ast::ModuleDecl) -> Option<Module> {
+        self.capitalized = module
+            .syntax()
+            .parent()
+            .is_some_and(|parent| parent.kind() == SyntaxKind::SOURCE_FILE);
+        let name = verbatim(&module.name()?.token()?);
+        let options = module
+            .options()
+            .filter_map(|option| self.module_option(&option))
+            .collect();
+        Some(Module {
+            name,
+            options,
+            range: module.syntax().text_range(),
+        })
+    }
+
+    fn module_option(&mut self, option: &ast::ModuleOption) -> Option<ModuleOption> {
+        let name = self.name(option.name()?)?;
+        let values: Vec<_> = match option.value() {
+            Some(token) => vec![token],
+            None => option.names().filter_map(|name| name.token()).collect(),
+        };
+        if name.text == REQUIRE {
+            if let Some(value) = values
+                .iter()
+                .find(|token| token.kind() != SyntaxKind::SYMBOL)
+            {
+                self.diagnostics.push(Diagnostic::error(
+                    format!("`{REQUIRE}` takes module names, like `{REQUIRE} (stuff blah)`"),
+                    value.text_range(),
+                ));
+                return None;
+            }
+        }
+        Some(ModuleOption {
+            name,
+            values: values.iter().map(verbatim).collect(),
+        })
+    }
+
+    fn impl_decl(&mut self, decl: &ast::ImplDecl) -> Option<Impl> {
+        self.capitalized = decl
+            .syntax()
+            .parent()
+            .is_some_and(|parent| parent.kind() == SyntaxKind::SOURCE_FILE);
+        self.params.clear();
+        let trait_ref = decl.trait_ref()?;
+        let range = trait_ref.syntax().text_range();
+        let trait_name = Name {
+            text: self.reference(trait_ref),
+            range,
+        };
+        let self_ty = self.type_ref(&decl.self_ty()?)?;
+        let members = decl
+            .members()
+            .filter_map(|member| self.impl_member(&member))
+            .collect();
+        Some(Impl {
+            trait_name,
+            self_ty,
+            members,
+            range: decl.syntax().text_range(),
+        })
+    }
+
+    fn impl_member(&mut self, member: &ast::ImplMember) -> Option<ImplMember> {
+        let name = self.name(member.name()?)?;
+        let signature = member
+            .signature()
+            .filter(|signature| !signature.is_todo())
+            .and_then(|signature| {
+                Some(Signature {
+                    params: self.type_refs(signature.params()),
+                    ret: self.type_ref(&signature.return_ty()?)?,
+                    range: signature.syntax().text_range(),
+                })
+            });
+        Some(ImplMember {
+            name,
+            signature,
+            body: member.body().and_then(|body| body.source()),
+            range: member.syntax().text_range(),
+        })
+    }
+
+    fn variant(&mut self, variant: &ast::Variant) -> Option<Variant> {
+        let name = self.name(variant.name()?)?;
+        let body = match variant {
+            ast::Variant::Unit(_) => VariantBody::Unit,
+            ast::Variant::Newtype(newtype) => VariantBody::Newtype(self.type_ref(&newtype.ty()?)?),
+            ast::Variant::Tuple(tuple) => VariantBody::Tuple(self.type_refs(tuple.fields())),
+            ast::Variant::Struct(record) => {
+                VariantBody::Struct(self.fields(record.fields(), false))
+            }
+        };
+        Some(Variant { name, body })
+    }
+
+    /// The fields of a record, or of a struct variant, which take no defaults.
+    fn fields(&mut self, fields: impl Iterator<Item = ast::Field>, record: bool) -> Vec<Field> {
+        fields
+            .filter_map(|field| self.field(&field, record))
+            .collect()
+    }
+
+    fn field(&mut self, field: &ast::Field, record: bool) -> Option<Field> {
+        let default = match field.default_value() {
+            Some(value) if !record => {
+                self.diagnostics.push(Diagnostic::error(
+                    "only the fields of a record take a default",
+                    value.syntax().text_range(),
+                ));
+                None
+            }
+            Some(value) => self.value(&value),
+            None => None,
+        };
+        Some(Field {
+            name: self.name(field.name()?)?,
+            ty: self.type_ref(&field.ty()?)?,
+            default,
+        })
+    }
+
+    fn value(&mut self, value: &ast::Value) -> Option<ValueExpr> {
+        let kind = match value {
+            ast::Value::Literal(literal) => {
+                let token = literal.token()?;
+                let text = token.text();
+                match (token.kind(), text) {
+                    (SyntaxKind::INT, _) => ValueKind::Int(text.to_string()),
+                    (SyntaxKind::FLOAT, _) => ValueKind::Float(text.to_string()),
+                    (SyntaxKind::STRING, _) => ValueKind::String(string_value(text)),
+                    (SyntaxKind::SYMBOL, "true") => ValueKind::Bool(true),
+                    (SyntaxKind::SYMBOL, "false") => ValueKind::Bool(false),
+                    (SyntaxKind::SYMBOL, "none") => ValueKind::None,
+                    (SyntaxKind::SYMBOL, "inf" | "-inf" | "nan") => {
+                        ValueKind::Float(text.to_string())
+                    }
+                    _ => {
+                        self.diagnostics.push(Diagnostic::error(
+                            format!("`{text}` is not a value; strings are written in quotes"),
+                            token.text_range(),
+                        ));
+                        return None;
+                    }
+                }
+            }
+            ast::Value::List(list) => ValueKind::List(
+                list.elements()
+                    .filter_map(|element| self.value(&element))
+                    .collect(),
+            ),
+            ast::Value::Map(map) if map.fields().next().is_some() => {
+                if let Some(entry) = map.entries().next() {
+                    self.diagnostics.push(Diagnostic::error(
+                        "a record value takes `field: value` pairs only",
+                        entry.syntax().text_range(),
+                    ));
+                    return None;
+                }
+                let fields = map.fields().filter_map(|field| {
+                    let name = field.name_ref()?.token()?;
+                    let name = Name {
+                        text: self.spelled(name.text()),
+                        range: name.text_range(),
+                    };
+                    Some((name, self.value(&field.value()?)?))
+                });
+                ValueKind::Record(fields.collect())
+            }
+            ast::Value::Map(map) => {
+                let entries = map.entries().filter_map(|entry| {
+                    Some((self.value(&entry.key()?)?, self.value(&entry.value()?)?))
+                });
+                ValueKind::Map(entries.collect())
+            }
+            ast::Value::Variant(variant) => {
+                let ty = variant.type_ref()?;
+                let range = ty.syntax().text_range();
+                let ty = Name {
+                    text: self.reference(ty),
+                    range,
+                };
+                let name = variant.variant()?.token()?;
+                let payload = match variant.payload() {
+                    Some(payload) => Some(Box::new(self.value(&payload)?)),
+                    None => None,
+                };
+                ValueKind::Variant {
+                    ty,
+                    variant: Name {
+                        text: self.spelled(name.text()),
+                        range: name.text_range(),
+                    },
+                    payload,
+                }
+            }
+            ast::Value::Tagged(tagged) => {
+                let token = tagged.tag()?;
+                let Some(tag) = ValueTag::from_name(token.text()) else {
+                    let tags: Vec<_> = ValueTag::ALL.iter().map(|tag| format!("`{tag}`")).collect();
+                    self.diagnostics.push(Diagnostic::error(
+                        format!(
+                            "unknown tag `{}`; the tags are {}",
+                            token.text(),
+                            tags.join(", ")
+                        ),
+                        token.text_range(),
+                    ));
+                    return None;
+                };
+                ValueKind::Tagged {
+                    tag,
+                    value: Box::new(self.value(&tagged.value()?)?),
+                }
+            }
+        };
+        Some(ValueExpr {
+            kind,
+            range: value.syntax().text_range(),
+        })
+    }
+
+    fn type_refs(&mut self, types: impl Iterator<Item = ast::TypeExpr>) -> Vec<TypeRef> {
+        types.filter_map(|ty| self.type_ref(&ty)).collect()
+    }
+
+    fn boxed(&mut self, ty: Option<ast::TypeExpr>) -> Option<Box<TypeRef>> {
+        self.type_ref(&ty?).map(Box::new)
+    }
+
+    fn type_ref(&mut self, ty: &ast::TypeExpr) -> Option<TypeRef> {
+        let kind = match ty {
+            ast::TypeExpr::Named(named) => TypeKind::Named {
+                name: self.reference(named.name_ref()?),
+                args: Vec::new(),
+            },
+            ast::TypeExpr::Generic(generic) => TypeKind::Named {
+                name: self.reference(generic.name_ref()?),
+                args: self.type_refs(generic.args()),
+            },
+            ast::TypeExpr::Option(option) => TypeKind::Option(self.boxed(option.inner())?),
+            ast::TypeExpr::Result(result) => {
+                TypeKind::Result(self.boxed(result.ok())?, self.boxed(result.err())?)
+            }
+            ast::TypeExpr::Vec(vec) => TypeKind::Vec(self.boxed(vec.element())?),
+            ast::TypeExpr::Set(set) => TypeKind::Set(self.boxed(set.element())?),
+            ast::TypeExpr::Map(map) => {
+                TypeKind::Map(self.boxed(map.key())?, self.boxed(map.value())?)
+            }
+            ast::TypeExpr::Array(array) => {
+                let token = array.len_token()?;
+                let Some(length) = array.length() else {
+                    self.diagnostics.push(Diagnostic::error(
+                        format!("array length `{}` is too large", token.text()),
+                        token.text_range(),
+                    ));
+                    return None;
+                };
+                TypeKind::Array(length, self.boxed(array.element())?)
+            }
+            ast::TypeExpr::Box(boxed) => TypeKind::Box(self.boxed(boxed.inner())?),
+            ast::TypeExpr::Tuple(tuple) => TypeKind::Tuple(self.type_refs(tuple.elements())),
+            ast::TypeExpr::Literal(_) => TypeKind::Named {
+                name: Primitive::String.name().to_string(),
+                args: Vec::new(),
+            },
+        };
+        Some(TypeRef {
+            kind,
+            range: ty.syntax().text_range(),
+        })
+    }
+
+    /// A declared name, in kebab-case.
+    fn name(&self, name: ast::Name) -> Option<Name> {
+        let token = name.token()?;
+        Some(Name {
+            text: self.spelled(token.text()),
+            range: token.text_range(),
+        })
+    }
+
+    /// The name a type reference refers to, in kebab-case unless it is a type parameter.
+    /// The module of a qualified reference, `stuff/user`, keeps its spelling.
+    fn reference(&self, name: ast::NameRef) -> String {
+        let text = name.text();
+        if self.params.contains(&text) {
+            return text;
+        }
+        match text.split_once('/') {
+            Some((module, local)) => format!("{module}/{}", self.spelled(local)),
+            None => self.spelled(&text),
+        }
+    }
+
+    fn spelled(&self, text: &str) -> String {
+        if self.capitalized {
+            kebab_case(text)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+fn type_param(param: ast::TypeParam) -> Option<Name> {
+    Some(verbatim(&param.token()?))
+}
+
+/// A name as written, like type parameters and module names, which are the same in either
+/// dialect.
+fn verbatim(token: &SyntaxToken) -> Name {
+    Name {
+        text: token.text().to_string(),
+        range: token.text_range(),
+    }
+}
@@ -0,0 +1,571 @@
+// The semantic model: what a schema declares, stripped of concrete syntax.
+//
+// Names are kept as aski/v1 writes them, in kebab-case; names from the capitalized
+// dialect are respelled to match. Mapping them to a target language is the code
+// generator's business. Every name and type reference keeps the source range it came
+// from so later passes can report precise diagnostics.
+
+use std::fmt;
+
+use aski_syntax::TextRange;
+
+/// All declarations of one schema, in source order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Schema {
+    pub items: Vec<Item>,
+    /// The document's module declaration, if it makes one.
+    pub module: Option<Module>,
+    pub impls: Vec<Impl>,
+}
+
+impl Schema {
+    pub fn item(&self, name: &str) -> Option<&Item> {
+        self.items.iter().find(|item| item.name.text == name)
+    }
+}
+
+/// A name as written in the source, with where it was written.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Name {
+    pub text: String,
+    pub range: TextRange,
+}
+
+impl fmt::Display for Name {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+/// `(module name {option value ...})`: what a document calls itself, and the modules whose
+/// declarations it refers to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Module {
+    pub name: Name,
+    pub options: Vec<ModuleOption>,
+    /// The whole declaration form.
+    pub range: TextRange,
+}
+
+impl Module {
+    /// The modules named by the `require` options, in source order.
+    pub fn requires(&self) -> impl Iterator<Item = &Name> {
+        self.options
+            .iter()
+            .filter(|option| option.name.text == REQUIRE)
+            .flat_map(|option| &option.values)
+    }
+}
+
+/// The option listing the modules a module refers to.
+pub const REQUIRE: &str = "require";
+
+/// One option of a module declaration, with its value or list of values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleOption {
+    pub name: Name,
+    pub values: Vec<Name>,
+}
+
+/// `(impl trait type {member [...] ...})`: a trait implemented for a declared type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Impl {
+    pub trait_name: Name,
+    pub self_ty: TypeRef,
+    pub members: Vec<ImplMember>,
+    /// The whole declaration form.
+    pub range: TextRange,
+}
+
+/// `member [(type-signature ...) (function-body ...)]`. A part written `TODO`, or not
+/// written at all, is `None`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImplMember {
+    pub name: Name,
+    pub signature: Option<Signature>,
+    /// The Rust source of the function body.
+    pub body: Option<String>,
+    pub range: TextRange,
+}
+
+/// `(type-signature [param ...] return)`, in which `self` stands for the implementing type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature {
+    pub params: Vec<TypeRef>,
+    pub ret: TypeRef,
+    pub range: TextRange,
+}
+
+/// The name standing for the implementing type in an impl member's signature.
+pub const SELF_TYPE: &str = "self";
+
+/// One type declaration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Item {
+    pub name: Name,
+    /// Type parameters of a generic declaration, like `T` in `(record (page T) {...})`.
+    pub params: Vec<Name>,
+    pub kind: ItemKind,
+    /// The whole declaration form.
+    pub range: TextRange,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ItemKind {
+    Newtype(TypeRef),
+    Record(Vec<Field>),
+    Tuple(Vec<TypeRef>),
+    Enum(Vec<Variant>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Field {
+    pub name: Name,
+    pub ty: TypeRef,
+    /// The value of `(default value)`, which only record fields take.
+    pub default: Option<ValueExpr>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Variant {
+    pub name: Name,
+    pub body: VariantBody,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VariantBody {
+    Unit,
+    Newtype(TypeRef),
+    Tuple(Vec<TypeRef>),
+    Struct(Vec<Field>),
+}
+
+/// A type expression in a field, variant or newtype position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeRef {
+    pub kind: TypeKind,
+    pub range: TextRange,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypeKind {
+    /// A primitive, a declared type or a type parameter, possibly applied to arguments.
+    Named {
+        name: String,
+        args: Vec<TypeRef>,
+    },
+    Option(Box<TypeRef>),
+    Result(Box<TypeRef>, Box<TypeRef>),
+    Vec(Box<TypeRef>),
+    Set(Box<TypeRef>),
+    Map(Box<TypeRef>, Box<TypeRef>),
+    Array(u64, Box<TypeRef>),
+    /// An explicit indirection, needed for a type to contain itself.
+    Box(Box<TypeRef>),
+    Tuple(Vec<TypeRef>),
+}
+
+/// A value written in a schema, like the default of a record field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValueExpr {
+    pub kind: ValueKind,
+    pub range: TextRange,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValueKind {
+    Bool(bool),
+    /// An integer, as written.
+    Int(String),
+    /// A floating-point number, as written, or one of `inf`, `-inf` and `nan`.
+    Float(String),
+    String(String),
+    /// `none`, the absent value of an option.
+    None,
+    /// `[value ...]`: the elements of a vec, set, array or tuple, or the fields of a
+    /// tuple declaration.
+    List(Vec<ValueExpr>),
+    /// `{key value ...}`. `{}` is the empty map, and the record without fields.
+    Map(Vec<(ValueExpr, ValueExpr)>),
+    /// `{field: value ...}`.
+    Record(Vec<(Name, ValueExpr)>),
+    /// `(type variant payload)`.
+    Variant {
+        ty: Name,
+        variant: Name,
+        payload: Option<Box<ValueExpr>>,
+    },
+    /// `#tag value`.
+    Tagged {
+        tag: ValueTag,
+        value: Box<ValueExpr>,
+    },
+}
+
+/// The tags a value can be written with, each telling how the value after it is read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValueTag {
+    /// `#uuid "67e55044-10b1-426f-9247-bb680e5fe0c8"`.
+    Uuid,
+    /// `#bytes "00ff"`: two hex digits per byte.
+    Bytes,
+    /// `#ok value`, a value of a result that succeeded.
+    Ok,
+    /// `#err value`, a value of a result that failed.
+    Err,
+}
+
+impl ValueTag {
+    pub const ALL: [ValueTag; 4] = [ValueTag::Uuid, ValueTag::Bytes, ValueTag::Ok, ValueTag::Err];
+
+    /// The tag `#name` stands for.
+    pub fn from_name(name: &str) -> Option<ValueTag> {
+        ValueTag::ALL.into_iter().find(|tag| tag.name() == name)
+    }
+
+    /// The tag as written, `#uuid`.
+    pub fn name(self) -> &'static str {
+        match self {
+            ValueTag::Uuid => "#uuid",
+            ValueTag::Bytes => "#bytes",
+            ValueTag::Ok => "#ok",
+            ValueTag::Err => "#err",
+        }
+    }
+}
+
+impl fmt::Display for ValueTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The bytes a `#bytes` string stands for, `"00ff"` being `[0, 255]`, if it is an even
+/// number of hex digits.
+pub fn hex_bytes(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) || !text.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|start| u8::from_str_radix(&text[start..start + 2], 16).ok())
+        .collect()
+}
+
+impl ValueExpr {
+    /// Moves this value and everything nested in it `offset` bytes further on.
+    pub fn shift(&mut self, offset: usize) {
+        self.range = self.range.shifted(offset);
+        match &mut self.kind {
+            ValueKind::List(elements) => elements.iter_mut().for_each(|e| e.shift(offset)),
+            ValueKind::Map(entries) => {
+                for (key, value) in entries {
+                    key.shift(offset);
+                    value.shift(offset);
+                }
+            }
+            ValueKind::Record(fields) => {
+                for (name, value) in fields {
+                    name.range = name.range.shifted(offset);
+                    value.shift(offset);
+                }
+            }
+            ValueKind::Variant {
+                ty,
+                variant,
+                payload,
+            } => {
+                ty.range = ty.range.shifted(offset);
+                variant.range = variant.range.shifted(offset);
+                if let Some(payload) = payload {
+                    payload.shift(offset);
+                }
+            }
+            ValueKind::Tagged { value, .. } => value.shift(offset),
+            ValueKind::Bool(_)
+            | ValueKind::Int(_)
+            | ValueKind::Float(_)
+            | ValueKind::String(_)
+            | ValueKind::None => {}
+        }
+    }
+}
+
+impl TypeRef {
+    /// The type references directly nested in this one.
+    pub fn children(&self) -> Vec<&TypeRef> {
+        match &self.kind {
+            TypeKind::Named { args, .. } => args.iter().collect(),
+            TypeKind::Option(inner)
+            | TypeKind::Vec(inner)
+            | TypeKind::Set(inner)
+            | TypeKind::Array(_, inner)
+            | TypeKind::Box(inner) => vec![inner],
+            TypeKind::Result(ok, err) => vec![ok, err],
+            TypeKind::Map(key, value) => vec![key, value],
+            TypeKind::Tuple(elements) => elements.iter().collect(),
+        }
+    }
+
+    /// The type references directly nested in this one, for rewriting them.
+    pub fn children_mut(&mut self) -> Vec<&mut TypeRef> {
+        match &mut self.kind {
+            TypeKind::Named { args: nested, .. } | TypeKind::Tuple(nested) => {
+                nested.iter_mut().collect()
+            }
+            TypeKind::Option(inner)
+            | TypeKind::Vec(inner)
+            | TypeKind::Set(inner)
+            | TypeKind::Array(_, inner)
+            | TypeKind::Box(inner) => vec![inner],
+            TypeKind::Result(first, second) | TypeKind::Map(first, second) => {
+                vec![first, second]
+            }
+        }
+    }
+
+    /// Moves this reference and everything nested in it `offset` bytes further on.
+    pub fn shift(&mut self, offset: usize) {
+        self.range = self.range.shifted(offset);
+        match &mut self.kind {
+            TypeKind::Named { args: nested, .. } | TypeKind::Tuple(nested) => {
+                nested.iter_mut().for_each(|ty| ty.shift(offset));
+            }
+            TypeKind::Option(inner)
+            | TypeKind::Vec(inner)
+            | TypeKind::Set(inner)
+            | TypeKind::Array(_, inner)
+            | TypeKind::Box(inner) => inner.shift(offset),
+            TypeKind::Result(first, second) | TypeKind::Map(first, second) => {
+                first.shift(offset);
+                second.shift(offset);
+            }
+        }
+    }
+
+    /// This reference with each of `params`, the parameters of a generic declaration,
+    /// replaced by the matching one of `args`.
+    pub fn substituted(&self, params: &[Name], args: &[TypeRef]) -> TypeRef {
+        if let TypeKind::Named { name, args: none } = &self.kind {
+            let param = params.iter().position(|param| &param.text == name);
+            if let Some(arg) = param.filter(|_| none.is_empty()).and_then(|i| args.get(i)) {
+                return arg.clone();
+            }
+        }
+        let mut ty = self.clone();
+        for child in ty.children_mut() {
+            *child = child.substituted(params, args);
+        }
+        ty
+    }
+
+    /// This reference and every reference nested in it, outermost first.
+    pub fn walk(&self) -> Vec<&TypeRef> {
+        let mut out = vec![self];
+        let mut index = 0;
+        while index < out.len() {
+            let children = out[index].children();
+            out.extend(children);
+            index += 1;
+        }
+        out
+    }
+}
+
+/// The type as aski/v1 writes it.
+impl fmt::Display for TypeRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let form = |f: &mut fmt::Formatter<'_>, head: &str, types: &[&TypeRef]| {
+            write!(f, "({head}")?;
+            for ty in types {
+                write!(f, " {ty}")?;
+            }
+            f.write_str(")")
+        };
+        match &self.kind {
+            TypeKind::Named { name, args } if args.is_empty() => f.write_str(name),
+            TypeKind::Named { name, args } => form(f, name, &args.iter().collect::<Vec<_>>()),
+            TypeKind::Option(inner) => form(f, "?", &[inner]),
+            TypeKind::Result(ok, err) => form(f, "result", &[ok, err]),
+            TypeKind::Vec(element) => form(f, "vec", &[element]),
+            TypeKind::Set(element) => form(f, "set", &[element]),
+            TypeKind::Map(key, value) => form(f, "map", &[key, value]),
+            TypeKind::Array(len, element) => form(f, &format!("array {len}"), &[element]),
+            TypeKind::Box(inner) => form(f, "box", &[inner]),
+            TypeKind::Tuple(types) => {
+                let types: Vec<String> = types.iter().map(TypeRef::to_string).collect();
+                write!(f, "[{}]", types.join(" "))
+            }
+        }
+    }
+}
+
+impl Item {
+    /// Moves every range in this declaration `offset` bytes further into the text, for
+    /// declarations lowered on their own and placed back into their document.
+    pub fn shift(&mut self, offset: usize) {
+        self.range = self.range.shifted(offset);
+        self.name.range = self.name.range.shifted(offset);
+        for param in &mut self.params {
+            param.range = param.range.shifted(offset);
+        }
+        let shift_fields = |fields: &mut Vec<Field>| {
+            for field in fields {
+                field.name.range = field.name.range.shifted(offset);
+                field.ty.shift(offset);
+                if let Some(default) = &mut field.default {
+                    default.shift(offset);
+                }
+            }
+        };
+        match &mut self.kind {
+            ItemKind::Newtype(ty) => ty.shift(offset),
+            ItemKind::Record(fields) => shift_fields(fields),
+            ItemKind::Tuple(types) => types.iter_mut().for_each(|ty| ty.shift(offset)),
+            ItemKind::Enum(variants) => {
+                for variant in variants {
+                    variant.name.range = variant.name.range.shifted(offset);
+                    match &mut variant.body {
+                        VariantBody::Unit => {}
+                        VariantBody::Newtype(ty) => ty.shift(offset),
+                        VariantBody::Tuple(types) => {
+                            types.iter_mut().for_each(|ty| ty.shift(offset))
+                        }
+                        VariantBody::Struct(fields) => shift_fields(fields),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Every type reference appearing directly in this declaration's body, for rewriting
+    /// them.
+    pub fn type_refs_mut(&mut self) -> Vec<&mut TypeRef> {
+        match &mut self.kind {
+            ItemKind::Newtype(ty) => vec![ty],
+            ItemKind::Record(fields) => fields.iter_mut().map(|field| &mut field.ty).collect(),
+            ItemKind::Tuple(types) => types.iter_mut().collect(),
+            ItemKind::Enum(variants) => variants
+                .iter_mut()
+                .flat_map(|variant| match &mut variant.body {
+                    VariantBody::Unit => Vec::new(),
+                    VariantBody::Newtype(ty) => vec![ty],
+                    VariantBody::Tuple(types) => types.iter_mut().collect(),
+                    VariantBody::Struct(fields) => {
+                        fields.iter_mut().map(|field| &mut field.ty).collect()
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    /// Every type reference appearing directly in this declaration's body.
+    pub fn type_refs(&self) -> Vec<&TypeRef> {
+        match &self.kind {
+            ItemKind::Newtype(ty) => vec![ty],
+            ItemKind::Record(fields) => fields.iter().map(|field| &field.ty).collect(),
+            ItemKind::Tuple(types) => types.iter().collect(),
+            ItemKind::Enum(variants) => variants
+                .iter()
+                .flat_map(|variant| match &variant.body {
+                    VariantBody::Unit => Vec::new(),
+                    VariantBody::Newtype(ty) => vec![ty],
+                    VariantBody::Tuple(types) => types.iter().collect(),
+                    VariantBody::Struct(fields) => fields.iter().map(|field| &field.ty).collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// The built-in atomic types of aski/v1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Primitive {
+    Bool,
+    Char,
+    String,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    Isize,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    Usize,
+    F32,
+    F64,
+    Unit,
+    Uuid,
+    Bytes,
+}
+
+impl Primitive {
+    pub const ALL: [Primitive; 20] = [
+        Primitive::Bool,
+        Primitive::Char,
+        Primitive::String,
+        Primitive::I8,
+        Primitive::I16,
+        Primitive::I32,
+        Primitive::I64,
+        Primitive::I128,
+        Primitive::Isize,
+        Primitive::U8,
+        Primitive::U16,
+        Primitive::U32,
+        Primitive::U64,
+        Primitive::U128,
+        Primitive::Usize,
+        Primitive::F32,
+        Primitive::F64,
+        Primitive::Unit,
+        Primitive::Uuid,
+        Primitive::Bytes,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Primitive> {
+        Primitive::ALL
+            .into_iter()
+            .find(|primitive| primitive.name() == name)
+    }
+
+    /// The symbol naming this primitive in aski/v1.
+    pub fn name(self) -> &'static str {
+        match self {
+            Primitive::Bool => "bool",
+            Primitive::Char => "char",
+            Primitive::String => "string",
+            Primitive::I8 => "i8",
+            Primitive::I16 => "i16",
+            Primitive::I32 => "i32",
+            Primitive::I64 => "i64",
+            Primitive::I128 => "i128",
+            Primitive::Isize => "isize",
+            Primitive::U8 => "u8",
+            Primitive::U16 => "u16",
+            Primitive::U32 => "u32",
+            Primitive::U64 => "u64",
+            Primitive::U128 => "u128",
+            Primitive::Usize => "usize",
+            Primitive::F32 => "f32",
+            Primitive::F64 => "f64",
+            Primitive::Unit => "unit",
+            Primitive::Uuid => "uuid",
+            Primitive::Bytes => "bytes",
+        }
+    }
+
+    pub fn is_float(self) -> bool {
+        matches!(self, Primitive::F32 | Primitive::F64)
+    }
+}
+
+impl fmt::Display for Primitive {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
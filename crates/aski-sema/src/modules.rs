@@ -0,0 +1,178 @@
+// Schemas spread over several documents.
+//
+// A document names itself with `(module name {...})`, or by its file name when it makes
+// no module declaration, and lists the modules it refers to in the `require` option.
+// Requiring `stuff` loads `stuff.aski` from the requiring document's directory, and lets
+// the requiring document refer to the declarations of `stuff` by qualified name,
+// `stuff/user`. What `stuff` requires in turn is not visible; a document requires every
+// module it refers to.
+//
+// Each module is loaded once, however many documents require it. Modules requiring each
+// other in a cycle are an error, reported where the cycle closes, so the modules always
+// form a hierarchy that can be checked, and generated, dependencies first.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use aski_syntax::{lex, parse, parse_capitalized, Diagnostic, Parse, SyntaxKind};
+
+use crate::defaults::check_defaults;
+use crate::impls::check_impls;
+use crate::lower::lower_source_file;
+use crate::model::Schema;
+use crate::ordering::check_ordering;
+use crate::recursion::check_recursion;
+use crate::resolve::{resolve_against, Declarations};
+
+/// One document of a schema spread over several.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleFile {
+    /// The name the module declares, or the stem of its file name.
+    pub name: String,
+    pub path: PathBuf,
+    pub text: String,
+    pub schema: Schema,
+    /// Everything wrong with the document, in source order.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// The documents of a schema, each after the modules it requires.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Modules {
+    pub files: Vec<ModuleFile>,
+}
+
+impl Modules {
+    pub fn file(&self, name: &str) -> Option<&ModuleFile> {
+        self.files.iter().find(|file| file.name == name)
+    }
+
+    /// Whether any document has an error.
+    pub fn has_errors(&self) -> bool {
+        self.files
+            .iter()
+            .flat_map(|file| &file.diagnostics)
+            .any(Diagnostic::is_error)
+    }
+}
+
+/// Loads the document at `root` and every module it requires, directly or not, reading
+/// documents with `read`. Each document is parsed in the dialect it is written in, then
+/// checked against the modules it requires. Only failing to read `root` itself is an
+/// error; failing to read a required module is reported where it is required.
+pub fn load_modules(
+    root: &Path,
+    read: impl FnMut(&Path) -> io::Result<String>,
+) -> io::Result<Modules> {
+    let mut loader = Loader {
+        read,
+        modules: Modules::default(),
+        loading: Vec::new(),
+    };
+    let text = (loader.read)(root)?;
+    loader.load(root.to_path_buf(), text);
+    Ok(loader.modules)
+}
+
+struct Loader<F> {
+    read: F,
+    modules: Modules,
+    /// The names of the modules being loaded, each requiring the next.
+    loading: Vec<String>,
+}
+
+impl<F: FnMut(&Path) -> io::Result<String>> Loader<F> {
+    /// Loads the document at `path` after the modules it requires, returning its name.
+    fn load(&mut self, path: PathBuf, text: String) -> String {
+        let parse = parse_document(&text);
+        let (schema, lowering) = lower_source_file(&parse.tree());
+        let mut diagnostics = parse.errors().to_vec();
+        diagnostics.extend(lowering);
+        let name = match &schema.module {
+            Some(module) => module.name.text.clone(),
+            None => path
+                .file_stem()
+                .map_or_else(String::new, |stem| stem.to_string_lossy().into_owned()),
+        };
+
+        self.loading.push(name.clone());
+        let (mut declarations, declared) = Declarations::new(&schema.items);
+        diagnostics.extend(declared);
+        for required in schema.module.iter().flat_map(|module| module.requires()) {
+            if let Some(start) = self.loading.iter().position(|name| *name == required.text) {
+                let mut cycle = self.loading[start..].to_vec();
+                cycle.push(required.text.clone());
+                diagnostics.push(Diagnostic::error(
+                    format!("modules require each other: {}", cycle.join(" -> ")),
+                    required.range,
+                ));
+                continue;
+            }
+            if self.modules.file(&required.text).is_none() {
+                let path = path
+                    .parent()
+                    .unwrap_or(Path::new(""))
+                    .join(format!("{}.aski", required.text));
+                let text = match (self.read)(&path) {
+                    Ok(text) => text,
+                    Err(error) => {
+                        diagnostics.push(Diagnostic::error(
+                            format!(
+                                "cannot read module `{required}` from `{}`: {error}",
+                                path.display()
+                            ),
+                            required.range,
+                        ));
+                        continue;
+                    }
+                };
+                let loaded = self.load(path, text);
+                if loaded != required.text {
+                    diagnostics.push(Diagnostic::error(
+                        format!("`{required}.aski` declares module `{loaded}` instead"),
+                        required.range,
+                    ));
+                    continue;
+                }
+            }
+            let file = self
+                .modules
+                .file(&required.text)
+                .expect("the module is loaded");
+            let (required_declarations, _) = Declarations::new(&file.schema.items);
+            declarations.require(&required.text, &required_declarations);
+        }
+        self.loading.pop();
+
+        let (_, resolution) = resolve_against(&schema, &declarations, Vec::new());
+        diagnostics.extend(resolution);
+        diagnostics.extend(check_recursion(&schema));
+        diagnostics.extend(check_ordering(&schema));
+        diagnostics.extend(check_impls(&schema));
+        diagnostics.extend(check_defaults(&schema));
+        diagnostics.sort_by_key(|diagnostic| diagnostic.range.start());
+        self.modules.files.push(ModuleFile {
+            name: name.clone(),
+            path,
+            text,
+            schema,
+            diagnostics,
+        });
+        name
+    }
+}
+
+/// Parses `text` as aski/v1 if it starts with a version form, and in the capitalized
+/// dialect otherwise.
+fn parse_document(text: &str) -> Parse {
+    let (tokens, _) = lex(text);
+    let mut significant = tokens.iter().filter(|token| !token.kind.is_trivia());
+    match (significant.next(), significant.next()) {
+        (Some(open), Some(version))
+            if open.kind == SyntaxKind::L_PAREN && version.text.starts_with("aski/") =>
+        {
+            parse(text)
+        }
+        _ => parse_capitalized(text),
+    }
+}
@@ -0,0 +1,158 @@
+// Map keys and set elements must be ordered.
+//
+// Generated code keeps sets and maps in `BTreeSet` and `BTreeMap`, so a key or element
+// derives `Ord`, and so does everything reachable from it. Floats have no `Ord`, so a key
+// or element that can hold one, itself, through a declaration or as an argument of a
+// generic declaration, is an error. A generic declaration keeping a parameter in a key or
+// element orders its argument the same way, so `(page f64)` is an error too when `page`
+// keeps its items in a set.
+
+use std::collections::{HashMap, HashSet};
+
+use aski_syntax::Diagnostic;
+
+use crate::model::{Item, Primitive, Schema, TypeKind, TypeRef};
+
+/// Reports every map key, set element and ordered argument that can hold a float.
+pub fn check_ordering(schema: &Schema) -> Vec<Diagnostic> {
+    let ordered = ordered_params(schema);
+    let mut diagnostics = Vec::new();
+    for item in &schema.items {
+        for ty in item.type_refs() {
+            check_type(schema, &ordered, ty, &mut diagnostics);
+        }
+    }
+    diagnostics.sort_by_key(|diagnostic| diagnostic.range.start());
+    diagnostics
+}
+
+/// Checks the ordered positions inside `ty`, leaving out whatever is inside one already
+/// reported.
+fn check_type(
+    schema: &Schema,
+    ordered: &HashMap<String, Vec<bool>>,
+    ty: &TypeRef,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let check = |ty: &TypeRef, what: String, diagnostics: &mut Vec<Diagnostic>| {
+        if holds_float(schema, ty) {
+            diagnostics.push(Diagnostic::error(
+                format!("{what} cannot hold a float: floats have no order"),
+                ty.range,
+            ));
+        } else {
+            check_type(schema, ordered, ty, diagnostics);
+        }
+    };
+    match &ty.kind {
+        TypeKind::Map(key, value) => {
+            check(key, "a map key".to_string(), diagnostics);
+            check_type(schema, ordered, value, diagnostics);
+        }
+        TypeKind::Set(element) => check(element, "a set element".to_string(), diagnostics),
+        TypeKind::Named { name, args } => {
+            let flags = ordered.get(name);
+            for (index, arg) in args.iter().enumerate() {
+                if flags.is_some_and(|flags| flags.get(index) == Some(&true)) {
+                    let what = format!("`{name}` orders its argument, which");
+                    check(arg, what, diagnostics);
+                } else {
+                    check_type(schema, ordered, arg, diagnostics);
+                }
+            }
+        }
+        _ => {
+            for child in ty.children() {
+                check_type(schema, ordered, child, diagnostics);
+            }
+        }
+    }
+}
+
+/// Whether a float is reachable from `ty`, following named types into their
+/// declarations.
+fn holds_float(schema: &Schema, ty: &TypeRef) -> bool {
+    let mut seen = HashSet::new();
+    let mut stack = vec![ty];
+    while let Some(ty) = stack.pop() {
+        for nested in ty.walk() {
+            let TypeKind::Named { name, .. } = &nested.kind else {
+                continue;
+            };
+            if Primitive::from_name(name).is_some_and(Primitive::is_float) {
+                return true;
+            }
+            if let Some(item) = schema.item(name) {
+                if seen.insert(name.as_str()) {
+                    stack.extend(item.type_refs());
+                }
+            }
+        }
+    }
+    false
+}
+
+/// Which parameters of each generic item reach a map key or set element, directly or
+/// through the parameters of other generic items. Iterates to a fixpoint since generic
+/// items can use each other in any order.
+pub fn ordered_params(schema: &Schema) -> HashMap<String, Vec<bool>> {
+    let mut ord: HashMap<String, Vec<bool>> = schema
+        .items
+        .iter()
+        .filter(|item| !item.params.is_empty())
+        .map(|item| (item.name.text.clone(), vec![false; item.params.len()]))
+        .collect();
+    loop {
+        let mut changed = false;
+        for item in schema.items.iter().filter(|item| !item.params.is_empty()) {
+            let mut found = HashSet::new();
+            for ty in item.type_refs() {
+                mark_ord_params(ty, false, item, &ord, &mut found);
+            }
+            let flags = ord.get_mut(&item.name.text).unwrap();
+            for (index, param) in item.params.iter().enumerate() {
+                if !flags[index] && found.contains(param.text.as_str()) {
+                    flags[index] = true;
+                    changed = true;
+                }
+            }
+        }
+        if !changed {
+            return ord;
+        }
+    }
+}
+
+/// Collects the parameters of `item` used inside `ty` where they must be `Ord`.
+fn mark_ord_params<'a>(
+    ty: &'a TypeRef,
+    in_key: bool,
+    item: &Item,
+    ord: &HashMap<String, Vec<bool>>,
+    found: &mut HashSet<&'a str>,
+) {
+    match &ty.kind {
+        TypeKind::Named { name, .. } if item.params.iter().any(|p| &p.text == name) => {
+            if in_key {
+                found.insert(name);
+            }
+        }
+        TypeKind::Named { name, args } => {
+            let needs_ord = ord.get(name);
+            for (index, arg) in args.iter().enumerate() {
+                let arg_in_key = needs_ord.is_some_and(|flags| flags.get(index) == Some(&true));
+                mark_ord_params(arg, in_key || arg_in_key, item, ord, found);
+            }
+        }
+        TypeKind::Map(key, value) => {
+            mark_ord_params(key, true, item, ord, found);
+            mark_ord_params(value, in_key, item, ord, found);
+        }
+        TypeKind::Set(element) => mark_ord_params(element, true, item, ord, found),
+        _ => {
+            for child in ty.children() {
+                mark_ord_params(child, in_key, item, ord, found);
+            }
+        }
+    }
+}
@@ -0,0 +1,165 @@
+// Recursive types must have a finite size.
+//
+// A type is stored inline in whatever contains it unless something puts it behind a
+// pointer: `vec`, `set` and `map` allocate their contents, and `box` does so explicitly.
+// Options, results, arrays and tuples store their contents inline, so `(? node)` inside
+// `node` is still infinitely large; `(? (box node))` is how to write an optional link.
+//
+// A generic declaration stores an argument inline exactly when it stores the matching
+// parameter inline, so `(wrapped node)` inside `node` is a cycle while `(page node)`, with
+// `page` keeping its items in a vec, is not.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use aski_syntax::{Diagnostic, TextRange};
+
+use crate::model::{Item, Schema, TypeKind, TypeRef};
+
+/// Reports every cycle of declarations containing each other without indirection.
+pub fn check_recursion(schema: &Schema) -> Vec<Diagnostic> {
+    let inline_params = inline_params(schema);
+    let edges: HashMap<&str, Vec<Edge>> = schema
+        .items
+        .iter()
+        .map(|item| {
+            let mut contents = Contents::default();
+            for ty in item.type_refs() {
+                contents.collect(schema, item, &inline_params, ty);
+            }
+            (item.name.text.as_str(), contents.items)
+        })
+        .collect();
+
+    let mut reported = HashSet::new();
+    let mut diagnostics = Vec::new();
+    for item in &schema.items {
+        let start = item.name.text.as_str();
+        if reported.contains(start) {
+            continue;
+        }
+        let Some(cycle) = shortest_cycle(&edges, start) else {
+            continue;
+        };
+        let mut path = vec![start];
+        path.extend(cycle.iter().map(|edge| edge.target));
+        reported.extend(path.iter().copied());
+        diagnostics.push(Diagnostic::error(
+            format!(
+                "recursive type `{start}` has infinite size: {}; put one of these references in a `(box ...)`",
+                path.join(" -> ")
+            ),
+            cycle[0].range,
+        ));
+    }
+    diagnostics
+}
+
+/// A declaration stores `target` inline, referring to it at `range`.
+#[derive(Clone, Copy)]
+struct Edge<'a> {
+    target: &'a str,
+    range: TextRange,
+}
+
+/// What one declaration stores inline.
+#[derive(Default)]
+struct Contents<'a> {
+    items: Vec<Edge<'a>>,
+    params: HashSet<&'a str>,
+}
+
+impl<'a> Contents<'a> {
+    fn collect(
+        &mut self,
+        schema: &'a Schema,
+        item: &Item,
+        inline_params: &HashMap<&str, Vec<bool>>,
+        ty: &'a TypeRef,
+    ) {
+        match &ty.kind {
+            TypeKind::Vec(_) | TypeKind::Set(_) | TypeKind::Map(..) | TypeKind::Box(_) => {}
+            TypeKind::Named { name, .. } if item.params.iter().any(|p| &p.text == name) => {
+                self.params.insert(name);
+            }
+            TypeKind::Named { name, args } => {
+                let Some(declared) = schema.item(name) else {
+                    return;
+                };
+                self.items.push(Edge {
+                    target: &declared.name.text,
+                    range: ty.range,
+                });
+                let inline = inline_params.get(name.as_str());
+                for (index, arg) in args.iter().enumerate() {
+                    if inline.is_some_and(|flags| flags.get(index) == Some(&true)) {
+                        self.collect(schema, item, inline_params, arg);
+                    }
+                }
+            }
+            _ => {
+                for child in ty.children() {
+                    self.collect(schema, item, inline_params, child);
+                }
+            }
+        }
+    }
+}
+
+/// Which parameters of each generic declaration it stores inline. Iterates to a fixpoint
+/// since generic declarations can pass their parameters on to each other.
+fn inline_params(schema: &Schema) -> HashMap<&str, Vec<bool>> {
+    let mut inline: HashMap<&str, Vec<bool>> = schema
+        .items
+        .iter()
+        .filter(|item| !item.params.is_empty())
+        .map(|item| (item.name.text.as_str(), vec![false; item.params.len()]))
+        .collect();
+    loop {
+        let mut changed = false;
+        for item in schema.items.iter().filter(|item| !item.params.is_empty()) {
+            let mut contents = Contents::default();
+            for ty in item.type_refs() {
+                contents.collect(schema, item, &inline, ty);
+            }
+            let flags = inline.get_mut(item.name.text.as_str()).unwrap();
+            for (index, param) in item.params.iter().enumerate() {
+                if !flags[index] && contents.params.contains(param.text.as_str()) {
+                    flags[index] = true;
+                    changed = true;
+                }
+            }
+        }
+        if !changed {
+            return inline;
+        }
+    }
+}
+
+/// The shortest path of edges leading from `start` back to itself.
+fn shortest_cycle<'a>(
+    edges: &HashMap<&'a str, Vec<Edge<'a>>>,
+    start: &'a str,
+) -> Option<Vec<Edge<'a>>> {
+    let mut came_from: HashMap<&str, (&str, Edge)> = HashMap::new();
+    let mut queue = VecDeque::from([start]);
+    while let Some(node) = queue.pop_front() {
+        for &edge in edges.get(node).into_iter().flatten() {
+            if edge.target == start {
+                let mut path = vec![edge];
+                let mut at = node;
+                while at != start {
+                    let (previous, edge) = came_from[at];
+                    path.push(edge);
+                    at = previous;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            if !came_from.contains_key(edge.target) {
+                came_from.insert(edge.target, (node, edge));
+                queue.push_back(edge.target);
+            }
+        }
+    }
+    None
+}
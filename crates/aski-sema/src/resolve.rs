@@ -0,0 +1,301 @@
+// Name resolution.
+//
+// Every named type reference is bound to one of three things, tried in order: a type
+// parameter of the enclosing declaration, a primitive, or a declaration of the schema.
+// A qualified reference, `stuff/user`, is bound to the declaration `user` of the required
+// module `stuff` instead. Anything else is unresolved. Declarations must have distinct
+// names, distinct from the primitives, and so must the fields of a record or struct
+// variant and the variants of an enum. Generic arity is checked here too, since it
+// depends on what a name is bound to.
+//
+// The types an impl mentions resolve the same way, except that `self` in a member's
+// signature is bound to whatever the implementing type is.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use aski_syntax::{Diagnostic, TextRange};
+
+use crate::generics::{check_arity, check_params};
+use crate::model::{
+    Field, Impl, Item, ItemKind, Name, Primitive, Schema, TypeKind, TypeRef, VariantBody, SELF_TYPE,
+};
+
+/// What a type name refers to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Binding {
+    Primitive(Primitive),
+    /// A declaration of the schema, by name, or of a required module, by qualified name.
+    Item(String),
+    /// The type parameter at this index of the enclosing declaration.
+    Param(usize),
+}
+
+/// The binding of every resolved type reference, keyed by the reference's range.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Resolution {
+    bindings: HashMap<TextRange, Binding>,
+}
+
+impl Resolution {
+    /// What the named type reference `ty` refers to, if it resolved.
+    pub fn binding(&self, ty: &TypeRef) -> Option<&Binding> {
+        self.bindings.get(&ty.range)
+    }
+
+    /// The ranges of every type reference bound to the declaration `item`, in source order.
+    pub fn references(&self, item: &str) -> Vec<TextRange> {
+        let mut ranges: Vec<_> = self
+            .bindings
+            .iter()
+            .filter(|(_, binding)| matches!(binding, Binding::Item(name) if name == item))
+            .map(|(range, _)| *range)
+            .collect();
+        ranges.sort();
+        ranges
+    }
+}
+
+/// The declared names of a schema, with how many type parameters each takes.
+///
+/// This is all resolving a declaration needs to know about the rest of the schema, so
+/// edits that leave it unchanged cannot change how other declarations resolve.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Declarations {
+    arities: BTreeMap<String, usize>,
+    /// The declarations of each required module.
+    modules: BTreeMap<String, Declarations>,
+}
+
+impl Declarations {
+    /// Collects the declarations of `items`, reporting names declared twice or shadowed
+    /// by a primitive. The first declaration of a name wins.
+    pub fn new<'a>(items: impl IntoIterator<Item = &'a Item>) -> (Declarations, Vec<Diagnostic>) {
+        let mut declarations = Declarations::default();
+        let mut diagnostics = Vec::new();
+        for item in items {
+            let name = &item.name;
+            if name.text.contains('/') {
+                diagnostics.push(Diagnostic::error(
+                    format!("`{name}` cannot be declared: `/` separates a module from its types"),
+                    name.range,
+                ));
+            } else if Primitive::from_name(&name.text).is_some() {
+                diagnostics.push(Diagnostic::error(
+                    format!("`{name}` is a primitive type and cannot be declared"),
+                    name.range,
+                ));
+            } else if declarations.arities.contains_key(&name.text) {
+                diagnostics.push(Diagnostic::error(
+                    format!("type `{name}` is declared more than once"),
+                    name.range,
+                ));
+            } else {
+                declarations
+                    .arities
+                    .insert(name.text.clone(), item.params.len());
+            }
+        }
+        (declarations, diagnostics)
+    }
+
+    /// How many type parameters the declaration `name` takes, if there is one.
+    pub fn arity(&self, name: &str) -> Option<usize> {
+        self.arities.get(name).copied()
+    }
+
+    /// Makes the declarations of the required module `module` available to qualified
+    /// references. What that module requires in turn is not.
+    pub fn require(&mut self, module: impl Into<String>, declarations: &Declarations) {
+        let arities = declarations.arities.clone();
+        self.modules.insert(
+            module.into(),
+            Declarations {
+                arities,
+                modules: BTreeMap::new(),
+            },
+        );
+    }
+
+    /// The declarations of the required module `module`.
+    pub fn module(&self, module: &str) -> Option<&Declarations> {
+        self.modules.get(module)
+    }
+}
+
+/// Binds every type reference of `schema` and reports the names that do not resolve or
+/// are declared more than once.
+pub fn resolve(schema: &Schema) -> (Resolution, Vec<Diagnostic>) {
+    let (declarations, diagnostics) = Declarations::new(&schema.items);
+    resolve_against(schema, &declarations, diagnostics)
+}
+
+/// Binds every type reference of `schema` against `declarations`, adding to the
+/// `diagnostics` collecting them reported.
+pub(crate) fn resolve_against(
+    schema: &Schema,
+    declarations: &Declarations,
+    mut diagnostics: Vec<Diagnostic>,
+) -> (Resolution, Vec<Diagnostic>) {
+    let mut resolution = Resolution::default();
+    for item in &schema.items {
+        let (item_resolution, item_diagnostics) = resolve_item(declarations, item);
+        resolution.bindings.extend(item_resolution.bindings);
+        diagnostics.extend(item_diagnostics);
+    }
+    for imp in &schema.impls {
+        let (impl_resolution, impl_diagnostics) = resolve_impl(declarations, imp);
+        resolution.bindings.extend(impl_resolution.bindings);
+        diagnostics.extend(impl_diagnostics);
+    }
+    diagnostics.sort_by_key(|diagnostic| diagnostic.range.start());
+    (resolution, diagnostics)
+}
+
+/// Binds the type references of a single declaration against `declarations`.
+pub fn resolve_item(declarations: &Declarations, item: &Item) -> (Resolution, Vec<Diagnostic>) {
+    let mut resolver = Resolver {
+        declarations,
+        resolution: Resolution::default(),
+        diagnostics: Vec::new(),
+    };
+    check_params(item, &mut resolver.diagnostics);
+    resolver.members(item);
+    for ty in item.type_refs() {
+        resolver.type_ref(&item.params, ty);
+    }
+    resolver
+        .diagnostics
+        .sort_by_key(|diagnostic| diagnostic.range.start());
+    (resolver.resolution, resolver.diagnostics)
+}
+
+/// Binds the implementing type of an impl and the types of its members' signatures
+/// against `declarations`.
+pub fn resolve_impl(declarations: &Declarations, imp: &Impl) -> (Resolution, Vec<Diagnostic>) {
+    let mut resolver = Resolver {
+        declarations,
+        resolution: Resolution::default(),
+        diagnostics: Vec::new(),
+    };
+    resolver.impl_types(imp);
+    resolver
+        .diagnostics
+        .sort_by_key(|diagnostic| diagnostic.range.start());
+    (resolver.resolution, resolver.diagnostics)
+}
+
+struct Resolver<'a> {
+    declarations: &'a Declarations,
+    resolution: Resolution,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Resolver<'_> {
+    /// Duplicate fields and variants within one declaration.
+    fn members(&mut self, item: &Item) {
+        match &item.kind {
+            ItemKind::Record(fields) => self.fields(fields),
+            ItemKind::Enum(variants) => {
+                self.distinct("variant", variants.iter().map(|variant| &variant.name));
+                for variant in variants {
+                    if let VariantBody::Struct(fields) = &variant.body {
+                        self.fields(fields);
+                    }
+                }
+            }
+            ItemKind::Newtype(_) | ItemKind::Tuple(_) => {}
+        }
+    }
+
+    fn fields(&mut self, fields: &[Field]) {
+        self.distinct("field", fields.iter().map(|field| &field.name));
+    }
+
+    fn distinct<'n>(&mut self, what: &str, names: impl Iterator<Item = &'n Name>) {
+        let mut seen = HashSet::new();
+        for name in names {
+            if !seen.insert(name.text.as_str()) {
+                self.diagnostics.push(Diagnostic::error(
+                    format!("{what} `{name}` is declared more than once"),
+                    name.range,
+                ));
+            }
+        }
+    }
+
+    /// The implementing type of an impl and the types of its members' signatures.
+    fn impl_types(&mut self, imp: &Impl) {
+        self.type_ref(&[], &imp.self_ty);
+        let itself = self.resolution.binding(&imp.self_ty).cloned();
+        for signature in imp.members.iter().filter_map(|m| m.signature.as_ref()) {
+            for ty in signature.params.iter().chain([&signature.ret]) {
+                self.signature_type(ty, itself.as_ref());
+            }
+        }
+    }
+
+    /// Like `type_ref`, with `self` bound to `itself`, the implementing type.
+    fn signature_type(&mut self, ty: &TypeRef, itself: Option<&Binding>) {
+        match &ty.kind {
+            TypeKind::Named { name, args } if name == SELF_TYPE && args.is_empty() => {
+                if let Some(binding) = itself {
+                    self.resolution.bindings.insert(ty.range, binding.clone());
+                }
+            }
+            _ => {
+                for child in ty.children() {
+                    self.signature_type(child, itself);
+                }
+                self.named(&[], ty);
+            }
+        }
+    }
+
+    /// Binds `ty` and every type nested in it, in a declaration taking the type
+    /// parameters `params`.
+    fn type_ref(&mut self, params: &[Name], ty: &TypeRef) {
+        for child in ty.children() {
+            self.type_ref(params, child);
+        }
+        self.named(params, ty);
+    }
+
+    /// Binds `ty` itself, if it is a named type.
+    fn named(&mut self, params: &[Name], ty: &TypeRef) {
+        let TypeKind::Named { name, args } = &ty.kind else {
+            return;
+        };
+        let (binding, arity) = if let Some(index) = params.iter().position(|p| &p.text == name) {
+            (Binding::Param(index), 0)
+        } else if let Some(primitive) = Primitive::from_name(name) {
+            (Binding::Primitive(primitive), 0)
+        } else if let Some((module, local)) = name.split_once('/') {
+            let Some(declarations) = self.declarations.module(module) else {
+                self.diagnostics.push(Diagnostic::error(
+                    format!("cannot find module `{module}`; is it in the module's `require`?"),
+                    ty.range,
+                ));
+                return;
+            };
+            let Some(arity) = declarations.arity(local) else {
+                self.diagnostics.push(Diagnostic::error(
+                    format!("module `{module}` has no type `{local}`"),
+                    ty.range,
+                ));
+                return;
+            };
+            (Binding::Item(name.clone()), arity)
+        } else if let Some(arity) = self.declarations.arity(name) {
+            (Binding::Item(name.clone()), arity)
+        } else {
+            self.diagnostics.push(Diagnostic::error(
+                format!("cannot find type `{name}`"),
+                ty.range,
+            ));
+            return;
+        };
+        self.diagnostics
+            .extend(check_arity(name, arity, args.len(), ty.range));
+        self.resolution.bindings.insert(ty.range, binding);
+    }
+}
@@ -0,0 +1,75 @@
+//! Capitalized definitions lower to the model aski/v1 declarations do, in kebab-case.
+
+use aski_sema::{lower_source_file, resolve, ItemKind, Schema, TypeKind, VariantBody};
+use aski_syntax::parse_capitalized;
+
+const PROTOTYPE: &str = include_str!("../../../encoder/examples/prototype-design.aski");
+
+fn lower(text: &str) -> Schema {
+    let parse = parse_capitalized(text);
+    assert_eq!(parse.errors(), &[]);
+    let (schema, diagnostics) = lower_source_file(&parse.tree());
+    assert_eq!(diagnostics, &[]);
+    schema
+}
+
+fn named(kind: &TypeKind) -> &str {
+    match kind {
+        TypeKind::Named { name, .. } => name,
+        other => panic!("expected a named type, found {other:?}"),
+    }
+}
+
+#[test]
+fn prototype_design_lowers_in_kebab_case() {
+    let schema = lower(PROTOTYPE);
+    let names: Vec<_> = schema.items.iter().map(|item| &item.name.text).collect();
+    assert_eq!(names, ["user", "species", "age"]);
+
+    let ItemKind::Record(fields) = &schema.item("user").unwrap().kind else {
+        panic!("expected a record");
+    };
+    let fields: Vec<_> = fields
+        .iter()
+        .map(|field| (field.name.text.as_str(), named(&field.ty.kind)))
+        .collect();
+    assert_eq!(fields, [("first-name", "string"), ("last-name", "string")]);
+
+    let ItemKind::Enum(variants) = &schema.item("species").unwrap().kind else {
+        panic!("expected an enum");
+    };
+    assert_eq!(variants[0].name.text, "first-variant");
+    assert!(matches!(&variants[0].body, VariantBody::Newtype(ty) if named(&ty.kind) == "u32"));
+    assert_eq!(variants[1].name.text, "second-variant");
+    assert_eq!(variants[1].body, VariantBody::Unit);
+
+    let ItemKind::Newtype(age) = &schema.item("age").unwrap().kind else {
+        panic!("expected a newtype");
+    };
+    assert_eq!(named(&age.kind), "u16");
+    assert_eq!(resolve(&schema).1, &[]);
+}
+
+#[test]
+fn type_parameters_keep_their_spelling() {
+    let schema = lower("(Page T) {items (Vec T) nextPage (Option (Page T)) owner UserId}");
+    let item = &schema.items[0];
+    assert_eq!(item.name.text, "page");
+    assert_eq!(item.params[0].text, "T");
+    let ItemKind::Record(fields) = &item.kind else {
+        panic!("expected a record");
+    };
+    let TypeKind::Vec(element) = &fields[0].ty.kind else {
+        panic!("expected a vec");
+    };
+    assert_eq!(named(&element.kind), "T");
+    assert_eq!(fields[1].name.text, "next-page");
+    let TypeKind::Option(next) = &fields[1].ty.kind else {
+        panic!("expected an option");
+    };
+    let TypeKind::Named { name, args } = &next.kind else {
+        panic!("expected a generic application");
+    };
+    assert_eq!((name.as_str(), named(&args[0].kind)), ("page", "T"));
+    assert_eq!(named(&fields[2].ty.kind), "user-id");
+}
@@ -0,0 +1,179 @@
+//! Record fields take default values of their type.
+
+use aski_sema::{check_defaults, lower_source_file, Schema, ValueKind};
+use aski_syntax::parse;
+
+fn lower(text: &str) -> Schema {
+    let parse = parse(text);
+    assert_eq!(parse.errors(), &[]);
+    let (schema, diagnostics) = lower_source_file(&parse.tree());
+    assert_eq!(diagnostics, &[]);
+    schema
+}
+
+/// What `check_defaults` reports, as the text it points at and its message.
+fn check(text: &str) -> Vec<(&str, String)> {
+    check_defaults(&lower(text))
+        .into_iter()
+        .map(|d| (&text[d.range.start()..d.range.end()], d.message))
+        .collect()
+}
+
+#[test]
+fn defaults_of_every_kind_of_type() {
+    let text = r#"(aski/v1
+  (newtype port u16)
+  (record point {x: f64 y: f64})
+  (record nothing {})
+  (tuple span [u32 u32])
+  (enum shape (dot) (circle f64) (rect [f64 f64]) (poly {points: (vec point)}))
+  (enum (maybe T) (just T) (nothing))
+  (record settings {
+    on: bool (default true)
+    port: port (default 8080)
+    offset: i8 (default -128)
+    big: u128 (default 340282366920938463463374607431768211455)
+    ratio: f32 (default 1)
+    scale: f64 (default -2.5e-3)
+    name: string (default "local")
+    initial: char (default "λ")
+    id: uuid (default "67e55044-10b1-426f-9247-bb680e5fe0c8")
+    nil: unit (default [])
+    raw: bytes (default [0 255])
+    blob: bytes (default #bytes "00ff")
+    tagged-id: uuid (default #uuid "67e55044-10b1-426f-9247-bb680e5fe0c8")
+    parsed: (result u8 string) (default #ok 1)
+    failed: (result u8 string) (default #err "no")
+    parent: (? string) (default none)
+    label: (? string) (default "x")
+    tags: (vec string) (default [])
+    seen: (set u8) (default [1 2])
+    grid: (array 2 u8) (default [3 4])
+    pair: [i32 string] (default [1 "a"])
+    names: (map u8 string) (default {1 "one" 2 "two"})
+    empty: (map string u8) (default {})
+    origin: point (default {x: 0 y: 0.5})
+    none: nothing (default {})
+    span: span (default [1 2])
+    link: (box point) (default {y: 1 x: 2})
+    dot: shape (default (shape dot))
+    circle: shape (default (shape circle 1.5))
+    rect: shape (default (shape rect [1 2]))
+    poly: shape (default (shape poly {points: [{x: 0 y: 0}]}))
+    maybe: (maybe u8) (default (maybe just 3))})
+  (record (page T) {items: (vec T) (default []) total: u32 (default 0)}))"#;
+    assert_eq!(check(text), []);
+
+    let schema = lower(text);
+    let settings = schema.item("settings").unwrap();
+    let aski_sema::ItemKind::Record(fields) = &settings.kind else {
+        panic!("expected a record");
+    };
+    assert_eq!(
+        fields[6].default.as_ref().map(|value| &value.kind),
+        Some(&ValueKind::String("local".to_string()))
+    );
+}
+
+#[test]
+fn defaults_must_fit_their_type() {
+    let text = r#"(aski/v1
+  (record point {x: f64 y: f64})
+  (enum shape (dot) (circle f64))
+  (record (page T) {first: T (default 1)})
+  (record settings {
+    port: u8 (default 256)
+    name: string (default 1)
+    initial: char (default "ab")
+    id: uuid (default "nope")
+    ratio: f64 (default "x")
+    grid: (array 2 u8) (default [1])
+    origin: point (default {x: 0 z: 1})
+    shape: shape (default (shape square))
+    dot: shape (default (shape dot 1))
+    circle: shape (default (shape circle))
+    other: shape (default (point dot))
+    parse: (result u8 string) (default 1)
+    failed: (result u8 string) (default #err 2)
+    blob: bytes (default #bytes "0g")
+    tagged: uuid (default #bytes "00")}))"#;
+    assert_eq!(
+        check(text),
+        [
+            (
+                "1",
+                "`T` is a type parameter, so its values cannot be written".to_string()
+            ),
+            ("256", "`256` is out of range for `u8`".to_string()),
+            (
+                "1",
+                "expected a value of `string`, found an integer".to_string()
+            ),
+            (
+                "\"ab\"",
+                "a `char` is written as a string of exactly one character".to_string()
+            ),
+            (
+                "\"nope\"",
+                "a `uuid` is written as a string like \"67e55044-10b1-426f-9247-bb680e5fe0c8\""
+                    .to_string()
+            ),
+            (
+                "\"x\"",
+                "expected a value of `f64`, found a string".to_string()
+            ),
+            (
+                "[1]",
+                "a value of `(array 2 u8)` has 2 elements, not 1".to_string()
+            ),
+            ("z", "`point` has no field `z`".to_string()),
+            ("{x: 0 z: 1}", "missing field `y`".to_string()),
+            ("square", "`shape` has no variant `square`".to_string()),
+            ("1", "variant `dot` of `shape` takes no payload".to_string()),
+            (
+                "(shape circle)",
+                "variant `circle` of `shape` takes a payload".to_string()
+            ),
+            (
+                "(point dot)",
+                "expected a value of `shape`, found a `point` variant".to_string()
+            ),
+            (
+                "1",
+                "expected a value of `(result u8 string)`, found an integer".to_string()
+            ),
+            (
+                "2",
+                "expected a value of `string`, found an integer".to_string()
+            ),
+            (
+                "\"0g\"",
+                "`#bytes` takes a string of hex digits, two per byte, like \"00ff\"".to_string()
+            ),
+            (
+                "#bytes \"00\"",
+                "expected a value of `uuid`, found a `#bytes` value".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn defaults_only_go_on_record_fields() {
+    let parse =
+        parse("(aski/v1 (enum e (v {x: u8 (default 1)})) (record r {x: nope (default 1)}))");
+    let (_, diagnostics) = lower_source_file(&parse.tree());
+    let messages: Vec<_> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+    assert_eq!(messages, ["only the fields of a record take a default"]);
+}
+
+#[test]
+fn values_take_known_tags_only() {
+    let parse = parse("(aski/v1 (record r {id: uuid (default #guid \"x\")}))");
+    let (_, diagnostics) = lower_source_file(&parse.tree());
+    let messages: Vec<_> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+    assert_eq!(
+        messages,
+        ["unknown tag `#guid`; the tags are `#uuid`, `#bytes`, `#ok`, `#err`"]
+    );
+}
@@ -0,0 +1,161 @@
+use std::collections::HashSet;
+
+use aski_sema::{expected_at, expected_at_path, Expected};
+use aski_syntax::ast::AstNode;
+use aski_syntax::{parse, SyntaxKind};
+
+const ALL_TYPES: &str = include_str!("../../../encoder/drafts/all-types.aski");
+
+/// What `text` expects where `$0` marks it.
+fn at(text: &str) -> Expected {
+    let offset = text.find("$0").expect("a `$0` marker");
+    let text = text.replacen("$0", "", 1);
+    expected_at(&parse(&text).tree(), offset)
+}
+
+/// What the draft expects at `$0` in `context`, which occurs once in it.
+fn in_draft(context: &str) -> Expected {
+    let marker = context.find("$0").expect("a `$0` marker");
+    let needle = context.replacen("$0", "", 1);
+    let mut found = ALL_TYPES.match_indices(&needle);
+    let (start, _) = found.next().expect("the context occurs in the draft");
+    assert!(found.next().is_none(), "`{needle}` occurs more than once");
+    expected_at(&parse(ALL_TYPES).tree(), start + marker)
+}
+
+#[test]
+fn every_position_of_the_draft_has_an_answer() {
+    let file = parse(ALL_TYPES).tree();
+    let answers: Vec<Expected> = (0..=ALL_TYPES.len())
+        .map(|offset| expected_at(&file, offset))
+        .collect();
+
+    for token in file
+        .syntax()
+        .descendants_with_tokens()
+        .filter_map(|element| element.into_token())
+    {
+        let range = token.text_range();
+        let inside = &answers[range.start()..=range.end()];
+        match token.kind() {
+            SyntaxKind::COMMENT => {
+                assert!(inside[1..]
+                    .iter()
+                    .all(|&answer| answer == Expected::Nothing))
+            }
+            SyntaxKind::SYMBOL | SyntaxKind::INT => {
+                assert_ne!(inside[0], Expected::Nothing, "{:?}", token.text());
+                assert!(inside.iter().all(|&answer| answer == inside[0]));
+            }
+            kind if kind.is_keyword() => assert!(inside.iter().all(|&answer| answer == inside[0])),
+            _ => {}
+        }
+    }
+
+    let seen: HashSet<Expected> = answers.into_iter().collect();
+    assert_eq!(seen.len(), 11, "every kind of answer occurs: {seen:?}");
+}
+
+#[test]
+fn words_are_what_they_stand_for() {
+    assert_eq!(in_draft("(aski/$0v1"), Expected::Version);
+    assert_eq!(in_draft("($0newtype user-id"), Expected::Declaration);
+    assert_eq!(in_draft("(newtype us$0er-id uuid)"), Expected::DeclName);
+    assert_eq!(in_draft("(newtype user-id uu$0id)"), Expected::Type);
+    assert_eq!(in_draft("(newtype ($0wrapped T) T)"), Expected::DeclName);
+    assert_eq!(in_draft("(newtype (wrapped $0T) T)"), Expected::TypeParam);
+    assert_eq!(in_draft("(newtype (wrapped T) $0T)"), Expected::Type);
+    assert_eq!(in_draft("{ $0bool-value"), Expected::FieldKey);
+    assert_eq!(in_draft("bool-value: bo$0ol"), Expected::Type);
+    assert_eq!(in_draft("bool-value: bool$0"), Expected::Type);
+    assert_eq!(in_draft("($0not-found)"), Expected::Variant);
+    assert_eq!(in_draft("(text $0string)"), Expected::VariantBody);
+    assert_eq!(in_draft("(batch (vec $0message))"), Expected::Type);
+    assert_eq!(
+        in_draft("(batch ($0vec message))"),
+        Expected::TypeConstructor
+    );
+    assert_eq!(in_draft("($0? u32)"), Expected::TypeConstructor);
+    assert_eq!(in_draft("(array $03 u16)"), Expected::ArrayLength);
+    assert_eq!(in_draft("(array 3 $0u16)"), Expected::Type);
+    assert_eq!(in_draft("($0wrapped pair)"), Expected::TypeConstructor);
+    assert_eq!(in_draft("(wrapped $0pair)"), Expected::Type);
+    assert_eq!(in_draft(";; Canonical$0 enum"), Expected::Nothing);
+}
+
+#[test]
+fn gaps_take_what_the_form_takes_next() {
+    assert_eq!(in_draft("(aski/v1\n$0"), Expected::Declaration);
+    assert_eq!(in_draft("(newtype user-id uuid)$0"), Expected::Declaration);
+    assert_eq!(in_draft("(enum shape\n   $0 (unit)"), Expected::Variant);
+    assert_eq!(in_draft("(named string))$0"), Expected::Declaration);
+    assert_eq!(in_draft("(circle $0{r: f64})"), Expected::VariantBody);
+    assert_eq!(in_draft("(circle {$0r: f64})"), Expected::FieldKey);
+    assert_eq!(in_draft("(circle {r: f64}$0)"), Expected::Nothing);
+    assert_eq!(in_draft("(rect [f64 $0f64])"), Expected::Type);
+    assert_eq!(in_draft("(rect [$0f64 f64])"), Expected::Type);
+    assert_eq!(in_draft("(tuple pair $0[i32 i32])"), Expected::Nothing);
+    assert_eq!(in_draft("(record status\n $0"), Expected::Nothing);
+    assert_eq!(in_draft("{ ok: bool\n $0"), Expected::FieldKey);
+    assert_eq!(in_draft("code: $0(? u32)"), Expected::Type);
+    assert_eq!(in_draft("code: (? u32)$0"), Expected::Nothing);
+    assert_eq!(in_draft("(? $0u32)"), Expected::Type);
+    assert_eq!(in_draft("(result string $0error-code)"), Expected::Type);
+    assert_eq!(
+        in_draft("mixed-tuple: [i32 string bool]$0"),
+        Expected::Nothing
+    );
+    assert_eq!(
+        in_draft("unit-struct-value: unit-struct } )\n$0"),
+        Expected::Declaration
+    );
+    assert_eq!(
+        in_draft("unit-struct-value: unit-struct } )\n)$0"),
+        Expected::Nothing
+    );
+    assert_eq!(expected_at(&parse(ALL_TYPES).tree(), 0), Expected::Nothing);
+}
+
+#[test]
+fn incomplete_input_still_has_answers() {
+    assert_eq!(at("$0"), Expected::Nothing);
+    assert_eq!(at("(aski/v1 $0"), Expected::Declaration);
+    assert_eq!(at("(aski/v1 ($0"), Expected::Declaration);
+    assert_eq!(at("(aski/v1 (record $0"), Expected::DeclName);
+    assert_eq!(at("(aski/v1 (record (page $0"), Expected::TypeParam);
+    assert_eq!(at("(aski/v1 (record r { $0"), Expected::FieldKey);
+    assert_eq!(at("(aski/v1 (record r { a: $0"), Expected::Type);
+    assert_eq!(at("(aski/v1 (record r { a $0"), Expected::Nothing);
+    assert_eq!(at("(aski/v1 (enum e $0"), Expected::Variant);
+    assert_eq!(at("(aski/v1 (enum e (v $0"), Expected::VariantBody);
+    assert_eq!(at("(aski/v1 (newtype n $0"), Expected::Type);
+    assert_eq!(at("(aski/v1 (newtype n (array $0"), Expected::ArrayLength);
+    assert_eq!(at("(aski/v1 (newtype n (map u8 $0"), Expected::Type);
+    assert_eq!(at("(aski/v1 (newtype n (map u8 u8 $0"), Expected::Nothing);
+}
+
+#[test]
+fn node_paths_ask_where_their_node_starts() {
+    let file = parse(ALL_TYPES).tree();
+    let expected = |path: &str| expected_at_path(&file, &path.parse().unwrap());
+    assert_eq!(expected("all-types"), Some(Expected::Declaration));
+    assert_eq!(
+        expected("all-types/fields/status"),
+        Some(Expected::FieldKey)
+    );
+    assert_eq!(
+        expected("all-types/fields/status/type"),
+        Some(Expected::Type)
+    );
+    assert_eq!(
+        expected("all-types/fields/mixed-tuple/type/fields/2"),
+        Some(Expected::Type)
+    );
+    assert_eq!(expected("shape/variants/rect"), Some(Expected::Variant));
+    assert_eq!(
+        expected("message/variants/text/type"),
+        Some(Expected::VariantBody)
+    );
+    assert_eq!(expected("wrapped/params/T"), Some(Expected::TypeParam));
+    assert_eq!(expected("all-types/fields/missing"), None);
+}
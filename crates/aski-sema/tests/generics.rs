@@ -0,0 +1,106 @@
+//! Generic declarations of every kind, and the arity of their uses.
+
+use aski_sema::{lower_source_file, resolve, ItemKind, Schema};
+use aski_syntax::parse;
+
+fn lower(text: &str) -> Schema {
+    let parse = parse(text);
+    assert_eq!(parse.errors(), &[]);
+    let (schema, diagnostics) = lower_source_file(&parse.tree());
+    assert_eq!(diagnostics, &[]);
+    schema
+}
+
+/// `(source text at the range, message)` for every resolver diagnostic of `text`.
+fn check(text: &str) -> Vec<(&str, String)> {
+    resolve(&lower(text))
+        .1
+        .into_iter()
+        .map(|d| (&text[d.range.start()..d.range.end()], d.message))
+        .collect()
+}
+
+#[test]
+fn every_declaration_kind_can_be_generic() {
+    let schema = lower(
+        "(aski/v1
+  (newtype (wrapped T) T)
+  (record (page T) {items: (vec T) next: (? string)})
+  (tuple (pair A B) [A B])
+  (enum (either L R) (left L) (right R)))",
+    );
+    let params: Vec<Vec<&str>> = schema
+        .items
+        .iter()
+        .map(|item| item.params.iter().map(|p| p.text.as_str()).collect())
+        .collect();
+    assert_eq!(
+        params,
+        [vec!["T"], vec!["T"], vec!["A", "B"], vec!["L", "R"]]
+    );
+    assert!(matches!(schema.items[1].kind, ItemKind::Record(_)));
+    assert_eq!(resolve(&schema).1, &[]);
+}
+
+#[test]
+fn all_types_is_well_formed() {
+    let text = include_str!("../../../encoder/drafts/all-types.aski");
+    assert_eq!(check(text), Vec::<(&str, String)>::new());
+}
+
+#[test]
+fn uses_must_match_the_declared_arity() {
+    let text = "(aski/v1
+  (newtype (wrapped T) T)
+  (tuple (pair A B) [A B])
+  (record id {})
+  (record uses {
+    a: (wrapped pair)
+    b: (pair u8)
+    c: (id u8)
+    d: (string u8)
+    e: (wrapped (pair u8 u8))}))";
+    assert_eq!(
+        check(text),
+        [
+            (
+                "pair",
+                "`pair` takes 2 type arguments but 0 were given".to_string()
+            ),
+            (
+                "(pair u8)",
+                "`pair` takes 2 type arguments but 1 was given".to_string()
+            ),
+            (
+                "(id u8)",
+                "`id` takes no type arguments but 1 was given".to_string()
+            ),
+            (
+                "(string u8)",
+                "`string` takes no type arguments but 1 was given".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn parameters_must_be_distinct_used_and_bare() {
+    let text = "(aski/v1
+  (tuple (twice T T) [T])
+  (record (ghost T) {})
+  (newtype (applied T) (T u8)))";
+    assert_eq!(
+        check(text),
+        [
+            ("T", "type parameter `T` is declared twice".to_string()),
+            (
+                "T",
+                "type parameter `T` of `ghost` is never used".to_string()
+            ),
+            (
+                "(T u8)",
+                "`T` takes no type arguments but 1 was given".to_string()
+            ),
+        ]
+    );
+}
@@ -0,0 +1,149 @@
+//! Impls implement a known trait for a declared type, with the signature the trait gives.
+
+use aski_sema::{check_impls, lower_source_file, resolve, Binding, Schema};
+use aski_syntax::{parse, parse_capitalized, Severity};
+
+const PROTOTYPE: &str = include_str!("../../../encoder/examples/prototype-design.aski");
+
+fn lower(text: &str) -> Schema {
+    let parse = parse(text);
+    assert_eq!(parse.errors(), &[]);
+    let (schema, diagnostics) = lower_source_file(&parse.tree());
+    assert_eq!(diagnostics, &[]);
+    schema
+}
+
+/// What `check_impls` and resolution report, as the text they point at and their message,
+/// with warnings marked.
+fn check(text: &str) -> Vec<(&str, String)> {
+    let schema = lower(text);
+    let mut diagnostics = check_impls(&schema);
+    diagnostics.extend(resolve(&schema).1);
+    diagnostics.sort_by_key(|d| d.range.start());
+    diagnostics
+        .into_iter()
+        .map(|d| {
+            let message = match d.severity {
+                Severity::Warning => format!("warning: {}", d.message),
+                _ => d.message,
+            };
+            (&text[d.range.start()..d.range.end()], message)
+        })
+        .collect()
+}
+
+#[test]
+fn known_traits_are_implemented_with_their_signatures() {
+    let text = r#"(aski/v1
+  (newtype age u16)
+  (record parse-error {message: string})
+  (impl from-str age
+    {from-str [(type-signature [string] (result self parse-error))
+               (function-body "s.parse().map(Age).map_err(|e| ParseError { message: e.to_string() })")]})
+  (impl try-from age {try-from [(type-signature [u32] (result self string)) (function-body "todo!()")]})
+  (impl display age {fmt [(type-signature [self] string) (function-body "self.0.to_string()")]})
+  (impl default age {default [(type-signature [] self) (function-body "Age(18)")]}))"#;
+    assert_eq!(check(text), []);
+
+    let schema = lower(text);
+    let imp = &schema.impls[0];
+    assert_eq!(imp.trait_name.text, "from-str");
+    let member = &imp.members[0];
+    assert_eq!(member.name.text, "from-str");
+    assert_eq!(
+        member.body.as_deref(),
+        Some("s.parse().map(Age).map_err(|e| ParseError { message: e.to_string() })")
+    );
+
+    let resolution = resolve(&schema).0;
+    let signature = schema.impls[3].members[0].signature.as_ref().unwrap();
+    assert_eq!(
+        resolution.binding(&signature.ret),
+        Some(&Binding::Item("age".to_string()))
+    );
+}
+
+#[test]
+fn impls_are_checked_against_their_trait() {
+    let text = r#"(aski/v1
+  (newtype age u16)
+  (record (page T) {items: (vec T)})
+  (impl hash age {hash [(type-signature [self] u64)]})
+  (impl display age {fmt [(type-signature [self] (vec string)) (function-body "")]
+                     to-string [(type-signature [self] string)]})
+  (impl default age {})
+  (impl default age {default [(type-signature [] self) (function-body "Age(0)")]})
+  (impl default (page u8) {default [(type-signature [] self)]})
+  (impl default u8 {default [(type-signature [] self)]})
+  (impl from-str age {from-str [(type-signature [string] (result self nope)) (function-body "")]}))"#;
+    assert_eq!(
+        check(text),
+        [
+            (
+                "hash",
+                "cannot find trait `hash`; impls may implement `from-str`, `try-from`, \
+                 `display` or `default`"
+                    .to_string()
+            ),
+            (
+                "(type-signature [self] (vec string))",
+                "`fmt` takes the signature `[self] string`".to_string()
+            ),
+            (
+                "to-string",
+                "trait `display` has no member `to-string`; its member is `fmt`".to_string()
+            ),
+            (
+                "default",
+                "the impl of `default` for `age` is missing its member `default`".to_string()
+            ),
+            (
+                "default",
+                "`default` is implemented for `age` more than once".to_string()
+            ),
+            (
+                "(page u8)",
+                "traits are implemented for the non-generic types the document declares"
+                    .to_string()
+            ),
+            (
+                "u8",
+                "traits are implemented for the non-generic types the document declares"
+                    .to_string()
+            ),
+            ("nope", "cannot find type `nope`".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn members_left_todo_are_warned_about() {
+    let text = "(aski/v1
+  (newtype age u16)
+  (impl display age {fmt [(type-signature TODO) (function-body TODO)]}))";
+    assert_eq!(
+        check(text),
+        [
+            (
+                "fmt",
+                "warning: the signature of `fmt` is still `TODO`, so the impl is not generated"
+                    .to_string()
+            ),
+            (
+                "fmt",
+                "warning: the body of `fmt` is still `TODO`, so it panics when called".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn the_prototype_implements_known_traits() {
+    let parse = parse_capitalized(PROTOTYPE);
+    let (schema, diagnostics) = lower_source_file(&parse.tree());
+    assert_eq!(diagnostics, []);
+    // Its members are all still `TODO`, which is only a warning.
+    let diagnostics = check_impls(&schema);
+    assert!(!diagnostics.is_empty());
+    assert!(diagnostics.iter().all(|d| !d.is_error()), "{diagnostics:?}");
+}
@@ -0,0 +1,248 @@
+//! Schemas spread over several documents, requiring each other's modules.
+
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use aski_sema::{load_modules, lower_source_file, Modules};
+use aski_syntax::{parse, parse_capitalized};
+
+/// Loads `root` from the documents `files`, by path.
+fn load(root: &str, files: &[(&str, &str)]) -> Modules {
+    let files: HashMap<PathBuf, String> = files
+        .iter()
+        .map(|(path, text)| (PathBuf::from(path), text.to_string()))
+        .collect();
+    load_modules(Path::new(root), |path| {
+        files
+            .get(path)
+            .cloned()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "not found"))
+    })
+    .unwrap()
+}
+
+/// Every diagnostic, as the module it is in, the text it points at and its message.
+fn diagnostics(modules: &Modules) -> Vec<(&str, &str, &str)> {
+    modules
+        .files
+        .iter()
+        .flat_map(|file| {
+            file.diagnostics.iter().map(|diagnostic| {
+                let range = diagnostic.range;
+                (
+                    file.name.as_str(),
+                    &file.text[range.start()..range.end()],
+                    diagnostic.message.as_str(),
+                )
+            })
+        })
+        .collect()
+}
+
+#[test]
+fn required_modules_load_first_in_either_dialect() {
+    let modules = load(
+        "schema/simple.aski",
+        &[
+            (
+                "schema/simple.aski",
+                "module simple {specialOption whatever require (stuff blah)}\n\
+                 User {account stuff/Account tags (Vec blah/Tag)}\n",
+            ),
+            (
+                "schema/stuff.aski",
+                "(aski/v1 (module stuff {require (blah)}) (record account {tag: blah/tag}))",
+            ),
+            ("schema/blah.aski", "(aski/v1 (newtype tag string))"),
+        ],
+    );
+    assert_eq!(diagnostics(&modules), []);
+    let names: Vec<_> = modules
+        .files
+        .iter()
+        .map(|file| file.name.as_str())
+        .collect();
+    assert_eq!(names, ["blah", "stuff", "simple"]);
+
+    let simple = modules.file("simple").unwrap();
+    let module = simple.schema.module.as_ref().unwrap();
+    let options: Vec<_> = module
+        .options
+        .iter()
+        .map(|option| {
+            let values: Vec<_> = option
+                .values
+                .iter()
+                .map(|value| value.text.as_str())
+                .collect();
+            (option.name.text.as_str(), values)
+        })
+        .collect();
+    assert_eq!(
+        options,
+        [
+            ("special-option", vec!["whatever"]),
+            ("require", vec!["stuff", "blah"])
+        ]
+    );
+    assert_eq!(
+        simple.path,
+        PathBuf::from("schema/simple.aski"),
+        "the root keeps its path"
+    );
+    assert_eq!(
+        modules.file("blah").unwrap().path,
+        PathBuf::from("schema/blah.aski")
+    );
+}
+
+#[test]
+fn documents_without_a_module_declaration_are_named_after_their_file() {
+    let modules = load(
+        "main.aski",
+        &[
+            ("main.aski", "(aski/v1 (newtype id u64))"),
+            ("other.aski", "(aski/v1 (newtype id u64))"),
+        ],
+    );
+    assert_eq!(modules.files.len(), 1);
+    assert_eq!(modules.files[0].name, "main");
+    assert!(!modules.has_errors());
+}
+
+#[test]
+fn modules_requiring_each_other_are_reported_where_the_cycle_closes() {
+    let modules = load(
+        "a.aski",
+        &[
+            ("a.aski", "module a {require (b)}\nA {b b/B}"),
+            ("b.aski", "module b {require (c)}\nB {c c/C}"),
+            ("c.aski", "module c {require (a)}\nC {a a/A}"),
+        ],
+    );
+    assert_eq!(
+        diagnostics(&modules),
+        [
+            ("c", "a", "modules require each other: a -> b -> c -> a"),
+            (
+                "c",
+                "a/A",
+                "cannot find module `a`; is it in the module's `require`?"
+            ),
+        ]
+    );
+    assert!(modules.has_errors());
+}
+
+#[test]
+fn qualified_references_need_a_required_module_declaring_the_type() {
+    let modules = load(
+        "main.aski",
+        &[
+            (
+                "main.aski",
+                "module main {require (stuff missing)}\n\
+                 User {a stuff/Nope b other/User c (stuff/Page U8) d stuff/Page}",
+            ),
+            ("stuff.aski", "module things\n{}\n(Page T) {items (Vec T)}"),
+        ],
+    );
+    assert_eq!(
+        diagnostics(&modules),
+        [
+            (
+                "main",
+                "stuff",
+                "`stuff.aski` declares module `things` instead"
+            ),
+            (
+                "main",
+                "missing",
+                "cannot read module `missing` from `missing.aski`: not found"
+            ),
+            (
+                "main",
+                "stuff/Nope",
+                "cannot find module `stuff`; is it in the module's `require`?"
+            ),
+            (
+                "main",
+                "other/User",
+                "cannot find module `other`; is it in the module's `require`?"
+            ),
+            (
+                "main",
+                "(stuff/Page U8)",
+                "cannot find module `stuff`; is it in the module's `require`?"
+            ),
+            (
+                "main",
+                "stuff/Page",
+                "cannot find module `stuff`; is it in the module's `require`?"
+            ),
+        ]
+    );
+
+    let modules = load(
+        "main.aski",
+        &[
+            (
+                "main.aski",
+                "module main {require stuff}\n\
+                 User {a stuff/Nope c (stuff/Page U8) d stuff/Page}",
+            ),
+            ("stuff.aski", "(Page T) {items (Vec T)}"),
+        ],
+    );
+    assert_eq!(
+        diagnostics(&modules),
+        [
+            ("main", "stuff/Nope", "module `stuff` has no type `nope`"),
+            (
+                "main",
+                "stuff/Page",
+                "`stuff/page` takes 1 type argument but 0 were given"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn a_document_is_one_module() {
+    let text = "(aski/v1 (module one {require \"two\"}) (module two {}))";
+    let (schema, diagnostics) = lower_source_file(&parse(text).tree());
+    let messages: Vec<_> = diagnostics
+        .iter()
+        .map(|d| (&text[d.range.start()..d.range.end()], d.message.as_str()))
+        .collect();
+    assert_eq!(
+        messages,
+        [
+            (
+                "\"two\"",
+                "`require` takes module names, like `require (stuff blah)`"
+            ),
+            (
+                "two",
+                "module `two` is declared after `one`; a document is one module"
+            ),
+        ]
+    );
+    let module = schema.module.unwrap();
+    assert_eq!(module.name.text, "one");
+    assert_eq!(module.requires().count(), 0);
+}
+
+#[test]
+fn module_names_keep_their_spelling() {
+    let text = "module myStuff {require (otherStuff)}\nUser {a otherStuff/UserId}";
+    let (schema, diagnostics) = lower_source_file(&parse_capitalized(text).tree());
+    assert_eq!(diagnostics, []);
+    let module = schema.module.unwrap();
+    assert_eq!(module.name.text, "myStuff");
+    let required: Vec<_> = module.requires().map(|name| name.text.as_str()).collect();
+    assert_eq!(required, ["otherStuff"]);
+    let field = format!("{:?}", schema.items[0].type_refs()[0].kind);
+    assert!(field.contains("\"otherStuff/user-id\""), "{field}");
+}
@@ -0,0 +1,56 @@
+//! Map keys and set elements are ordered, so no float may be reachable from them.
+
+use aski_sema::{check_ordering, lower_source_file, Schema};
+use aski_syntax::parse;
+
+fn lower(text: &str) -> Schema {
+    let parse = parse(text);
+    assert_eq!(parse.errors(), &[]);
+    let (schema, diagnostics) = lower_source_file(&parse.tree());
+    assert_eq!(diagnostics, &[]);
+    schema
+}
+
+fn check(text: &str) -> Vec<(&str, String)> {
+    check_ordering(&lower(text))
+        .into_iter()
+        .map(|d| (&text[d.range.start()..d.range.end()], d.message))
+        .collect()
+}
+
+#[test]
+fn all_types_orders_only_what_it_can() {
+    let text = include_str!("../../../encoder/drafts/all-types.aski");
+    assert_eq!(check(text), Vec::<(&str, String)>::new());
+}
+
+#[test]
+fn floats_are_fine_outside_keys_and_elements() {
+    let text = "(aski/v1
+  (record point {x: f64 y: f64})
+  (record (wrapped T) {value: T})
+  (record (sorted T) {items: (set T)})
+  (record shapes {by-name: (map string point) wrapped: (wrapped f32) sorted: (sorted u8)}))";
+    assert_eq!(check(text), Vec::<(&str, String)>::new());
+}
+
+#[test]
+fn floats_reachable_from_keys_and_elements_are_errors() {
+    let text = "(aski/v1
+  (record point {x: f64 y: f64})
+  (record (wrapped T) {value: T})
+  (record (sorted T) {items: (set T)})
+  (record keyed {m: (map (wrapped f64) u8)})
+  (record points {all: (set (vec point)) nested: (set (set f32))})
+  (record listing {sorted: (sorted (? point))}))";
+    let message = |what: &str| format!("{what} cannot hold a float: floats have no order");
+    assert_eq!(
+        check(text),
+        [
+            ("(wrapped f64)", message("a map key")),
+            ("(vec point)", message("a set element")),
+            ("(set f32)", message("a set element")),
+            ("(? point)", message("`sorted` orders its argument, which")),
+        ]
+    );
+}
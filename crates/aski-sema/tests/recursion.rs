@@ -0,0 +1,69 @@
+//! Recursion is fine behind a vec, set, map or box, and an error anywhere else.
+
+use aski_sema::{check_recursion, lower_source_file, Schema};
+use aski_syntax::parse;
+
+fn lower(text: &str) -> Schema {
+    let parse = parse(text);
+    assert_eq!(parse.errors(), &[]);
+    let (schema, diagnostics) = lower_source_file(&parse.tree());
+    assert_eq!(diagnostics, &[]);
+    schema
+}
+
+fn check(text: &str) -> Vec<(&str, String)> {
+    check_recursion(&lower(text))
+        .into_iter()
+        .map(|d| (&text[d.range.start()..d.range.end()], d.message))
+        .collect()
+}
+
+#[test]
+fn all_types_is_finite() {
+    let text = include_str!("../../../encoder/drafts/all-types.aski");
+    assert_eq!(check(text), Vec::<(&str, String)>::new());
+}
+
+#[test]
+fn indirection_breaks_cycles() {
+    let text = "(aski/v1
+  (record node {value: i64 next: (? (box node))})
+  (record tree {children: (vec tree) index: (map string tree) leaves: (set tree)})
+  (record (page T) {items: (vec T)})
+  (record listing {more: (page listing)}))";
+    assert_eq!(check(text), Vec::<(&str, String)>::new());
+}
+
+#[test]
+fn inline_cycles_report_their_path() {
+    let text = "(aski/v1
+  (record node {next: (? node)})
+  (record a {b: [u8 b]})
+  (enum b (leaf) (branch (array 2 c)))
+  (newtype c (result a string))
+  (newtype (wrapped T) T)
+  (record shell {inner: (wrapped shell)}))";
+    assert_eq!(
+        check(text),
+        [
+            (
+                "node",
+                "recursive type `node` has infinite size: node -> node; \
+                 put one of these references in a `(box ...)`"
+                    .to_string()
+            ),
+            (
+                "b",
+                "recursive type `a` has infinite size: a -> b -> c -> a; \
+                 put one of these references in a `(box ...)`"
+                    .to_string()
+            ),
+            (
+                "shell",
+                "recursive type `shell` has infinite size: shell -> shell; \
+                 put one of these references in a `(box ...)`"
+                    .to_string()
+            ),
+        ]
+    );
+}
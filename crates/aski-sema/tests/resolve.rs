@@ -0,0 +1,110 @@
+//! Every type reference binds to a declaration, a primitive or a type parameter.
+
+use aski_sema::{lower_source_file, resolve, Binding, Primitive, Schema, TypeKind, TypeRef};
+use aski_syntax::parse;
+
+const ALL_TYPES_ASKI: &str = include_str!("../../../encoder/drafts/all-types.aski");
+
+fn lower(text: &str) -> Schema {
+    let parse = parse(text);
+    assert_eq!(parse.errors(), &[]);
+    let (schema, diagnostics) = lower_source_file(&parse.tree());
+    assert_eq!(diagnostics, &[]);
+    schema
+}
+
+fn diagnostics(text: &str) -> Vec<(&str, String)> {
+    resolve(&lower(text))
+        .1
+        .into_iter()
+        .map(|d| (&text[d.range.start()..d.range.end()], d.message))
+        .collect()
+}
+
+#[test]
+fn all_types_resolves_completely() {
+    let schema = lower(ALL_TYPES_ASKI);
+    let (resolution, diagnostics) = resolve(&schema);
+    assert_eq!(diagnostics, &[]);
+
+    let named: Vec<&TypeRef> = schema
+        .items
+        .iter()
+        .flat_map(|item| item.type_refs())
+        .flat_map(TypeRef::walk)
+        .filter(|ty| matches!(ty.kind, TypeKind::Named { .. }))
+        .collect();
+    assert!(named.iter().all(|ty| resolution.binding(ty).is_some()));
+
+    let binding_of = |text: &str| {
+        let ty = named
+            .iter()
+            .find(|ty| &ALL_TYPES_ASKI[ty.range.start()..ty.range.end()] == text)
+            .unwrap();
+        resolution.binding(ty).cloned()
+    };
+    assert_eq!(
+        binding_of("error-code"),
+        Some(Binding::Item("error-code".to_string()))
+    );
+    assert_eq!(
+        binding_of("user-id"),
+        Some(Binding::Item("user-id".to_string()))
+    );
+    assert_eq!(binding_of("i64"), Some(Binding::Primitive(Primitive::I64)));
+    assert_eq!(binding_of("T"), Some(Binding::Param(0)));
+
+    let references: Vec<_> = resolution
+        .references("message")
+        .into_iter()
+        .map(|range| &ALL_TYPES_ASKI[range.start()..range.end()])
+        .collect();
+    assert!(!references.is_empty());
+    assert!(references.iter().all(|text| *text == "message"));
+}
+
+#[test]
+fn unresolved_names_are_reported() {
+    let text = "(aski/v1
+  (record account {owner: user-id roles: (set role) limits: (map string quota)})
+  (newtype quota u32))";
+    assert_eq!(
+        diagnostics(text),
+        [
+            ("user-id", "cannot find type `user-id`".to_string()),
+            ("role", "cannot find type `role`".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn duplicate_names_are_reported() {
+    let text = "(aski/v1
+  (newtype user-id u64)
+  (newtype user-id string)
+  (record string {})
+  (record login {name: string name: string})
+  (enum state (on) (off) (on {since: u64 since: u64})))";
+    assert_eq!(
+        diagnostics(text),
+        [
+            (
+                "user-id",
+                "type `user-id` is declared more than once".to_string()
+            ),
+            (
+                "string",
+                "`string` is a primitive type and cannot be declared".to_string()
+            ),
+            (
+                "name",
+                "field `name` is declared more than once".to_string()
+            ),
+            ("on", "variant `on` is declared more than once".to_string()),
+            (
+                "since",
+                "field `since` is declared more than once".to_string()
+            ),
+        ]
+    );
+}
@@ -0,0 +1,845 @@
+// Deserializing values as their generated Rust types deserialize.
+//
+// The mirror of serializing: each part of the type asks the deserializer for what serde's
+// derive and its impls for the standard types ask for, and accepts what they accept.
+// Integers must fit their width, floats also take integers, a `char` also takes a
+// one-character string, an `Option` also takes a unit, a uuid takes a hyphenated string,
+// 16 bytes or a seq of 16 `u8`. Sets and maps come out sorted, as `BTreeSet` and
+// `BTreeMap` keep them: a set drops repeated elements and a map keeps the last value of a
+// repeated key.
+//
+// Structs take their fields in any order and ignore fields they do not declare; a field of
+// an `Option` type may be left out. So does the `{variant, data}` envelope of an enum:
+// data that comes before its variant is held on to as it was given and read once the
+// variant is known, which a self-describing format, the only kind that can write it that
+// way, allows.
+
+use std::fmt;
+
+use serde::de::{
+    DeserializeSeed, Deserializer, EnumAccess, Error, IgnoredAny, MapAccess, SeqAccess, Unexpected,
+    VariantAccess, Visitor,
+};
+
+use aski_sema::{Primitive, Schema, TypeKind, TypeRef, ENUM_CONTENT, ENUM_TAG};
+use aski_value::{Uuid, Value, VariantData};
+
+use crate::content::{Content, ContentDeserializer};
+use crate::declared::{declared, named, optional, Body, Declared};
+use crate::names::{field_name, intern_list, type_name};
+use crate::order::compare;
+
+/// The fields of the envelope of an enum value.
+const ENVELOPE: &[&str] = &[ENUM_TAG, ENUM_CONTENT];
+
+/// Deserializes a value of a declaration of a schema, from what the Rust type generated for
+/// the declaration serializes to.
+#[derive(Clone)]
+pub struct ValueSeed<'a> {
+    schema: &'a Schema,
+    ty: TypeRef,
+}
+
+impl<'a> ValueSeed<'a> {
+    /// A seed for values of the declaration `name` of `schema`, which must not be generic.
+    pub fn new(schema: &'a Schema, name: &str) -> ValueSeed<'a> {
+        ValueSeed {
+            schema,
+            ty: named(name),
+        }
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for ValueSeed<'_> {
+    type Value = Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        Seed::new(self.schema, &self.ty).deserialize(deserializer)
+    }
+}
+
+/// Deserializes a part of a value, of the type `ty`.
+#[derive(Clone, Copy)]
+struct Seed<'a> {
+    schema: &'a Schema,
+    ty: &'a TypeRef,
+}
+
+impl<'a> Seed<'a> {
+    fn new(schema: &'a Schema, ty: &'a TypeRef) -> Seed<'a> {
+        Seed { schema, ty }
+    }
+
+    fn named<'de, D: Deserializer<'de>>(
+        self,
+        name: &str,
+        args: &[TypeRef],
+        deserializer: D,
+    ) -> Result<Value, D::Error> {
+        if let Some(primitive) = Primitive::from_name(name) {
+            return primitive_value(primitive, deserializer);
+        }
+        let declared = declared(self.schema, name, args).map_err(D::Error::custom)?;
+        let schema = self.schema;
+        let ty = name.to_string();
+        match declared {
+            Declared::Newtype(inner) => {
+                let value = deserializer.deserialize_newtype_struct(
+                    type_name(name),
+                    Newtype(Seed::new(schema, &inner)),
+                )?;
+                Ok(Value::Newtype {
+                    ty,
+                    value: Box::new(value),
+                })
+            }
+            Declared::Record(fields) if fields.is_empty() => {
+                deserializer.deserialize_unit_struct(type_name(name), UnitVisitor)?;
+                Ok(Value::Record {
+                    ty,
+                    fields: Vec::new(),
+                })
+            }
+            Declared::Record(fields) => {
+                let fields = deserialize_struct(schema, name, &fields, deserializer)?;
+                Ok(Value::Record { ty, fields })
+            }
+            Declared::Tuple(types) => {
+                let fields = if let [inner] = types.as_slice() {
+                    vec![deserializer.deserialize_newtype_struct(
+                        type_name(name),
+                        Newtype(Seed::new(schema, inner)),
+                    )?]
+                } else {
+                    let visitor =
+                        Positional::new(schema, types.iter().collect(), format!("tuple `{name}`"));
+                    deserializer.deserialize_tuple_struct(type_name(name), types.len(), visitor)?
+                };
+                Ok(Value::TupleStruct { ty, fields })
+            }
+            Declared::Enum(variants) => {
+                let names = intern_list(variants.iter().map(|(v, _)| type_name(v)).collect());
+                let visitor = Envelope {
+                    schema,
+                    name,
+                    variants: &variants,
+                    tag: Tag {
+                        name: type_name(name),
+                        variant: Identifier::variant(names),
+                    },
+                };
+                deserializer.deserialize_struct(type_name(name), ENVELOPE, visitor)
+            }
+        }
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for Seed<'_> {
+    type Value = Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        let schema = self.schema;
+        match &self.ty.kind {
+            TypeKind::Box(inner) => Seed::new(schema, inner).deserialize(deserializer),
+            TypeKind::Option(inner) => {
+                deserializer.deserialize_option(OptionVisitor(Seed::new(schema, inner)))
+            }
+            TypeKind::Result(ok, err) => deserializer.deserialize_enum(
+                "Result",
+                &["Ok", "Err"],
+                ResultVisitor {
+                    ok: Seed::new(schema, ok),
+                    err: Seed::new(schema, err),
+                },
+            ),
+            TypeKind::Vec(element) => deserializer.deserialize_seq(Elements {
+                element: Seed::new(schema, element),
+                set: false,
+            }),
+            TypeKind::Set(element) => deserializer.deserialize_seq(Elements {
+                element: Seed::new(schema, element),
+                set: true,
+            }),
+            TypeKind::Map(key, value) => deserializer.deserialize_map(Entries {
+                key: Seed::new(schema, key),
+                value: Seed::new(schema, value),
+            }),
+            TypeKind::Array(len, element) => {
+                let types = vec![&**element; *len as usize];
+                let visitor = Positional::new(schema, types, format!("an array of {len} elements"));
+                Ok(Value::Array(
+                    deserializer.deserialize_tuple(*len as usize, visitor)?,
+                ))
+            }
+            TypeKind::Tuple(types) if types.is_empty() => {
+                deserializer.deserialize_unit(UnitVisitor)?;
+                Ok(Value::Tuple(Vec::new()))
+            }
+            TypeKind::Tuple(types) => {
+                let visitor = Positional::new(
+                    schema,
+                    types.iter().collect(),
+                    format!("a tuple of {} elements", types.len()),
+                );
+                Ok(Value::Tuple(
+                    deserializer.deserialize_tuple(types.len(), visitor)?,
+                ))
+            }
+            TypeKind::Named { name, args } => self.named(name, args, deserializer),
+        }
+    }
+}
+
+/// Deserializes the fields of a record, or of a struct variant, of the declaration `name`,
+/// from a struct named `name`.
+fn deserialize_struct<'de, D: Deserializer<'de>>(
+    schema: &Schema,
+    name: &str,
+    fields: &[(String, TypeRef)],
+    deserializer: D,
+) -> Result<Vec<(String, Value)>, D::Error> {
+    let names = intern_list(fields.iter().map(|(field, _)| field_name(field)).collect());
+    let visitor = Fields {
+        schema,
+        name,
+        fields,
+        names,
+    };
+    deserializer.deserialize_struct(type_name(name), names, visitor)
+}
+
+fn primitive_value<'de, D: Deserializer<'de>>(
+    primitive: Primitive,
+    deserializer: D,
+) -> Result<Value, D::Error> {
+    let visitor = PrimitiveVisitor(primitive);
+    match primitive {
+        Primitive::Bool => deserializer.deserialize_bool(visitor),
+        Primitive::Char => deserializer.deserialize_char(visitor),
+        Primitive::String => deserializer.deserialize_string(visitor),
+        Primitive::I8 => deserializer.deserialize_i8(visitor),
+        Primitive::I16 => deserializer.deserialize_i16(visitor),
+        Primitive::I32 => deserializer.deserialize_i32(visitor),
+        Primitive::I64 | Primitive::Isize => deserializer.deserialize_i64(visitor),
+        Primitive::I128 => deserializer.deserialize_i128(visitor),
+        Primitive::U8 => deserializer.deserialize_u8(visitor),
+        Primitive::U16 => deserializer.deserialize_u16(visitor),
+        Primitive::U32 => deserializer.deserialize_u32(visitor),
+        Primitive::U64 | Primitive::Usize => deserializer.deserialize_u64(visitor),
+        Primitive::U128 => deserializer.deserialize_u128(visitor),
+        Primitive::F32 => deserializer.deserialize_f32(visitor),
+        Primitive::F64 => deserializer.deserialize_f64(visitor),
+        Primitive::Unit => deserializer.deserialize_unit(visitor),
+        Primitive::Uuid if deserializer.is_human_readable() => {
+            deserializer.deserialize_str(visitor)
+        }
+        Primitive::Uuid => deserializer.deserialize_bytes(visitor),
+        Primitive::Bytes => deserializer.deserialize_seq(visitor),
+    }
+}
+
+#[derive(Clone, Copy)]
+struct PrimitiveVisitor(Primitive);
+
+impl PrimitiveVisitor {
+    fn int<E: Error>(self, v: i128) -> Result<Value, E> {
+        let value = match self.0 {
+            Primitive::I8 => i8::try_from(v).ok().map(Value::I8),
+            Primitive::I16 => i16::try_from(v).ok().map(Value::I16),
+            Primitive::I32 => i32::try_from(v).ok().map(Value::I32),
+            Primitive::I64 => i64::try_from(v).ok().map(Value::I64),
+            Primitive::I128 => Some(Value::I128(v)),
+            Primitive::Isize => isize::try_from(v).ok().map(Value::Isize),
+            Primitive::U8 => u8::try_from(v).ok().map(Value::U8),
+            Primitive::U16 => u16::try_from(v).ok().map(Value::U16),
+            Primitive::U32 => u32::try_from(v).ok().map(Value::U32),
+            Primitive::U64 => u64::try_from(v).ok().map(Value::U64),
+            Primitive::U128 => u128::try_from(v).ok().map(Value::U128),
+            Primitive::Usize => usize::try_from(v).ok().map(Value::Usize),
+            Primitive::F32 => Some(Value::F32(v as f32)),
+            Primitive::F64 => Some(Value::F64(v as f64)),
+            _ => {
+                let integer = format!("integer `{v}`");
+                return Err(E::invalid_type(Unexpected::Other(&integer), &self));
+            }
+        };
+        value.ok_or_else(|| {
+            let integer = format!("integer `{v}`");
+            E::invalid_value(Unexpected::Other(&integer), &self)
+        })
+    }
+}
+
+impl<'de> Visitor<'de> for PrimitiveVisitor {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a value of `{}`", self.0)
+    }
+
+    fn visit_bool<E: Error>(self, v: bool) -> Result<Value, E> {
+        match self.0 {
+            Primitive::Bool => Ok(Value::Bool(v)),
+            _ => Err(E::invalid_type(Unexpected::Bool(v), &self)),
+        }
+    }
+
+    fn visit_i64<E: Error>(self, v: i64) -> Result<Value, E> {
+        self.int(v.into())
+    }
+
+    fn visit_i128<E: Error>(self, v: i128) -> Result<Value, E> {
+        self.int(v)
+    }
+
+    fn visit_u64<E: Error>(self, v: u64) -> Result<Value, E> {
+        self.int(v.into())
+    }
+
+    fn visit_u128<E: Error>(self, v: u128) -> Result<Value, E> {
+        match (i128::try_from(v), self.0) {
+            (Ok(v), _) => self.int(v),
+            (Err(_), Primitive::U128) => Ok(Value::U128(v)),
+            (Err(_), Primitive::F32) => Ok(Value::F32(v as f32)),
+            (Err(_), Primitive::F64) => Ok(Value::F64(v as f64)),
+            (Err(_), _) => {
+                let integer = format!("integer `{v}`");
+                Err(E::invalid_value(Unexpected::Other(&integer), &self))
+            }
+        }
+    }
+
+    fn visit_f64<E: Error>(self, v: f64) -> Result<Value, E> {
+        match self.0 {
+            Primitive::F32 => Ok(Value::F32(v as f32)),
+            Primitive::F64 => Ok(Value::F64(v)),
+            _ => Err(E::invalid_type(Unexpected::Float(v), &self)),
+        }
+    }
+
+    fn visit_char<E: Error>(self, v: char) -> Result<Value, E> {
+        match self.0 {
+            Primitive::Char => Ok(Value::Char(v)),
+            Primitive::String => Ok(Value::String(v.to_string())),
+            _ => Err(E::invalid_type(Unexpected::Char(v), &self)),
+        }
+    }
+
+    fn visit_str<E: Error>(self, v: &str) -> Result<Value, E> {
+        match self.0 {
+            Primitive::String => Ok(Value::String(v.to_string())),
+            Primitive::Char => {
+                let mut chars = v.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Ok(Value::Char(c)),
+                    _ => Err(E::invalid_value(Unexpected::Str(v), &self)),
+                }
+            }
+            Primitive::Uuid => v
+                .parse()
+                .map(Value::Uuid)
+                .map_err(|_| E::invalid_value(Unexpected::Str(v), &self)),
+            _ => Err(E::invalid_type(Unexpected::Str(v), &self)),
+        }
+    }
+
+    fn visit_bytes<E: Error>(self, v: &[u8]) -> Result<Value, E> {
+        match self.0 {
+            Primitive::String => std::str::from_utf8(v)
+                .map(|text| Value::String(text.to_string()))
+                .map_err(|_| E::invalid_value(Unexpected::Bytes(v), &self)),
+            Primitive::Uuid => <[u8; 16]>::try_from(v)
+                .map(|bytes| Value::Uuid(Uuid(u128::from_be_bytes(bytes))))
+                .map_err(|_| E::invalid_length(v.len(), &self)),
+            _ => Err(E::invalid_type(Unexpected::Bytes(v), &self)),
+        }
+    }
+
+    fn visit_unit<E: Error>(self) -> Result<Value, E> {
+        match self.0 {
+            Primitive::Unit => Ok(Value::Unit),
+            _ => Err(E::invalid_type(Unexpected::Unit, &self)),
+        }
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        let mut bytes = Vec::new();
+        match self.0 {
+            Primitive::Bytes => {
+                while let Some(byte) = seq.next_element::<u8>()? {
+                    bytes.push(byte);
+                }
+                Ok(Value::Bytes(bytes))
+            }
+            Primitive::Uuid => {
+                for index in 0..16 {
+                    let byte = seq
+                        .next_element::<u8>()?
+                        .ok_or_else(|| A::Error::invalid_length(index, &self))?;
+                    bytes.push(byte);
+                }
+                self.visit_bytes(&bytes)
+            }
+            _ => Err(A::Error::invalid_type(Unexpected::Seq, &self)),
+        }
+    }
+}
+
+/// Accepts a unit, for unit structs and the empty tuple type.
+struct UnitVisitor;
+
+impl<'de> Visitor<'de> for UnitVisitor {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a unit")
+    }
+
+    fn visit_unit<E: Error>(self) -> Result<(), E> {
+        Ok(())
+    }
+}
+
+struct OptionVisitor<'a>(Seed<'a>);
+
+impl<'de> Visitor<'de> for OptionVisitor<'_> {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a value of `{}`", self.0.ty)
+    }
+
+    fn visit_none<E: Error>(self) -> Result<Value, E> {
+        Ok(Value::Option(None))
+    }
+
+    fn visit_unit<E: Error>(self) -> Result<Value, E> {
+        Ok(Value::Option(None))
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        let value = self.0.deserialize(deserializer)?;
+        Ok(Value::Option(Some(Box::new(value))))
+    }
+}
+
+struct ResultVisitor<'a> {
+    ok: Seed<'a>,
+    err: Seed<'a>,
+}
+
+impl<'de> Visitor<'de> for ResultVisitor<'_> {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("enum Result")
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Value, A::Error> {
+        let (index, variant) = data.variant_seed(Identifier::variant(&["Ok", "Err"]))?;
+        let result = if index == 0 {
+            Ok(Box::new(variant.newtype_variant_seed(self.ok)?))
+        } else {
+            Err(Box::new(variant.newtype_variant_seed(self.err)?))
+        };
+        Ok(Value::Result(result))
+    }
+}
+
+/// The elements of a vec or a set.
+struct Elements<'a> {
+    element: Seed<'a>,
+    set: bool,
+}
+
+impl<'de> Visitor<'de> for Elements<'_> {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(if self.set { "a set" } else { "a sequence" })
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        let mut elements = Vec::new();
+        if !self.set {
+            while let Some(element) = seq.next_element_seed(self.element)? {
+                elements.push(element);
+            }
+            return Ok(Value::Vec(elements));
+        }
+        let schema = self.element.schema;
+        while let Some(element) = seq.next_element_seed(self.element)? {
+            if let Err(index) = elements.binary_search_by(|e| compare(schema, e, &element)) {
+                elements.insert(index, element);
+            }
+        }
+        Ok(Value::Set(elements))
+    }
+}
+
+struct Entries<'a> {
+    key: Seed<'a>,
+    value: Seed<'a>,
+}
+
+impl<'de> Visitor<'de> for Entries<'_> {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a map")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+        let schema = self.key.schema;
+        let mut entries: Vec<(Value, Value)> = Vec::new();
+        while let Some(key) = map.next_key_seed(self.key)? {
+            let value = map.next_value_seed(self.value)?;
+            match entries.binary_search_by(|(k, _)| compare(schema, k, &key)) {
+                Ok(index) => entries[index].1 = value,
+                Err(index) => entries.insert(index, (key, value)),
+            }
+        }
+        Ok(Value::Map(entries))
+    }
+}
+
+/// One value per type, of an array, a tuple type, a tuple declaration or a tuple variant.
+struct Positional<'a> {
+    schema: &'a Schema,
+    types: Vec<&'a TypeRef>,
+    /// What the values make up, for messages.
+    expecting: String,
+}
+
+impl<'a> Positional<'a> {
+    fn new(schema: &'a Schema, types: Vec<&'a TypeRef>, expecting: String) -> Positional<'a> {
+        Positional {
+            schema,
+            types,
+            expecting,
+        }
+    }
+}
+
+impl<'de> Visitor<'de> for Positional<'_> {
+    type Value = Vec<Value>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.expecting)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<Value>, A::Error> {
+        let mut values = Vec::with_capacity(self.types.len());
+        for (index, ty) in self.types.iter().enumerate() {
+            let value = seq
+                .next_element_seed(Seed::new(self.schema, ty))?
+                .ok_or_else(|| A::Error::invalid_length(index, &self))?;
+            values.push(value);
+        }
+        Ok(values)
+    }
+}
+
+/// The value a newtype struct wraps.
+struct Newtype<'a>(Seed<'a>);
+
+impl<'de> Visitor<'de> for Newtype<'_> {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a newtype of `{}`", self.0.ty)
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Value, D::Error> {
+        self.0.deserialize(deserializer)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        seq.next_element_seed(self.0)?
+            .ok_or_else(|| A::Error::invalid_length(0, &self))
+    }
+}
+
+/// The fields of a record or of a struct variant.
+struct Fields<'a> {
+    schema: &'a Schema,
+    /// The declaration the fields are declared by.
+    name: &'a str,
+    fields: &'a [(String, TypeRef)],
+    names: &'static [&'static str],
+}
+
+impl<'de> Visitor<'de> for Fields<'_> {
+    type Value = Vec<(String, Value)>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the fields of `{}`", self.name)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut values: Vec<Option<Value>> = vec![None; self.fields.len()];
+        while let Some(index) = map.next_key_seed(Identifier::field(self.names))? {
+            let Some(slot) = values.get_mut(index) else {
+                map.next_value::<IgnoredAny>()?;
+                continue;
+            };
+            if slot.is_some() {
+                return Err(A::Error::duplicate_field(self.names[index]));
+            }
+            *slot = Some(map.next_value_seed(Seed::new(self.schema, &self.fields[index].1))?);
+        }
+        self.fields
+            .iter()
+            .zip(self.names.iter().copied())
+            .zip(values)
+            .map(|(((field, ty), name), value)| match value {
+                Some(value) => Ok((field.clone(), value)),
+                None if optional(ty) => Ok((field.clone(), Value::Option(None))),
+                None => Err(A::Error::missing_field(name)),
+            })
+            .collect()
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut values = Vec::with_capacity(self.fields.len());
+        for (index, (field, ty)) in self.fields.iter().enumerate() {
+            let value = seq
+                .next_element_seed(Seed::new(self.schema, ty))?
+                .ok_or_else(|| A::Error::invalid_length(index, &self))?;
+            values.push((field.clone(), value));
+        }
+        Ok(values)
+    }
+}
+
+/// The `{variant, data}` envelope of a value of the enum `name`.
+struct Envelope<'a> {
+    schema: &'a Schema,
+    name: &'a str,
+    variants: &'a [(String, Body)],
+    tag: Tag,
+}
+
+impl Envelope<'_> {
+    fn value(&self, index: usize, data: VariantData) -> Value {
+        Value::Variant {
+            ty: self.name.to_string(),
+            variant: self.variants[index].0.clone(),
+            data,
+        }
+    }
+
+    fn payload(&self, index: usize) -> Payload<'_> {
+        let (variant, body) = &self.variants[index];
+        Payload {
+            schema: self.schema,
+            variant,
+            body,
+        }
+    }
+}
+
+impl<'de> Visitor<'de> for Envelope<'_> {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a variant of `{}`", self.name)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+        let mut variant = None;
+        let mut data = None;
+        // Data given before its variant.
+        let mut content = None;
+        while let Some(key) = map.next_key_seed(Identifier::field(ENVELOPE))? {
+            match key {
+                0 if variant.is_some() => return Err(A::Error::duplicate_field(ENUM_TAG)),
+                0 => variant = Some(map.next_value_seed(self.tag)?),
+                1 if data.is_some() || content.is_some() => {
+                    return Err(A::Error::duplicate_field(ENUM_CONTENT))
+                }
+                1 => match variant {
+                    Some(index) => data = Some(map.next_value_seed(self.payload(index))?),
+                    None => content = Some(map.next_value::<Content>()?),
+                },
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        let index = variant.ok_or_else(|| A::Error::missing_field(ENUM_TAG))?;
+        if let Some(content) = content {
+            data = Some(
+                self.payload(index)
+                    .deserialize(ContentDeserializer::new(content))?,
+            );
+        }
+        let data = match (data, &self.variants[index].1) {
+            (Some(data), _) => data,
+            (None, Body::Unit) => VariantData::Unit,
+            (None, _) => return Err(A::Error::missing_field(ENUM_CONTENT)),
+        };
+        Ok(self.value(index, data))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        let index = seq
+            .next_element_seed(self.tag)?
+            .ok_or_else(|| A::Error::invalid_length(0, &self))?;
+        let data = match &self.variants[index].1 {
+            Body::Unit => VariantData::Unit,
+            _ => seq
+                .next_element_seed(self.payload(index))?
+                .ok_or_else(|| A::Error::invalid_length(1, &self))?,
+        };
+        Ok(self.value(index, data))
+    }
+}
+
+/// What the variant `variant` holds, under `data`.
+struct Payload<'a> {
+    schema: &'a Schema,
+    variant: &'a str,
+    body: &'a Body,
+}
+
+impl<'de> DeserializeSeed<'de> for Payload<'_> {
+    type Value = VariantData;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<VariantData, D::Error> {
+        let schema = self.schema;
+        Ok(match self.body {
+            Body::Unit => {
+                deserializer.deserialize_unit(UnitVisitor)?;
+                VariantData::Unit
+            }
+            Body::Newtype(ty) => {
+                VariantData::Newtype(Box::new(Seed::new(schema, ty).deserialize(deserializer)?))
+            }
+            Body::Tuple(types) => {
+                if let [ty] = types.as_slice() {
+                    VariantData::Tuple(vec![Seed::new(schema, ty).deserialize(deserializer)?])
+                } else {
+                    let visitor = Positional::new(
+                        schema,
+                        types.iter().collect(),
+                        format!("the values of variant `{}`", self.variant),
+                    );
+                    VariantData::Tuple(deserializer.deserialize_tuple(types.len(), visitor)?)
+                }
+            }
+            Body::Struct(fields) => VariantData::Struct(deserialize_struct(
+                schema,
+                self.variant,
+                fields,
+                deserializer,
+            )?),
+        })
+    }
+}
+
+/// A field or a variant, by name or by index, as the index of its name in `names`.
+///
+/// A field it does not know is given the index past the last name, to be ignored; a
+/// variant it does not know is an error.
+#[derive(Clone, Copy)]
+struct Identifier {
+    names: &'static [&'static str],
+    variant: bool,
+}
+
+impl Identifier {
+    fn field(names: &'static [&'static str]) -> Identifier {
+        Identifier {
+            names,
+            variant: false,
+        }
+    }
+
+    fn variant(names: &'static [&'static str]) -> Identifier {
+        Identifier {
+            names,
+            variant: true,
+        }
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for Identifier {
+    type Value = usize;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<usize, D::Error> {
+        deserializer.deserialize_identifier(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Identifier {
+    type Value = usize;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(if self.variant {
+            "a variant identifier"
+        } else {
+            "a field identifier"
+        })
+    }
+
+    fn visit_u64<E: Error>(self, v: u64) -> Result<usize, E> {
+        match usize::try_from(v) {
+            Ok(index) if index < self.names.len() => Ok(index),
+            _ if self.variant => Err(E::invalid_value(Unexpected::Unsigned(v), &self)),
+            _ => Ok(self.names.len()),
+        }
+    }
+
+    fn visit_str<E: Error>(self, v: &str) -> Result<usize, E> {
+        match self.names.iter().position(|name| *name == v) {
+            Some(index) => Ok(index),
+            None if self.variant => Err(E::unknown_variant(v, self.names)),
+            None => Ok(self.names.len()),
+        }
+    }
+
+    fn visit_bytes<E: Error>(self, v: &[u8]) -> Result<usize, E> {
+        match std::str::from_utf8(v) {
+            Ok(text) => self.visit_str(text),
+            Err(_) if self.variant => Err(E::invalid_value(Unexpected::Bytes(v), &self)),
+            Err(_) => Ok(self.names.len()),
+        }
+    }
+}
+
+/// The variant of a value of the enum `name`, under `variant`, written as a unit variant.
+#[derive(Clone, Copy)]
+struct Tag {
+    name: &'static str,
+    variant: Identifier,
+}
+
+impl<'de> DeserializeSeed<'de> for Tag {
+    type Value = usize;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<usize, D::Error> {
+        deserializer.deserialize_enum(self.name, self.variant.names, self)
+    }
+}
+
+impl<'de> Visitor<'de> for Tag {
+    type Value = usize;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a variant")
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<usize, A::Error> {
+        let (index, variant) = data.variant_seed(self.variant)?;
+        variant.unit_variant()?;
+        Ok(index)
+    }
+
+    fn visit_str<E: Error>(self, v: &str) -> Result<usize, E> {
+        self.variant.visit_str(v)
+    }
+}
@@ -0,0 +1,87 @@
+// Declarations as the wire sees them.
+//
+// Serializing and deserializing both walk a type down to the declarations it names. A
+// declaration is looked up once per value, with the arguments it is given put in place of
+// its parameters, so a `(wrapped pair)` is a newtype of `pair` from then on. Names are
+// kept as the schema writes them; the wire names are taken from them where needed.
+
+use aski_sema::{Field, ItemKind, Schema, TypeKind, TypeRef, VariantBody};
+use aski_syntax::TextRange;
+
+/// A declaration, with its arguments in place of its parameters.
+pub(crate) enum Declared {
+    Newtype(TypeRef),
+    Record(Vec<(String, TypeRef)>),
+    Tuple(Vec<TypeRef>),
+    Enum(Vec<(String, Body)>),
+}
+
+/// What a variant holds.
+pub(crate) enum Body {
+    Unit,
+    Newtype(TypeRef),
+    Tuple(Vec<TypeRef>),
+    Struct(Vec<(String, TypeRef)>),
+}
+
+/// The declaration `name` of `schema`, given `args`.
+pub(crate) fn declared(schema: &Schema, name: &str, args: &[TypeRef]) -> Result<Declared, String> {
+    let Some(item) = schema.item(name) else {
+        return Err(if name.contains('/') {
+            format!("cannot handle a value of `{name}`, which another module declares")
+        } else {
+            format!("cannot find type `{name}`")
+        });
+    };
+    if item.params.len() != args.len() {
+        return Err(format!(
+            "`{name}` takes {} type arguments, not {}",
+            item.params.len(),
+            args.len()
+        ));
+    }
+    let substituted = |ty: &TypeRef| ty.substituted(&item.params, args);
+    let fields = |fields: &[Field]| -> Vec<(String, TypeRef)> {
+        fields
+            .iter()
+            .map(|field| (field.name.text.clone(), substituted(&field.ty)))
+            .collect()
+    };
+    Ok(match &item.kind {
+        ItemKind::Newtype(inner) => Declared::Newtype(substituted(inner)),
+        ItemKind::Record(declared) => Declared::Record(fields(declared)),
+        ItemKind::Tuple(types) => Declared::Tuple(types.iter().map(substituted).collect()),
+        ItemKind::Enum(variants) => Declared::Enum(
+            variants
+                .iter()
+                .map(|variant| {
+                    let body = match &variant.body {
+                        VariantBody::Unit => Body::Unit,
+                        VariantBody::Newtype(inner) => Body::Newtype(substituted(inner)),
+                        VariantBody::Tuple(types) => {
+                            Body::Tuple(types.iter().map(substituted).collect())
+                        }
+                        VariantBody::Struct(declared) => Body::Struct(fields(declared)),
+                    };
+                    (variant.name.text.clone(), body)
+                })
+                .collect(),
+        ),
+    })
+}
+
+/// The type naming the declaration `name` without arguments.
+pub(crate) fn named(name: &str) -> TypeRef {
+    TypeRef {
+        kind: TypeKind::Named {
+            name: name.to_string(),
+            args: Vec::new(),
+        },
+        range: TextRange::default(),
+    }
+}
+
+/// Whether a field of type `ty` may be left out, as serde leaves out an `Option`.
+pub(crate) fn optional(ty: &TypeRef) -> bool {
+    matches!(ty.kind, TypeKind::Option(_))
+}
@@ -0,0 +1,18 @@
+//! Serde support for dynamic aski values.
+//!
+//! [`ValueSeed`] deserializes a [`Value`](aski_value::Value) of a declaration of a schema
+//! from any serde format, and [`TypedValue`] serializes one, both without the Rust types
+//! generated from the schema. They read and write exactly what serde reads and writes for
+//! the generated types: fields and variants under their Rust names, enums in the
+//! `{variant, data}` envelope, newtypes as the value they wrap. A gateway can then take in
+//! and hand on the payloads of a Rust service knowing only its schema.
+
+mod content;
+mod de;
+mod declared;
+mod names;
+mod order;
+mod ser;
+
+pub use crate::de::ValueSeed;
+pub use crate::ser::TypedValue;
@@ -0,0 +1,53 @@
+// Names on the wire.
+//
+// The generated Rust types go by their Rust names: fields as `bool_value`, declarations
+// and variants as `UserId` and `NotFound`, with the `r#` of a raw identifier dropped as
+// serde drops it. Serde takes names as `&'static str`, so each distinct name, and each
+// list of them, is leaked once and shared from then on. A schema has a bounded number of
+// names, so this holds on to no more than the schemas themselves would.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, OnceLock};
+
+use aski_codegen::{to_rust_field, to_rust_type};
+
+/// The name of the field `name` on the wire.
+pub(crate) fn field_name(name: &str) -> &'static str {
+    let ident = to_rust_field(name);
+    intern(ident.strip_prefix("r#").unwrap_or(&ident))
+}
+
+/// The name of the declaration or variant `name` on the wire.
+pub(crate) fn type_name(name: &str) -> &'static str {
+    let local = name.rsplit('/').next().unwrap_or(name);
+    intern(&to_rust_type(local))
+}
+
+pub(crate) fn intern(name: &str) -> &'static str {
+    static NAMES: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+    let mut names = NAMES
+        .get_or_init(Default::default)
+        .lock()
+        .expect("the names are never left half-updated");
+    if let Some(name) = names.get(name) {
+        return name;
+    }
+    let name: &'static str = Box::leak(name.into());
+    names.insert(name);
+    name
+}
+
+pub(crate) fn intern_list(list: Vec<&'static str>) -> &'static [&'static str] {
+    static LISTS: OnceLock<Mutex<HashMap<Vec<&'static str>, &'static [&'static str]>>> =
+        OnceLock::new();
+    let mut lists = LISTS
+        .get_or_init(Default::default)
+        .lock()
+        .expect("the lists are never left half-updated");
+    if let Some(interned) = lists.get(&list) {
+        return interned;
+    }
+    let interned: &'static [&'static str] = Box::leak(list.clone().into_boxed_slice());
+    lists.insert(list, interned);
+    interned
+}
@@ -0,0 +1,105 @@
+// The order of values, as the generated Rust types derive `Ord`.
+//
+// A set or a map key is a value of a type that derives `Ord`, and `BTreeSet` and
+// `BTreeMap` keep them in that order. The derived order compares fields in declaration
+// order and variants by their position in the enum, so `None` comes before `Some`, `Ok`
+// before `Err`, and the variants of a declared enum in the order the schema lists them.
+// Containers compare element by element, as Rust's own impls do. Floats have no `Ord`, so
+// they never get here; they are compared by `total_cmp` all the same.
+
+use std::cmp::Ordering;
+
+use aski_sema::{ItemKind, Schema};
+use aski_value::{Value, VariantData};
+
+/// Compares two values of the same type of `schema`.
+pub(crate) fn compare(schema: &Schema, a: &Value, b: &Value) -> Ordering {
+    let elements = |a: &[Value], b: &[Value]| lexicographic(a, b, |a, b| compare(schema, a, b));
+    match (a, b) {
+        (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+        (Value::Char(a), Value::Char(b)) => a.cmp(b),
+        (Value::String(a), Value::String(b)) => a.cmp(b),
+        (Value::I8(a), Value::I8(b)) => a.cmp(b),
+        (Value::I16(a), Value::I16(b)) => a.cmp(b),
+        (Value::I32(a), Value::I32(b)) => a.cmp(b),
+        (Value::I64(a), Value::I64(b)) => a.cmp(b),
+        (Value::I128(a), Value::I128(b)) => a.cmp(b),
+        (Value::Isize(a), Value::Isize(b)) => a.cmp(b),
+        (Value::U8(a), Value::U8(b)) => a.cmp(b),
+        (Value::U16(a), Value::U16(b)) => a.cmp(b),
+        (Value::U32(a), Value::U32(b)) => a.cmp(b),
+        (Value::U64(a), Value::U64(b)) => a.cmp(b),
+        (Value::U128(a), Value::U128(b)) => a.cmp(b),
+        (Value::Usize(a), Value::Usize(b)) => a.cmp(b),
+        (Value::F32(a), Value::F32(b)) => a.total_cmp(b),
+        (Value::F64(a), Value::F64(b)) => a.total_cmp(b),
+        (Value::Uuid(a), Value::Uuid(b)) => a.cmp(b),
+        (Value::Bytes(a), Value::Bytes(b)) => a.cmp(b),
+        (Value::Option(a), Value::Option(b)) => match (a, b) {
+            (Some(a), Some(b)) => compare(schema, a, b),
+            _ => a.is_some().cmp(&b.is_some()),
+        },
+        (Value::Result(a), Value::Result(b)) => match (a, b) {
+            (Ok(a), Ok(b)) | (Err(a), Err(b)) => compare(schema, a, b),
+            _ => a.is_err().cmp(&b.is_err()),
+        },
+        (Value::Vec(a), Value::Vec(b))
+        | (Value::Set(a), Value::Set(b))
+        | (Value::Array(a), Value::Array(b))
+        | (Value::Tuple(a), Value::Tuple(b))
+        | (Value::TupleStruct { fields: a, .. }, Value::TupleStruct { fields: b, .. }) => {
+            elements(a, b)
+        }
+        (Value::Map(a), Value::Map(b)) => lexicographic(a, b, |(ka, va), (kb, vb)| {
+            compare(schema, ka, kb).then_with(|| compare(schema, va, vb))
+        }),
+        (Value::Newtype { value: a, .. }, Value::Newtype { value: b, .. }) => compare(schema, a, b),
+        (Value::Record { fields: a, .. }, Value::Record { fields: b, .. }) => fields(schema, a, b),
+        (
+            Value::Variant {
+                ty,
+                variant: a_variant,
+                data: a,
+            },
+            Value::Variant {
+                variant: b_variant,
+                data: b,
+                ..
+            },
+        ) => {
+            if a_variant != b_variant {
+                return position(schema, ty, a_variant).cmp(&position(schema, ty, b_variant));
+            }
+            match (a, b) {
+                (VariantData::Newtype(a), VariantData::Newtype(b)) => compare(schema, a, b),
+                (VariantData::Tuple(a), VariantData::Tuple(b)) => elements(a, b),
+                (VariantData::Struct(a), VariantData::Struct(b)) => fields(schema, a, b),
+                _ => Ordering::Equal,
+            }
+        }
+        _ => Ordering::Equal,
+    }
+}
+
+fn fields(schema: &Schema, a: &[(String, Value)], b: &[(String, Value)]) -> Ordering {
+    lexicographic(a, b, |(_, a), (_, b)| compare(schema, a, b))
+}
+
+fn lexicographic<T>(a: &[T], b: &[T], compare: impl Fn(&T, &T) -> Ordering) -> Ordering {
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| compare(a, b))
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or_else(|| a.len().cmp(&b.len()))
+}
+
+/// The position of `variant` in the enum `ty`, or past the last one if there is none.
+fn position(schema: &Schema, ty: &str, variant: &str) -> usize {
+    match schema.item(ty).map(|item| &item.kind) {
+        Some(ItemKind::Enum(variants)) => variants
+            .iter()
+            .position(|declared| declared.name.text == variant)
+            .unwrap_or(variants.len()),
+        _ => usize::MAX,
+    }
+}
@@ -0,0 +1,340 @@
+// Serializing values as their generated Rust types serialize.
+//
+// The value is walked along its type, each part handed to the serializer the way serde's
+// derive and its impls for the standard types hand it:
+//
+//   primitives          as themselves; `isize` and `usize` as 64 bits, `bytes` as a seq
+//   uuid                a hyphenated string, or 16 bytes where the format is not
+//                       human-readable
+//   (? T), (result a b) `none`/`some`, and the variants `Ok` and `Err` of `Result`
+//   vec, set, map       seqs and maps; arrays and tuple types as tuples
+//   newtypes            newtype structs, which most formats write as the value wrapped
+//   records             structs, or unit structs without fields
+//   tuples              tuple structs, or newtype structs with a single field
+//   enums               a struct of the variant under `variant` and its payload under
+//                       `data`, a struct variant's payload being a struct named after it
+//
+// Values that do not fit their type fail with serde's custom error, so a value is best
+// checked with `check_value` first, which reports every mismatch rather than the first.
+
+use serde::ser::{
+    Error, Serialize, SerializeMap, SerializeSeq, SerializeStruct, SerializeTuple,
+    SerializeTupleStruct, Serializer,
+};
+
+use aski_sema::{Primitive, Schema, TypeKind, TypeRef, ENUM_CONTENT, ENUM_TAG};
+use aski_value::{Value, VariantData};
+
+use crate::declared::{declared, named, Body, Declared};
+use crate::names::{field_name, type_name};
+
+/// A value of a declaration of a schema, serialized as the Rust type generated for the
+/// declaration serializes.
+pub struct TypedValue<'a> {
+    schema: &'a Schema,
+    ty: TypeRef,
+    value: &'a Value,
+}
+
+impl<'a> TypedValue<'a> {
+    /// `value` as a value of the declaration `name` of `schema`, which must not be generic.
+    pub fn new(schema: &'a Schema, name: &str, value: &'a Value) -> TypedValue<'a> {
+        TypedValue {
+            schema,
+            ty: named(name),
+            value,
+        }
+    }
+}
+
+impl Serialize for TypedValue<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Typed::new(self.schema, &self.ty, self.value).serialize(serializer)
+    }
+}
+
+/// A part of a value, with its type.
+#[derive(Clone, Copy)]
+struct Typed<'a> {
+    schema: &'a Schema,
+    ty: &'a TypeRef,
+    value: &'a Value,
+}
+
+impl<'a> Typed<'a> {
+    fn new(schema: &'a Schema, ty: &'a TypeRef, value: &'a Value) -> Typed<'a> {
+        Typed { schema, ty, value }
+    }
+
+    fn with<'b>(self, ty: &'b TypeRef, value: &'b Value) -> Typed<'b>
+    where
+        'a: 'b,
+    {
+        Typed::new(self.schema, ty, value)
+    }
+
+    fn mismatch<E: Error>(self) -> E {
+        E::custom(format!(
+            "expected a value of `{}`, found {}",
+            self.ty,
+            self.value.describe()
+        ))
+    }
+
+    fn named<S: Serializer>(
+        self,
+        name: &str,
+        args: &[TypeRef],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        if let Some(primitive) = Primitive::from_name(name) {
+            return self.primitive(primitive, serializer);
+        }
+        let declared = declared(self.schema, name, args).map_err(S::Error::custom)?;
+        match (declared, self.value) {
+            (Declared::Newtype(inner), Value::Newtype { ty, value }) if ty == name => {
+                serializer.serialize_newtype_struct(type_name(name), &self.with(&inner, value))
+            }
+            (Declared::Record(fields), Value::Record { ty, fields: values }) if ty == name => {
+                if fields.is_empty() && values.is_empty() {
+                    return serializer.serialize_unit_struct(type_name(name));
+                }
+                let mut state = serializer.serialize_struct(type_name(name), fields.len())?;
+                self.fields(name, &fields, values, &mut state)?;
+                state.end()
+            }
+            (Declared::Tuple(types), Value::TupleStruct { ty, fields }) if ty == name => {
+                self.count(types.len(), fields.len())?;
+                if let ([ty], [value]) = (types.as_slice(), fields.as_slice()) {
+                    return serializer
+                        .serialize_newtype_struct(type_name(name), &self.with(ty, value));
+                }
+                let mut state = serializer.serialize_tuple_struct(type_name(name), types.len())?;
+                for (ty, value) in types.iter().zip(fields) {
+                    state.serialize_field(&self.with(ty, value))?;
+                }
+                state.end()
+            }
+            (Declared::Enum(variants), Value::Variant { ty, variant, data }) if ty == name => {
+                let Some(index) = variants.iter().position(|(v, _)| v == variant) else {
+                    return Err(S::Error::custom(format!(
+                        "`{name}` has no variant `{variant}`"
+                    )));
+                };
+                let tag = Tag {
+                    name: type_name(name),
+                    index: index as u32,
+                    variant: type_name(variant),
+                };
+                let body = &variants[index].1;
+                if let (Body::Unit, VariantData::Unit) = (body, data) {
+                    let mut state = serializer.serialize_struct(tag.name, 1)?;
+                    state.serialize_field(ENUM_TAG, &tag)?;
+                    return state.end();
+                }
+                let payload = Payload {
+                    typed: self,
+                    name,
+                    variant,
+                    body,
+                    data,
+                };
+                let mut state = serializer.serialize_struct(tag.name, 2)?;
+                state.serialize_field(ENUM_TAG, &tag)?;
+                state.serialize_field(ENUM_CONTENT, &payload)?;
+                state.end()
+            }
+            _ => Err(self.mismatch()),
+        }
+    }
+
+    /// Serializes the fields of a record, or of a struct variant, of the declaration `name`.
+    fn fields<S: SerializeStruct>(
+        self,
+        name: &str,
+        fields: &[(String, TypeRef)],
+        values: &[(String, Value)],
+        state: &mut S,
+    ) -> Result<(), S::Error> {
+        if let Some((field, _)) = values
+            .iter()
+            .find(|(given, _)| !fields.iter().any(|(declared, _)| declared == given))
+        {
+            return Err(S::Error::custom(format!("`{name}` has no field `{field}`")));
+        }
+        for (field, ty) in fields {
+            let Some((_, value)) = values.iter().find(|(given, _)| given == field) else {
+                return Err(S::Error::custom(format!("missing field `{field}`")));
+            };
+            state.serialize_field(field_name(field), &self.with(ty, value))?;
+        }
+        Ok(())
+    }
+
+    fn count<E: Error>(self, expected: usize, given: usize) -> Result<(), E> {
+        if expected == given {
+            return Ok(());
+        }
+        Err(E::custom(format!(
+            "a value of `{}` has {expected} elements, not {given}",
+            self.ty
+        )))
+    }
+
+    /// Serializes one value per type as a tuple.
+    fn tuple<S: Serializer>(
+        self,
+        types: &[&TypeRef],
+        values: &[Value],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        self.count(types.len(), values.len())?;
+        let mut state = serializer.serialize_tuple(types.len())?;
+        for (ty, value) in types.iter().zip(values) {
+            state.serialize_element(&self.with(ty, value))?;
+        }
+        state.end()
+    }
+
+    fn primitive<S: Serializer>(
+        self,
+        primitive: Primitive,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match (primitive, self.value) {
+            (Primitive::Bool, Value::Bool(v)) => serializer.serialize_bool(*v),
+            (Primitive::Char, Value::Char(v)) => serializer.serialize_char(*v),
+            (Primitive::String, Value::String(v)) => serializer.serialize_str(v),
+            (Primitive::I8, Value::I8(v)) => serializer.serialize_i8(*v),
+            (Primitive::I16, Value::I16(v)) => serializer.serialize_i16(*v),
+            (Primitive::I32, Value::I32(v)) => serializer.serialize_i32(*v),
+            (Primitive::I64, Value::I64(v)) => serializer.serialize_i64(*v),
+            (Primitive::I128, Value::I128(v)) => serializer.serialize_i128(*v),
+            (Primitive::Isize, Value::Isize(v)) => serializer.serialize_i64(*v as i64),
+            (Primitive::U8, Value::U8(v)) => serializer.serialize_u8(*v),
+            (Primitive::U16, Value::U16(v)) => serializer.serialize_u16(*v),
+            (Primitive::U32, Value::U32(v)) => serializer.serialize_u32(*v),
+            (Primitive::U64, Value::U64(v)) => serializer.serialize_u64(*v),
+            (Primitive::U128, Value::U128(v)) => serializer.serialize_u128(*v),
+            (Primitive::Usize, Value::Usize(v)) => serializer.serialize_u64(*v as u64),
+            (Primitive::F32, Value::F32(v)) => serializer.serialize_f32(*v),
+            (Primitive::F64, Value::F64(v)) => serializer.serialize_f64(*v),
+            (Primitive::Unit, Value::Unit) => serializer.serialize_unit(),
+            (Primitive::Uuid, Value::Uuid(uuid)) => {
+                if serializer.is_human_readable() {
+                    serializer.collect_str(uuid)
+                } else {
+                    serializer.serialize_bytes(&uuid.0.to_be_bytes())
+                }
+            }
+            (Primitive::Bytes, Value::Bytes(bytes)) => serializer.collect_seq(bytes),
+            _ => Err(self.mismatch()),
+        }
+    }
+}
+
+impl Serialize for Typed<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let typed = *self;
+        match (&self.ty.kind, self.value) {
+            (TypeKind::Box(inner), value) => typed.with(inner, value).serialize(serializer),
+            (TypeKind::Option(_), Value::Option(None)) => serializer.serialize_none(),
+            (TypeKind::Option(inner), Value::Option(Some(value))) => {
+                serializer.serialize_some(&typed.with(inner, value))
+            }
+            (TypeKind::Result(ok, _), Value::Result(Ok(value))) => {
+                serializer.serialize_newtype_variant("Result", 0, "Ok", &typed.with(ok, value))
+            }
+            (TypeKind::Result(_, err), Value::Result(Err(value))) => {
+                serializer.serialize_newtype_variant("Result", 1, "Err", &typed.with(err, value))
+            }
+            (TypeKind::Vec(element), Value::Vec(elements))
+            | (TypeKind::Set(element), Value::Set(elements)) => {
+                let mut state = serializer.serialize_seq(Some(elements.len()))?;
+                for value in elements {
+                    state.serialize_element(&typed.with(element, value))?;
+                }
+                state.end()
+            }
+            (TypeKind::Map(key, val), Value::Map(entries)) => {
+                let mut state = serializer.serialize_map(Some(entries.len()))?;
+                for (k, v) in entries {
+                    state.serialize_entry(&typed.with(key, k), &typed.with(val, v))?;
+                }
+                state.end()
+            }
+            (TypeKind::Array(len, element), Value::Array(elements)) => {
+                let types = vec![&**element; *len as usize];
+                typed.tuple(&types, elements, serializer)
+            }
+            (TypeKind::Tuple(types), Value::Tuple(elements)) if types.is_empty() => {
+                typed.count(0, elements.len())?;
+                serializer.serialize_unit()
+            }
+            (TypeKind::Tuple(types), Value::Tuple(elements)) => {
+                let types: Vec<_> = types.iter().collect();
+                typed.tuple(&types, elements, serializer)
+            }
+            (TypeKind::Named { name, args }, _) => typed.named(name, args, serializer),
+            _ => Err(typed.mismatch()),
+        }
+    }
+}
+
+/// The variant of an enum value, under `variant`.
+struct Tag {
+    name: &'static str,
+    index: u32,
+    variant: &'static str,
+}
+
+impl Serialize for Tag {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_unit_variant(self.name, self.index, self.variant)
+    }
+}
+
+/// What a variant of an enum value holds, under `data`.
+struct Payload<'a> {
+    typed: Typed<'a>,
+    /// The enum.
+    name: &'a str,
+    variant: &'a str,
+    body: &'a Body,
+    data: &'a VariantData,
+}
+
+impl Serialize for Payload<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let typed = self.typed;
+        match (self.body, self.data) {
+            (Body::Newtype(ty), VariantData::Newtype(value)) => {
+                typed.with(ty, value).serialize(serializer)
+            }
+            (Body::Tuple(types), VariantData::Tuple(values)) => {
+                if let ([ty], [value]) = (types.as_slice(), values.as_slice()) {
+                    return typed.with(ty, value).serialize(serializer);
+                }
+                let types: Vec<_> = types.iter().collect();
+                typed.tuple(&types, values, serializer)
+            }
+            (Body::Struct(fields), VariantData::Struct(values)) => {
+                let mut state =
+                    serializer.serialize_struct(type_name(self.variant), fields.len())?;
+                typed.fields(self.name, fields, values, &mut state)?;
+                state.end()
+            }
+            (body, _) => Err(S::Error::custom(format!(
+                "variant `{}` of `{}` holds {}",
+                self.variant,
+                self.name,
+                match body {
+                    Body::Unit => "nothing",
+                    Body::Newtype(_) => "one value",
+                    Body::Tuple(_) => "a tuple of values",
+                    Body::Struct(_) => "fields",
+                }
+            ))),
+        }
+    }
+}
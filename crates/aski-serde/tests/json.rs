@@ -0,0 +1,341 @@
+//! Values go to and from JSON as the generated Rust types do, knowing only the schema.
+
+use aski_sema::{lower_source_file, Schema};
+use aski_serde::{TypedValue, ValueSeed};
+use aski_syntax::parse;
+use aski_value::{Value, VariantData};
+use serde::de::DeserializeSeed;
+use serde_json::json;
+
+const ALL_TYPES: &str = include_str!("../../../encoder/drafts/all-types.aski");
+
+fn all_types_schema() -> Schema {
+    let parse = parse(ALL_TYPES);
+    assert_eq!(parse.errors(), &[]);
+    let (schema, diagnostics) = lower_source_file(&parse.tree());
+    assert_eq!(diagnostics, &[]);
+    schema
+}
+
+fn string(text: &str) -> Value {
+    Value::String(text.to_string())
+}
+
+fn user_id() -> Value {
+    Value::Newtype {
+        ty: "user-id".to_string(),
+        value: Box::new(Value::Uuid(
+            "67e55044-10b1-426f-9247-bb680e5fe0c8".parse().unwrap(),
+        )),
+    }
+}
+
+fn record(ty: &str, fields: Vec<(&str, Value)>) -> Value {
+    Value::Record {
+        ty: ty.to_string(),
+        fields: fields
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect(),
+    }
+}
+
+fn variant(ty: &str, variant: &str, data: VariantData) -> Value {
+    Value::Variant {
+        ty: ty.to_string(),
+        variant: variant.to_string(),
+        data,
+    }
+}
+
+/// A value of `all-types` with every field filled in, in declaration order.
+fn all_types() -> Value {
+    record(
+        "all-types",
+        vec![
+            ("bool-value", Value::Bool(true)),
+            ("char-value", Value::Char('λ')),
+            ("string-value", string("hello")),
+            ("i8-value", Value::I8(-8)),
+            ("i16-value", Value::I16(-16)),
+            ("i32-value", Value::I32(-32)),
+            ("i64-value", Value::I64(-64)),
+            ("i128-value", Value::I128(i128::MIN)),
+            ("isize-value", Value::Isize(-1)),
+            ("u8-value", Value::U8(8)),
+            ("u16-value", Value::U16(16)),
+            ("u32-value", Value::U32(32)),
+            ("u64-value", Value::U64(64)),
+            ("u128-value", Value::U128(u128::MAX)),
+            ("usize-value", Value::Usize(1)),
+            ("f32-value", Value::F32(1.5)),
+            ("f64-value", Value::F64(-2.25)),
+            (
+                "maybe-i64-value",
+                Value::Option(Some(Box::new(Value::I64(7)))),
+            ),
+            (
+                "outcome-string-or-error-code",
+                Value::Result(Err(Box::new(variant(
+                    "error-code",
+                    "invalid",
+                    VariantData::Newtype(Box::new(string("bad"))),
+                )))),
+            ),
+            ("string-vector", Value::Vec(vec![string("a"), string("b")])),
+            (
+                "mixed-tuple",
+                Value::Tuple(vec![Value::I32(1), string("one"), Value::Bool(false)]),
+            ),
+            (
+                "u16-array-len-3",
+                Value::Array(vec![Value::U16(1), Value::U16(2), Value::U16(3)]),
+            ),
+            ("string-set", Value::Set(vec![string("x"), string("y")])),
+            (
+                "string-to-u32-map",
+                Value::Map(vec![(string("one"), Value::U32(1))]),
+            ),
+            (
+                "user-id-to-i64-map",
+                Value::Map(vec![(user_id(), Value::I64(-1))]),
+            ),
+            ("user-id", user_id()),
+            (
+                "blob-bytes",
+                Value::Newtype {
+                    ty: "blob".to_string(),
+                    value: Box::new(Value::Bytes(vec![0, 255])),
+                },
+            ),
+            (
+                "wrapped-pair",
+                Value::Newtype {
+                    ty: "wrapped".to_string(),
+                    value: Box::new(Value::TupleStruct {
+                        ty: "pair".to_string(),
+                        fields: vec![Value::I32(1), Value::I32(2)],
+                    }),
+                },
+            ),
+            (
+                "shape",
+                variant(
+                    "shape",
+                    "circle",
+                    VariantData::Struct(vec![("r".to_string(), Value::F64(1.5))]),
+                ),
+            ),
+            (
+                "message",
+                variant(
+                    "message",
+                    "batch",
+                    VariantData::Newtype(Box::new(Value::Vec(vec![
+                        variant("message", "ping", VariantData::Unit),
+                        variant(
+                            "message",
+                            "kv",
+                            VariantData::Newtype(Box::new(Value::Map(vec![(
+                                string("k"),
+                                string("v"),
+                            )]))),
+                        ),
+                    ]))),
+                ),
+            ),
+            (
+                "status",
+                record(
+                    "status",
+                    vec![
+                        ("ok", Value::Bool(true)),
+                        ("code", Value::Option(None)),
+                        ("note", Value::Option(Some(Box::new(string("fine"))))),
+                    ],
+                ),
+            ),
+            ("unit-value", Value::Unit),
+            ("unit-struct-value", record("unit-struct", Vec::new())),
+        ],
+    )
+}
+
+fn from_json(schema: &Schema, name: &str, text: &str) -> Result<Value, String> {
+    let mut deserializer = serde_json::Deserializer::from_str(text);
+    ValueSeed::new(schema, name)
+        .deserialize(&mut deserializer)
+        .map_err(|error| error.to_string())
+}
+
+#[test]
+fn all_types_round_trips_through_json() {
+    let schema = all_types_schema();
+    let value = all_types();
+    let text = serde_json::to_string(&TypedValue::new(&schema, "all-types", &value)).unwrap();
+    assert_eq!(from_json(&schema, "all-types", &text), Ok(value));
+}
+
+#[test]
+fn fields_variants_and_newtypes_take_their_rust_shape() {
+    let schema = all_types_schema();
+    let value = all_types();
+    let text = serde_json::to_string(&TypedValue::new(&schema, "all-types", &value)).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&text).unwrap();
+    assert_eq!(json["char_value"], "λ");
+    assert_eq!(json["maybe_i64_value"], 7);
+    assert_eq!(
+        json["outcome_string_or_error_code"],
+        json!({"Err": {"variant": "Invalid", "data": "bad"}})
+    );
+    assert_eq!(json["mixed_tuple"], json!([1, "one", false]));
+    assert_eq!(json["u16_array_len_3"], json!([1, 2, 3]));
+    assert_eq!(
+        json["user_id_to_i64_map"],
+        json!({"67e55044-10b1-426f-9247-bb680e5fe0c8": -1})
+    );
+    assert_eq!(json["user_id"], "67e55044-10b1-426f-9247-bb680e5fe0c8");
+    assert_eq!(json["blob_bytes"], json!([0, 255]));
+    assert_eq!(json["wrapped_pair"], json!([1, 2]));
+    assert_eq!(
+        json["shape"],
+        json!({"variant": "Circle", "data": {"r": 1.5}})
+    );
+    assert_eq!(
+        json["message"],
+        json!({"variant": "Batch", "data": [{"variant": "Ping"}, {"variant": "Kv", "data": {"k": "v"}}]})
+    );
+    assert_eq!(
+        json["status"],
+        json!({"ok": true, "code": null, "note": "fine"})
+    );
+    assert_eq!(json["unit_value"], json!(null));
+    assert_eq!(json["unit_struct_value"], json!(null));
+}
+
+#[test]
+fn payloads_are_read_as_the_rust_types_read_them() {
+    let schema = all_types_schema();
+    assert_eq!(
+        from_json(&schema, "status", r#"{"extra": [1], "ok": false}"#),
+        Ok(record(
+            "status",
+            vec![
+                ("ok", Value::Bool(false)),
+                ("code", Value::Option(None)),
+                ("note", Value::Option(None)),
+            ]
+        ))
+    );
+    assert_eq!(
+        from_json(&schema, "shape", r#"{"variant": "Rect", "data": [1, 2.5]}"#),
+        Ok(variant(
+            "shape",
+            "rect",
+            VariantData::Tuple(vec![Value::F64(1.0), Value::F64(2.5)])
+        ))
+    );
+    assert_eq!(
+        from_json(&schema, "error-code", r#"{"variant": "NotFound"}"#),
+        Ok(variant("error-code", "not-found", VariantData::Unit))
+    );
+}
+
+#[test]
+fn data_may_come_before_its_variant() {
+    let schema = all_types_schema();
+    let data_first = |name: &str, text: &str| {
+        let value = from_json(&schema, name, text).unwrap();
+        let json = serde_json::to_value(TypedValue::new(&schema, name, &value)).unwrap();
+        let reordered: serde_json::Value = serde_json::from_str(text).unwrap();
+        assert_eq!(json, reordered);
+        value
+    };
+    assert_eq!(
+        data_first("shape", r#"{"data": {"r": 1.5}, "variant": "Circle"}"#),
+        variant(
+            "shape",
+            "circle",
+            VariantData::Struct(vec![("r".to_string(), Value::F64(1.5))])
+        )
+    );
+    data_first(
+        "message",
+        r#"{"data": [{"data": {"k": "v"}, "variant": "Kv"}, {"variant": "Ping"}], "variant": "Batch"}"#,
+    );
+    data_first("error-code", r#"{"data": "bad", "variant": "Invalid"}"#);
+    assert_eq!(
+        data_first("shape", r#"{"data": [1.0, 2.5], "variant": "Rect"}"#),
+        from_json(
+            &schema,
+            "shape",
+            r#"{"variant": "Rect", "data": [1.0, 2.5]}"#
+        )
+        .unwrap()
+    );
+}
+
+#[test]
+fn sets_and_maps_are_sorted_and_keep_one_entry_per_key() {
+    let parse = parse(
+        "(aski/v1
+  (enum level (low) (high))
+  (record tags
+    { names: (set string)
+      levels: (set level)
+      counts: (map (? u8) u32) }))",
+    );
+    let schema = lower_source_file(&parse.tree()).0;
+    let text = r#"{
+        "names": ["b", "a", "b", "c"],
+        "levels": [{"variant": "High"}, {"variant": "Low"}, {"variant": "High"}],
+        "counts": {"2": 1, "1": 2, "2": 3}
+    }"#;
+    let level = |name: &str| variant("level", name, VariantData::Unit);
+    let count = |key: u8, value: u32| {
+        (
+            Value::Option(Some(Box::new(Value::U8(key)))),
+            Value::U32(value),
+        )
+    };
+    assert_eq!(
+        from_json(&schema, "tags", text),
+        Ok(record(
+            "tags",
+            vec![
+                (
+                    "names",
+                    Value::Set(vec![string("a"), string("b"), string("c")])
+                ),
+                ("levels", Value::Set(vec![level("low"), level("high")])),
+                ("counts", Value::Map(vec![count(1, 2), count(2, 3)])),
+            ]
+        ))
+    );
+}
+
+#[test]
+fn payloads_that_do_not_fit_are_rejected() {
+    let schema = all_types_schema();
+    let error = |name: &str, text: &str| from_json(&schema, name, text).unwrap_err();
+    assert!(error("pair", "[1, 3000000000]")
+        .starts_with("invalid value: integer `3000000000`, expected a value of `i32`"));
+    assert!(error("shape", r#"{"variant": "Hexagon"}"#).starts_with(
+        "unknown variant `Hexagon`, expected one of `Unit`, `Circle`, `Rect`, `Named`"
+    ));
+    assert!(error("status", r#"{"code": 1}"#).starts_with("missing field `ok`"));
+    assert!(error("user-id", r#""not a uuid""#)
+        .starts_with("invalid value: string \"not a uuid\", expected a value of `uuid`"));
+    assert!(error("nope", "null").starts_with("cannot find type `nope`"));
+}
+
+#[test]
+fn values_that_do_not_fit_fail_to_serialize() {
+    let schema = all_types_schema();
+    let status = record("status", vec![("ok", Value::Bool(true))]);
+    let error = serde_json::to_string(&TypedValue::new(&schema, "status", &status)).unwrap_err();
+    assert_eq!(error.to_string(), "missing field `code`");
+    let shape = variant("shape", "unit", VariantData::Newtype(Box::new(Value::Unit)));
+    let error = serde_json::to_string(&TypedValue::new(&schema, "shape", &shape)).unwrap_err();
+    assert_eq!(error.to_string(), "variant `unit` of `shape` holds nothing");
+}
@@ -0,0 +1,916 @@
+//! Typed views over the syntax tree.
+//!
+//! Each declaration form and type expression gets its own wrapper around a [`SyntaxNode`],
+//! with accessors for its parts. Both dialects share the wrappers: a capitalized
+//! definition is the same node as the aski/v1 declaration of its kind. Accessors return
+//! `Option` because the tree may come from broken input; a missing part means the parser
+//! reported an error.
+
+use crate::lexer::string_value;
+use crate::parser::TODO;
+use crate::{SyntaxKind, SyntaxNode, SyntaxToken};
+
+use SyntaxKind::*;
+
+/// A typed wrapper around a syntax node of a known kind.
+pub trait AstNode: Sized {
+    fn can_cast(kind: SyntaxKind) -> bool;
+
+    fn cast(syntax: SyntaxNode) -> Option<Self>;
+
+    fn syntax(&self) -> &SyntaxNode;
+}
+
+/// Nodes that carry a `NAME`: declarations, fields and variants.
+pub trait HasName: AstNode {
+    /// The declared name, looking through `(name T ...)` for generic declarations.
+    fn name(&self) -> Option<Name> {
+        support::child(self.syntax()).or_else(|| {
+            support::child::<GenericName>(self.syntax()).and_then(|generic| generic.name())
+        })
+    }
+}
+
+/// Declarations that may be generic, naming themselves `(name P ...)`.
+pub trait HasTypeParams: AstNode {
+    /// The type parameters of a generic declaration; empty for a plain one.
+    fn type_params(&self) -> impl Iterator<Item = TypeParam> {
+        support::child::<GenericName>(self.syntax())
+            .into_iter()
+            .flat_map(|generic| generic.type_params())
+    }
+}
+
+mod support {
+    use super::AstNode;
+    use crate::{SyntaxKind, SyntaxNode, SyntaxToken};
+
+    pub(super) fn child<N: AstNode>(parent: &SyntaxNode) -> Option<N> {
+        parent.children().find_map(N::cast)
+    }
+
+    pub(super) fn children<N: AstNode>(parent: &SyntaxNode) -> impl Iterator<Item = N> {
+        parent.children().filter_map(N::cast)
+    }
+
+    pub(super) fn nth_child<N: AstNode>(parent: &SyntaxNode, n: usize) -> Option<N> {
+        children(parent).nth(n)
+    }
+
+    pub(super) fn token(parent: &SyntaxNode, kind: SyntaxKind) -> Option<SyntaxToken> {
+        parent
+            .children_with_tokens()
+            .filter_map(|element| element.into_token())
+            .find(|token| token.kind() == kind)
+    }
+}
+
+macro_rules! ast_node {
+    ($(#[$meta:meta])* $name:ident, $kind:ident) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub struct $name {
+            syntax: SyntaxNode,
+        }
+
+        impl AstNode for $name {
+            fn can_cast(kind: SyntaxKind) -> bool {
+                kind == $kind
+            }
+
+            fn cast(syntax: SyntaxNode) -> Option<Self> {
+                Self::can_cast(syntax.kind()).then_some($name { syntax })
+            }
+
+            fn syntax(&self) -> &SyntaxNode {
+                &self.syntax
+            }
+        }
+    };
+}
+
+macro_rules! ast_enum {
+    ($(#[$meta:meta])* $name:ident { $($variant:ident($node:ident)),* $(,)? }) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($variant($node),)*
+        }
+
+        impl AstNode for $name {
+            fn can_cast(kind: SyntaxKind) -> bool {
+                $($node::can_cast(kind))||*
+            }
+
+            fn cast(syntax: SyntaxNode) -> Option<Self> {
+                $(
+                    if $node::can_cast(syntax.kind()) {
+                        return $node::cast(syntax).map($name::$variant);
+                    }
+                )*
+                None
+            }
+
+            fn syntax(&self) -> &SyntaxNode {
+                match self {
+                    $($name::$variant(node) => node.syntax(),)*
+                }
+            }
+        }
+    };
+}
+
+// --- documents ---
+
+ast_node!(
+    /// A whole `.aski` file.
+    SourceFile,
+    SOURCE_FILE
+);
+
+impl SourceFile {
+    /// The `(aski/v1 ...)` form. A well-formed document has exactly one.
+    pub fn schema(&self) -> Option<Schema> {
+        support::child(&self.syntax)
+    }
+
+    /// The type definitions of a capitalized document, which sit at top level.
+    pub fn decls(&self) -> impl Iterator<Item = Decl> {
+        support::children(&self.syntax)
+    }
+
+    pub fn modules(&self) -> impl Iterator<Item = ModuleDecl> {
+        support::children(&self.syntax)
+    }
+
+    pub fn impls(&self) -> impl Iterator<Item = ImplDecl> {
+        support::children(&self.syntax)
+    }
+
+    /// The value of a document holding a single value.
+    pub fn value(&self) -> Option<Value> {
+        support::child(&self.syntax)
+    }
+}
+
+ast_node!(
+    /// `(aski/v1 decl ...)`.
+    Schema,
+    SCHEMA
+);
+
+impl Schema {
+    /// The `aski/v1` head symbol.
+    pub fn version(&self) -> Option<SyntaxToken> {
+        support::token(&self.syntax, SYMBOL)
+    }
+
+    pub fn decls(&self) -> impl Iterator<Item = Decl> {
+        support::children(&self.syntax)
+    }
+
+    pub fn modules(&self) -> impl Iterator<Item = ModuleDecl> {
+        support::children(&self.syntax)
+    }
+
+    pub fn impls(&self) -> impl Iterator<Item = ImplDecl> {
+        support::children(&self.syntax)
+    }
+}
+
+// --- declarations ---
+
+ast_enum!(
+    /// Any type declaration.
+    Decl {
+        Newtype(NewtypeDecl),
+        Record(RecordDecl),
+        Tuple(TupleDecl),
+        Enum(EnumDecl),
+    }
+);
+
+impl HasName for Decl {}
+
+impl HasTypeParams for Decl {}
+
+ast_node!(
+    /// `(newtype name T)` or `(newtype (name P ...) T)`.
+    NewtypeDecl,
+    NEWTYPE_DECL
+);
+
+impl HasName for NewtypeDecl {}
+
+impl HasTypeParams for NewtypeDecl {}
+
+impl NewtypeDecl {
+    /// The wrapped type.
+    pub fn ty(&self) -> Option<TypeExpr> {
+        support::child(&self.syntax)
+    }
+}
+
+ast_node!(
+    /// `(record name {field: T ...})` or `(record (name P ...) {field: T ...})`.
+    RecordDecl,
+    RECORD_DECL
+);
+
+impl HasName for RecordDecl {}
+
+impl HasTypeParams for RecordDecl {}
+
+impl RecordDecl {
+    pub fn field_list(&self) -> Option<FieldList> {
+        support::child(&self.syntax)
+    }
+
+    pub fn fields(&self) -> impl Iterator<Item = Field> {
+        self.field_list().into_iter().flat_map(|list| list.fields())
+    }
+}
+
+ast_node!(
+    /// `(tuple name [T ...])` or `(tuple (name P ...) [T ...])`.
+    TupleDecl,
+    TUPLE_DECL
+);
+
+impl HasName for TupleDecl {}
+
+impl HasTypeParams for TupleDecl {}
+
+impl TupleDecl {
+    pub fn field_list(&self) -> Option<TupleFieldList> {
+        support::child(&self.syntax)
+    }
+
+    /// The positional field types, in order.
+    pub fn fields(&self) -> impl Iterator<Item = TypeExpr> {
+        self.field_list().into_iter().flat_map(|list| list.types())
+    }
+}
+
+ast_node!(
+    /// `(enum name variant ...)` or `(enum (name P ...) variant ...)`.
+    EnumDecl,
+    ENUM_DECL
+);
+
+impl HasName for EnumDecl {}
+
+impl HasTypeParams for EnumDecl {}
+
+impl EnumDecl {
+    pub fn variants(&self) -> impl Iterator<Item = Variant> {
+        support::children(&self.syntax)
+    }
+}
+
+// --- names ---
+
+ast_node!(
+    /// `(name P ...)` in declaration-name position.
+    GenericName,
+    GENERIC_NAME
+);
+
+impl GenericName {
+    pub fn name(&self) -> Option<Name> {
+        support::child(&self.syntax)
+    }
+
+    pub fn type_params(&self) -> impl Iterator<Item = TypeParam> {
+        support::children(&self.syntax)
+    }
+}
+
+ast_node!(
+    /// A name being declared.
+    Name,
+    NAME
+);
+
+impl Name {
+    pub fn token(&self) -> Option<SyntaxToken> {
+        support::token(&self.syntax, SYMBOL)
+    }
+
+    pub fn text(&self) -> String {
+        self.token()
+            .map_or_else(String::new, |token| token.text().to_string())
+    }
+}
+
+ast_node!(
+    /// A reference to a type by name.
+    NameRef,
+    NAME_REF
+);
+
+impl NameRef {
+    pub fn token(&self) -> Option<SyntaxToken> {
+        support::token(&self.syntax, SYMBOL)
+    }
+
+    pub fn text(&self) -> String {
+        self.token()
+            .map_or_else(String::new, |token| token.text().to_string())
+    }
+}
+
+ast_node!(
+    /// A type parameter of a generic declaration, like the `T` in `(wrapped T)`.
+    TypeParam,
+    TYPE_PARAM
+);
+
+impl TypeParam {
+    pub fn token(&self) -> Option<SyntaxToken> {
+        support::token(&self.syntax, SYMBOL)
+    }
+
+    pub fn text(&self) -> String {
+        self.token()
+            .map_or_else(String::new, |token| token.text().to_string())
+    }
+}
+
+// --- fields ---
+
+ast_node!(
+    /// `{field: T ...}`.
+    FieldList,
+    FIELD_LIST
+);
+
+impl FieldList {
+    pub fn fields(&self) -> impl Iterator<Item = Field> {
+        support::children(&self.syntax)
+    }
+}
+
+ast_node!(
+    /// `field: T`.
+    Field,
+    FIELD
+);
+
+impl HasName for Field {}
+
+impl Field {
+    pub fn colon(&self) -> Option<SyntaxToken> {
+        support::token(&self.syntax, COLON)
+    }
+
+    pub fn ty(&self) -> Option<TypeExpr> {
+        support::child(&self.syntax)
+    }
+
+    /// The value of `(default value)` after the type.
+    pub fn default_value(&self) -> Option<Value> {
+        support::child::<DefaultValue>(&self.syntax).and_then(|default| default.value())
+    }
+}
+
+ast_node!(
+    /// `(default value)`.
+    DefaultValue,
+    DEFAULT_VALUE
+);
+
+impl DefaultValue {
+    pub fn value(&self) -> Option<Value> {
+        support::child(&self.syntax)
+    }
+}
+
+ast_node!(
+    /// `[T ...]` holding the positional fields of a tuple declaration or variant.
+    TupleFieldList,
+    TUPLE_FIELD_LIST
+);
+
+impl TupleFieldList {
+    pub fn types(&self) -> impl Iterator<Item = TypeExpr> {
+        support::children(&self.syntax)
+    }
+}
+
+// --- variants ---
+
+ast_enum!(
+    /// One variant of an enum declaration.
+    Variant {
+        Unit(UnitVariant),
+        Newtype(NewtypeVariant),
+        Tuple(TupleVariant),
+        Struct(StructVariant),
+    }
+);
+
+impl HasName for Variant {}
+
+ast_node!(
+    /// `(name)`.
+    UnitVariant,
+    UNIT_VARIANT
+);
+
+impl HasName for UnitVariant {}
+
+ast_node!(
+    /// `(name T)`.
+    NewtypeVariant,
+    NEWTYPE_VARIANT
+);
+
+impl HasName for NewtypeVariant {}
+
+impl NewtypeVariant {
+    pub fn ty(&self) -> Option<TypeExpr> {
+        support::child(&self.syntax)
+    }
+}
+
+ast_node!(
+    /// `(name [T ...])`.
+    TupleVariant,
+    TUPLE_VARIANT
+);
+
+impl HasName for TupleVariant {}
+
+impl TupleVariant {
+    pub fn field_list(&self) -> Option<TupleFieldList> {
+        support::child(&self.syntax)
+    }
+
+    pub fn fields(&self) -> impl Iterator<Item = TypeExpr> {
+        self.field_list().into_iter().flat_map(|list| list.types())
+    }
+}
+
+ast_node!(
+    /// `(name {field: T ...})`.
+    StructVariant,
+    STRUCT_VARIANT
+);
+
+impl HasName for StructVariant {}
+
+impl StructVariant {
+    pub fn field_list(&self) -> Option<FieldList> {
+        support::child(&self.syntax)
+    }
+
+    pub fn fields(&self) -> impl Iterator<Item = Field> {
+        self.field_list().into_iter().flat_map(|list| list.fields())
+    }
+}
+
+// --- type expressions ---
+
+ast_enum!(
+    /// Anything that can appear in type position.
+    TypeExpr {
+        Named(NamedType),
+        Generic(GenericType),
+        Option(OptionType),
+        Result(ResultType),
+        Vec(VecType),
+        Set(SetType),
+        Map(MapType),
+        Array(ArrayType),
+        Box(BoxType),
+        Tuple(TupleType),
+        Literal(LiteralType),
+    }
+);
+
+ast_node!(
+    /// A primitive, a declared type or a type parameter: `string`, `user-id`, `T`.
+    NamedType,
+    NAMED_TYPE
+);
+
+impl NamedType {
+    pub fn name_ref(&self) -> Option<NameRef> {
+        support::child(&self.syntax)
+    }
+}
+
+ast_node!(
+    /// A generic declaration applied to arguments: `(wrapped pair)`.
+    GenericType,
+    GENERIC_TYPE
+);
+
+impl GenericType {
+    pub fn name_ref(&self) -> Option<NameRef> {
+        support::child(&self.syntax)
+    }
+
+    pub fn args(&self) -> impl Iterator<Item = TypeExpr> {
+        support::children(&self.syntax)
+    }
+}
+
+ast_node!(
+    /// `(? T)`.
+    OptionType,
+    OPTION_TYPE
+);
+
+impl OptionType {
+    pub fn inner(&self) -> Option<TypeExpr> {
+        support::child(&self.syntax)
+    }
+}
+
+ast_node!(
+    /// `(result T E)`.
+    ResultType,
+    RESULT_TYPE
+);
+
+impl ResultType {
+    pub fn ok(&self) -> Option<TypeExpr> {
+        support::nth_child(&self.syntax, 0)
+    }
+
+    pub fn err(&self) -> Option<TypeExpr> {
+        support::nth_child(&self.syntax, 1)
+    }
+}
+
+ast_node!(
+    /// `(vec T)`.
+    VecType,
+    VEC_TYPE
+);
+
+impl VecType {
+    pub fn element(&self) -> Option<TypeExpr> {
+        support::child(&self.syntax)
+    }
+}
+
+ast_node!(
+    /// `(box T)`: `T` behind a pointer, which is what lets a type contain itself.
+    BoxType,
+    BOX_TYPE
+);
+
+impl BoxType {
+    pub fn inner(&self) -> Option<TypeExpr> {
+        support::child(&self.syntax)
+    }
+}
+
+ast_node!(
+    /// `(set T)`.
+    SetType,
+    SET_TYPE
+);
+
+impl SetType {
+    pub fn element(&self) -> Option<TypeExpr> {
+        support::child(&self.syntax)
+    }
+}
+
+ast_node!(
+    /// `(map K V)`.
+    MapType,
+    MAP_TYPE
+);
+
+impl MapType {
+    pub fn key(&self) -> Option<TypeExpr> {
+        support::nth_child(&self.syntax, 0)
+    }
+
+    pub fn value(&self) -> Option<TypeExpr> {
+        support::nth_child(&self.syntax, 1)
+    }
+}
+
+ast_node!(
+    /// `(array N T)`.
+    ArrayType,
+    ARRAY_TYPE
+);
+
+impl ArrayType {
+    pub fn len_token(&self) -> Option<SyntaxToken> {
+        support::token(&self.syntax, INT)
+    }
+
+    /// The array length, if present and representable.
+    pub fn length(&self) -> Option<u64> {
+        self.len_token()?.text().parse().ok()
+    }
+
+    pub fn element(&self) -> Option<TypeExpr> {
+        support::child(&self.syntax)
+    }
+}
+
+ast_node!(
+    /// `[T ...]` in type position.
+    TupleType,
+    TUPLE_TYPE
+);
+
+impl TupleType {
+    pub fn elements(&self) -> impl Iterator<Item = TypeExpr> {
+        support::children(&self.syntax)
+    }
+}
+
+ast_node!(
+    /// `"..."` in type position: an example value standing for its type, `string`.
+    LiteralType,
+    LITERAL_TYPE
+);
+
+impl LiteralType {
+    pub fn token(&self) -> Option<SyntaxToken> {
+        support::token(&self.syntax, STRING)
+    }
+}
+
+// --- values ---
+
+ast_enum!(
+    /// Anything that can appear in value position.
+    Value {
+        Literal(LiteralValue),
+        List(ListValue),
+        Map(MapValue),
+        Variant(VariantValue),
+        Tagged(TaggedValue),
+    }
+);
+
+ast_node!(
+    /// A number, a string, `true`, `false` or `none`.
+    LiteralValue,
+    LITERAL_VALUE
+);
+
+impl LiteralValue {
+    pub fn token(&self) -> Option<SyntaxToken> {
+        self.syntax
+            .children_with_tokens()
+            .filter_map(|element| element.into_token())
+            .find(|token| !token.kind().is_trivia())
+    }
+}
+
+ast_node!(
+    /// `[value ...]`.
+    ListValue,
+    LIST_VALUE
+);
+
+impl ListValue {
+    pub fn elements(&self) -> impl Iterator<Item = Value> {
+        support::children(&self.syntax)
+    }
+}
+
+ast_node!(
+    /// `{key value ...}`, or `{field: value ...}` for a record.
+    MapValue,
+    MAP_VALUE
+);
+
+impl MapValue {
+    pub fn entries(&self) -> impl Iterator<Item = MapEntry> {
+        support::children(&self.syntax)
+    }
+
+    pub fn fields(&self) -> impl Iterator<Item = FieldValue> {
+        support::children(&self.syntax)
+    }
+}
+
+ast_node!(
+    /// `key value` in a map value.
+    MapEntry,
+    MAP_ENTRY
+);
+
+impl MapEntry {
+    pub fn key(&self) -> Option<Value> {
+        support::nth_child(&self.syntax, 0)
+    }
+
+    pub fn value(&self) -> Option<Value> {
+        support::nth_child(&self.syntax, 1)
+    }
+}
+
+ast_node!(
+    /// `field: value` in a record value.
+    FieldValue,
+    FIELD_VALUE
+);
+
+impl FieldValue {
+    pub fn name_ref(&self) -> Option<NameRef> {
+        support::child(&self.syntax)
+    }
+
+    pub fn value(&self) -> Option<Value> {
+        support::child(&self.syntax)
+    }
+}
+
+ast_node!(
+    /// `(type variant payload)`.
+    VariantValue,
+    VARIANT_VALUE
+);
+
+impl VariantValue {
+    /// The enum the variant belongs to.
+    pub fn type_ref(&self) -> Option<NameRef> {
+        support::nth_child(&self.syntax, 0)
+    }
+
+    pub fn variant(&self) -> Option<NameRef> {
+        support::nth_child(&self.syntax, 1)
+    }
+
+    pub fn payload(&self) -> Option<Value> {
+        support::child(&self.syntax)
+    }
+}
+
+ast_node!(
+    /// `#tag value`.
+    TaggedValue,
+    TAGGED_VALUE
+);
+
+impl TaggedValue {
+    /// The `#tag` token.
+    pub fn tag(&self) -> Option<SyntaxToken> {
+        support::token(&self.syntax, TAG)
+    }
+
+    pub fn value(&self) -> Option<Value> {
+        support::child(&self.syntax)
+    }
+}
+
+// --- modules and impls ---
+
+ast_node!(
+    /// `module name {option value ...}`, or `(module name {option value ...})` in aski/v1.
+    ModuleDecl,
+    MODULE_DECL
+);
+
+impl HasName for ModuleDecl {}
+
+impl ModuleDecl {
+    pub fn options(&self) -> impl Iterator<Item = ModuleOption> {
+        support::child::<ModuleOptions>(&self.syntax)
+            .into_iter()
+            .flat_map(|options| support::children(&options.syntax))
+    }
+}
+
+ast_node!(
+    /// `{option value ...}`.
+    ModuleOptions,
+    MODULE_OPTIONS
+);
+
+ast_node!(
+    /// `option value` or `option (name ...)`.
+    ModuleOption,
+    MODULE_OPTION
+);
+
+impl HasName for ModuleOption {}
+
+impl ModuleOption {
+    /// A symbol, string or integer value.
+    pub fn value(&self) -> Option<SyntaxToken> {
+        self.syntax
+            .children_with_tokens()
+            .filter_map(|element| element.into_token())
+            .find(|token| matches!(token.kind(), SYMBOL | STRING | INT))
+    }
+
+    /// The names of a `(name ...)` value.
+    pub fn names(&self) -> impl Iterator<Item = NameRef> {
+        support::child::<NameList>(&self.syntax)
+            .into_iter()
+            .flat_map(|list| support::children(&list.syntax))
+    }
+}
+
+ast_node!(
+    /// `(name ...)` as an option value.
+    NameList,
+    NAME_LIST
+);
+
+ast_node!(
+    /// `impl Trait Type {member [...] ...}`, or `(impl trait type {...})` in aski/v1.
+    ImplDecl,
+    IMPL_DECL
+);
+
+impl ImplDecl {
+    pub fn trait_ref(&self) -> Option<NameRef> {
+        support::child(&self.syntax)
+    }
+
+    /// The type the trait is implemented for.
+    pub fn self_ty(&self) -> Option<TypeExpr> {
+        support::child(&self.syntax)
+    }
+
+    pub fn members(&self) -> impl Iterator<Item = ImplMember> {
+        support::children(&self.syntax)
+    }
+}
+
+ast_node!(
+    /// `member [...]`.
+    ImplMember,
+    IMPL_MEMBER
+);
+
+impl HasName for ImplMember {}
+
+impl ImplMember {
+    pub fn signature(&self) -> Option<TypeSignature> {
+        support::child(&self.syntax)
+    }
+
+    pub fn body(&self) -> Option<FunctionBody> {
+        support::child(&self.syntax)
+    }
+}
+
+ast_node!(
+    /// `(type-signature [param ...] return)`, or `(type-signature TODO)`.
+    TypeSignature,
+    TYPE_SIGNATURE
+);
+
+impl TypeSignature {
+    /// Whether the signature is still to be written.
+    pub fn is_todo(&self) -> bool {
+        is_todo(&self.syntax)
+    }
+
+    pub fn params(&self) -> impl Iterator<Item = TypeExpr> {
+        support::children::<ParamList>(&self.syntax)
+            .flat_map(|list| support::children(&list.syntax))
+    }
+
+    pub fn return_ty(&self) -> Option<TypeExpr> {
+        support::child(&self.syntax)
+    }
+}
+
+ast_node!(
+    /// `[param ...]`.
+    ParamList,
+    PARAM_LIST
+);
+
+ast_node!(
+    /// `(function-body "...")`, or `(function-body TODO)`.
+    FunctionBody,
+    FUNCTION_BODY
+);
+
+impl FunctionBody {
+    /// Whether the body is still to be written.
+    pub fn is_todo(&self) -> bool {
+        is_todo(&self.syntax)
+    }
+
+    /// The Rust source of the body, with the string literal's escapes resolved.
+    pub fn source(&self) -> Option<String> {
+        support::token(&self.syntax, STRING).map(|token| string_value(token.text()))
+    }
+}
+
+/// Whether a signature or body is written `TODO`.
+fn is_todo(node: &SyntaxNode) -> bool {
+    node.children_with_tokens()
+        .filter_map(|element| element.into_token())
+        .any(|token| token.kind() == SYMBOL && token.text() == TODO)
+}
@@ -0,0 +1,448 @@
+// Converting documents between aski/v1 and the capitalized dialect.
+//
+// Conversion rewrites the tokens of a parsed document one by one and keeps everything
+// between them, so comments stay where they were: `(record status {ok: bool})` becomes
+// `Status {ok Bool}`, `(enum shape (circle {r: f64}))` becomes `Shape [Circle {r F64}]`,
+// and back. Names are respelled the way lowering respells them, in impl member signatures
+// too; type parameters, module names, option values and function bodies are kept as
+// written.
+//
+// Going to the capitalized dialect drops the `(aski/v1 ...)` form and moves its body out
+// by one indentation level. Going to aski/v1 wraps the definitions back into one and lays
+// the result out canonically, since the capitalized layout has nothing to say about
+// where the parens of aski/v1 go. Either way, converting back gives the same tokens and
+// comments in the same order, except that comments between `(aski/v1` and the first
+// declaration end up above the `(aski/v1`.
+//
+// A conversion is refused when the document has syntax errors, or when some part of it
+// would not convert back to itself: a name whose respelling reads back differently, a
+// tuple of one field, which the capitalized dialect cannot tell from a newtype, or a
+// generic type named like a capitalized type constructor.
+
+use crate::ast::{AstNode, Decl, GenericName, TypeExpr};
+use crate::{
+    camel_case, format, is_symbol_char, kebab_case, parse, parse_capitalized, pascal_case,
+    Diagnostic, Parse, SyntaxElement, SyntaxKind, SyntaxNode, SyntaxToken, SCHEMA_VERSION,
+};
+
+use SyntaxKind::*;
+
+const INDENT: usize = 2;
+
+/// Rewrites an aski/v1 document in the capitalized dialect.
+pub fn to_capitalized(text: &str) -> Result<String, Vec<Diagnostic>> {
+    let parse = parse(text);
+    check(&parse)?;
+    let mut converter = Converter::default();
+    converter.capitalized_node(&parse.syntax_node());
+    converter.finish()
+}
+
+/// Rewrites a document in the capitalized dialect in aski/v1, laid out canonically.
+pub fn to_aski_v1(text: &str) -> Result<String, Vec<Diagnostic>> {
+    let parse = parse_capitalized(text);
+    check(&parse)?;
+    let mut converter = Converter::default();
+    converter.v1_file(&parse.syntax_node());
+    format(&converter.finish()?)
+}
+
+fn check(parse: &Parse) -> Result<(), Vec<Diagnostic>> {
+    if parse.errors().iter().any(Diagnostic::is_error) {
+        return Err(parse.errors().to_vec());
+    }
+    Ok(())
+}
+
+/// What a symbol names, which decides how it is spelled in either dialect.
+#[derive(Clone, Copy)]
+enum Role {
+    /// Types, variants and traits: `user-id`, `UserId`.
+    Type,
+    /// Fields, in declarations and values, and module options: `first-name`, `firstName`.
+    Field,
+    /// Impl members, named like Rust functions: `from-str`, `from_str`.
+    Member,
+    /// Everything else is written the same in both dialects.
+    Kept,
+}
+
+#[derive(Default)]
+struct Converter {
+    out: String,
+    errors: Vec<Diagnostic>,
+    /// The type parameters of the declaration being converted.
+    params: Vec<String>,
+    /// Whether the last thing written is a comment, which nothing may follow on its line.
+    after_comment: bool,
+    /// Where the last comment written ends.
+    comment_end: usize,
+    /// Whether the whitespace before the next token is dropped.
+    skip_whitespace: bool,
+}
+
+impl Converter {
+    fn finish(self) -> Result<String, Vec<Diagnostic>> {
+        if self.errors.is_empty() {
+            Ok(self.out)
+        } else {
+            Err(self.errors)
+        }
+    }
+
+    // --- output ---
+
+    /// Writes `text`, keeping it apart from a comment or symbol before it.
+    fn push(&mut self, text: &str) {
+        if self.after_comment && !text.starts_with('\n') {
+            self.out.push('\n');
+        }
+        let joins = self.out.ends_with(is_atom_char) && text.starts_with(is_atom_char);
+        if joins {
+            self.out.push(' ');
+        }
+        self.out.push_str(text);
+        self.after_comment = false;
+    }
+
+    fn comment(&mut self, text: &str) {
+        self.push(text);
+        self.after_comment = true;
+        self.comment_end = self.out.len();
+    }
+
+    /// Drops the whitespace written last, before a token that is dropped itself.
+    fn trim(&mut self) {
+        let kept = self.out.trim_end().len();
+        self.out.truncate(kept);
+        self.after_comment = kept > 0 && self.comment_end >= kept;
+    }
+
+    /// Checks that `converted`, the respelling of `token`, reads back as `token`.
+    fn respelled(&mut self, token: &SyntaxToken, converted: String, back: fn(&str) -> String) {
+        if back(&converted) != token.text() {
+            self.errors.push(Diagnostic::error(
+                format!(
+                    "`{}` would be respelled `{converted}`, which does not convert back",
+                    token.text()
+                ),
+                token.text_range(),
+            ));
+        }
+        self.push(&converted);
+    }
+
+    fn role(&self, token: &SyntaxToken) -> Role {
+        let parent = token.parent();
+        let owner = parent.parent().map(|owner| owner.kind());
+        match (parent.kind(), owner) {
+            (NAME, Some(FIELD | MODULE_OPTION)) => Role::Field,
+            (NAME, Some(IMPL_MEMBER)) => Role::Member,
+            (NAME, Some(MODULE_DECL)) => Role::Kept,
+            (NAME, _) => Role::Type,
+            (NAME_REF, Some(NAME_LIST)) => Role::Kept,
+            (NAME_REF, Some(FIELD_VALUE)) => Role::Field,
+            (NAME_REF, _) if self.params.iter().any(|param| param == token.text()) => Role::Kept,
+            (NAME_REF, _) => Role::Type,
+            _ => Role::Kept,
+        }
+    }
+
+    /// Takes note of the type parameters of a declaration before converting it.
+    fn enter_decl(&mut self, node: &SyntaxNode) {
+        if Decl::can_cast(node.kind()) || matches!(node.kind(), MODULE_DECL | IMPL_DECL) {
+            self.params = node
+                .children()
+                .filter_map(GenericName::cast)
+                .flat_map(|generic| generic.type_params())
+                .map(|param| param.text())
+                .collect();
+        }
+    }
+
+    // --- aski/v1 to capitalized ---
+
+    fn capitalized_node(&mut self, node: &SyntaxNode) {
+        self.enter_decl(node);
+        if matches!(node.kind(), TUPLE_DECL | TUPLE_VARIANT) {
+            let fields = node
+                .children()
+                .filter(|child| child.kind() == TUPLE_FIELD_LIST)
+                .flat_map(|list| list.children())
+                .filter(|child| TypeExpr::can_cast(child.kind()))
+                .count();
+            if fields == 1 {
+                self.errors.push(Diagnostic::error(
+                    "a tuple of one field would read as a newtype in the capitalized dialect",
+                    node.text_range(),
+                ));
+            }
+        }
+        for element in node.children_with_tokens() {
+            match element {
+                SyntaxElement::Node(child) => {
+                    if is_payload(node, &child) && !is_constructor(child.kind()) {
+                        self.push("(");
+                    }
+                    self.capitalized_node(&child);
+                    if node.kind() == ENUM_DECL && matches!(child.kind(), NAME | GENERIC_NAME) {
+                        self.push(" [");
+                    }
+                }
+                SyntaxElement::Token(token) => self.capitalized_token(&token),
+            }
+        }
+    }
+
+    fn capitalized_token(&mut self, token: &SyntaxToken) {
+        let parent = token.parent();
+        if token.kind() == WHITESPACE {
+            // The space after an enum's name is the one before its `[`.
+            let after_bracket = parent.kind() == ENUM_DECL && self.out.ends_with('[');
+            if self.skip_whitespace || (after_bracket && !token.text().contains('\n')) {
+                return;
+            }
+            if token.ancestors().any(|node| node.kind() == SCHEMA) {
+                if !self.out.is_empty() {
+                    self.push(&dedent(token.text()));
+                }
+            } else {
+                self.push(token.text());
+            }
+            return;
+        }
+        self.skip_whitespace = false;
+        match token.kind() {
+            COMMENT => self.comment(token.text()),
+            L_PAREN if opens(&parent, token) && is_bare(parent.kind()) => {}
+            R_PAREN if closes(&parent, token) && is_bare(parent.kind()) => match parent.kind() {
+                ENUM_DECL => self.push("]"),
+                NEWTYPE_DECL | NEWTYPE_VARIANT if !wraps_constructor(&parent) => self.push(")"),
+                _ => self.trim(),
+            },
+            // The lines after the version take the place of those before the schema.
+            SYMBOL if parent.kind() == SCHEMA => self.trim(),
+            // The declaration keywords go, with the space after them.
+            NEWTYPE_KW | RECORD_KW | TUPLE_KW | ENUM_KW => self.skip_whitespace = true,
+            L_BRACK if parent.kind() == TUPLE_FIELD_LIST => self.push("("),
+            R_BRACK if parent.kind() == TUPLE_FIELD_LIST => self.push(")"),
+            // Record fields drop the colon; record values keep it.
+            COLON if parent.kind() == FIELD => {}
+            SYMBOL
+                if parent.kind() == NAME_REF
+                    && parent.parent().is_some_and(|ty| ty.kind() == GENERIC_TYPE)
+                    && SyntaxKind::from_capitalized_type_keyword(&pascal_case(token.text()))
+                        .is_some() =>
+            {
+                self.errors.push(Diagnostic::error(
+                    format!(
+                        "generic type `{}` would read as a type constructor in the \
+                         capitalized dialect",
+                        token.text()
+                    ),
+                    token.text_range(),
+                ));
+            }
+            SYMBOL => match self.role(token) {
+                Role::Type => {
+                    let converted = qualified(token.text(), pascal_case);
+                    self.respelled(token, converted, |name| qualified(name, kebab_case))
+                }
+                Role::Field => self.respelled(token, camel_case(token.text()), kebab_case),
+                Role::Member => self.respelled(token, snake_case(token.text()), kebab_case),
+                Role::Kept => self.push(token.text()),
+            },
+            kind => match capitalized_keyword(kind) {
+                Some(keyword) => self.push(keyword),
+                None => self.push(token.text()),
+            },
+        }
+    }
+
+    // --- capitalized to aski/v1 ---
+
+    fn v1_file(&mut self, node: &SyntaxNode) {
+        let first = node.first_child();
+        for element in node.children_with_tokens() {
+            match element {
+                SyntaxElement::Node(child) => {
+                    if Some(&child) == first.as_ref() {
+                        self.push(&format!("({SCHEMA_VERSION}\n\n"));
+                    }
+                    self.v1_node(&child);
+                }
+                SyntaxElement::Token(token) => self.v1_token(&token),
+            }
+        }
+        if first.is_none() {
+            self.push(&format!("({SCHEMA_VERSION}"));
+        }
+        self.push("\n)\n");
+    }
+
+    fn v1_node(&mut self, node: &SyntaxNode) {
+        self.enter_decl(node);
+        match node.kind() {
+            NEWTYPE_DECL => self.push("(newtype "),
+            RECORD_DECL => self.push("(record "),
+            TUPLE_DECL => self.push("(tuple "),
+            ENUM_DECL => self.push("(enum "),
+            kind if is_bare(kind) => self.push("("),
+            _ => {}
+        }
+        for element in node.children_with_tokens() {
+            match element {
+                SyntaxElement::Node(child) => {
+                    self.v1_node(&child);
+                    if node.kind() == FIELD && child.kind() == NAME {
+                        self.push(":");
+                    }
+                }
+                SyntaxElement::Token(token) => self.v1_token(&token),
+            }
+        }
+        match node.kind() {
+            ENUM_DECL => {}
+            NEWTYPE_DECL | NEWTYPE_VARIANT if has_parens(node) => {}
+            kind if is_bare(kind) => self.push(")"),
+            _ => {}
+        }
+    }
+
+    fn v1_token(&mut self, token: &SyntaxToken) {
+        let parent = token.parent();
+        match (token.kind(), parent.kind()) {
+            (WHITESPACE, _) => self.push(token.text()),
+            (COMMENT, _) => self.comment(token.text()),
+            // A newtype's parens become its declaration's.
+            (L_PAREN, NEWTYPE_DECL | NEWTYPE_VARIANT) => {}
+            (L_BRACK, ENUM_DECL) => {}
+            (R_BRACK, ENUM_DECL) => self.push(")"),
+            (L_PAREN, TUPLE_FIELD_LIST) => self.push("["),
+            (R_PAREN, TUPLE_FIELD_LIST) => self.push("]"),
+            (SYMBOL, _) => match self.role(token) {
+                Role::Type => {
+                    let converted = qualified(token.text(), kebab_case);
+                    self.respelled(token, converted, |name| qualified(name, pascal_case))
+                }
+                Role::Field => self.respelled(token, kebab_case(token.text()), camel_case),
+                Role::Member => self.respelled(token, kebab_case(token.text()), snake_case),
+                Role::Kept => self.push(token.text()),
+            },
+            (kind, _) => match v1_keyword(kind) {
+                Some(keyword) => self.push(keyword),
+                None => self.push(token.text()),
+            },
+        }
+    }
+}
+
+/// Forms the capitalized dialect writes without parens of their own.
+fn is_bare(kind: SyntaxKind) -> bool {
+    matches!(
+        kind,
+        SCHEMA
+            | NEWTYPE_DECL
+            | RECORD_DECL
+            | TUPLE_DECL
+            | ENUM_DECL
+            | MODULE_DECL
+            | IMPL_DECL
+            | UNIT_VARIANT
+            | NEWTYPE_VARIANT
+            | TUPLE_VARIANT
+            | STRUCT_VARIANT
+    )
+}
+
+/// Type constructors, which need no parens around them as a payload.
+fn is_constructor(kind: SyntaxKind) -> bool {
+    matches!(
+        kind,
+        OPTION_TYPE | RESULT_TYPE | VEC_TYPE | SET_TYPE | MAP_TYPE | ARRAY_TYPE | BOX_TYPE
+    )
+}
+
+/// Whether `child` is the type a newtype declaration or variant wraps.
+fn is_payload(node: &SyntaxNode, child: &SyntaxNode) -> bool {
+    matches!(node.kind(), NEWTYPE_DECL | NEWTYPE_VARIANT) && TypeExpr::can_cast(child.kind())
+}
+
+/// Whether a newtype declaration or variant wraps a type constructor, which the
+/// capitalized dialect writes without parens around it.
+fn wraps_constructor(node: &SyntaxNode) -> bool {
+    node.children()
+        .any(|child| is_payload(node, &child) && is_constructor(child.kind()))
+}
+
+/// Whether a newtype definition or variant in the capitalized dialect has parens around
+/// its payload.
+fn has_parens(node: &SyntaxNode) -> bool {
+    node.children_with_tokens()
+        .any(|child| child.kind() == L_PAREN)
+}
+
+fn opens(node: &SyntaxNode, token: &SyntaxToken) -> bool {
+    node.first_token().as_ref() == Some(token)
+}
+
+fn closes(node: &SyntaxNode, token: &SyntaxToken) -> bool {
+    node.last_token().as_ref() == Some(token)
+}
+
+fn is_atom_char(c: char) -> bool {
+    is_symbol_char(c) || c == '"'
+}
+
+/// `spell` applied to a type name, leaving the module of a qualified one, `stuff/user`,
+/// as it is.
+fn qualified(name: &str, spell: fn(&str) -> String) -> String {
+    match name.split_once('/') {
+        Some((module, local)) => format!("{module}/{}", spell(local)),
+        None => spell(name),
+    }
+}
+
+fn snake_case(name: &str) -> String {
+    name.replace('-', "_")
+}
+
+/// Whitespace moved out of the schema, one indentation level further left.
+fn dedent(text: &str) -> String {
+    let mut lines = text.split('\n');
+    let mut out = lines.next().unwrap_or_default().to_string();
+    for line in lines {
+        let indent = line.len() - line.trim_start_matches(' ').len();
+        out.push('\n');
+        out.push_str(&line[indent.min(INDENT)..]);
+    }
+    out
+}
+
+fn capitalized_keyword(kind: SyntaxKind) -> Option<&'static str> {
+    let keyword = match kind {
+        OPTION_KW => "Option",
+        RESULT_KW => "Result",
+        VEC_KW => "Vec",
+        SET_KW => "Set",
+        MAP_KW => "Map",
+        ARRAY_KW => "Array",
+        BOX_KW => "Box",
+        _ => return None,
+    };
+    Some(keyword)
+}
+
+fn v1_keyword(kind: SyntaxKind) -> Option<&'static str> {
+    let keyword = match kind {
+        OPTION_KW => "?",
+        RESULT_KW => "result",
+        VEC_KW => "vec",
+        SET_KW => "set",
+        MAP_KW => "map",
+        ARRAY_KW => "array",
+        BOX_KW => "box",
+        _ => return None,
+    };
+    Some(keyword)
+}
@@ -0,0 +1,74 @@
+use crate::TextRange;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A message about a span of source text.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub range: TextRange,
+}
+
+impl Diagnostic {
+    pub fn error(message: impl Into<String>, range: TextRange) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Error,
+            message: message.into(),
+            range,
+        }
+    }
+
+    pub fn warning(message: impl Into<String>, range: TextRange) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Warning,
+            message: message.into(),
+            range,
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    /// The diagnostic the way a compiler prints one: the message, the line and column it
+    /// is at in `source`, the text of the file at `path`, and the line itself with the
+    /// range underlined. A range running over several lines is underlined to the end of
+    /// its first.
+    pub fn render(&self, path: &str, source: &str) -> String {
+        let start = self.range.start().min(source.len());
+        let line_start = source[..start].rfind('\n').map_or(0, |newline| newline + 1);
+        let line_end = source[start..]
+            .find('\n')
+            .map_or(source.len(), |newline| start + newline);
+        let line = source[line_start..line_end].trim_end_matches('\r');
+        let number = source[..start].matches('\n').count() + 1;
+        let before = &source[line_start..start];
+        let column = before.chars().count() + 1;
+        let end = self.range.end().clamp(start, line_start + line.len());
+        let carets = source[start..end].chars().count().max(1);
+
+        // Tabs stay tabs so that the underline lines up however they are displayed.
+        let padding: String = before
+            .chars()
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let gutter = " ".repeat(number.to_string().len());
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        format!(
+            "{severity}: {}\n{gutter}--> {path}:{number}:{column}\n\
+             {gutter} |\n\
+             {number} | {line}\n\
+             {gutter} | {padding}{}\n",
+            self.message,
+            "^".repeat(carets)
+        )
+    }
+}
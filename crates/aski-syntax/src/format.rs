@@ -0,0 +1,300 @@
+// The canonical layout of an aski document, printed from its syntax tree.
+//
+// The layout is the one all-types.aski is written in. The schema's declarations go one
+// per line, two spaces in, with its closing paren on a line of its own. A record with
+// fields puts its field list on the next line, one field per line after the opening
+// brace, and an enum with variants puts each variant on a line of its own. Everything
+// else (type expressions, newtypes, tuples, the variants themselves) is written on one
+// line with single spaces between its elements.
+//
+// A document in the capitalized dialect is laid out by the same rules, its definitions
+// being the declarations without their parens: a record's field list goes on the line
+// after its name, and an enum's variants each on a line of their own after `Name [`.
+//
+// Comments stay where they are relative to the tokens around them: one trailing a token
+// keeps trailing it, and one on a line of its own keeps a line of its own. A comment runs
+// to the end of its line, so whatever follows one starts a new line, even in a form that
+// is otherwise written on one line. Blank lines between the declarations of a schema, the
+// fields of a record, the variants of an enum or the forms of a file are kept, collapsed
+// to one; blank lines anywhere else go away. Since the layout depends only on the tree,
+// formatting formatted text changes nothing.
+
+use crate::{
+    lex, parse, parse_capitalized, Diagnostic, Parse, SyntaxElement, SyntaxKind, SyntaxNode,
+};
+
+use SyntaxKind::*;
+
+const INDENT: usize = 2;
+
+/// Lays `text` out canonically, in whichever dialect it is written. Text with syntax
+/// errors is not formatted; its errors are returned instead.
+pub fn format(text: &str) -> Result<String, Vec<Diagnostic>> {
+    let parse = parse_document(text);
+    if parse.errors().iter().any(Diagnostic::is_error) {
+        return Err(parse.errors().to_vec());
+    }
+    let mut formatter = Formatter::default();
+    formatter.source_file(&parse.syntax_node());
+    Ok(formatter.out)
+}
+
+/// Parses `text` as aski/v1 if it starts with a version form, and in the capitalized
+/// dialect otherwise.
+fn parse_document(text: &str) -> Parse {
+    let (tokens, _) = lex(text);
+    let mut significant = tokens.iter().filter(|token| !token.kind.is_trivia());
+    match (significant.next(), significant.next()) {
+        (Some(open), Some(version))
+            if open.kind == L_PAREN && version.text.starts_with("aski/") =>
+        {
+            parse(text)
+        }
+        _ => parse_capitalized(text),
+    }
+}
+
+/// A significant element or comment, with the number of line breaks before it.
+struct Entry {
+    element: SyntaxElement,
+    newlines: usize,
+}
+
+impl Entry {
+    fn is_comment(&self) -> bool {
+        self.element.kind() == COMMENT
+    }
+}
+
+/// The children of `node` other than whitespace.
+fn entries(node: &SyntaxNode) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    let mut newlines = 0;
+    for element in node.children_with_tokens() {
+        match &element {
+            SyntaxElement::Token(token) if token.kind() == WHITESPACE => {
+                newlines += token.text().matches('\n').count();
+            }
+            _ => {
+                entries.push(Entry { element, newlines });
+                newlines = 0;
+            }
+        }
+    }
+    entries
+}
+
+#[derive(Default)]
+struct Formatter {
+    out: String,
+    /// Whether the last thing written is a comment, which the next element must not follow
+    /// on the same line.
+    after_comment: bool,
+}
+
+impl Formatter {
+    fn source_file(&mut self, node: &SyntaxNode) {
+        for (index, entry) in entries(node).iter().enumerate() {
+            if index > 0 {
+                if entry.is_comment() && entry.newlines == 0 {
+                    self.out.push(' ');
+                } else {
+                    self.line(entry.newlines, 0);
+                }
+            }
+            self.element(&entry.element, 0);
+        }
+        if !self.out.is_empty() {
+            self.out.push('\n');
+        }
+    }
+
+    /// Writes `element`, which starts on a line indented by `indent`.
+    fn element(&mut self, element: &SyntaxElement, indent: usize) {
+        let node = match element {
+            SyntaxElement::Token(token) if token.kind() == COMMENT => {
+                // Trailing whitespace is not part of the layout.
+                self.out.push_str(token.text().trim_end());
+                self.after_comment = true;
+                return;
+            }
+            SyntaxElement::Token(token) => {
+                self.out.push_str(token.text());
+                self.after_comment = false;
+                return;
+            }
+            SyntaxElement::Node(node) => node,
+        };
+        match node.kind() {
+            SCHEMA => self.vertical(node, 1, indent),
+            ENUM_DECL if node.children().any(|child| is_variant(child.kind())) => {
+                self.vertical(node, if is_bare(node) { 1 } else { 2 }, indent)
+            }
+            RECORD_DECL
+                if node
+                    .children()
+                    .any(|child| child.kind() == FIELD_LIST && is_multiline(&child)) =>
+            {
+                self.vertical(node, if is_bare(node) { 0 } else { 2 }, indent)
+            }
+            FIELD_LIST if node.parent().map(|parent| parent.kind()) == Some(RECORD_DECL) => {
+                self.field_list(node, indent)
+            }
+            _ => self.inline(node, indent + INDENT),
+        }
+    }
+
+    /// Writes a form with its first `head` elements after its first one on the opening
+    /// line and each of the rest on a line of its own.
+    fn vertical(&mut self, node: &SyntaxNode, head: usize, indent: usize) {
+        let inner = indent + INDENT;
+        // The opening delimiter and the head are written first.
+        let mut written = 0;
+        let mut space = false;
+        for entry in entries(node) {
+            let in_body = written > head;
+            // A schema may start with a blank line; other forms only separate their
+            // elements with them.
+            let newlines = if written > head + 1 || (in_body && node.kind() == SCHEMA) {
+                entry.newlines
+            } else {
+                1
+            };
+            match &entry.element {
+                _ if entry.is_comment() => {
+                    if entry.newlines == 0 {
+                        self.out.push(' ');
+                    } else {
+                        self.line(newlines, inner);
+                    }
+                    self.element(&entry.element, inner);
+                }
+                SyntaxElement::Token(token) if token.kind().is_closing_delimiter() => {
+                    if self.after_comment || node.kind() == SCHEMA {
+                        self.line(1, indent);
+                    }
+                    self.out.push_str(token.text());
+                }
+                element if written <= head => {
+                    self.separate(inner, space);
+                    self.element(element, inner);
+                    space = !element.kind().is_opening_delimiter();
+                    written += 1;
+                }
+                element => {
+                    self.line(newlines, inner);
+                    self.element(element, inner);
+                    written += 1;
+                }
+            }
+        }
+    }
+
+    /// Writes the field list of a record, one field per line, with the opening brace on a
+    /// line indented by `indent`.
+    fn field_list(&mut self, node: &SyntaxNode, indent: usize) {
+        let inner = indent + INDENT;
+        let mut fields = 0;
+        for entry in entries(node) {
+            match &entry.element {
+                SyntaxElement::Token(token) if token.kind() == L_BRACE => {
+                    self.out.push_str(token.text());
+                }
+                SyntaxElement::Token(token) if token.kind() == R_BRACE => {
+                    if self.after_comment {
+                        self.line(1, indent);
+                    } else {
+                        self.out.push(' ');
+                    }
+                    self.out.push_str(token.text());
+                }
+                element => {
+                    let trailing = entry.is_comment() && entry.newlines == 0;
+                    if (fields == 0 && !self.after_comment) || trailing {
+                        self.out.push(' ');
+                    } else {
+                        self.line(if fields == 0 { 1 } else { entry.newlines }, inner);
+                    }
+                    self.element(element, inner);
+                    if !entry.is_comment() {
+                        fields += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Writes a form on one line, breaking lines only after comments; continuation lines
+    /// are indented by `indent`.
+    fn inline(&mut self, node: &SyntaxNode, indent: usize) {
+        let mut space = false;
+        for entry in entries(node) {
+            match &entry.element {
+                _ if entry.is_comment() => {
+                    if entry.newlines == 0 {
+                        self.out.push(' ');
+                    } else {
+                        self.line(1, indent);
+                    }
+                    self.element(&entry.element, indent);
+                }
+                SyntaxElement::Token(token) => {
+                    let kind = token.kind();
+                    let spaced = space && !kind.is_closing_delimiter() && kind != COLON;
+                    self.separate(indent, spaced);
+                    self.out.push_str(token.text());
+                    space = !kind.is_opening_delimiter();
+                }
+                SyntaxElement::Node(child) => {
+                    self.separate(indent, space);
+                    self.inline(child, indent);
+                    space = true;
+                }
+            }
+        }
+    }
+
+    /// Separates the next element from the last one: by a line break after a comment,
+    /// otherwise by a space if `space`.
+    fn separate(&mut self, indent: usize, space: bool) {
+        if self.after_comment {
+            self.line(1, indent);
+        } else if space {
+            self.out.push(' ');
+        }
+    }
+
+    /// Starts a new line indented by `indent`, after a blank one if `newlines` is more
+    /// than one.
+    fn line(&mut self, newlines: usize, indent: usize) {
+        let kept = self.out.trim_end_matches(' ').len();
+        self.out.truncate(kept);
+        if newlines > 1 {
+            self.out.push('\n');
+        }
+        self.out.push('\n');
+        self.out.extend(std::iter::repeat_n(' ', indent));
+        self.after_comment = false;
+    }
+}
+
+/// Whether a declaration is a definition in the capitalized dialect, written without
+/// parens around it.
+fn is_bare(decl: &SyntaxNode) -> bool {
+    decl.children_with_tokens()
+        .next()
+        .is_some_and(|first| first.kind() != L_PAREN)
+}
+
+fn is_variant(kind: SyntaxKind) -> bool {
+    matches!(
+        kind,
+        UNIT_VARIANT | NEWTYPE_VARIANT | TUPLE_VARIANT | STRUCT_VARIANT
+    )
+}
+
+/// Whether a record's field list has anything in it to lay out line by line.
+fn is_multiline(list: &SyntaxNode) -> bool {
+    list.children_with_tokens()
+        .any(|child| matches!(child.kind(), FIELD | COMMENT))
+}
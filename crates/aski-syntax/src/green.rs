@@ -0,0 +1,209 @@
+// Immutable, position-independent syntax tree ("green" tree).
+//
+// Green nodes know their kind, their text length and their children, but not where they
+// sit in the document. That makes them cheap to share: an edit rebuilds only the spine
+// from the changed token up to the root, and untouched subtrees compare equal to their
+// previous versions, which is what the incremental layers key their caches on.
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::SyntaxKind;
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct GreenNode(Arc<GreenNodeData>);
+
+#[derive(PartialEq, Eq, Hash)]
+struct GreenNodeData {
+    kind: SyntaxKind,
+    text_len: usize,
+    children: Vec<GreenElement>,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct GreenToken(Arc<GreenTokenData>);
+
+#[derive(PartialEq, Eq, Hash)]
+struct GreenTokenData {
+    kind: SyntaxKind,
+    text: Box<str>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum GreenElement {
+    Node(GreenNode),
+    Token(GreenToken),
+}
+
+impl GreenNode {
+    pub fn new(kind: SyntaxKind, children: Vec<GreenElement>) -> GreenNode {
+        let text_len = children.iter().map(GreenElement::text_len).sum();
+        GreenNode(Arc::new(GreenNodeData {
+            kind,
+            text_len,
+            children,
+        }))
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.0.kind
+    }
+
+    pub fn text_len(&self) -> usize {
+        self.0.text_len
+    }
+
+    pub fn children(&self) -> &[GreenElement] {
+        &self.0.children
+    }
+
+    /// A copy of this node with the child at `index` swapped for `child`.
+    pub fn replace_child(&self, index: usize, child: GreenElement) -> GreenNode {
+        let mut children = self.0.children.clone();
+        children[index] = child;
+        GreenNode::new(self.kind(), children)
+    }
+
+    /// Whether both handles point at the very same allocation, not merely equal trees.
+    pub fn ptr_eq(&self, other: &GreenNode) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+
+    fn write_text(&self, out: &mut impl fmt::Write) -> fmt::Result {
+        for child in self.children() {
+            match child {
+                GreenElement::Node(node) => node.write_text(out)?,
+                GreenElement::Token(token) => out.write_str(token.text())?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for GreenNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_text(f)
+    }
+}
+
+impl fmt::Debug for GreenNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}@{}", self.kind(), self.text_len())
+    }
+}
+
+impl GreenToken {
+    pub fn new(kind: SyntaxKind, text: &str) -> GreenToken {
+        GreenToken(Arc::new(GreenTokenData {
+            kind,
+            text: text.into(),
+        }))
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.0.kind
+    }
+
+    pub fn text(&self) -> &str {
+        &self.0.text
+    }
+
+    pub fn text_len(&self) -> usize {
+        self.0.text.len()
+    }
+}
+
+impl fmt::Debug for GreenToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} {:?}", self.kind(), self.text())
+    }
+}
+
+impl GreenElement {
+    pub fn kind(&self) -> SyntaxKind {
+        match self {
+            GreenElement::Node(node) => node.kind(),
+            GreenElement::Token(token) => token.kind(),
+        }
+    }
+
+    pub fn text_len(&self) -> usize {
+        match self {
+            GreenElement::Node(node) => node.text_len(),
+            GreenElement::Token(token) => token.text_len(),
+        }
+    }
+}
+
+impl From<GreenNode> for GreenElement {
+    fn from(node: GreenNode) -> GreenElement {
+        GreenElement::Node(node)
+    }
+}
+
+impl From<GreenToken> for GreenElement {
+    fn from(token: GreenToken) -> GreenElement {
+        GreenElement::Token(token)
+    }
+}
+
+/// A position in the builder to which a node can later be retroactively opened.
+#[derive(Debug, Clone, Copy)]
+pub struct Checkpoint(usize);
+
+/// Builds a green tree from a flat stream of start/token/finish events.
+#[derive(Default)]
+pub struct GreenNodeBuilder {
+    parents: Vec<(SyntaxKind, usize)>,
+    children: Vec<GreenElement>,
+}
+
+impl GreenNodeBuilder {
+    pub fn new() -> GreenNodeBuilder {
+        GreenNodeBuilder::default()
+    }
+
+    pub fn start_node(&mut self, kind: SyntaxKind) {
+        self.parents.push((kind, self.children.len()));
+    }
+
+    pub fn token(&mut self, kind: SyntaxKind, text: &str) {
+        self.children.push(GreenToken::new(kind, text).into());
+    }
+
+    pub fn finish_node(&mut self) {
+        let (kind, first_child) = self.parents.pop().expect("unbalanced finish_node");
+        let children = self.children.split_off(first_child);
+        self.children.push(GreenNode::new(kind, children).into());
+    }
+
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint(self.children.len())
+    }
+
+    /// Opens a node that adopts everything emitted since `checkpoint`.
+    pub fn start_node_at(&mut self, checkpoint: Checkpoint, kind: SyntaxKind) {
+        let Checkpoint(first_child) = checkpoint;
+        assert!(
+            first_child <= self.children.len(),
+            "checkpoint no longer valid, was finish_node called early?"
+        );
+        if let Some(&(_, parent_first_child)) = self.parents.last() {
+            assert!(
+                first_child >= parent_first_child,
+                "checkpoint no longer valid, was an unmatched start_node called?"
+            );
+        }
+        self.parents.push((kind, first_child));
+    }
+
+    /// Finishes building and returns the single root node.
+    pub fn finish(mut self) -> GreenNode {
+        assert!(self.parents.is_empty(), "unfinished nodes left in builder");
+        assert_eq!(self.children.len(), 1, "builder must produce a single root");
+        match self.children.pop() {
+            Some(GreenElement::Node(node)) => node,
+            _ => panic!("builder root must be a node"),
+        }
+    }
+}
@@ -0,0 +1,183 @@
+// Splits aski source text into tokens, trivia included.
+//
+// The lexer never fails: every byte of the input ends up in exactly one token, so
+// concatenating the token texts always reproduces the input. Malformed input becomes
+// `ERROR_TOKEN`s (plus a diagnostic) and is left for the parser to report in context.
+
+use crate::{Diagnostic, SyntaxKind, TextRange};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token<'a> {
+    pub kind: SyntaxKind,
+    pub text: &'a str,
+}
+
+pub fn lex(text: &str) -> (Vec<Token<'_>>, Vec<Diagnostic>) {
+    let mut lexer = Lexer {
+        text,
+        pos: 0,
+        tokens: Vec::new(),
+        errors: Vec::new(),
+    };
+    while lexer.pos < text.len() {
+        lexer.next_token();
+    }
+    (lexer.tokens, lexer.errors)
+}
+
+/// Characters that can appear anywhere in a symbol.
+pub fn is_symbol_char(c: char) -> bool {
+    c.is_alphanumeric() || "-_?!*+<>=/.&%$".contains(c)
+}
+
+/// Characters that can start a symbol. Digits start numbers instead.
+pub fn is_symbol_start(c: char) -> bool {
+    is_symbol_char(c) && !c.is_ascii_digit()
+}
+
+/// The text a string literal stands for: `"a\"b\n"` is `a"b` and a newline. A backslash
+/// before any other character stands for that character.
+pub fn string_value(literal: &str) -> String {
+    let inner = literal.strip_prefix('"').unwrap_or(literal);
+    let inner = inner.strip_suffix('"').unwrap_or(inner);
+    let mut value = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => value.push('\n'),
+            Some('t') => value.push('\t'),
+            Some('r') => value.push('\r'),
+            Some(escaped) => value.push(escaped),
+            None => {}
+        }
+    }
+    value
+}
+
+struct Lexer<'a> {
+    text: &'a str,
+    pos: usize,
+    tokens: Vec<Token<'a>>,
+    errors: Vec<Diagnostic>,
+}
+
+impl<'a> Lexer<'a> {
+    fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    fn eat_while(&mut self, pred: impl Fn(char) -> bool) {
+        let len = self
+            .rest()
+            .char_indices()
+            .find(|&(_, c)| !pred(c))
+            .map_or(self.rest().len(), |(i, _)| i);
+        self.pos += len;
+    }
+
+    fn next_token(&mut self) {
+        let start = self.pos;
+        let c = self.rest().chars().next().expect("lexer ran past the end");
+        self.pos += c.len_utf8();
+        let kind = match c {
+            c if c.is_whitespace() => {
+                self.eat_while(char::is_whitespace);
+                SyntaxKind::WHITESPACE
+            }
+            ';' => {
+                self.eat_while(|c| c != '\n');
+                SyntaxKind::COMMENT
+            }
+            '(' => SyntaxKind::L_PAREN,
+            ')' => SyntaxKind::R_PAREN,
+            '[' => SyntaxKind::L_BRACK,
+            ']' => SyntaxKind::R_BRACK,
+            '{' => SyntaxKind::L_BRACE,
+            '}' => SyntaxKind::R_BRACE,
+            ':' => SyntaxKind::COLON,
+            '"' => self.string(start),
+            '#' if self.rest().starts_with(is_symbol_start) => {
+                self.eat_while(is_symbol_char);
+                SyntaxKind::TAG
+            }
+            c if c.is_ascii_digit() => self.number(start),
+            '-' if self.rest().starts_with(|c: char| c.is_ascii_digit()) => self.number(start),
+            c if is_symbol_start(c) => {
+                self.eat_while(is_symbol_char);
+                SyntaxKind::SYMBOL
+            }
+            c => {
+                self.error(start, format!("unexpected character `{c}`"));
+                SyntaxKind::ERROR_TOKEN
+            }
+        };
+        self.tokens.push(Token {
+            kind,
+            text: &self.text[start..self.pos],
+        });
+    }
+
+    fn string(&mut self, start: usize) -> SyntaxKind {
+        let mut escaped = false;
+        for (i, c) in self.rest().char_indices() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => {
+                    self.pos += i + 1;
+                    return SyntaxKind::STRING;
+                }
+                _ => {}
+            }
+        }
+        self.pos = self.text.len();
+        self.error(start, "unterminated string literal".to_string());
+        SyntaxKind::STRING
+    }
+
+    fn number(&mut self, start: usize) -> SyntaxKind {
+        self.eat_while(is_symbol_char);
+        let text = &self.text[start..self.pos];
+        let digits = text.strip_prefix('-').unwrap_or(text);
+        if is_digits(digits) {
+            SyntaxKind::INT
+        } else if is_float(digits) {
+            SyntaxKind::FLOAT
+        } else {
+            self.error(start, format!("invalid number `{text}`"));
+            SyntaxKind::ERROR_TOKEN
+        }
+    }
+
+    fn error(&mut self, start: usize, message: String) {
+        let range = TextRange::new(start, self.pos);
+        self.errors.push(Diagnostic::error(message, range));
+    }
+}
+
+fn is_digits(text: &str) -> bool {
+    !text.is_empty() && text.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// `1.5`, `1e9`, `2.5e-3`: digits with a fraction, an exponent or both.
+fn is_float(text: &str) -> bool {
+    let (mantissa, exponent) = match text.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => {
+            let exponent = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+            (mantissa, Some(exponent))
+        }
+        None => (text, None),
+    };
+    let (whole, fraction) = match mantissa.split_once('.') {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (mantissa, None),
+    };
+    is_digits(whole)
+        && fraction.is_none_or(is_digits)
+        && exponent.is_none_or(is_digits)
+        && (fraction.is_some() || exponent.is_some())
+}
@@ -0,0 +1,45 @@
+//! Lossless syntax for the aski schema DSL.
+//!
+//! Text goes in, a concrete syntax tree comes out, and the tree's text is always the input
+//! byte for byte: whitespace and `;;` comments are kept as trivia tokens and malformed
+//! input is kept inside `ERROR` nodes. Positions are byte offsets into the source.
+//!
+//! The tree is split the usual way: an immutable, shareable [`GreenNode`] tree holds the
+//! structure, and [`SyntaxNode`] is a cheap positioned cursor over it. The [`ast`] module
+//! layers one typed wrapper per declaration form and type expression on top of that, and
+//! a [`NodePath`] addresses a node by the declarations, fields and variants leading to it.
+//! [`format`] prints a tree back out in the canonical layout. [`parse_value`] reads a
+//! document holding a single value, like an instance of a declaration, in the notation of
+//! record field defaults.
+//!
+//! [`parse_capitalized`] reads the capitalized dialect, `User {firstName String}`, into the
+//! same nodes, and [`kebab_case`], [`pascal_case`] and [`camel_case`] map its names to and
+//! from the kebab-case of aski/v1. [`to_capitalized`] and [`to_aski_v1`] rewrite a whole
+//! document from one dialect into the other, comments included.
+
+pub mod ast;
+mod convert;
+mod diagnostic;
+mod format;
+pub mod green;
+mod lexer;
+mod node_path;
+mod parser;
+mod red;
+mod spelling;
+mod syntax_kind;
+mod text_range;
+
+pub use crate::convert::{to_aski_v1, to_capitalized};
+pub use crate::diagnostic::{Diagnostic, Severity};
+pub use crate::format::format;
+pub use crate::green::{GreenElement, GreenNode, GreenToken};
+pub use crate::lexer::{is_symbol_char, is_symbol_start, lex, string_value, Token};
+pub use crate::node_path::{NodePath, NodePathError, Step};
+pub use crate::parser::{
+    parse, parse_capitalized, parse_value, reparse_form, Parse, SCHEMA_VERSION,
+};
+pub use crate::red::{SyntaxElement, SyntaxNode, SyntaxToken, TokenAtOffset, WalkEvent};
+pub use crate::spelling::{camel_case, kebab_case, pascal_case};
+pub use crate::syntax_kind::SyntaxKind;
+pub use crate::text_range::TextRange;
@@ -0,0 +1,240 @@
+// Structural addresses of syntax nodes.
+//
+// A node path names a node by the declarations, fields and variants leading to it rather
+// than by its offset, so it stays valid while the text around it changes: `all-types`
+// is a declaration, `all-types/fields/status` one of its fields, and
+// `shape/variants/circle/fields/r` a field of a struct variant. Positional fields of
+// tuples are addressed by index, `pair/fields/0`, which cannot be confused with a name
+// since names never start with a digit. `type` steps into the type of a field, newtype
+// or newtype variant, and `params/T` addresses a type parameter.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::ast::{self, AstNode, HasName, HasTypeParams};
+use crate::{SyntaxKind, SyntaxNode};
+
+use SyntaxKind::*;
+
+/// The address of a declaration or of a node inside one.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NodePath {
+    /// The name of the top-level declaration the path starts at.
+    pub decl: String,
+    pub steps: Vec<Step>,
+}
+
+/// One step of a [`NodePath`] below its declaration.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Step {
+    /// `fields/name`: a named field of a record or struct variant.
+    Field(String),
+    /// `fields/0`: a positional field of a tuple, tuple variant or tuple type.
+    Element(usize),
+    /// `variants/name`: a variant of an enum.
+    Variant(String),
+    /// `params/T`: a type parameter of a generic declaration.
+    Param(String),
+    /// `type`: the type of a field, newtype or newtype variant.
+    Type,
+}
+
+impl NodePath {
+    /// The path of a whole declaration.
+    pub fn decl(name: impl Into<String>) -> NodePath {
+        NodePath {
+            decl: name.into(),
+            steps: Vec::new(),
+        }
+    }
+
+    /// This path extended by one step.
+    pub fn join(mut self, step: Step) -> NodePath {
+        self.steps.push(step);
+        self
+    }
+
+    /// The node this path addresses in `file`, if there is one. When names are declared
+    /// more than once, the first declaration wins, as in name resolution.
+    pub fn resolve(&self, file: &ast::SourceFile) -> Option<SyntaxNode> {
+        let decl = file
+            .schema()?
+            .decls()
+            .find(|decl| decl.name().is_some_and(|name| name.text() == self.decl))?;
+        let mut node = decl.syntax().clone();
+        for step in &self.steps {
+            node = step.resolve(&node)?;
+        }
+        Some(node)
+    }
+
+    /// The path addressing `node`, if it is addressable: a declaration, field, variant,
+    /// type parameter, or the type in a slot of one of those.
+    pub fn of(node: &SyntaxNode) -> Option<NodePath> {
+        let mut steps = Vec::new();
+        let mut node = node.clone();
+        loop {
+            let parent = node.parent()?;
+            match node.kind() {
+                NEWTYPE_DECL | RECORD_DECL | TUPLE_DECL | ENUM_DECL if parent.kind() == SCHEMA => {
+                    let name = ast::Decl::cast(node)?.name()?.text();
+                    steps.reverse();
+                    return Some(NodePath { decl: name, steps });
+                }
+                FIELD => {
+                    steps.push(Step::Field(ast::Field::cast(node.clone())?.name()?.text()));
+                    node = parent.parent()?;
+                }
+                UNIT_VARIANT | NEWTYPE_VARIANT | TUPLE_VARIANT | STRUCT_VARIANT => {
+                    steps.push(Step::Variant(
+                        ast::Variant::cast(node.clone())?.name()?.text(),
+                    ));
+                    node = parent;
+                }
+                TYPE_PARAM => {
+                    steps.push(Step::Param(node.text()));
+                    node = parent.parent()?;
+                }
+                _ if ast::TypeExpr::can_cast(node.kind()) => match parent.kind() {
+                    FIELD | NEWTYPE_DECL | NEWTYPE_VARIANT => {
+                        steps.push(Step::Type);
+                        node = parent;
+                    }
+                    TUPLE_FIELD_LIST | TUPLE_TYPE => {
+                        let index = parent
+                            .children()
+                            .filter(|child| ast::TypeExpr::can_cast(child.kind()))
+                            .position(|child| child == node)?;
+                        steps.push(Step::Element(index));
+                        node = if parent.kind() == TUPLE_FIELD_LIST {
+                            parent.parent()?
+                        } else {
+                            parent
+                        };
+                    }
+                    _ => return None,
+                },
+                _ => return None,
+            }
+        }
+    }
+}
+
+impl Step {
+    fn resolve(&self, node: &SyntaxNode) -> Option<SyntaxNode> {
+        let found = match self {
+            Step::Field(name) => {
+                let mut fields: Box<dyn Iterator<Item = ast::Field>> = match node.kind() {
+                    RECORD_DECL => Box::new(ast::RecordDecl::cast(node.clone())?.fields()),
+                    STRUCT_VARIANT => Box::new(ast::StructVariant::cast(node.clone())?.fields()),
+                    _ => return None,
+                };
+                fields
+                    .find(|field| field.name().is_some_and(|n| n.text() == *name))?
+                    .syntax()
+                    .clone()
+            }
+            Step::Element(index) => {
+                let mut types: Box<dyn Iterator<Item = ast::TypeExpr>> = match node.kind() {
+                    TUPLE_DECL => Box::new(ast::TupleDecl::cast(node.clone())?.fields()),
+                    TUPLE_VARIANT => Box::new(ast::TupleVariant::cast(node.clone())?.fields()),
+                    TUPLE_TYPE => Box::new(ast::TupleType::cast(node.clone())?.elements()),
+                    _ => return None,
+                };
+                types.nth(*index)?.syntax().clone()
+            }
+            Step::Variant(name) => ast::EnumDecl::cast(node.clone())?
+                .variants()
+                .find(|variant| variant.name().is_some_and(|n| n.text() == *name))?
+                .syntax()
+                .clone(),
+            Step::Param(name) => ast::Decl::cast(node.clone())?
+                .type_params()
+                .find(|param| param.text() == *name)?
+                .syntax()
+                .clone(),
+            Step::Type => {
+                let ty = match node.kind() {
+                    FIELD => ast::Field::cast(node.clone())?.ty(),
+                    NEWTYPE_DECL => ast::NewtypeDecl::cast(node.clone())?.ty(),
+                    NEWTYPE_VARIANT => ast::NewtypeVariant::cast(node.clone())?.ty(),
+                    _ => None,
+                };
+                ty?.syntax().clone()
+            }
+        };
+        Some(found)
+    }
+}
+
+impl fmt::Display for NodePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.decl)?;
+        for step in &self.steps {
+            write!(f, "/{step}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Step::Field(name) => write!(f, "fields/{name}"),
+            Step::Element(index) => write!(f, "fields/{index}"),
+            Step::Variant(name) => write!(f, "variants/{name}"),
+            Step::Param(name) => write!(f, "params/{name}"),
+            Step::Type => f.write_str("type"),
+        }
+    }
+}
+
+/// Why a string is not a node path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodePathError {
+    pub message: String,
+}
+
+impl fmt::Display for NodePathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for NodePathError {}
+
+impl FromStr for NodePath {
+    type Err = NodePathError;
+
+    fn from_str(text: &str) -> Result<NodePath, NodePathError> {
+        let error = |message: String| NodePathError { message };
+        let mut segments = text.split('/');
+        let decl = segments.next().filter(|decl| !decl.is_empty());
+        let decl =
+            decl.ok_or_else(|| error("a node path starts with a declaration name".into()))?;
+        let mut path = NodePath::decl(decl);
+        while let Some(segment) = segments.next() {
+            let mut operand = || {
+                segments
+                    .next()
+                    .filter(|operand| !operand.is_empty())
+                    .ok_or_else(|| error(format!("`{segment}` must be followed by a name")))
+            };
+            let step = match segment {
+                "fields" => {
+                    let operand = operand()?;
+                    match operand.parse() {
+                        Ok(index) => Step::Element(index),
+                        Err(_) => Step::Field(operand.to_string()),
+                    }
+                }
+                "variants" => Step::Variant(operand()?.to_string()),
+                "params" => Step::Param(operand()?.to_string()),
+                "type" => Step::Type,
+                _ => return Err(error(format!("unknown node path step `{segment}`"))),
+            };
+            path.steps.push(step);
+        }
+        Ok(path)
+    }
+}
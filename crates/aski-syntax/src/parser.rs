@@ -0,0 +1,998 @@
+// Recursive-descent parser for the aski/v1 schema dialect.
+//
+// The grammar is small enough that every form is decided by its first one or two
+// significant tokens: a declaration or type constructor is a parenthesised form whose
+// head symbol names it, `{...}` is a field list and `[...]` a positional list. Trivia is
+// attached to whichever node is open when it is encountered, so the tree holds every byte
+// of the input.
+//
+// A record field may end in `(default value)`. Values are delimited the same way: `[...]`
+// is a list, `{...}` a map, or a record when its keys are followed by colons, and
+// `(type variant payload)` an enum value. A tag like `#uuid` takes the value after it;
+// anything else is a single token. A document may also hold a single value on its own.
+//
+// The parser never gives up. Unexpected input is wrapped in `ERROR` nodes and parsing
+// resumes at the next delimiter the enclosing form is waiting for.
+//
+// A declaration missing its `)`, the usual state of one being typed, would otherwise run
+// on into the declarations after it and take them along. So a declaration never reaches
+// past the start of another one that begins a line no further in than it does: the
+// declarations after a broken one keep their own nodes.
+//
+// The capitalized dialect lives in `capitalized`; it shares this plumbing and parses its
+// declarations into the same nodes.
+
+mod capitalized;
+
+use crate::ast::{AstNode, SourceFile, TypeExpr};
+use crate::green::{GreenNode, GreenNodeBuilder};
+use crate::lexer::{self, Token};
+use crate::{Diagnostic, SyntaxKind, SyntaxNode, SyntaxToken, TextRange};
+
+pub use self::capitalized::parse_capitalized;
+
+use SyntaxKind::*;
+
+/// The only schema version this parser understands.
+pub const SCHEMA_VERSION: &str = "aski/v1";
+
+/// The head of a record field's default value.
+const DEFAULT_HEAD: &str = "default";
+
+/// The heads of the two parts of an impl member's definition.
+const TYPE_SIGNATURE_HEAD: &str = "type-signature";
+const FUNCTION_BODY_HEAD: &str = "function-body";
+
+/// Stands for a signature or body that is still to be written.
+pub(crate) const TODO: &str = "TODO";
+
+/// The result of parsing: a lossless tree plus everything that was wrong with the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Parse {
+    green: GreenNode,
+    errors: Vec<Diagnostic>,
+}
+
+impl Parse {
+    pub fn green(&self) -> &GreenNode {
+        &self.green
+    }
+
+    pub fn syntax_node(&self) -> SyntaxNode {
+        SyntaxNode::new_root(self.green.clone())
+    }
+
+    /// The typed root of the tree.
+    pub fn tree(&self) -> SourceFile {
+        SourceFile::cast(self.syntax_node()).expect("parser always produces a SOURCE_FILE root")
+    }
+
+    pub fn errors(&self) -> &[Diagnostic] {
+        &self.errors
+    }
+}
+
+/// Parses an aski/v1 document.
+pub fn parse(text: &str) -> Parse {
+    let (tokens, lex_errors) = lexer::lex(text);
+    let mut parser = Parser::new(tokens, lex_errors);
+    parser.source_file();
+    parser.into_parse()
+}
+
+/// Parses a document holding a single value, written as a record field's default is, like
+/// `(shape circle {r: 1.5})`. The value is the only form of the tree's root.
+pub fn parse_value(text: &str) -> Parse {
+    let (tokens, lex_errors) = lexer::lex(text);
+    let mut parser = Parser::new(tokens, lex_errors);
+    parser.value_file();
+    parser.into_parse()
+}
+
+/// Parses `text` as the new text of `node`, a delimited form, in the place `node` holds.
+///
+/// This is what incremental reparsing builds on. The parser decides on a form by its own
+/// tokens, and the text around a form lexes the same whatever is inside it, so a form that
+/// stays one balanced form opened by the same delimiter parses the same on its own as it
+/// would in the whole document. `None` means the text is not such a form, or `node` is not
+/// one the parser starts at, and an enclosing form has to be reparsed instead. Diagnostic
+/// ranges are relative to the start of `text`.
+pub fn reparse_form(node: &SyntaxNode, text: &str) -> Option<(GreenNode, Vec<Diagnostic>)> {
+    let open = node.first_token()?.kind();
+    let (tokens, lex_errors) = lexer::lex(text);
+    if !is_one_form(&tokens, open) {
+        return None;
+    }
+    let mut parser = Parser::new(tokens, lex_errors);
+    // A declaration starting a line may end the one before it, so the form has to start
+    // one exactly when it did, and no declaration may start a line inside it: the columns
+    // that decides by are not known here.
+    let head = std::iter::successors(node.first_token(), SyntaxToken::next_token)
+        .skip(1)
+        .find(|token| !token.kind().is_trivia());
+    let started_decl =
+        head.is_some_and(|head| SyntaxKind::from_decl_keyword(head.text()).is_some());
+    if parser.starts_decl(0) != started_decl
+        || (1..parser.tokens.len())
+            .any(|index| parser.starts_decl(index) && parser.line_position(index).1)
+    {
+        return None;
+    }
+    match node.kind() {
+        // At top level a form is only a schema if a version symbol follows its `(`.
+        SCHEMA if parser.nth_kind(1) == Some(SYMBOL) => parser.schema(),
+        NEWTYPE_DECL | RECORD_DECL | TUPLE_DECL | ENUM_DECL => parser.decl(),
+        GENERIC_NAME => parser.decl_name(),
+        UNIT_VARIANT | NEWTYPE_VARIANT | TUPLE_VARIANT | STRUCT_VARIANT => parser.variant(),
+        FIELD_LIST => parser.field_list(),
+        TUPLE_FIELD_LIST => parser.type_list(TUPLE_FIELD_LIST),
+        kind if kind != NAMED_TYPE && TypeExpr::can_cast(kind) => parser.type_expr("a type"),
+        _ => return None,
+    }
+    if parser.pos < parser.tokens.len() {
+        return None;
+    }
+    let mut errors = parser.errors;
+    errors.sort_by_key(|error| error.range.start());
+    Some((parser.builder.finish(), errors))
+}
+
+/// Whether `tokens` are one form opened by `open` and closed by its match, with every
+/// delimiter inside it matched too.
+fn is_one_form(tokens: &[Token], open: SyntaxKind) -> bool {
+    if tokens.first().map(|token| token.kind) != Some(open) {
+        return false;
+    }
+    let mut closers = Vec::new();
+    for (index, token) in tokens.iter().enumerate() {
+        if let Some(close) = token.kind.closing() {
+            closers.push(close);
+        } else if token.kind.is_closing_delimiter() {
+            if closers.pop() != Some(token.kind) {
+                return false;
+            }
+            if closers.is_empty() {
+                return index == tokens.len() - 1;
+            }
+        }
+    }
+    false
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token<'a>>,
+    /// Index of the next unconsumed token, trivia included.
+    pos: usize,
+    /// Byte offset of `tokens[pos]`.
+    offset: usize,
+    builder: GreenNodeBuilder,
+    errors: Vec<Diagnostic>,
+    /// The index of the token opening the declaration being parsed, and its column.
+    decl: Option<(usize, usize)>,
+    /// Whether the input is in the capitalized dialect.
+    capitalized: bool,
+}
+
+impl<'a> Parser<'a> {
+    fn new(tokens: Vec<Token<'a>>, errors: Vec<Diagnostic>) -> Parser<'a> {
+        Parser {
+            tokens,
+            pos: 0,
+            offset: 0,
+            builder: GreenNodeBuilder::new(),
+            errors,
+            decl: None,
+            capitalized: false,
+        }
+    }
+
+    fn into_parse(self) -> Parse {
+        let mut errors = self.errors;
+        errors.sort_by_key(|error| error.range.start());
+        Parse {
+            green: self.builder.finish(),
+            errors,
+        }
+    }
+
+    // --- token plumbing ---
+
+    /// Index and offset of the `n`th significant token from the current position, short
+    /// of a declaration the one being parsed cannot reach past.
+    fn lookahead(&self, n: usize) -> Option<(usize, usize)> {
+        let mut offset = self.offset;
+        let mut seen = 0;
+        for (index, token) in self.tokens.iter().enumerate().skip(self.pos) {
+            if !token.kind.is_trivia() {
+                if self.ends_decl(index) {
+                    return None;
+                }
+                if seen == n {
+                    return Some((index, offset));
+                }
+                seen += 1;
+            }
+            offset += token.text.len();
+        }
+        None
+    }
+
+    fn nth(&self, n: usize) -> Option<Token<'a>> {
+        self.lookahead(n).map(|(index, _)| self.tokens[index])
+    }
+
+    fn nth_kind(&self, n: usize) -> Option<SyntaxKind> {
+        self.nth(n).map(|token| token.kind)
+    }
+
+    fn at(&self, kind: SyntaxKind) -> bool {
+        self.nth_kind(0) == Some(kind)
+    }
+
+    fn at_eof(&self) -> bool {
+        self.nth(0).is_none()
+    }
+
+    /// Whether the current list has run out: end of input or some closing delimiter.
+    fn at_list_end(&self) -> bool {
+        self.nth_kind(0)
+            .is_none_or(SyntaxKind::is_closing_delimiter)
+    }
+
+    fn at_type_start(&self) -> bool {
+        matches!(self.nth_kind(0), Some(SYMBOL | STRING | L_PAREN | L_BRACK))
+    }
+
+    /// Range of the current significant token, or an empty range at the end of input or
+    /// right after the declaration being parsed.
+    fn current_range(&self) -> TextRange {
+        match self.lookahead(0) {
+            Some((index, offset)) => TextRange::at(offset, self.tokens[index].text.len()),
+            None if self.at_decl_end() => TextRange::empty(self.offset),
+            None => TextRange::empty(self.tokens.iter().map(|t| t.text.len()).sum()),
+        }
+    }
+
+    /// Whether the declaration being parsed has run into the start of the next one.
+    fn at_decl_end(&self) -> bool {
+        self.at_eof() && self.tokens[self.pos..].iter().any(|t| !t.kind.is_trivia())
+    }
+
+    /// Whether the token at `index` starts a declaration the one being parsed cannot reach
+    /// past: one starting a line, indented no further than the one being parsed.
+    fn ends_decl(&self, index: usize) -> bool {
+        self.decl.is_some_and(|(open, column)| {
+            index > open
+                && self.starts_decl(index)
+                && matches!(self.line_position(index), (at, true) if at <= column)
+        })
+    }
+
+    /// Whether the token at `index` is a `(` followed by a declaration keyword.
+    fn starts_decl(&self, index: usize) -> bool {
+        if self.capitalized {
+            return self.starts_definition(index);
+        }
+        self.tokens[index].kind == L_PAREN
+            && self.tokens[index + 1..]
+                .iter()
+                .find(|token| !token.kind.is_trivia())
+                .is_some_and(|head| {
+                    head.kind == SYMBOL && SyntaxKind::from_decl_keyword(head.text).is_some()
+                })
+    }
+
+    /// The column of the token at `index`, in characters, and whether it is the first
+    /// token on its line.
+    fn line_position(&self, index: usize) -> (usize, bool) {
+        let mut column = 0;
+        let mut first = true;
+        for token in self.tokens[..index].iter().rev() {
+            let (line, newline) = match token.text.rfind('\n') {
+                Some(newline) => (&token.text[newline + 1..], true),
+                None => (token.text, false),
+            };
+            column += line.chars().count();
+            first &= token.kind == WHITESPACE;
+            if newline {
+                return (column, first);
+            }
+        }
+        (column, first)
+    }
+
+    fn skip_trivia(&mut self) {
+        while let Some(token) = self.tokens.get(self.pos) {
+            if !token.kind.is_trivia() {
+                break;
+            }
+            self.push_token(token.kind);
+        }
+    }
+
+    fn push_token(&mut self, kind: SyntaxKind) {
+        let token = self.tokens[self.pos];
+        self.builder.token(kind, token.text);
+        self.pos += 1;
+        self.offset += token.text.len();
+    }
+
+    fn bump(&mut self) {
+        let kind = self.nth_kind(0).expect("bump at end of input");
+        self.bump_as(kind);
+    }
+
+    /// Consumes the current token, recording it under a different kind.
+    fn bump_as(&mut self, kind: SyntaxKind) {
+        self.skip_trivia();
+        self.push_token(kind);
+    }
+
+    fn start(&mut self, kind: SyntaxKind) {
+        self.skip_trivia();
+        self.builder.start_node(kind);
+    }
+
+    fn finish(&mut self) {
+        self.builder.finish_node();
+    }
+
+    fn error(&mut self, message: impl Into<String>) {
+        let range = self.current_range();
+        self.errors.push(Diagnostic::error(message, range));
+    }
+
+    fn describe_current(&self) -> String {
+        match self.nth(0) {
+            Some(token) => format!("`{}`", token.text),
+            None if self.at_decl_end() => "the next declaration".to_string(),
+            None => "end of input".to_string(),
+        }
+    }
+
+    /// Reports an error and swallows the current element into an `ERROR` node.
+    ///
+    /// A whole delimited form is swallowed at once so that recovery resumes at a sibling,
+    /// not somewhere inside the bad form. Closing delimiters are left alone: they belong
+    /// to an enclosing form.
+    fn error_element(&mut self, message: impl Into<String>) {
+        self.error(message);
+        match self.nth_kind(0) {
+            None => {}
+            Some(kind) if kind.is_closing_delimiter() => {}
+            Some(_) => {
+                self.start(ERROR);
+                self.balanced();
+                self.finish();
+            }
+        }
+    }
+
+    /// Consumes one token, or one delimited form including everything nested inside it.
+    fn balanced(&mut self) {
+        let Some(open) = self.nth_kind(0) else {
+            return;
+        };
+        self.bump();
+        let Some(close) = open.closing() else {
+            return;
+        };
+        while !self.at_eof() && !self.at(close) {
+            if self.at_list_end() {
+                // A mismatched closer; keep it inside the error rather than unbalancing
+                // whoever is waiting for it further out.
+                self.bump();
+            } else {
+                self.balanced();
+            }
+        }
+        if self.at(close) {
+            self.bump();
+        }
+    }
+
+    fn expect_closing(&mut self, kind: SyntaxKind, form: &str) {
+        if self.at(kind) {
+            self.bump();
+        } else {
+            let delimiter = match kind {
+                R_PAREN => ")",
+                R_BRACK => "]",
+                _ => "}",
+            };
+            self.error(format!("expected `{delimiter}` to close {form}"));
+        }
+    }
+
+    /// Finishes a parenthesised form, reporting anything left over before its `)`.
+    fn close_form(&mut self, form: &str) {
+        while !self.at_list_end() {
+            let message = format!("unexpected {} in {form}", self.describe_current());
+            self.error_element(message);
+        }
+        self.expect_closing(R_PAREN, form);
+    }
+
+    // --- grammar ---
+
+    fn source_file(&mut self) {
+        self.builder.start_node(SOURCE_FILE);
+        while !self.at_eof() {
+            if self.at(L_PAREN) && self.nth_kind(1) == Some(SYMBOL) {
+                self.schema();
+            } else {
+                self.error_element(format!("expected `({SCHEMA_VERSION} ...)`"));
+                if self.at_list_end() && !self.at_eof() {
+                    // A stray closer at top level; there is nothing for it to close.
+                    self.start(ERROR);
+                    self.bump();
+                    self.finish();
+                }
+            }
+        }
+        self.skip_trivia();
+        self.finish();
+    }
+
+    fn value_file(&mut self) {
+        self.builder.start_node(SOURCE_FILE);
+        self.value();
+        while !self.at_eof() {
+            self.error_element("expected the end of input after the value");
+            if self.at_list_end() && !self.at_eof() {
+                self.start(ERROR);
+                self.bump();
+                self.finish();
+            }
+        }
+        self.skip_trivia();
+        self.finish();
+    }
+
+    fn schema(&mut self) {
+        self.start(SCHEMA);
+        self.bump();
+        let version = self.nth(0).map(|token| token.text);
+        if version != Some(SCHEMA_VERSION) {
+            let message = format!(
+                "unsupported schema version {}, expected `{SCHEMA_VERSION}`",
+                self.describe_current()
+            );
+            self.error(message);
+        }
+        self.bump();
+        while !self.at_list_end() {
+            self.decl();
+        }
+        self.expect_closing(R_PAREN, "the schema");
+        self.finish();
+    }
+
+    fn decl(&mut self) {
+        let outer = self.decl;
+        if let Some((open, _)) = self.lookahead(0) {
+            self.decl = Some((open, self.line_position(open).0));
+        }
+        let keyword = match (self.nth(0), self.nth(1)) {
+            (Some(open), Some(head)) if open.kind == L_PAREN && head.kind == SYMBOL => {
+                SyntaxKind::from_decl_keyword(head.text)
+            }
+            _ => None,
+        };
+        match keyword {
+            Some(NEWTYPE_KW) => self.newtype_decl(),
+            Some(RECORD_KW) => self.record_decl(),
+            Some(TUPLE_KW) => self.tuple_decl(),
+            Some(ENUM_KW) => self.enum_decl(),
+            Some(MODULE_KW) => self.module_decl(),
+            Some(IMPL_KW) => self.impl_decl(),
+            _ => self.error_element(
+                "expected a declaration: `newtype`, `record`, `tuple`, `enum`, `module` or `impl`",
+            ),
+        }
+        self.decl = outer;
+    }
+
+    /// Opens a declaration form and consumes its `(` and head keyword.
+    fn start_decl(&mut self, kind: SyntaxKind, keyword: SyntaxKind) {
+        self.start(kind);
+        self.bump();
+        self.bump_as(keyword);
+    }
+
+    fn newtype_decl(&mut self) {
+        self.start_decl(NEWTYPE_DECL, NEWTYPE_KW);
+        self.decl_name();
+        self.type_expr("the underlying type");
+        self.close_form("newtype declaration");
+        self.finish();
+    }
+
+    fn record_decl(&mut self) {
+        self.start_decl(RECORD_DECL, RECORD_KW);
+        self.decl_name();
+        if self.at(L_BRACE) {
+            self.field_list();
+        } else {
+            self.error("expected `{` to start the record fields");
+        }
+        self.close_form("record declaration");
+        self.finish();
+    }
+
+    fn tuple_decl(&mut self) {
+        self.start_decl(TUPLE_DECL, TUPLE_KW);
+        self.decl_name();
+        if self.at(L_BRACK) {
+            self.type_list(TUPLE_FIELD_LIST);
+        } else {
+            self.error("expected `[` to start the tuple fields");
+        }
+        self.close_form("tuple declaration");
+        self.finish();
+    }
+
+    fn enum_decl(&mut self) {
+        self.start_decl(ENUM_DECL, ENUM_KW);
+        self.decl_name();
+        while !self.at_list_end() {
+            if self.at(L_PAREN) {
+                self.variant();
+            } else {
+                self.error_element("expected a variant like `(name)` or `(name T)`");
+            }
+        }
+        self.expect_closing(R_PAREN, "enum declaration");
+        self.finish();
+    }
+
+    /// `name` or `(name T ...)` in declaration-name position.
+    fn decl_name(&mut self) {
+        if self.at(SYMBOL) {
+            self.name();
+        } else if self.at(L_PAREN) {
+            self.start(GENERIC_NAME);
+            self.bump();
+            if self.at(SYMBOL) {
+                self.name();
+            } else {
+                self.error("expected a type name");
+            }
+            while self.at(SYMBOL) {
+                self.start(TYPE_PARAM);
+                self.bump();
+                self.finish();
+            }
+            self.close_form("generic type name");
+            self.finish();
+        } else {
+            self.error("expected a type name");
+        }
+    }
+
+    fn name(&mut self) {
+        self.start(NAME);
+        self.bump();
+        self.finish();
+    }
+
+    fn field_list(&mut self) {
+        self.start(FIELD_LIST);
+        self.bump();
+        while !self.at_list_end() {
+            if self.at(SYMBOL) {
+                self.field();
+            } else {
+                self.error_element("expected a field name");
+            }
+        }
+        self.expect_closing(R_BRACE, "field list");
+        self.finish();
+    }
+
+    fn field(&mut self) {
+        self.start(FIELD);
+        self.name();
+        // Capitalized fields are written `name Type`, without the colon.
+        if !self.capitalized {
+            if self.at(COLON) {
+                self.bump();
+            } else {
+                self.error("expected `:` after the field name");
+            }
+        }
+        self.type_expr("a field type");
+        if self.at(L_PAREN) && self.nth(1).is_some_and(|head| head.text == DEFAULT_HEAD) {
+            self.start(DEFAULT_VALUE);
+            self.bump();
+            self.bump();
+            self.value();
+            self.close_form("default value");
+            self.finish();
+        }
+        self.finish();
+    }
+
+    /// A value: a literal, `[value ...]`, `{key value ...}`, `{field: value ...}`,
+    /// `(type variant payload)` or `#tag value`.
+    fn value(&mut self) {
+        match self.nth_kind(0) {
+            Some(INT | FLOAT | STRING | SYMBOL) => {
+                self.start(LITERAL_VALUE);
+                self.bump();
+                self.finish();
+            }
+            Some(L_BRACK) => {
+                self.start(LIST_VALUE);
+                self.bump();
+                while !self.at_list_end() {
+                    self.value();
+                }
+                self.expect_closing(R_BRACK, "list value");
+                self.finish();
+            }
+            Some(L_BRACE) => {
+                self.start(MAP_VALUE);
+                self.bump();
+                while !self.at_list_end() {
+                    if self.at(SYMBOL) && self.nth_kind(1) == Some(COLON) {
+                        self.start(FIELD_VALUE);
+                        self.name_ref();
+                        self.bump();
+                        self.value();
+                    } else {
+                        self.start(MAP_ENTRY);
+                        self.value();
+                        self.value();
+                    }
+                    self.finish();
+                }
+                self.expect_closing(R_BRACE, "map value");
+                self.finish();
+            }
+            Some(L_PAREN) => {
+                self.start(VARIANT_VALUE);
+                self.bump();
+                for expected in ["a type name", "a variant name"] {
+                    if self.at(SYMBOL) {
+                        self.name_ref();
+                    } else {
+                        let message =
+                            format!("expected {expected}, found {}", self.describe_current());
+                        self.error(message);
+                    }
+                }
+                if !self.at_list_end() {
+                    self.value();
+                }
+                self.close_form("variant value");
+                self.finish();
+            }
+            Some(TAG) => {
+                self.start(TAGGED_VALUE);
+                let tag = self.nth(0).map_or("", |token| token.text);
+                self.bump();
+                if self.at_list_end() {
+                    self.error(format!("expected a value after `{tag}`"));
+                } else {
+                    self.value();
+                }
+                self.finish();
+            }
+            _ => {
+                let message = format!("expected a value, found {}", self.describe_current());
+                self.error_element(message);
+            }
+        }
+    }
+
+    fn variant(&mut self) {
+        let body = if self.nth_kind(1) == Some(SYMBOL) {
+            self.nth_kind(2)
+        } else {
+            self.nth_kind(1)
+        };
+        let kind = match body {
+            Some(R_PAREN) | None => UNIT_VARIANT,
+            Some(L_BRACE) => STRUCT_VARIANT,
+            Some(L_BRACK) => TUPLE_VARIANT,
+            Some(_) => NEWTYPE_VARIANT,
+        };
+        self.start(kind);
+        self.bump();
+        if self.at(SYMBOL) {
+            self.name();
+        } else {
+            self.error("expected a variant name");
+        }
+        match kind {
+            STRUCT_VARIANT => self.field_list(),
+            TUPLE_VARIANT => self.type_list(TUPLE_FIELD_LIST),
+            NEWTYPE_VARIANT => {
+                self.type_expr("the variant payload type");
+                if self.at_type_start() {
+                    self.error("a newtype variant holds one type; wrap several in `[...]`");
+                }
+            }
+            _ => {}
+        }
+        self.close_form("variant");
+        self.finish();
+    }
+
+    /// `[T ...]`, as a tuple type or as the positional fields of a declaration; the
+    /// capitalized dialect also writes positional fields `(T ...)`.
+    fn type_list(&mut self, kind: SyntaxKind) {
+        let close = self
+            .nth_kind(0)
+            .and_then(SyntaxKind::closing)
+            .unwrap_or(R_BRACK);
+        self.start(kind);
+        self.bump();
+        while !self.at_list_end() {
+            if self.at_type_start() {
+                self.type_expr("a type");
+            } else {
+                let message = format!("expected a type, found {}", self.describe_current());
+                self.error_element(message);
+            }
+        }
+        self.expect_closing(close, "tuple");
+        self.finish();
+    }
+
+    fn type_expr(&mut self, expected: &str) {
+        match self.nth_kind(0) {
+            Some(SYMBOL) => {
+                self.start(NAMED_TYPE);
+                self.name_ref();
+                self.finish();
+            }
+            Some(STRING) => {
+                self.start(LITERAL_TYPE);
+                self.bump();
+                self.finish();
+            }
+            Some(L_BRACK) => self.type_list(TUPLE_TYPE),
+            Some(L_PAREN) => self.type_form(),
+            _ => {
+                let message = format!("expected {expected}, found {}", self.describe_current());
+                self.error(message);
+            }
+        }
+    }
+
+    fn name_ref(&mut self) {
+        self.start(NAME_REF);
+        self.bump();
+        self.finish();
+    }
+
+    /// A parenthesised type: a container like `(vec T)` or a generic application.
+    fn type_form(&mut self) {
+        let head = self.nth(1).filter(|token| token.kind == SYMBOL);
+        let Some(head) = head else {
+            self.error_element("expected a type constructor like `(vec T)` or `(? T)`");
+            return;
+        };
+        let keyword = if self.capitalized {
+            SyntaxKind::from_capitalized_type_keyword(head.text)
+        } else {
+            SyntaxKind::from_type_keyword(head.text)
+        };
+        let (kind, form) = match keyword {
+            Some(OPTION_KW) => (OPTION_TYPE, "option type"),
+            Some(RESULT_KW) => (RESULT_TYPE, "result type"),
+            Some(VEC_KW) => (VEC_TYPE, "vec type"),
+            Some(SET_KW) => (SET_TYPE, "set type"),
+            Some(MAP_KW) => (MAP_TYPE, "map type"),
+            Some(ARRAY_KW) => (ARRAY_TYPE, "array type"),
+            Some(BOX_KW) => (BOX_TYPE, "box type"),
+            _ => (GENERIC_TYPE, "generic type"),
+        };
+        self.start(kind);
+        self.bump();
+        match keyword {
+            Some(keyword) => self.bump_as(keyword),
+            None => self.name_ref(),
+        }
+        match kind {
+            OPTION_TYPE => self.type_expr("the optional type"),
+            BOX_TYPE => self.type_expr("the boxed type"),
+            VEC_TYPE | SET_TYPE => self.type_expr("the element type"),
+            RESULT_TYPE => {
+                self.type_expr("the success type");
+                self.type_expr("the error type");
+            }
+            MAP_TYPE => {
+                self.type_expr("the key type");
+                self.type_expr("the value type");
+            }
+            ARRAY_TYPE => {
+                if self.at(INT) {
+                    self.bump();
+                } else {
+                    self.error("expected the array length");
+                }
+                self.type_expr("the element type");
+            }
+            _ => {
+                while self.at_type_start() {
+                    self.type_expr("a type argument");
+                }
+            }
+        }
+        self.close_form(form);
+        self.finish();
+    }
+
+    /// `module name {...}`, or `(module name {...})` in aski/v1.
+    fn module_decl(&mut self) {
+        self.start(MODULE_DECL);
+        if !self.capitalized {
+            self.bump();
+        }
+        self.bump_as(MODULE_KW);
+        self.name();
+        if self.at(L_BRACE) {
+            self.module_options();
+        } else {
+            self.error("expected `{` to start the module options");
+        }
+        if !self.capitalized {
+            self.close_form("module declaration");
+        }
+        self.finish();
+    }
+
+    fn module_options(&mut self) {
+        self.start(MODULE_OPTIONS);
+        self.bump();
+        while !self.at_list_end() {
+            if self.at(SYMBOL) {
+                self.module_option();
+            } else {
+                self.error_element("expected an option name");
+            }
+        }
+        self.expect_closing(R_BRACE, "module options");
+        self.finish();
+    }
+
+    fn module_option(&mut self) {
+        self.start(MODULE_OPTION);
+        self.name();
+        match self.nth_kind(0) {
+            Some(SYMBOL | STRING | INT) => self.bump(),
+            Some(L_PAREN) => self.name_list(),
+            _ => {
+                let message = format!(
+                    "expected a value for the option, found {}",
+                    self.describe_current()
+                );
+                self.error(message);
+            }
+        }
+        self.finish();
+    }
+
+    fn name_list(&mut self) {
+        self.start(NAME_LIST);
+        self.bump();
+        while self.at(SYMBOL) {
+            self.name_ref();
+        }
+        self.close_form("name list");
+        self.finish();
+    }
+
+    /// `impl Trait Type {...}`, or `(impl trait type {...})` in aski/v1.
+    fn impl_decl(&mut self) {
+        self.start(IMPL_DECL);
+        if !self.capitalized {
+            self.bump();
+        }
+        self.bump_as(IMPL_KW);
+        if self.at(SYMBOL) {
+            self.name_ref();
+        } else {
+            self.error("expected a trait name");
+        }
+        self.type_expr("the implementing type");
+        if self.at(L_BRACE) {
+            self.bump();
+            while !self.at_list_end() {
+                if self.at(SYMBOL) {
+                    self.impl_member();
+                } else {
+                    self.error_element("expected a member name");
+                }
+            }
+            self.expect_closing(R_BRACE, "impl members");
+        } else {
+            self.error("expected `{` to start the impl members");
+        }
+        if !self.capitalized {
+            self.close_form("impl declaration");
+        }
+        self.finish();
+    }
+
+    fn impl_member(&mut self) {
+        self.start(IMPL_MEMBER);
+        self.name();
+        if self.at(L_BRACK) {
+            self.bump();
+            while !self.at_list_end() {
+                let head = self.nth(1).filter(|token| token.kind == SYMBOL);
+                match head.map(|token| token.text) {
+                    Some(TYPE_SIGNATURE_HEAD) if self.at(L_PAREN) => self.type_signature(),
+                    Some(FUNCTION_BODY_HEAD) if self.at(L_PAREN) => self.function_body(),
+                    _ => self
+                        .error_element("expected `(type-signature ...)` or `(function-body ...)`"),
+                }
+            }
+            self.expect_closing(R_BRACK, "the member definition");
+        } else {
+            self.error("expected `[` to start the member definition");
+        }
+        self.finish();
+    }
+
+    /// `(type-signature [param ...] return)`, or `(type-signature TODO)`.
+    fn type_signature(&mut self) {
+        self.start(TYPE_SIGNATURE);
+        self.bump();
+        self.bump();
+        if self.at_todo() {
+            self.bump();
+        } else {
+            if self.at(L_BRACK) {
+                self.start(PARAM_LIST);
+                self.bump();
+                while !self.at_list_end() {
+                    if self.at_type_start() {
+                        self.type_expr("a parameter type");
+                    } else {
+                        let message = format!(
+                            "expected a parameter type, found {}",
+                            self.describe_current()
+                        );
+                        self.error_element(message);
+                    }
+                }
+                self.expect_closing(R_BRACK, "the parameter types");
+                self.finish();
+            } else {
+                self.error("expected `[` to start the parameter types, or `TODO`");
+            }
+            self.type_expr("the return type");
+        }
+        self.close_form("type signature");
+        self.finish();
+    }
+
+    /// `(function-body "...")`, or `(function-body TODO)`.
+    fn function_body(&mut self) {
+        self.start(FUNCTION_BODY);
+        self.bump();
+        self.bump();
+        if self.at(STRING) || self.at_todo() {
+            self.bump();
+        } else {
+            let message = format!(
+                "expected the function body as a string, or `TODO`, found {}",
+                self.describe_current()
+            );
+            self.error(message);
+        }
+        self.close_form("function body");
+        self.finish();
+    }
+
+    /// Whether `TODO` stands here for a part of a member still to be written.
+    fn at_todo(&self) -> bool {
+        self.nth(0)
+            .is_some_and(|token| token.kind == SYMBOL && token.text == TODO)
+    }
+}
@@ -0,0 +1,223 @@
+// The capitalized dialect sketched in encoder/examples/prototype-design.aski.
+//
+// A document is a sequence of top-level forms, with no version form around them. A
+// capitalized name followed by a body defines a type, and the body's delimiter says which
+// kind: `Name {field Type ...}` is a record, `Name [Variant ...]` an enum, `Name (Type)` a
+// newtype and `Name (Type Type ...)` a tuple. A variant is a bare name, or a name followed
+// by `(Type ...)` or `{field Type ...}` the same way. Generic definitions name themselves
+// `(Name P ...)`, type constructors are capitalized, `(Vec T)`, `(Option T)`, and `[A B]`
+// is a tuple type.
+//
+// The parens around a payload are the tuple's own, so a one-element tuple has no spelling
+// of its own: `(Type)` always wraps. A type constructor needs no parens around it, since
+// `Batch (Vec Message)` cannot mean anything but the one type, but a generic type does:
+// `Page ((Paged User))` wraps one, where `Page (Paged User)` holds two.
+//
+// Definitions parse into the same nodes as aski/v1 declarations, so the typed AST and
+// lowering read both dialects alike; only the spelling of names differs. The other two
+// forms, `module name {option value ...}` and `impl Trait Type {member [...] ...}`, have
+// nodes of their own, shared with aski/v1, where they are written in parens.
+
+use super::{Parse, Parser};
+use crate::lexer;
+use crate::SyntaxKind::{self, *};
+
+/// Parses a document in the capitalized dialect.
+pub fn parse_capitalized(text: &str) -> Parse {
+    let (tokens, lex_errors) = lexer::lex(text);
+    let mut parser = Parser::new(tokens, lex_errors);
+    parser.capitalized = true;
+    parser.capitalized_file();
+    parser.into_parse()
+}
+
+/// Whether `text` names a type in the capitalized dialect.
+fn is_type_name(text: &str) -> bool {
+    text.chars().next().is_some_and(char::is_uppercase)
+}
+
+impl Parser<'_> {
+    /// Whether the token at `index` starts a definition: a type name followed by its body,
+    /// a generic type name, or a `module` or `impl` form.
+    pub(super) fn starts_definition(&self, index: usize) -> bool {
+        let token = self.tokens[index];
+        let next = self.tokens[index + 1..]
+            .iter()
+            .find(|token| !token.kind.is_trivia());
+        match token.kind {
+            SYMBOL if matches!(token.text, "module" | "impl") => {
+                next.is_some_and(|next| next.kind == SYMBOL)
+            }
+            SYMBOL => {
+                is_type_name(token.text)
+                    && next.is_some_and(|next| next.kind.is_opening_delimiter())
+            }
+            L_PAREN => next.is_some_and(|next| {
+                next.kind == SYMBOL
+                    && is_type_name(next.text)
+                    && SyntaxKind::from_capitalized_type_keyword(next.text).is_none()
+            }),
+            _ => false,
+        }
+    }
+
+    /// The kinds of the significant tokens from the current one on, short of a
+    /// declaration the one being parsed cannot reach past.
+    fn significant(&self) -> impl Iterator<Item = SyntaxKind> + '_ {
+        (self.pos..self.tokens.len())
+            .filter(|&index| !self.tokens[index].kind.is_trivia())
+            .take_while(|&index| !self.ends_decl(index))
+            .map(|index| self.tokens[index].kind)
+    }
+
+    /// How many forms the delimited form at the `n`th significant token holds.
+    fn forms_in(&self, n: usize) -> usize {
+        let mut depth = 0;
+        let mut forms = 0;
+        for kind in self.significant().skip(n + 1) {
+            if depth == 0 {
+                if kind.is_closing_delimiter() {
+                    break;
+                }
+                forms += 1;
+            }
+            if kind.is_opening_delimiter() {
+                depth += 1;
+            } else if kind.is_closing_delimiter() {
+                depth -= 1;
+            }
+        }
+        forms
+    }
+
+    /// Whether the `n`th significant token opens a type constructor, which is a payload
+    /// without parens around it.
+    fn at_payload_type(&self, n: usize) -> bool {
+        self.nth_kind(n) == Some(L_PAREN)
+            && self.nth(n + 1).is_some_and(|head| {
+                head.kind == SYMBOL
+                    && SyntaxKind::from_capitalized_type_keyword(head.text).is_some()
+            })
+    }
+
+    /// Whether the current token is the reserved word `keyword` heading its form.
+    fn at_reserved(&self, keyword: &str) -> bool {
+        self.nth(0)
+            .is_some_and(|token| token.kind == SYMBOL && token.text == keyword)
+            && self.nth_kind(1) == Some(SYMBOL)
+    }
+
+    /// Whether a type name, `Name` or `(Name P ...)`, starts here.
+    fn at_type_name(&self) -> bool {
+        let name = if self.at(L_PAREN) {
+            self.nth(1)
+        } else {
+            self.nth(0)
+        };
+        name.is_some_and(|token| token.kind == SYMBOL && is_type_name(token.text))
+    }
+
+    // --- grammar ---
+
+    fn capitalized_file(&mut self) {
+        self.builder.start_node(SOURCE_FILE);
+        while !self.at_eof() {
+            if self.at_reserved("module") || self.at_reserved("impl") || self.at_type_name() {
+                self.definition();
+            } else {
+                self.error_element(
+                    "expected a type definition like `Name {...}`, or `module` or `impl`",
+                );
+                if self.at_list_end() && !self.at_eof() {
+                    // A stray closer at top level; there is nothing for it to close.
+                    self.start(ERROR);
+                    self.bump();
+                    self.finish();
+                }
+            }
+        }
+        self.skip_trivia();
+        self.finish();
+    }
+
+    fn definition(&mut self) {
+        let outer = self.decl;
+        if let Some((start, _)) = self.lookahead(0) {
+            self.decl = Some((start, self.line_position(start).0));
+        }
+        if self.at_reserved("module") {
+            self.module_decl();
+        } else if self.at_reserved("impl") {
+            self.impl_decl();
+        } else {
+            self.type_definition();
+        }
+        self.decl = outer;
+    }
+
+    fn type_definition(&mut self) {
+        self.skip_trivia();
+        let checkpoint = self.builder.checkpoint();
+        self.decl_name();
+        let kind = match self.nth_kind(0) {
+            Some(L_BRACE) => RECORD_DECL,
+            Some(L_BRACK) => ENUM_DECL,
+            Some(L_PAREN) if self.at_payload_type(0) || self.forms_in(0) == 1 => NEWTYPE_DECL,
+            Some(L_PAREN) => TUPLE_DECL,
+            _ => ERROR,
+        };
+        self.builder.start_node_at(checkpoint, kind);
+        match kind {
+            RECORD_DECL => self.field_list(),
+            ENUM_DECL => self.variant_list(),
+            NEWTYPE_DECL => self.payload("newtype definition"),
+            TUPLE_DECL => self.type_list(TUPLE_FIELD_LIST),
+            _ => self.error("expected `{...}`, `[...]` or `(...)` after the type name"),
+        }
+        self.finish();
+    }
+
+    /// `(T)`, or a type constructor like `(Vec T)`: the one type a newtype definition or
+    /// variant wraps.
+    fn payload(&mut self, form: &str) {
+        if self.at_payload_type(0) {
+            self.type_expr("the wrapped type");
+            return;
+        }
+        self.bump();
+        self.type_expr("the wrapped type");
+        self.close_form(form);
+    }
+
+    fn variant_list(&mut self) {
+        self.bump();
+        while !self.at_list_end() {
+            if self.at(SYMBOL) {
+                self.capitalized_variant();
+            } else {
+                self.error_element(
+                    "expected a variant like `Name`, `Name (T)` or `Name {field T}`",
+                );
+            }
+        }
+        self.expect_closing(R_BRACK, "variant list");
+    }
+
+    fn capitalized_variant(&mut self) {
+        let kind = match self.nth_kind(1) {
+            Some(L_BRACE) => STRUCT_VARIANT,
+            Some(L_PAREN) if self.at_payload_type(1) || self.forms_in(1) == 1 => NEWTYPE_VARIANT,
+            Some(L_PAREN) => TUPLE_VARIANT,
+            _ => UNIT_VARIANT,
+        };
+        self.start(kind);
+        self.name();
+        match kind {
+            STRUCT_VARIANT => self.field_list(),
+            NEWTYPE_VARIANT => self.payload("variant"),
+            TUPLE_VARIANT => self.type_list(TUPLE_FIELD_LIST),
+            _ => {}
+        }
+        self.finish();
+    }
+}
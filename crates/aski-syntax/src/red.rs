@@ -0,0 +1,416 @@
+// Positioned view over a green tree ("red" tree).
+//
+// A `SyntaxNode` is a green node plus its absolute offset and a link to its parent, built
+// lazily while walking down from the root. Red nodes are cheap, thread-local handles;
+// anything that needs to be cached or sent across threads should hold the green tree.
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+
+use crate::green::{GreenElement, GreenNode, GreenToken};
+use crate::{SyntaxKind, TextRange};
+
+#[derive(Clone)]
+pub struct SyntaxNode(Rc<NodeData>);
+
+struct NodeData {
+    green: GreenNode,
+    parent: Option<SyntaxNode>,
+    index: usize,
+    offset: usize,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct SyntaxToken {
+    parent: SyntaxNode,
+    green: GreenToken,
+    index: usize,
+    offset: usize,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+/// Entering or leaving an element during a tree walk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WalkEvent<T> {
+    Enter(T),
+    Leave(T),
+}
+
+/// What lies at a byte offset: nothing, one token, or the two tokens it separates.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TokenAtOffset {
+    None,
+    Single(SyntaxToken),
+    Between(SyntaxToken, SyntaxToken),
+}
+
+impl SyntaxNode {
+    pub fn new_root(green: GreenNode) -> SyntaxNode {
+        SyntaxNode(Rc::new(NodeData {
+            green,
+            parent: None,
+            index: 0,
+            offset: 0,
+        }))
+    }
+
+    fn new_child(green: GreenNode, parent: SyntaxNode, index: usize, offset: usize) -> SyntaxNode {
+        SyntaxNode(Rc::new(NodeData {
+            green,
+            parent: Some(parent),
+            index,
+            offset,
+        }))
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.0.green.kind()
+    }
+
+    pub fn green(&self) -> &GreenNode {
+        &self.0.green
+    }
+
+    pub fn text_range(&self) -> TextRange {
+        TextRange::at(self.0.offset, self.0.green.text_len())
+    }
+
+    /// The source text covered by this node, trivia included.
+    pub fn text(&self) -> String {
+        self.0.green.to_string()
+    }
+
+    /// Position of this node among its parent's children, tokens included.
+    pub fn index(&self) -> usize {
+        self.0.index
+    }
+
+    pub fn parent(&self) -> Option<SyntaxNode> {
+        self.0.parent.clone()
+    }
+
+    /// This node followed by all of its ancestors, innermost first.
+    pub fn ancestors(&self) -> impl Iterator<Item = SyntaxNode> {
+        std::iter::successors(Some(self.clone()), SyntaxNode::parent)
+    }
+
+    pub fn children_with_tokens(&self) -> impl Iterator<Item = SyntaxElement> {
+        let parent = self.clone();
+        let mut offset = self.0.offset;
+        let children = self.0.green.children().to_vec();
+        children.into_iter().enumerate().map(move |(index, green)| {
+            let element = match green {
+                GreenElement::Node(green) => {
+                    SyntaxElement::Node(SyntaxNode::new_child(green, parent.clone(), index, offset))
+                }
+                GreenElement::Token(green) => SyntaxElement::Token(SyntaxToken {
+                    parent: parent.clone(),
+                    green,
+                    index,
+                    offset,
+                }),
+            };
+            offset += element.text_range().len();
+            element
+        })
+    }
+
+    pub fn children(&self) -> impl Iterator<Item = SyntaxNode> {
+        self.children_with_tokens()
+            .filter_map(SyntaxElement::into_node)
+    }
+
+    pub fn first_child(&self) -> Option<SyntaxNode> {
+        self.children().next()
+    }
+
+    pub fn first_token(&self) -> Option<SyntaxToken> {
+        self.children_with_tokens()
+            .find_map(|element| match element {
+                SyntaxElement::Token(token) => Some(token),
+                SyntaxElement::Node(node) => node.first_token(),
+            })
+    }
+
+    pub fn last_token(&self) -> Option<SyntaxToken> {
+        let children: Vec<_> = self.children_with_tokens().collect();
+        children
+            .into_iter()
+            .rev()
+            .find_map(|element| match element {
+                SyntaxElement::Token(token) => Some(token),
+                SyntaxElement::Node(node) => node.last_token(),
+            })
+    }
+
+    pub fn next_sibling(&self) -> Option<SyntaxNode> {
+        let parent = self.parent()?;
+        let index = self.index();
+        parent.children().find(|sibling| sibling.index() > index)
+    }
+
+    pub fn prev_sibling(&self) -> Option<SyntaxNode> {
+        let parent = self.parent()?;
+        let index = self.index();
+        parent
+            .children()
+            .take_while(|sibling| sibling.index() < index)
+            .last()
+    }
+
+    /// This node and every node below it, in preorder.
+    pub fn descendants(&self) -> impl Iterator<Item = SyntaxNode> {
+        self.descendants_with_tokens()
+            .filter_map(SyntaxElement::into_node)
+    }
+
+    /// This node and every node and token below it, in preorder.
+    pub fn descendants_with_tokens(&self) -> impl Iterator<Item = SyntaxElement> {
+        let mut stack = vec![SyntaxElement::Node(self.clone())];
+        std::iter::from_fn(move || {
+            let element = stack.pop()?;
+            if let SyntaxElement::Node(node) = &element {
+                let children: Vec<_> = node.children_with_tokens().collect();
+                stack.extend(children.into_iter().rev());
+            }
+            Some(element)
+        })
+    }
+
+    /// Walks the subtree, reporting both entry into and exit from every element.
+    pub fn preorder_with_tokens(&self) -> impl Iterator<Item = WalkEvent<SyntaxElement>> {
+        let mut stack = vec![WalkEvent::Enter(SyntaxElement::Node(self.clone()))];
+        std::iter::from_fn(move || {
+            let event = stack.pop()?;
+            if let WalkEvent::Enter(element) = &event {
+                stack.push(WalkEvent::Leave(element.clone()));
+                if let SyntaxElement::Node(node) = element {
+                    let children: Vec<_> = node.children_with_tokens().collect();
+                    stack.extend(children.into_iter().rev().map(WalkEvent::Enter));
+                }
+            }
+            Some(event)
+        })
+    }
+
+    /// The token(s) touching `offset`.
+    ///
+    /// An offset on the boundary between two tokens reports both, leaving it to the caller
+    /// to decide which side it cares about.
+    pub fn token_at_offset(&self, offset: usize) -> TokenAtOffset {
+        let range = self.text_range();
+        if !range.contains_inclusive(offset) {
+            return TokenAtOffset::None;
+        }
+        let mut touching = self
+            .descendants_with_tokens()
+            .filter_map(SyntaxElement::into_token)
+            .filter(|token| token.text_range().contains_inclusive(offset))
+            .filter(|token| !token.text_range().is_empty());
+        match (touching.next(), touching.next()) {
+            (Some(left), Some(right)) => TokenAtOffset::Between(left, right),
+            (Some(token), None) => TokenAtOffset::Single(token),
+            _ => TokenAtOffset::None,
+        }
+    }
+
+    /// The innermost node or token that fully contains `range`.
+    pub fn covering_element(&self, range: TextRange) -> SyntaxElement {
+        let mut current = SyntaxElement::Node(self.clone());
+        loop {
+            let SyntaxElement::Node(node) = &current else {
+                return current;
+            };
+            let child = node.children_with_tokens().find(|child| {
+                let child_range = child.text_range();
+                child_range.contains_range(range)
+                    && !(range.is_empty() && child_range.end() == range.start())
+            });
+            match child {
+                Some(child) => current = child,
+                None => return current,
+            }
+        }
+    }
+
+    /// A copy of the whole tree with this node's green subtree swapped for `replacement`.
+    ///
+    /// Only the path from this node up to the root is rebuilt.
+    pub fn replace_with(&self, replacement: GreenNode) -> GreenNode {
+        match self.parent() {
+            None => replacement,
+            Some(parent) => {
+                let new_parent = parent
+                    .green()
+                    .replace_child(self.index(), replacement.into());
+                parent.replace_with(new_parent)
+            }
+        }
+    }
+}
+
+impl PartialEq for SyntaxNode {
+    fn eq(&self, other: &SyntaxNode) -> bool {
+        self.0.green.ptr_eq(&other.0.green) && self.0.offset == other.0.offset
+    }
+}
+
+impl Eq for SyntaxNode {}
+
+impl Hash for SyntaxNode {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.kind().hash(state);
+        self.0.offset.hash(state);
+    }
+}
+
+impl fmt::Display for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0.green, f)
+    }
+}
+
+impl fmt::Debug for SyntaxNode {
+    /// `{:?}` prints the node header; `{:#?}` dumps the whole subtree, one element per line.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !f.alternate() {
+            return write!(f, "{:?}@{:?}", self.kind(), self.text_range());
+        }
+        let mut depth = 0;
+        for event in self.preorder_with_tokens() {
+            match event {
+                WalkEvent::Enter(element) => {
+                    write!(f, "{:indent$}", "", indent = depth * 2)?;
+                    match element {
+                        SyntaxElement::Node(node) => writeln!(f, "{node:?}")?,
+                        SyntaxElement::Token(token) => writeln!(f, "{token:?}")?,
+                    }
+                    depth += 1;
+                }
+                WalkEvent::Leave(_) => depth -= 1,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl SyntaxToken {
+    pub fn kind(&self) -> SyntaxKind {
+        self.green.kind()
+    }
+
+    pub fn text(&self) -> &str {
+        self.green.text()
+    }
+
+    pub fn green(&self) -> &GreenToken {
+        &self.green
+    }
+
+    pub fn text_range(&self) -> TextRange {
+        TextRange::at(self.offset, self.green.text_len())
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn parent(&self) -> SyntaxNode {
+        self.parent.clone()
+    }
+
+    /// The token's parent followed by all of its ancestors.
+    pub fn ancestors(&self) -> impl Iterator<Item = SyntaxNode> {
+        self.parent.ancestors()
+    }
+
+    pub fn next_token(&self) -> Option<SyntaxToken> {
+        let root = self.parent.ancestors().last()?;
+        let end = self.text_range().end();
+        root.descendants_with_tokens()
+            .filter_map(SyntaxElement::into_token)
+            .find(|token| {
+                token.text_range().start() >= end && token != self && !token.text_range().is_empty()
+            })
+    }
+
+    pub fn prev_token(&self) -> Option<SyntaxToken> {
+        let root = self.parent.ancestors().last()?;
+        let start = self.text_range().start();
+        root.descendants_with_tokens()
+            .filter_map(SyntaxElement::into_token)
+            .take_while(|token| token.text_range().end() <= start && token != self)
+            .filter(|token| !token.text_range().is_empty())
+            .last()
+    }
+}
+
+impl fmt::Display for SyntaxToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.text())
+    }
+}
+
+impl fmt::Debug for SyntaxToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?}@{:?} {:?}",
+            self.kind(),
+            self.text_range(),
+            self.text()
+        )
+    }
+}
+
+impl SyntaxElement {
+    pub fn kind(&self) -> SyntaxKind {
+        match self {
+            SyntaxElement::Node(node) => node.kind(),
+            SyntaxElement::Token(token) => token.kind(),
+        }
+    }
+
+    pub fn text_range(&self) -> TextRange {
+        match self {
+            SyntaxElement::Node(node) => node.text_range(),
+            SyntaxElement::Token(token) => token.text_range(),
+        }
+    }
+
+    pub fn parent(&self) -> Option<SyntaxNode> {
+        match self {
+            SyntaxElement::Node(node) => node.parent(),
+            SyntaxElement::Token(token) => Some(token.parent()),
+        }
+    }
+
+    pub fn into_node(self) -> Option<SyntaxNode> {
+        match self {
+            SyntaxElement::Node(node) => Some(node),
+            SyntaxElement::Token(_) => None,
+        }
+    }
+
+    pub fn into_token(self) -> Option<SyntaxToken> {
+        match self {
+            SyntaxElement::Node(_) => None,
+            SyntaxElement::Token(token) => Some(token),
+        }
+    }
+}
+
+impl fmt::Debug for SyntaxElement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SyntaxElement::Node(node) => fmt::Debug::fmt(node, f),
+            SyntaxElement::Token(token) => fmt::Debug::fmt(token, f),
+        }
+    }
+}
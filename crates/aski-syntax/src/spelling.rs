@@ -0,0 +1,81 @@
+// Spelling names between kebab-case and the capitalized forms.
+//
+// aski/v1 writes every name in kebab-case. The capitalized dialect writes type and variant
+// names in PascalCase and field names in camelCase, which are also how Rust spells them,
+// so the code generator maps names with the same functions. On kebab-case names the
+// mapping is reversible:
+//
+//   `user-id`     <-> `UserId`  <-> `userId`
+//
+// A word starting with a digit has no capital letter to mark where it begins, so it is
+// joined with an underscore instead: `len-3` <-> `Len_3` <-> `len_3`.
+
+/// `user-id` -> `UserId`, `len-3` -> `Len_3`.
+pub fn pascal_case(name: &str) -> String {
+    let mut out = String::new();
+    for word in words(name) {
+        let mut chars = word.chars();
+        let first = chars.next().expect("words are not empty");
+        if first.is_ascii_digit() && !out.is_empty() {
+            out.push('_');
+        }
+        out.extend(first.to_uppercase());
+        out.extend(chars);
+    }
+    out
+}
+
+/// `first-name` -> `firstName`, `len-3` -> `len_3`.
+pub fn camel_case(name: &str) -> String {
+    let mut out = String::new();
+    for word in words(name) {
+        if out.is_empty() {
+            out.push_str(word);
+            continue;
+        }
+        let mut chars = word.chars();
+        let first = chars.next().expect("words are not empty");
+        if first.is_ascii_digit() {
+            out.push('_');
+        }
+        out.extend(first.to_uppercase());
+        out.extend(chars);
+    }
+    out
+}
+
+/// `UserId` -> `user-id`, `firstName` -> `first-name`, `Len_3` -> `len-3`,
+/// `HTTPServer` -> `http-server`.
+pub fn kebab_case(ident: &str) -> String {
+    let words: Vec<String> = ident
+        .split('_')
+        .filter(|part| !part.is_empty())
+        .flat_map(split_camel_case)
+        .collect();
+    words.join("-")
+}
+
+fn words(name: &str) -> impl Iterator<Item = &str> {
+    name.split(['-', '_']).filter(|word| !word.is_empty())
+}
+
+/// Splits `HTTPServer2Go` into `http`, `server2`, `go`.
+fn split_camel_case(ident: &str) -> Vec<String> {
+    let chars: Vec<char> = ident.chars().collect();
+    let mut words = Vec::new();
+    let mut word = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() && !word.is_empty() {
+            let prev_is_upper = chars[i - 1].is_uppercase();
+            let next_is_lower = chars.get(i + 1).is_some_and(|next| next.is_lowercase());
+            if !prev_is_upper || next_is_lower {
+                words.push(std::mem::take(&mut word));
+            }
+        }
+        word.extend(c.to_lowercase());
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
@@ -0,0 +1,209 @@
+// Every token and node kind of the aski concrete syntax tree, for both dialects.
+//
+// Token kinds come straight out of the lexer, except for the `*_KW` kinds: the lexer
+// only knows symbols, and the parser remaps a symbol to a keyword kind when it sits in
+// the head position of a form that gives it meaning (`record`, `vec`, `?`, ...).
+// Keywords are contextual, so `unit` or `map` stay usable as ordinary names elsewhere.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[allow(non_camel_case_types)]
+#[repr(u16)]
+pub enum SyntaxKind {
+    // trivia
+    WHITESPACE,
+    COMMENT,
+
+    // punctuation
+    L_PAREN,
+    R_PAREN,
+    L_BRACK,
+    R_BRACK,
+    L_BRACE,
+    R_BRACE,
+    COLON,
+
+    // atoms
+    SYMBOL,
+    INT,
+    FLOAT,
+    STRING,
+    /// `#uuid`: a tag, telling how the value after it is read.
+    TAG,
+
+    // contextual keywords
+    NEWTYPE_KW,
+    RECORD_KW,
+    TUPLE_KW,
+    ENUM_KW,
+    OPTION_KW,
+    RESULT_KW,
+    VEC_KW,
+    SET_KW,
+    MAP_KW,
+    ARRAY_KW,
+    BOX_KW,
+    MODULE_KW,
+    IMPL_KW,
+
+    /// A byte sequence the lexer could not make sense of.
+    ERROR_TOKEN,
+
+    // nodes
+    SOURCE_FILE,
+    SCHEMA,
+    NEWTYPE_DECL,
+    RECORD_DECL,
+    TUPLE_DECL,
+    ENUM_DECL,
+
+    /// `(wrapped T)` in declaration-name position.
+    GENERIC_NAME,
+    TYPE_PARAM,
+    NAME,
+    NAME_REF,
+
+    FIELD_LIST,
+    FIELD,
+    TUPLE_FIELD_LIST,
+
+    UNIT_VARIANT,
+    NEWTYPE_VARIANT,
+    TUPLE_VARIANT,
+    STRUCT_VARIANT,
+
+    NAMED_TYPE,
+    GENERIC_TYPE,
+    OPTION_TYPE,
+    RESULT_TYPE,
+    VEC_TYPE,
+    SET_TYPE,
+    MAP_TYPE,
+    ARRAY_TYPE,
+    BOX_TYPE,
+    TUPLE_TYPE,
+    /// A string literal in type position, standing for `string`.
+    LITERAL_TYPE,
+
+    /// `(default value)` after a record field's type.
+    DEFAULT_VALUE,
+    /// A number, a string, `true`, `false` or `none`.
+    LITERAL_VALUE,
+    /// `[value ...]`.
+    LIST_VALUE,
+    /// `{key value ...}`, or `{field: value ...}` for a record.
+    MAP_VALUE,
+    MAP_ENTRY,
+    FIELD_VALUE,
+    /// `(type variant payload)`, constructing an enum value.
+    VARIANT_VALUE,
+    /// `#tag value`, like `#uuid "..."` or `#ok 1`.
+    TAGGED_VALUE,
+
+    /// `module name {option value ...}`.
+    MODULE_DECL,
+    MODULE_OPTIONS,
+    MODULE_OPTION,
+    /// `(name ...)` as the value of a module option.
+    NAME_LIST,
+    /// `impl Trait Type {member [...] ...}`.
+    IMPL_DECL,
+    IMPL_MEMBER,
+    /// `(type-signature [param ...] return)`.
+    TYPE_SIGNATURE,
+    PARAM_LIST,
+    /// `(function-body "...")`.
+    FUNCTION_BODY,
+
+    /// Input the parser skipped over while recovering.
+    ERROR,
+}
+
+use SyntaxKind::*;
+
+impl SyntaxKind {
+    pub fn is_trivia(self) -> bool {
+        matches!(self, WHITESPACE | COMMENT)
+    }
+
+    pub fn is_keyword(self) -> bool {
+        matches!(
+            self,
+            NEWTYPE_KW
+                | RECORD_KW
+                | TUPLE_KW
+                | ENUM_KW
+                | OPTION_KW
+                | RESULT_KW
+                | VEC_KW
+                | SET_KW
+                | MAP_KW
+                | ARRAY_KW
+                | BOX_KW
+                | MODULE_KW
+                | IMPL_KW
+        )
+    }
+
+    pub fn is_opening_delimiter(self) -> bool {
+        matches!(self, L_PAREN | L_BRACK | L_BRACE)
+    }
+
+    pub fn is_closing_delimiter(self) -> bool {
+        matches!(self, R_PAREN | R_BRACK | R_BRACE)
+    }
+
+    /// The closing delimiter matching an opening one.
+    pub fn closing(self) -> Option<SyntaxKind> {
+        match self {
+            L_PAREN => Some(R_PAREN),
+            L_BRACK => Some(R_BRACK),
+            L_BRACE => Some(R_BRACE),
+            _ => None,
+        }
+    }
+
+    /// Keyword kind for a symbol in declaration-head position.
+    pub fn from_decl_keyword(text: &str) -> Option<SyntaxKind> {
+        let kind = match text {
+            "newtype" => NEWTYPE_KW,
+            "record" => RECORD_KW,
+            "tuple" => TUPLE_KW,
+            "enum" => ENUM_KW,
+            "module" => MODULE_KW,
+            "impl" => IMPL_KW,
+            _ => return None,
+        };
+        Some(kind)
+    }
+
+    /// Keyword kind for a symbol in type-constructor-head position.
+    pub fn from_type_keyword(text: &str) -> Option<SyntaxKind> {
+        let kind = match text {
+            "?" => OPTION_KW,
+            "result" => RESULT_KW,
+            "vec" => VEC_KW,
+            "set" => SET_KW,
+            "map" => MAP_KW,
+            "array" => ARRAY_KW,
+            "box" => BOX_KW,
+            _ => return None,
+        };
+        Some(kind)
+    }
+
+    /// Keyword kind for a symbol in type-constructor-head position in the capitalized
+    /// dialect.
+    pub fn from_capitalized_type_keyword(text: &str) -> Option<SyntaxKind> {
+        let kind = match text {
+            "Option" => OPTION_KW,
+            "Result" => RESULT_KW,
+            "Vec" => VEC_KW,
+            "Set" => SET_KW,
+            "Map" => MAP_KW,
+            "Array" => ARRAY_KW,
+            "Box" => BOX_KW,
+            _ => return None,
+        };
+        Some(kind)
+    }
+}
@@ -0,0 +1,194 @@
+use aski_syntax::ast::{AstNode, Decl, HasName, HasTypeParams, TypeExpr, Value, Variant};
+use aski_syntax::{parse, SyntaxKind};
+
+const ALL_TYPES: &str = include_str!("../../../encoder/drafts/all-types.aski");
+
+fn names<N: HasName>(nodes: impl Iterator<Item = N>) -> Vec<String> {
+    nodes
+        .map(|node| node.name().map(|name| name.text()).unwrap_or_default())
+        .collect()
+}
+
+#[test]
+fn declarations_expose_their_parts() {
+    let schema = parse(ALL_TYPES).tree().schema().unwrap();
+    assert_eq!(schema.version().unwrap().text(), "aski/v1");
+
+    let decls: Vec<Decl> = schema.decls().collect();
+    assert_eq!(
+        names(decls.iter().cloned()),
+        [
+            "user-id",
+            "blob",
+            "wrapped",
+            "unit-struct",
+            "pair",
+            "error-code",
+            "shape",
+            "message",
+            "status",
+            "all-types",
+        ]
+    );
+
+    let Decl::Newtype(wrapped) = &decls[2] else {
+        panic!("expected a newtype");
+    };
+    let params: Vec<_> = wrapped.type_params().map(|param| param.text()).collect();
+    assert_eq!(params, ["T"]);
+
+    let Decl::Tuple(pair) = &decls[4] else {
+        panic!("expected a tuple");
+    };
+    assert_eq!(pair.fields().count(), 2);
+
+    let Decl::Enum(shape) = &decls[6] else {
+        panic!("expected an enum");
+    };
+    let variants: Vec<Variant> = shape.variants().collect();
+    assert!(matches!(variants[0], Variant::Unit(_)));
+    let Variant::Struct(circle) = &variants[1] else {
+        panic!("expected a struct variant");
+    };
+    assert_eq!(names(circle.fields()), ["r"]);
+    let Variant::Tuple(rect) = &variants[2] else {
+        panic!("expected a tuple variant");
+    };
+    assert_eq!(rect.fields().count(), 2);
+    assert!(matches!(variants[3], Variant::Newtype(_)));
+}
+
+#[test]
+fn type_expressions_are_typed() {
+    let schema = parse(ALL_TYPES).tree().schema().unwrap();
+    let Some(Decl::Record(all_types)) = schema.decls().last() else {
+        panic!("expected a record");
+    };
+    let field_type = |name: &str| {
+        all_types
+            .fields()
+            .find(|field| field.name().unwrap().text() == name)
+            .and_then(|field| field.ty())
+            .unwrap()
+    };
+
+    let TypeExpr::Array(array) = field_type("u16-array-len-3") else {
+        panic!("expected an array type");
+    };
+    assert_eq!(array.length(), Some(3));
+    assert!(matches!(array.element(), Some(TypeExpr::Named(_))));
+
+    let TypeExpr::Map(map) = field_type("user-id-to-i64-map") else {
+        panic!("expected a map type");
+    };
+    let Some(TypeExpr::Named(key)) = map.key() else {
+        panic!("expected a named key type");
+    };
+    assert_eq!(key.name_ref().unwrap().text(), "user-id");
+
+    let TypeExpr::Generic(wrapped) = field_type("wrapped-pair") else {
+        panic!("expected a generic type");
+    };
+    assert_eq!(wrapped.name_ref().unwrap().text(), "wrapped");
+    assert_eq!(wrapped.args().count(), 1);
+
+    assert!(matches!(field_type("mixed-tuple"), TypeExpr::Tuple(t) if t.elements().count() == 3));
+    assert!(matches!(field_type("maybe-i64-value"), TypeExpr::Option(_)));
+    assert!(matches!(
+        field_type("outcome-string-or-error-code"),
+        TypeExpr::Result(_)
+    ));
+}
+
+#[test]
+fn impl_members_have_signatures_and_bodies() {
+    let text = r#"(aski/v1
+  (newtype age u16)
+  (impl from-str age {
+    from-str [(type-signature [string] (result self string))
+              (function-body "s.parse().map(Age).map_err(|e| e.to_string())")]})
+  (impl display age {fmt [(type-signature TODO) (function-body TODO)]}))"#;
+    let parse = parse(text);
+    assert_eq!(parse.errors(), &[]);
+    let impls: Vec<_> = parse.tree().schema().unwrap().impls().collect();
+
+    let member = impls[0].members().next().unwrap();
+    let signature = member.signature().unwrap();
+    assert!(!signature.is_todo());
+    let params: Vec<_> = signature.params().map(|p| p.syntax().text()).collect();
+    assert_eq!(params, ["string"]);
+    assert!(matches!(signature.return_ty(), Some(TypeExpr::Result(_))));
+    let body = member.body().unwrap();
+    assert!(!body.is_todo());
+    assert_eq!(
+        body.source().unwrap(),
+        "s.parse().map(Age).map_err(|e| e.to_string())"
+    );
+
+    let member = impls[1].members().next().unwrap();
+    assert!(member.signature().unwrap().is_todo());
+    assert_eq!(member.signature().unwrap().return_ty(), None);
+    assert!(member.body().unwrap().is_todo());
+    assert_eq!(member.body().unwrap().source(), None);
+}
+
+#[test]
+fn record_fields_have_default_values() {
+    let text = r#"(aski/v1
+  (record settings {port: u16 (default 8080) ratio: f64 (default -2.5e-3) name: string
+                    tags: (vec string) (default ["a" "b"]) limits: (map string i8) (default {"a" -1})
+                    origin: point (default {x: 0 y: 1}) mode: mode (default (mode fast))}))"#;
+    let parse = parse(text);
+    assert_eq!(parse.errors(), &[]);
+    let Some(Decl::Record(settings)) = parse.tree().schema().unwrap().decls().next() else {
+        panic!("expected a record");
+    };
+    let defaults: Vec<_> = settings.fields().map(|f| f.default_value()).collect();
+
+    let Some(Value::Literal(port)) = &defaults[0] else {
+        panic!("expected a literal");
+    };
+    assert_eq!(port.token().unwrap().kind(), SyntaxKind::INT);
+    let Some(Value::Literal(ratio)) = &defaults[1] else {
+        panic!("expected a literal");
+    };
+    assert_eq!(ratio.token().unwrap().kind(), SyntaxKind::FLOAT);
+    assert_eq!(ratio.token().unwrap().text(), "-2.5e-3");
+    assert!(defaults[2].is_none());
+
+    let Some(Value::List(tags)) = &defaults[3] else {
+        panic!("expected a list");
+    };
+    assert_eq!(tags.elements().count(), 2);
+    let Some(Value::Map(limits)) = &defaults[4] else {
+        panic!("expected a map");
+    };
+    let entry = limits.entries().next().unwrap();
+    assert_eq!(entry.key().unwrap().syntax().text(), "\"a\"");
+    assert_eq!(entry.value().unwrap().syntax().text(), "-1");
+    let Some(Value::Map(origin)) = &defaults[5] else {
+        panic!("expected a record value");
+    };
+    let fields: Vec<_> = origin
+        .fields()
+        .map(|f| {
+            (
+                f.name_ref().unwrap().syntax().text(),
+                f.value().unwrap().syntax().text(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        fields,
+        [
+            ("x".to_string(), "0".to_string()),
+            ("y".to_string(), "1".to_string())
+        ]
+    );
+    let Some(Value::Variant(mode)) = &defaults[6] else {
+        panic!("expected a variant");
+    };
+    assert_eq!(mode.type_ref().unwrap().syntax().text(), "mode");
+    assert_eq!(mode.variant().unwrap().syntax().text(), "fast");
+    assert!(mode.payload().is_none());
+}
@@ -0,0 +1,193 @@
+//! The capitalized dialect, parsed into the nodes aski/v1 declarations use.
+
+use aski_syntax::ast::{AstNode, Decl, HasName, HasTypeParams, TypeExpr, Variant};
+use aski_syntax::{parse_capitalized, SyntaxKind, TextRange};
+
+const PROTOTYPE: &str = include_str!("../../../encoder/examples/prototype-design.aski");
+
+fn kinds(decls: impl Iterator<Item = Decl>) -> Vec<SyntaxKind> {
+    decls.map(|decl| decl.syntax().kind()).collect()
+}
+
+#[test]
+fn prototype_design_parses() {
+    let parse = parse_capitalized(PROTOTYPE);
+    assert_eq!(parse.errors(), &[]);
+    assert_eq!(parse.syntax_node().text(), PROTOTYPE);
+    let file = parse.tree();
+
+    use SyntaxKind::*;
+    assert_eq!(kinds(file.decls()), [RECORD_DECL, ENUM_DECL, NEWTYPE_DECL]);
+    let names: Vec<_> = file
+        .decls()
+        .map(|decl| decl.name().unwrap().text())
+        .collect();
+    assert_eq!(names, ["User", "Species", "Age"]);
+
+    let Some(Decl::Enum(species)) = file.decls().nth(1) else {
+        panic!("expected the enum");
+    };
+    let variants: Vec<_> = species.variants().collect();
+    assert!(
+        matches!(&variants[0], Variant::Newtype(v) if v.name().unwrap().text() == "FirstVariant")
+    );
+    assert!(
+        matches!(&variants[1], Variant::Unit(v) if v.name().unwrap().text() == "SecondVariant")
+    );
+
+    let module = file.modules().next().unwrap();
+    assert_eq!(module.name().unwrap().text(), "simple");
+    let options: Vec<_> = module.options().collect();
+    assert_eq!(options[0].name().unwrap().text(), "specialOption");
+    assert_eq!(options[0].value().unwrap().text(), "whatever");
+    assert_eq!(options[1].name().unwrap().text(), "require");
+    let required: Vec<_> = options[1].names().map(|name| name.text()).collect();
+    assert_eq!(required, ["stuff", "blah"]);
+
+    let impls: Vec<_> = file.impls().collect();
+    let traits: Vec<_> = impls
+        .iter()
+        .map(|imp| imp.trait_ref().unwrap().text())
+        .collect();
+    assert_eq!(traits, ["FromStr", "TryFrom"]);
+    let imp = &impls[0];
+    assert!(
+        matches!(imp.self_ty(), Some(TypeExpr::Named(ty)) if ty.name_ref().unwrap().text() == "Age")
+    );
+    let members: Vec<_> = imp.members().map(|m| m.name().unwrap().text()).collect();
+    assert_eq!(members, ["from_str"]);
+    assert!(impls.iter().flat_map(|imp| imp.members()).all(|member| {
+        member
+            .signature()
+            .is_some_and(|signature| signature.is_todo())
+            && member.body().is_some_and(|body| body.is_todo())
+    }));
+}
+
+#[test]
+fn definitions_take_their_kind_from_their_body() {
+    let text = "\
+Id (Uuid)
+Pair (I32 I32)
+Empty ()
+Point {x F64 y F64}
+(Page T) {items (Vec T) next (Option (Page T)) spans [U32 U32]}
+Shape [Dot Circle {r F64} Rect (F64 F64) Named (String)]
+";
+    let parse = parse_capitalized(text);
+    assert_eq!(parse.errors(), &[]);
+    let file = parse.tree();
+
+    use SyntaxKind::*;
+    assert_eq!(
+        kinds(file.decls()),
+        [
+            NEWTYPE_DECL,
+            TUPLE_DECL,
+            TUPLE_DECL,
+            RECORD_DECL,
+            RECORD_DECL,
+            ENUM_DECL
+        ]
+    );
+    let decls: Vec<_> = file.decls().collect();
+    let Decl::Tuple(pair) = &decls[1] else {
+        panic!("expected a tuple");
+    };
+    assert_eq!(pair.fields().count(), 2);
+
+    let Decl::Record(page) = &decls[4] else {
+        panic!("expected a record");
+    };
+    let params: Vec<_> = page.type_params().map(|param| param.text()).collect();
+    assert_eq!(params, ["T"]);
+    let types: Vec<_> = page
+        .fields()
+        .map(|field| field.ty().unwrap().syntax().kind())
+        .collect();
+    assert_eq!(types, [VEC_TYPE, OPTION_TYPE, TUPLE_TYPE]);
+
+    let Decl::Enum(shape) = &decls[5] else {
+        panic!("expected an enum");
+    };
+    let variants: Vec<_> = shape.variants().map(|v| v.syntax().kind()).collect();
+    assert_eq!(
+        variants,
+        [UNIT_VARIANT, STRUCT_VARIANT, TUPLE_VARIANT, NEWTYPE_VARIANT]
+    );
+}
+
+#[test]
+fn string_literals_stand_for_their_type() {
+    let parse = parse_capitalized("User {name \"Ada\"}");
+    assert_eq!(parse.errors(), &[]);
+    let Some(Decl::Record(user)) = parse.tree().decls().next() else {
+        panic!("expected a record");
+    };
+    let field = user.fields().next().unwrap();
+    let Some(TypeExpr::Literal(literal)) = field.ty() else {
+        panic!("expected a literal type");
+    };
+    assert_eq!(literal.token().unwrap().text(), "\"Ada\"");
+}
+
+#[test]
+fn broken_definitions_end_at_the_next_one() {
+    let text = "User {name String\nAge (U16)\n";
+    let parse = parse_capitalized(text);
+    let file = parse.tree();
+    use SyntaxKind::*;
+    assert_eq!(kinds(file.decls()), [RECORD_DECL, NEWTYPE_DECL]);
+    let messages: Vec<_> = parse
+        .errors()
+        .iter()
+        .map(|error| (error.range, error.message.as_str()))
+        .collect();
+    assert_eq!(
+        messages,
+        [(TextRange::empty(17), "expected `}` to close field list")]
+    );
+}
+
+#[test]
+fn lowercase_names_do_not_define_types() {
+    let parse = parse_capitalized("user {name String}\nmodule");
+    let messages: Vec<_> = parse
+        .errors()
+        .iter()
+        .map(|error| error.message.as_str())
+        .collect();
+    assert_eq!(
+        messages,
+        [
+            "expected a type definition like `Name {...}`, or `module` or `impl`",
+            "expected a type definition like `Name {...}`, or `module` or `impl`",
+            "expected a type definition like `Name {...}`, or `module` or `impl`",
+        ]
+    );
+    assert_eq!(parse.syntax_node().text(), "user {name String}\nmodule");
+}
+
+#[test]
+fn type_constructors_are_payloads_of_their_own() {
+    let text = "Message [Batch (Vec Message) Page ((Paged User)) Both (Paged User)]";
+    let parse = parse_capitalized(text);
+    assert_eq!(parse.errors(), &[]);
+    let Some(Decl::Enum(message)) = parse.tree().decls().next() else {
+        panic!("expected an enum");
+    };
+    let variants: Vec<_> = message.variants().collect();
+    use SyntaxKind::*;
+    let Variant::Newtype(batch) = &variants[0] else {
+        panic!("expected a newtype variant");
+    };
+    assert_eq!(batch.ty().unwrap().syntax().kind(), VEC_TYPE);
+    let Variant::Newtype(page) = &variants[1] else {
+        panic!("expected a newtype variant");
+    };
+    assert_eq!(page.ty().unwrap().syntax().kind(), GENERIC_TYPE);
+    let Variant::Tuple(both) = &variants[2] else {
+        panic!("expected a tuple variant");
+    };
+    assert_eq!(both.fields().count(), 2);
+}
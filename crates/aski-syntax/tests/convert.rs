@@ -0,0 +1,247 @@
+//! Converting documents between aski/v1 and the capitalized dialect, and back.
+
+use std::path::Path;
+
+use aski_syntax::{
+    format, lex, parse, parse_capitalized, to_aski_v1, to_capitalized, SyntaxKind, TextRange,
+};
+
+/// The tokens of `text` other than whitespace, with comments trimmed.
+fn tokens(text: &str) -> Vec<(SyntaxKind, String)> {
+    lex(text)
+        .0
+        .into_iter()
+        .filter(|token| token.kind != SyntaxKind::WHITESPACE)
+        .map(|token| (token.kind, token.text.trim_end().to_string()))
+        .collect()
+}
+
+fn messages(errors: Vec<aski_syntax::Diagnostic>) -> Vec<(TextRange, String)> {
+    errors
+        .into_iter()
+        .map(|error| (error.range, error.message))
+        .collect()
+}
+
+fn aski_files(dir: &Path, found: &mut Vec<std::path::PathBuf>) {
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            aski_files(&path, found);
+        } else if path
+            .extension()
+            .is_some_and(|extension| extension == "aski")
+        {
+            found.push(path);
+        }
+    }
+}
+
+#[test]
+fn every_example_round_trips() {
+    let encoder = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../encoder");
+    let mut files = Vec::new();
+    aski_files(&encoder, &mut files);
+    let (mut v1, mut capitalized) = (0, 0);
+    for path in files {
+        let text = std::fs::read_to_string(&path).unwrap();
+        let name = path.display();
+        if text.contains("(aski/v1") {
+            let converted = to_capitalized(&text).unwrap();
+            assert_eq!(parse_capitalized(&converted).errors(), &[], "{name}");
+            assert_eq!(to_aski_v1(&converted), format(&text), "{name}");
+            v1 += 1;
+        } else {
+            let converted = to_aski_v1(&text).unwrap();
+            assert_eq!(parse(&converted).errors(), &[], "{name}");
+            let back = to_capitalized(&converted).unwrap();
+            assert_eq!(tokens(&back), tokens(&text), "{name}");
+            capitalized += 1;
+        }
+    }
+    assert!(v1 > 0 && capitalized > 0);
+}
+
+#[test]
+fn declarations_lose_their_keywords() {
+    let text = "\
+(aski/v1
+  (record status {ok: bool code: (? u32)})
+  (enum shape (circle {r: f64}))
+)
+";
+    assert_eq!(
+        to_capitalized(text).unwrap(),
+        "Status {ok Bool code (Option U32)}\nShape [Circle {r F64}]\n"
+    );
+    assert_eq!(
+        to_aski_v1("Shape [Circle {r F64}]").unwrap(),
+        "(aski/v1\n\n  (enum shape\n    (circle {r: f64}))\n)\n"
+    );
+}
+
+#[test]
+fn every_form_converts_both_ways() {
+    let v1 = "\
+(aski/v1
+
+  (newtype user-id uuid)
+  (newtype ids (vec user-id))
+  (newtype (wrapped T) T)
+  (newtype page (paged user))
+  (tuple pair [i32 i32])
+  (tuple empty [])
+  (record (paged T) {items: (vec T) next-page: (? (paged T)) spans: [u32 u32]})
+  (record settings {port: u16 (default 8080) mode: mode (default (mode fast-ish))
+                    origin: point (default {x: 0 y-pos: -1.5}) tags: (set string) (default [\"a\"])})
+  (enum message
+    (ping)
+    (batch (vec message))
+    (page (paged user))
+    (text string)
+    (rect [f64 f64])
+    (moved {from-x: i64 len-3: (array 3 u8)}))
+  (enum never)
+  (module simple {special-option whatever require (stuff blah)})
+  (impl from-str user-id {from-str [(type-signature TODO)]})
+  (impl try-from page {try-from [(type-signature [(vec user)] (result self string-error))
+                                 (function-body \"Err(StringError)\")]})
+)
+";
+    let capitalized = "\
+UserId (Uuid)
+Ids (Vec UserId)
+(Wrapped T) (T)
+Page ((Paged User))
+Pair (I32 I32)
+Empty ()
+(Paged T) {items (Vec T) nextPage (Option (Paged T)) spans [U32 U32]}
+Settings {port U16 (default 8080) mode Mode (default (Mode FastIsh))
+                  origin Point (default {x: 0 yPos: -1.5}) tags (Set String) (default [\"a\"])}
+Message [
+  Ping
+  Batch (Vec Message)
+  Page ((Paged User))
+  Text (String)
+  Rect (F64 F64)
+  Moved {fromX I64 len_3 (Array 3 U8)}]
+Never []
+module simple {specialOption whatever require (stuff blah)}
+impl FromStr UserId {from_str [(type-signature TODO)]}
+impl TryFrom Page {try_from [(type-signature [(Vec User)] (Result Self StringError))
+                               (function-body \"Err(StringError)\")]}
+";
+    assert_eq!(to_capitalized(v1).unwrap(), capitalized);
+    assert_eq!(to_aski_v1(capitalized), format(v1));
+}
+
+#[test]
+fn comments_stay_with_their_tokens() {
+    let v1 = "\
+;; The shapes.
+(aski/v1
+
+  ;; Where things are.
+  (record point {x: f64 ;; across
+                 y: f64})
+
+  (enum shape
+    ;; Nothing at all.
+    (dot) ;; the smallest
+    (circle {r: f64}) ;; round
+  )
+)
+";
+    let capitalized = to_capitalized(v1).unwrap();
+    assert_eq!(
+        capitalized,
+        "\
+;; The shapes.
+
+;; Where things are.
+Point {x F64 ;; across
+               y F64}
+
+Shape [
+  ;; Nothing at all.
+  Dot ;; the smallest
+  Circle {r F64} ;; round
+]
+"
+    );
+    // The wrapper goes around the definitions, below the comments leading up to them.
+    assert_eq!(
+        to_aski_v1(&capitalized).unwrap(),
+        "\
+;; The shapes.
+
+;; Where things are.
+(aski/v1
+
+  (record point
+    { x: f64 ;; across
+      y: f64 })
+
+  (enum shape
+    ;; Nothing at all.
+    (dot) ;; the smallest
+    (circle {r: f64}) ;; round
+  )
+)
+"
+    );
+}
+
+#[test]
+fn conversions_that_would_not_convert_back_are_refused() {
+    let text = "(aski/v1 (tuple single [u8]) (record http {x-y: (option u8)}))";
+    assert_eq!(
+        messages(to_capitalized(text).unwrap_err()),
+        [
+            (
+                TextRange::new(9, 28),
+                "a tuple of one field would read as a newtype in the capitalized dialect"
+                    .to_string()
+            ),
+            (
+                TextRange::new(49, 55),
+                "generic type `option` would read as a type constructor in the capitalized \
+                 dialect"
+                    .to_string()
+            ),
+        ]
+    );
+    assert_eq!(
+        messages(to_aski_v1("HTTPServer {userID U8}").unwrap_err()),
+        [
+            (
+                TextRange::new(0, 10),
+                "`HTTPServer` would be respelled `http-server`, which does not convert back"
+                    .to_string()
+            ),
+            (
+                TextRange::new(12, 18),
+                "`userID` would be respelled `user-id`, which does not convert back".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn documents_with_syntax_errors_are_not_converted() {
+    let errors = to_capitalized("(aski/v1 (record user {name: string)").unwrap_err();
+    assert!(!errors.is_empty());
+    let errors = to_aski_v1("User {name String").unwrap_err();
+    assert!(!errors.is_empty());
+}
+
+#[test]
+fn qualified_references_keep_their_module() {
+    let v1 = "(aski/v1\n\n  (module shop {require (geo-data)})\n  (record store {location: geo-data/point})\n)\n";
+    let capitalized = to_capitalized(v1).unwrap();
+    assert_eq!(
+        capitalized,
+        "module shop {require (geo-data)}\nStore {location geo-data/Point}\n"
+    );
+    assert_eq!(to_aski_v1(&capitalized), format(v1));
+}
@@ -0,0 +1,171 @@
+//! The canonical layout, on the examples and on hand-written input.
+
+use std::path::Path;
+
+use aski_syntax::{format, lex, SyntaxKind};
+
+const ALL_TYPES: &str = include_str!("../../../encoder/drafts/all-types.aski");
+
+/// The tokens of `text` other than whitespace, with comments trimmed.
+fn tokens(text: &str) -> Vec<(SyntaxKind, String)> {
+    lex(text)
+        .0
+        .into_iter()
+        .filter(|token| token.kind != SyntaxKind::WHITESPACE)
+        .map(|token| (token.kind, token.text.trim_end().to_string()))
+        .collect()
+}
+
+fn aski_files(dir: &Path, found: &mut Vec<std::path::PathBuf>) {
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            aski_files(&path, found);
+        } else if path
+            .extension()
+            .is_some_and(|extension| extension == "aski")
+        {
+            found.push(path);
+        }
+    }
+}
+
+#[test]
+fn every_example_is_formatted_idempotently() {
+    let encoder = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../encoder");
+    let mut files = Vec::new();
+    aski_files(&encoder, &mut files);
+    let mut formatted = 0;
+    for path in files {
+        let text = std::fs::read_to_string(&path).unwrap();
+        let once = format(&text).unwrap_or_else(|errors| panic!("{}: {errors:?}", path.display()));
+        assert_eq!(format(&once), Ok(once.clone()), "{}", path.display());
+        assert_eq!(tokens(&once), tokens(&text), "{}", path.display());
+        formatted += 1;
+    }
+    assert!(formatted > 0);
+}
+
+#[test]
+fn all_types_keeps_its_layout() {
+    let formatted = format(ALL_TYPES).unwrap();
+    assert!(formatted.starts_with(";; Aski schema DSL"));
+    assert!(
+        formatted.contains("\n\n(aski/v1\n\n  (newtype user-id uuid)\n  (newtype blob bytes)\n")
+    );
+    assert!(formatted.contains(
+        "  (record status\n    { ok: bool\n      code: (? u32)\n      note: (? string) })\n"
+    ));
+    assert!(formatted.contains("  (enum shape\n    (unit)\n    (circle {r: f64})\n"));
+    assert!(formatted.contains("      string-value: string\n\n      i8-value: i8\n"));
+    assert!(formatted.contains(
+        "      mixed-tuple: [i32 string bool] ;; sugar: bare vector in type position means tuple type\n"
+    ));
+    assert!(formatted.ends_with("      unit-struct-value: unit-struct })\n)\n"));
+}
+
+#[test]
+fn forms_are_laid_out_by_kind() {
+    let text = "(aski/v1 (newtype   id\n uuid) (record empty { })\n\n\n\
+                (record point {x: f64   y: f64}) (enum shape (unit) (circle { r: f64 }) (rect [ f64 f64 ]))\
+                (enum never) (tuple pair [i32\ti32]) (newtype (page T) (map string (vec (? T)))))";
+    let expected = "\
+(aski/v1
+  (newtype id uuid)
+  (record empty {})
+
+  (record point
+    { x: f64
+      y: f64 })
+  (enum shape
+    (unit)
+    (circle {r: f64})
+    (rect [f64 f64]))
+  (enum never)
+  (tuple pair [i32 i32])
+  (newtype (page T) (map string (vec (? T))))
+)
+";
+    assert_eq!(format(text).unwrap(), expected);
+    assert_eq!(format("(aski/v1)").unwrap(), "(aski/v1\n)\n");
+    assert_eq!(format("").unwrap(), "");
+}
+
+#[test]
+fn capitalized_definitions_are_laid_out_like_declarations() {
+    let text = "module simple {specialOption   whatever}\n\n\n\
+                Id (Uuid) Point {x F64   y F64} Empty {}\n\
+                Shape [ Unit Circle {r F64} Rect (F64 F64)] Never []";
+    let expected = "\
+module simple {specialOption whatever}
+
+Id (Uuid)
+Point
+  { x F64
+    y F64 }
+Empty {}
+Shape [
+  Unit
+  Circle {r F64}
+  Rect (F64 F64)]
+Never []
+";
+    assert_eq!(format(text).unwrap(), expected);
+    assert_eq!(format(expected).unwrap(), expected);
+}
+
+#[test]
+fn comments_stay_where_they_are() {
+    let text = "\
+;; header
+
+
+(aski/v1 ;; version
+  ;; first
+  (record r ;; after the name
+    {  ;; fields
+       a: u8 ;; trailing
+
+
+       ;; own line
+       b: (vec ;; element
+            u8) ;; last
+    })
+
+  (enum e   (x)
+    ;; between variants
+    (y) ;; end
+  )) ;; done
+";
+    let expected = "\
+;; header
+
+(aski/v1 ;; version
+  ;; first
+  (record r ;; after the name
+    { ;; fields
+      a: u8 ;; trailing
+
+      ;; own line
+      b: (vec ;; element
+        u8) ;; last
+    })
+
+  (enum e
+    (x)
+    ;; between variants
+    (y) ;; end
+  )
+) ;; done
+";
+    let formatted = format(text).unwrap();
+    assert_eq!(formatted, expected);
+    assert_eq!(format(&formatted).unwrap(), formatted);
+}
+
+#[test]
+fn documents_with_errors_are_not_formatted() {
+    let errors = format("(aski/v1 (record status { ok: bool code: (? ))").unwrap_err();
+    assert!(!errors.is_empty());
+    assert!(format("Status {ok Bool code (Option }").is_err());
+}
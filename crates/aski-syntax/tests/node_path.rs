@@ -0,0 +1,101 @@
+use aski_syntax::ast::AstNode;
+use aski_syntax::{parse, NodePath, Step};
+
+const ALL_TYPES: &str = include_str!("../../../encoder/drafts/all-types.aski");
+
+fn path(text: &str) -> NodePath {
+    text.parse().unwrap()
+}
+
+#[test]
+fn paths_parse_and_print() {
+    assert_eq!(
+        path("shape/variants/circle/fields/r"),
+        NodePath::decl("shape")
+            .join(Step::Variant("circle".into()))
+            .join(Step::Field("r".into()))
+    );
+    assert_eq!(
+        path("pair/fields/1"),
+        NodePath::decl("pair").join(Step::Element(1))
+    );
+    for text in [
+        "all-types",
+        "all-types/fields/status/type",
+        "wrapped/params/T",
+        "message/variants/text/type",
+        "all-types/fields/mixed-tuple/type/fields/2",
+    ] {
+        assert_eq!(path(text).to_string(), text);
+    }
+
+    let errors: Vec<_> = ["", "/fields/x", "status/fields", "status/members/ok"]
+        .into_iter()
+        .map(|text| text.parse::<NodePath>().unwrap_err().message)
+        .collect();
+    assert_eq!(
+        errors,
+        [
+            "a node path starts with a declaration name",
+            "a node path starts with a declaration name",
+            "`fields` must be followed by a name",
+            "unknown node path step `members`",
+        ]
+    );
+}
+
+#[test]
+fn paths_resolve_to_nodes() {
+    let file = parse(ALL_TYPES).tree();
+    let text = |path_text: &str| path(path_text).resolve(&file).map(|node| node.text());
+    assert_eq!(
+        text("all-types/fields/status").as_deref(),
+        Some("status: status")
+    );
+    assert_eq!(
+        text("all-types/fields/u16-array-len-3/type").as_deref(),
+        Some("(array 3 u16)")
+    );
+    assert_eq!(
+        text("shape/variants/circle/fields/r").as_deref(),
+        Some("r: f64")
+    );
+    assert_eq!(text("shape/variants/rect/fields/1").as_deref(), Some("f64"));
+    assert_eq!(text("pair/fields/0").as_deref(), Some("i32"));
+    assert_eq!(
+        text("all-types/fields/mixed-tuple/type/fields/1").as_deref(),
+        Some("string")
+    );
+    assert_eq!(text("wrapped/params/T").as_deref(), Some("T"));
+    assert_eq!(text("wrapped/type").as_deref(), Some("T"));
+    assert_eq!(
+        text("message/variants/batch/type").as_deref(),
+        Some("(vec message)")
+    );
+    assert_eq!(
+        text("error-code/variants/not-found").as_deref(),
+        Some("(not-found)")
+    );
+
+    assert_eq!(text("missing"), None);
+    assert_eq!(text("all-types/fields/missing"), None);
+    assert_eq!(text("pair/fields/2"), None);
+    assert_eq!(text("status/variants/ok"), None);
+    assert_eq!(text("user-id/fields/0"), None);
+}
+
+#[test]
+fn every_addressable_node_round_trips() {
+    let file = parse(ALL_TYPES).tree();
+    let mut addressed = 0;
+    for node in file.syntax().descendants() {
+        let Some(path) = NodePath::of(&node) else {
+            continue;
+        };
+        assert_eq!(path.resolve(&file), Some(node.clone()), "{path}");
+        assert_eq!(path.to_string().parse::<NodePath>().unwrap(), path);
+        addressed += 1;
+    }
+    // 10 declarations, 1 parameter, 11 variants, 37 fields and 52 types in slots.
+    assert_eq!(addressed, 111);
+}
@@ -0,0 +1,294 @@
+use aski_syntax::ast::{self, AstNode, HasName};
+use aski_syntax::{parse, parse_value, string_value, Diagnostic, SyntaxKind, TextRange};
+
+const ALL_TYPES: &str = include_str!("../../../encoder/drafts/all-types.aski");
+
+#[test]
+fn all_types_round_trips() {
+    let parse = parse(ALL_TYPES);
+    assert_eq!(parse.errors(), &[]);
+    assert_eq!(parse.syntax_node().text(), ALL_TYPES);
+}
+
+#[test]
+fn all_types_declarations() {
+    let root = parse(ALL_TYPES).syntax_node();
+    let decls: Vec<_> = root
+        .descendants()
+        .filter(|node| {
+            matches!(
+                node.kind(),
+                SyntaxKind::NEWTYPE_DECL
+                    | SyntaxKind::RECORD_DECL
+                    | SyntaxKind::TUPLE_DECL
+                    | SyntaxKind::ENUM_DECL
+            )
+        })
+        .map(|node| node.kind())
+        .collect();
+    use SyntaxKind::*;
+    assert_eq!(
+        decls,
+        [
+            NEWTYPE_DECL,
+            NEWTYPE_DECL,
+            NEWTYPE_DECL,
+            RECORD_DECL,
+            TUPLE_DECL,
+            ENUM_DECL,
+            ENUM_DECL,
+            ENUM_DECL,
+            RECORD_DECL,
+            RECORD_DECL,
+        ]
+    );
+}
+
+#[test]
+fn malformed_input_still_round_trips() {
+    let inputs = [
+        "",
+        ")",
+        "(aski/v1 (record status { ok: bool code: (? ",
+        "(aski/v1 (enum e (a f64 f64) (b {x}) 12) ] }",
+        "(aski/v2 (tuple t [i32 \"s\" 3x]))",
+        "(record loose {})",
+    ];
+    for input in inputs {
+        let parse = parse(input);
+        assert_eq!(parse.syntax_node().text(), input);
+        assert!(input.is_empty() || !parse.errors().is_empty(), "{input:?}");
+    }
+}
+
+#[test]
+fn half_typed_declarations_end_at_the_next_one() {
+    let text =
+        "(aski/v1\n  (record status { ok: bool code: (? \n  (newtype id uuid)\n  (enum e (a)))";
+    let parse = parse(text);
+    assert_eq!(parse.syntax_node().text(), text);
+    let decls: Vec<_> = parse
+        .tree()
+        .schema()
+        .unwrap()
+        .decls()
+        .map(|decl| (decl.syntax().kind(), decl.name().unwrap().text()))
+        .collect();
+    assert_eq!(
+        decls,
+        [
+            (SyntaxKind::RECORD_DECL, "status".to_string()),
+            (SyntaxKind::NEWTYPE_DECL, "id".to_string()),
+            (SyntaxKind::ENUM_DECL, "e".to_string()),
+        ]
+    );
+    let end = text.find("(? ").unwrap() + "(?".len();
+    let errors: Vec<_> = parse
+        .errors()
+        .iter()
+        .map(|error| (error.range, error.message.as_str()))
+        .collect();
+    assert_eq!(
+        errors,
+        [
+            (
+                TextRange::empty(end),
+                "expected the optional type, found the next declaration"
+            ),
+            (TextRange::empty(end), "expected `)` to close option type"),
+            (TextRange::empty(end), "expected `}` to close field list"),
+            (
+                TextRange::empty(end),
+                "expected `)` to close record declaration"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn a_missing_paren_stays_in_its_declaration() {
+    let text = "\
+(aski/v1
+  (record a { x: (vec u8 }
+  (enum b
+    (tuple [u8])
+    (record))
+  (newtype c a))
+";
+    let parse = parse(text);
+    let schema = parse.tree().schema().unwrap();
+    let names: Vec<_> = schema
+        .decls()
+        .map(|decl| decl.name().unwrap().text())
+        .collect();
+    assert_eq!(names, ["a", "b", "c"]);
+    let variants: Vec<_> = schema
+        .decls()
+        .nth(1)
+        .unwrap()
+        .syntax()
+        .children()
+        .filter(|child| ast::Variant::can_cast(child.kind()))
+        .map(|variant| variant.kind())
+        .collect();
+    assert_eq!(
+        variants,
+        [SyntaxKind::TUPLE_VARIANT, SyntaxKind::UNIT_VARIANT]
+    );
+    let messages: Vec<_> = parse
+        .errors()
+        .iter()
+        .map(|error| error.message.as_str())
+        .collect();
+    assert_eq!(
+        messages,
+        [
+            "expected `)` to close vec type",
+            "expected `)` to close record declaration"
+        ]
+    );
+}
+
+#[test]
+fn diagnostics_render_with_their_line() {
+    let text = "(aski/v1\n  (record status\n\t{ ok: bool code: (? })\n)\n";
+    let parse = parse(text);
+    let error = &parse.errors()[0];
+    assert_eq!(
+        error.render("status.aski", text),
+        "\
+error: expected the optional type, found `}`
+ --> status.aski:3:22
+  |
+3 | \t{ ok: bool code: (? })
+  | \t                    ^
+"
+    );
+    let missing = Diagnostic::error("missing", TextRange::empty(text.len()));
+    assert!(missing
+        .render("status.aski", text)
+        .ends_with(" --> status.aski:5:1\n  |\n5 | \n  | ^\n"));
+}
+
+#[test]
+fn string_literals_are_types() {
+    let parse = parse("(aski/v1 (record user {name: \"Ada\" tags: (vec \"tag\")}))");
+    assert_eq!(parse.errors(), &[]);
+    let types: Vec<_> = parse
+        .syntax_node()
+        .descendants()
+        .filter(|node| node.kind() == SyntaxKind::LITERAL_TYPE)
+        .map(|node| node.text().to_string())
+        .collect();
+    assert_eq!(types, ["\"Ada\"", "\"tag\""]);
+}
+
+#[test]
+fn impl_members_take_a_signature_and_a_body() {
+    let text = r#"(aski/v1
+  (impl display age {
+    fmt [(type-signature self string)
+         (function-body 12)
+         (returns string)]}))"#;
+    let parse = parse(text);
+    assert_eq!(parse.syntax_node().text(), text);
+    let errors: Vec<_> = parse
+        .errors()
+        .iter()
+        .map(|error| {
+            (
+                &text[error.range.start()..error.range.end()],
+                error.message.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        errors,
+        [
+            (
+                "self",
+                "expected `[` to start the parameter types, or `TODO`"
+            ),
+            ("string", "unexpected `string` in type signature"),
+            (
+                "12",
+                "expected the function body as a string, or `TODO`, found `12`"
+            ),
+            ("12", "unexpected `12` in function body"),
+            (
+                "(",
+                "expected `(type-signature ...)` or `(function-body ...)`"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn malformed_default_values_are_reported() {
+    let text = "(aski/v1
+  (record settings {port: u16 (default) ratio: f64 (default 1.2.3)
+                    mode: mode (default (mode fast slow 3)) tags: (vec u8) (default [1 2)}))";
+    let parse = parse(text);
+    assert_eq!(parse.syntax_node().text(), text);
+    let errors: Vec<_> = parse
+        .errors()
+        .iter()
+        .map(|error| {
+            (
+                &text[error.range.start()..error.range.end()],
+                error.message.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        errors,
+        [
+            (")", "expected a value, found `)`"),
+            ("1.2.3", "invalid number `1.2.3`"),
+            ("1.2.3", "expected a value, found `1.2.3`"),
+            ("3", "unexpected `3` in variant value"),
+            (")", "expected `]` to close list value"),
+        ]
+    );
+}
+
+#[test]
+fn string_values_resolve_their_escapes() {
+    assert_eq!(string_value(r#""plain""#), "plain");
+    assert_eq!(
+        string_value(r#""say \"hi\"\n\ttab \\ \q""#),
+        "say \"hi\"\n\ttab \\ q"
+    );
+}
+
+#[test]
+fn a_value_document_holds_one_value() {
+    let text =
+        r##"{id: #uuid "67e55044-10b1-426f-9247-bb680e5fe0c8" data: #bytes "00ff"} ;; done"##;
+    let parse = parse_value(text);
+    assert_eq!(parse.errors(), &[]);
+    assert_eq!(parse.syntax_node().text(), text);
+    let Some(ast::Value::Map(map)) = parse.tree().value() else {
+        panic!("expected a map value");
+    };
+    let tags: Vec<_> = map
+        .fields()
+        .filter_map(|field| match field.value()? {
+            ast::Value::Tagged(tagged) => Some(tagged.tag()?.text().to_string()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(tags, ["#uuid", "#bytes"]);
+
+    let text = "(shape unit)) #ok";
+    let parse = parse_value(text);
+    assert_eq!(parse.syntax_node().text(), text);
+    let errors: Vec<_> = parse.errors().iter().map(|e| e.message.as_str()).collect();
+    assert_eq!(
+        errors,
+        [
+            "expected the end of input after the value",
+            "expected the end of input after the value",
+        ]
+    );
+}
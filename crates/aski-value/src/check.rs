@@ -0,0 +1,266 @@
+// Checking a value against the declaration it claims to be a value of.
+//
+// The value must have the shape of its type all the way down: the same primitive, the
+// same container, the declarations it names with the fields and variants they declare.
+// The arguments of a generic declaration stand in for its parameters, so a value of
+// `(wrapped pair)` wraps a value of `pair`. Records give every field once, sets and map
+// keys do not repeat, and arrays and tuples have their declared length.
+//
+// Every mismatch is reported, each with the path to the value it is about, like
+// `all-types.shape.r` or `all-types.string-set[2]`.
+
+use std::fmt;
+
+use aski_sema::{Field, ItemKind, Name, Primitive, Schema, TypeKind, TypeRef, VariantBody};
+use aski_syntax::TextRange;
+
+use crate::value::{Value, VariantData};
+
+/// A part of a value that does not match its type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValueError {
+    /// Where the value is, starting from the declaration checked against.
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for ValueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+impl std::error::Error for ValueError {}
+
+/// Checks that `value` is a value of the declaration `name` of `schema`, which must not
+/// be generic.
+pub fn check_value(schema: &Schema, name: &str, value: &Value) -> Vec<ValueError> {
+    let mut checker = Checker {
+        schema,
+        path: name.to_string(),
+        errors: Vec::new(),
+    };
+    match schema.item(name) {
+        None => checker.error(format!("cannot find type `{name}`")),
+        Some(item) if !item.params.is_empty() => checker.error(format!(
+            "`{name}` is generic; check values of a declaration that gives it arguments"
+        )),
+        Some(_) => {
+            let ty = TypeRef {
+                kind: TypeKind::Named {
+                    name: name.to_string(),
+                    args: Vec::new(),
+                },
+                range: TextRange::default(),
+            };
+            checker.value(&ty, value);
+        }
+    }
+    checker.errors
+}
+
+struct Checker<'a> {
+    schema: &'a Schema,
+    /// The path to the value being checked.
+    path: String,
+    errors: Vec<ValueError>,
+}
+
+impl Checker<'_> {
+    fn value(&mut self, ty: &TypeRef, value: &Value) {
+        match (&ty.kind, value) {
+            (TypeKind::Box(inner), _) => self.value(inner, value),
+            (TypeKind::Option(inner), Value::Option(value)) => {
+                if let Some(value) = value {
+                    self.value(inner, value);
+                }
+            }
+            (TypeKind::Result(ok, err), Value::Result(result)) => match result {
+                Ok(value) => self.value(ok, value),
+                Err(value) => self.value(err, value),
+            },
+            (TypeKind::Vec(element), Value::Vec(elements)) => self.elements(element, elements),
+            (TypeKind::Set(element), Value::Set(elements)) => {
+                self.elements(element, elements);
+                self.distinct(elements.iter(), "element");
+            }
+            (TypeKind::Map(key, val), Value::Map(entries)) => {
+                for (index, (k, v)) in entries.iter().enumerate() {
+                    self.at(&format!("[{index}]"), |checker| {
+                        checker.value(key, k);
+                        checker.value(val, v);
+                    });
+                }
+                self.distinct(entries.iter().map(|(k, _)| k), "key");
+            }
+            (TypeKind::Array(len, element), Value::Array(elements)) => {
+                if self.count(ty, *len as usize, elements.len()) {
+                    self.elements(element, elements);
+                }
+            }
+            (TypeKind::Tuple(types), Value::Tuple(elements)) => {
+                self.positional(ty, types, elements)
+            }
+            (TypeKind::Named { name, args }, _) => self.named(ty, name, args, value),
+            _ => self.mismatch(ty, value),
+        }
+    }
+
+    fn named(&mut self, ty: &TypeRef, name: &str, args: &[TypeRef], value: &Value) {
+        if let Some(primitive) = Primitive::from_name(name) {
+            if value.primitive() != Some(primitive) {
+                self.mismatch(ty, value);
+            }
+            return;
+        }
+        let Some(item) = self.schema.item(name) else {
+            let message = if name.contains('/') {
+                format!("cannot check a value of `{name}`, which another module declares")
+            } else {
+                format!("cannot find type `{name}`")
+            };
+            return self.error(message);
+        };
+        let substituted = |ty: &TypeRef| ty.substituted(&item.params, args);
+        match (&item.kind, value) {
+            (ItemKind::Newtype(inner), Value::Newtype { ty: given, value }) if *given == name => {
+                self.value(&substituted(inner), value)
+            }
+            (
+                ItemKind::Record(fields),
+                Value::Record {
+                    ty: given,
+                    fields: values,
+                },
+            ) if *given == name => self.fields(name, fields, &item.params, args, values),
+            (ItemKind::Tuple(types), Value::TupleStruct { ty: given, fields })
+                if *given == name =>
+            {
+                let types: Vec<_> = types.iter().map(substituted).collect();
+                self.positional(ty, &types, fields)
+            }
+            (
+                ItemKind::Enum(variants),
+                Value::Variant {
+                    ty: given,
+                    variant,
+                    data,
+                },
+            ) if *given == name => {
+                let Some(declared) = variants.iter().find(|v| v.name.text == *variant) else {
+                    return self.error(format!("`{name}` has no variant `{variant}`"));
+                };
+                match (&declared.body, data) {
+                    (VariantBody::Unit, VariantData::Unit) => {}
+                    (VariantBody::Newtype(inner), VariantData::Newtype(value)) => {
+                        self.value(&substituted(inner), value)
+                    }
+                    (VariantBody::Tuple(types), VariantData::Tuple(values)) => {
+                        let types: Vec<_> = types.iter().map(substituted).collect();
+                        self.positional(ty, &types, values)
+                    }
+                    (VariantBody::Struct(fields), VariantData::Struct(values)) => {
+                        self.fields(name, fields, &item.params, args, values)
+                    }
+                    (body, _) => self.error(format!(
+                        "variant `{variant}` of `{name}` holds {}",
+                        match body {
+                            VariantBody::Unit => "nothing",
+                            VariantBody::Newtype(_) => "one value",
+                            VariantBody::Tuple(_) => "a tuple of values",
+                            VariantBody::Struct(_) => "fields",
+                        }
+                    )),
+                }
+            }
+            _ => self.mismatch(ty, value),
+        }
+    }
+
+    /// Checks the fields of a record, or of a struct variant, of the declaration `name`.
+    fn fields(
+        &mut self,
+        name: &str,
+        fields: &[Field],
+        params: &[Name],
+        args: &[TypeRef],
+        values: &[(String, Value)],
+    ) {
+        for (index, (field, value)) in values.iter().enumerate() {
+            if values[..index].iter().any(|(earlier, _)| earlier == field) {
+                self.error(format!("field `{field}` is given more than once"));
+                continue;
+            }
+            match fields.iter().find(|f| f.name.text == *field) {
+                Some(declared) => self.at(&format!(".{field}"), |checker| {
+                    checker.value(&declared.ty.substituted(params, args), value)
+                }),
+                None => self.error(format!("`{name}` has no field `{field}`")),
+            }
+        }
+        for field in fields {
+            if !values.iter().any(|(given, _)| *given == field.name.text) {
+                self.error(format!("missing field `{}`", field.name));
+            }
+        }
+    }
+
+    fn elements(&mut self, ty: &TypeRef, elements: &[Value]) {
+        for (index, element) in elements.iter().enumerate() {
+            self.at(&format!("[{index}]"), |checker| checker.value(ty, element));
+        }
+    }
+
+    /// Checks one value per type, of a tuple type, a tuple declaration or a tuple variant.
+    fn positional(&mut self, ty: &TypeRef, types: &[TypeRef], values: &[Value]) {
+        if self.count(ty, types.len(), values.len()) {
+            for (index, (ty, value)) in types.iter().zip(values).enumerate() {
+                self.at(&format!("[{index}]"), |checker| checker.value(ty, value));
+            }
+        }
+    }
+
+    /// Reports each value equal to one before it.
+    fn distinct<'v>(&mut self, values: impl Iterator<Item = &'v Value>, what: &str) {
+        let values: Vec<_> = values.collect();
+        for (index, value) in values.iter().enumerate() {
+            if values[..index].contains(value) {
+                self.at(&format!("[{index}]"), |checker| {
+                    checker.error(format!("the {what} is given more than once"))
+                });
+            }
+        }
+    }
+
+    /// Whether `given` values are the `expected` number, reporting it if not.
+    fn count(&mut self, ty: &TypeRef, expected: usize, given: usize) -> bool {
+        if expected != given {
+            self.error(format!(
+                "a value of `{ty}` has {expected} elements, not {given}"
+            ));
+        }
+        expected == given
+    }
+
+    fn mismatch(&mut self, ty: &TypeRef, value: &Value) {
+        self.error(format!(
+            "expected a value of `{ty}`, found {}",
+            value.describe()
+        ));
+    }
+
+    /// Runs `check` with `step` added to the path.
+    fn at(&mut self, step: &str, check: impl FnOnce(&mut Self)) {
+        let len = self.path.len();
+        self.path.push_str(step);
+        check(self);
+        self.path.truncate(len);
+    }
+
+    fn error(&mut self, message: String) {
+        self.errors.push(ValueError {
+            path: self.path.clone(),
+            message,
+        });
+    }
+}
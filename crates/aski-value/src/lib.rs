@@ -0,0 +1,23 @@
+//! Dynamic values of aski types.
+//!
+//! A [`Value`] is a value of any aski type, built and inspected at run time, for tools
+//! that handle data described by a schema without compiling the Rust types generated
+//! from it. Its variants mirror the aski type universe, so a value keeps everything that
+//! tells its type apart: the width of its integers, `char` from `string`, a tuple from a
+//! vec, the declaration and variant it belongs to.
+//!
+//! [`check_value`] checks a value against a declaration of a schema, reporting each part
+//! that does not match as a [`ValueError`].
+//!
+//! Values are written in the notation record field defaults are written in, like
+//! `(shape circle {r: 1.5})`, `#uuid "..."` or `#bytes "00ff"`. A value displays in it,
+//! and [`read_value`] reads it back as a value of a declaration of a schema.
+
+mod check;
+mod print;
+mod read;
+mod value;
+
+pub use crate::check::{check_value, ValueError};
+pub use crate::read::read_value;
+pub use crate::value::{InvalidUuid, Uuid, Value, VariantData};
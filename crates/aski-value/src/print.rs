@@ -0,0 +1,185 @@
+// Printing values in aski's value notation.
+//
+// A value prints as `read_value` reads it back, given the declaration it is a value of:
+// `(shape circle {r: 1.5})`, `#uuid "..."`, `#bytes "00ff"`, `#ok 1`, and `[1 2 3]` for
+// an array as for a vec or a tuple. What the notation leaves to the declaration is left
+// out, so an option holding a value prints as the value, and a newtype as what it wraps.
+// An option holding `none` prints as `none`, and so reads back as the empty option.
+//
+// Values print on one line. The alternate form, `{:#}`, puts every field of a record
+// on a line of its own, indented by how deep the record is.
+
+use std::fmt::{self, Write};
+
+use crate::value::{Value, VariantData};
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pretty = f.alternate();
+        let mut printer = Printer {
+            out: f,
+            pretty,
+            depth: 0,
+        };
+        printer.value(self)
+    }
+}
+
+struct Printer<'a, 'f> {
+    out: &'a mut fmt::Formatter<'f>,
+    /// Whether record fields go on lines of their own.
+    pretty: bool,
+    /// How many records the value being printed is inside.
+    depth: usize,
+}
+
+impl Printer<'_, '_> {
+    fn value(&mut self, value: &Value) -> fmt::Result {
+        match value {
+            Value::Bool(b) => write!(self.out, "{b}"),
+            Value::Char(c) => self.string(c.encode_utf8(&mut [0; 4])),
+            Value::String(text) => self.string(text),
+            Value::I8(n) => write!(self.out, "{n}"),
+            Value::I16(n) => write!(self.out, "{n}"),
+            Value::I32(n) => write!(self.out, "{n}"),
+            Value::I64(n) => write!(self.out, "{n}"),
+            Value::I128(n) => write!(self.out, "{n}"),
+            Value::Isize(n) => write!(self.out, "{n}"),
+            Value::U8(n) => write!(self.out, "{n}"),
+            Value::U16(n) => write!(self.out, "{n}"),
+            Value::U32(n) => write!(self.out, "{n}"),
+            Value::U64(n) => write!(self.out, "{n}"),
+            Value::U128(n) => write!(self.out, "{n}"),
+            Value::Usize(n) => write!(self.out, "{n}"),
+            Value::F32(x) => self.float(f64::from(*x), &format!("{x:?}")),
+            Value::F64(x) => self.float(*x, &format!("{x:?}")),
+            Value::Unit => self.out.write_str("[]"),
+            Value::Uuid(uuid) => write!(self.out, "#uuid \"{uuid}\""),
+            Value::Bytes(bytes) => {
+                self.out.write_str("#bytes \"")?;
+                for byte in bytes {
+                    write!(self.out, "{byte:02x}")?;
+                }
+                self.out.write_char('"')
+            }
+            Value::Option(None) => self.out.write_str("none"),
+            Value::Option(Some(value)) => self.value(value),
+            Value::Result(Ok(value)) => {
+                self.out.write_str("#ok ")?;
+                self.value(value)
+            }
+            Value::Result(Err(value)) => {
+                self.out.write_str("#err ")?;
+                self.value(value)
+            }
+            Value::Vec(elements)
+            | Value::Set(elements)
+            | Value::Array(elements)
+            | Value::Tuple(elements)
+            | Value::TupleStruct {
+                fields: elements, ..
+            } => self.list(elements),
+            Value::Map(entries) => {
+                self.out.write_char('{')?;
+                for (index, (key, value)) in entries.iter().enumerate() {
+                    if index > 0 {
+                        self.out.write_char(' ')?;
+                    }
+                    self.value(key)?;
+                    self.out.write_char(' ')?;
+                    self.value(value)?;
+                }
+                self.out.write_char('}')
+            }
+            Value::Newtype { value, .. } => self.value(value),
+            Value::Record { fields, .. } => self.fields(fields),
+            Value::Variant { ty, variant, data } => {
+                write!(self.out, "({ty} {variant}")?;
+                match data {
+                    VariantData::Unit => {}
+                    VariantData::Newtype(value) => {
+                        self.out.write_char(' ')?;
+                        self.value(value)?;
+                    }
+                    VariantData::Tuple(values) => {
+                        self.out.write_char(' ')?;
+                        self.list(values)?;
+                    }
+                    VariantData::Struct(fields) => {
+                        self.out.write_char(' ')?;
+                        self.fields(fields)?;
+                    }
+                }
+                self.out.write_char(')')
+            }
+        }
+    }
+
+    /// Writes a string literal, escaping what `string_value` unescapes.
+    fn string(&mut self, text: &str) -> fmt::Result {
+        self.out.write_char('"')?;
+        for c in text.chars() {
+            match c {
+                '"' => self.out.write_str("\\\"")?,
+                '\\' => self.out.write_str("\\\\")?,
+                '\n' => self.out.write_str("\\n")?,
+                '\t' => self.out.write_str("\\t")?,
+                '\r' => self.out.write_str("\\r")?,
+                c => self.out.write_char(c)?,
+            }
+        }
+        self.out.write_char('"')
+    }
+
+    /// Writes a float as Rust debug-prints it, which always reads back as a float, or as
+    /// `inf`, `-inf` or `nan`.
+    fn float(&mut self, x: f64, debug: &str) -> fmt::Result {
+        match x {
+            _ if x.is_nan() => self.out.write_str("nan"),
+            f64::INFINITY => self.out.write_str("inf"),
+            f64::NEG_INFINITY => self.out.write_str("-inf"),
+            _ => self.out.write_str(debug),
+        }
+    }
+
+    fn list(&mut self, elements: &[Value]) -> fmt::Result {
+        self.out.write_char('[')?;
+        for (index, element) in elements.iter().enumerate() {
+            if index > 0 {
+                self.out.write_char(' ')?;
+            }
+            self.value(element)?;
+        }
+        self.out.write_char(']')
+    }
+
+    fn fields(&mut self, fields: &[(String, Value)]) -> fmt::Result {
+        if fields.is_empty() {
+            return self.out.write_str("{}");
+        }
+        self.out.write_char('{')?;
+        self.depth += 1;
+        for (index, (name, value)) in fields.iter().enumerate() {
+            if self.pretty {
+                self.newline()?;
+            } else if index > 0 {
+                self.out.write_char(' ')?;
+            }
+            write!(self.out, "{name}: ")?;
+            self.value(value)?;
+        }
+        self.depth -= 1;
+        if self.pretty {
+            self.newline()?;
+        }
+        self.out.write_char('}')
+    }
+
+    fn newline(&mut self) -> fmt::Result {
+        self.out.write_char('\n')?;
+        for _ in 0..self.depth {
+            self.out.write_str("  ")?;
+        }
+        Ok(())
+    }
+}
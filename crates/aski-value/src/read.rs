@@ -0,0 +1,489 @@
+// Reading values written in aski's value notation.
+//
+// The notation is the one record field defaults are written in, so a value of `shape`
+// reads `(shape circle {r: 1.5})` and a value of `(result u8 string)` reads `#ok 1`. The
+// text alone does not tell `1` as a `u8` from `1` as an `f64`, or `[1 2]` as a vec from
+// an array, so the text is read as a value of a declaration, which decides the type of
+// every part of it:
+//
+//   bool                  true, false
+//   integers              an integer in the type's range
+//   floats                a number, or one of inf, -inf and nan
+//   string, char          a string, of exactly one character for a char
+//   uuid                  #uuid "..." or a string, in the hyphenated form
+//   unit, bytes           [] and, for bytes, #bytes "00ff" or a list of u8
+//   (? T)                 none, or a value of T
+//   (result a b)          #ok a, #err b
+//   vec, set, array       [element ...], as many as an array holds
+//   map                   {key value ...}
+//   tuple types           [element ...], one per element type
+//   newtypes, box         the value they wrap
+//   records               {field: value ...}, every field once; {} without fields
+//   tuple declarations    [field ...]
+//   enums                 (enum variant payload), the payload matching the variant
+//
+// Every part that does not fit its type is reported, with the range of the text it was
+// read from.
+
+use std::collections::HashSet;
+
+use aski_sema::{
+    hex_bytes, lower_value, Field, ItemKind, Name, Primitive, Schema, TypeKind, TypeRef, ValueExpr,
+    ValueKind, ValueTag, VariantBody,
+};
+use aski_syntax::{parse_value, Diagnostic, TextRange};
+
+use crate::value::{Uuid, Value, VariantData};
+
+/// Reads `text` as a value of the declaration `name` of `schema`, which must not be
+/// generic.
+pub fn read_value(schema: &Schema, name: &str, text: &str) -> Result<Value, Vec<Diagnostic>> {
+    let parse = parse_value(text);
+    if !parse.errors().is_empty() {
+        return Err(parse.errors().to_vec());
+    }
+    let Some(syntax) = parse.tree().value() else {
+        return Err(vec![Diagnostic::error(
+            "expected a value",
+            TextRange::default(),
+        )]);
+    };
+    let (expr, diagnostics) = lower_value(&syntax);
+    let Some(expr) = expr.filter(|_| diagnostics.is_empty()) else {
+        return Err(diagnostics);
+    };
+    let mut reader = Reader {
+        schema,
+        diagnostics: Vec::new(),
+    };
+    let value = match schema.item(name) {
+        None => reader.error(&expr, format!("cannot find type `{name}`")),
+        Some(item) if !item.params.is_empty() => reader.error(
+            &expr,
+            format!("`{name}` is generic; read values of a declaration that gives it arguments"),
+        ),
+        Some(_) => {
+            let ty = TypeRef {
+                kind: TypeKind::Named {
+                    name: name.to_string(),
+                    args: Vec::new(),
+                },
+                range: TextRange::default(),
+            };
+            reader.value(&expr, &ty)
+        }
+    };
+    match value {
+        Some(value) if reader.diagnostics.is_empty() => Ok(value),
+        _ => Err(reader.diagnostics),
+    }
+}
+
+struct Reader<'a> {
+    schema: &'a Schema,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Reader<'_> {
+    fn value(&mut self, expr: &ValueExpr, ty: &TypeRef) -> Option<Value> {
+        match (&ty.kind, &expr.kind) {
+            (TypeKind::Option(_), ValueKind::None) => Some(Value::Option(None)),
+            (TypeKind::Option(inner), _) => {
+                Some(Value::Option(Some(Box::new(self.value(expr, inner)?))))
+            }
+            (TypeKind::Box(inner), _) => self.value(expr, inner),
+            (TypeKind::Result(ok, err), ValueKind::Tagged { tag, value }) => match tag {
+                ValueTag::Ok => Some(Value::Result(Ok(Box::new(self.value(value, ok)?)))),
+                ValueTag::Err => Some(Value::Result(Err(Box::new(self.value(value, err)?)))),
+                _ => self.mismatch(expr, ty),
+            },
+            (TypeKind::Vec(element), ValueKind::List(elements)) => {
+                Some(Value::Vec(self.elements(elements, element)?))
+            }
+            (TypeKind::Set(element), ValueKind::List(elements)) => {
+                let values = self.elements(elements, element)?;
+                let refs: Vec<_> = values.iter().collect();
+                self.distinct(elements.iter(), &refs, "element");
+                Some(Value::Set(values))
+            }
+            (TypeKind::Array(len, element), ValueKind::List(elements)) => {
+                if !self.count(expr, ty, *len as usize, elements.len()) {
+                    return None;
+                }
+                Some(Value::Array(self.elements(elements, element)?))
+            }
+            (TypeKind::Tuple(types), ValueKind::List(_)) => {
+                Some(Value::Tuple(self.list(expr, ty, types)?))
+            }
+            (TypeKind::Map(key, val), ValueKind::Map(entries)) => {
+                let mut values = Vec::with_capacity(entries.len());
+                for (k, v) in entries {
+                    let k = self.value(k, key);
+                    let v = self.value(v, val);
+                    values.push(k.zip(v));
+                }
+                let values = values.into_iter().collect::<Option<Vec<_>>>()?;
+                let keys: Vec<_> = values.iter().map(|(k, _)| k).collect();
+                self.distinct(entries.iter().map(|(k, _)| k), &keys, "key");
+                Some(Value::Map(values))
+            }
+            (TypeKind::Named { name, args }, _) => self.named(expr, ty, name, args),
+            _ => self.mismatch(expr, ty),
+        }
+    }
+
+    fn named(
+        &mut self,
+        expr: &ValueExpr,
+        ty: &TypeRef,
+        name: &str,
+        args: &[TypeRef],
+    ) -> Option<Value> {
+        if let Some(primitive) = Primitive::from_name(name) {
+            return self.primitive(expr, ty, primitive);
+        }
+        let Some(item) = self.schema.item(name) else {
+            let message = if name.contains('/') {
+                format!("cannot read a value of `{name}`, which another module declares")
+            } else {
+                format!("cannot find type `{name}`")
+            };
+            return self.error(expr, message);
+        };
+        let substituted = |ty: &TypeRef| ty.substituted(&item.params, args);
+        let declared = item.name.text.clone();
+        match (&item.kind, &expr.kind) {
+            (ItemKind::Newtype(inner), _) => Some(Value::Newtype {
+                ty: declared,
+                value: Box::new(self.value(expr, &substituted(inner))?),
+            }),
+            (ItemKind::Record(fields), ValueKind::Record(given)) => Some(Value::Record {
+                ty: declared,
+                fields: self.fields(expr, name, fields, &item.params, args, given)?,
+            }),
+            (ItemKind::Record(fields), ValueKind::Map(entries))
+                if fields.is_empty() && entries.is_empty() =>
+            {
+                Some(Value::Record {
+                    ty: declared,
+                    fields: Vec::new(),
+                })
+            }
+            (ItemKind::Tuple(types), ValueKind::List(_)) => {
+                let types: Vec<_> = types.iter().map(substituted).collect();
+                Some(Value::TupleStruct {
+                    ty: declared,
+                    fields: self.list(expr, ty, &types)?,
+                })
+            }
+            (
+                ItemKind::Enum(variants),
+                ValueKind::Variant {
+                    ty: enum_name,
+                    variant,
+                    payload,
+                },
+            ) if enum_name.text == item.name.text => {
+                let Some(declared_variant) = variants.iter().find(|v| v.name.text == variant.text)
+                else {
+                    self.diagnostics.push(Diagnostic::error(
+                        format!("`{name}` has no variant `{variant}`"),
+                        variant.range,
+                    ));
+                    return None;
+                };
+                let data = match (&declared_variant.body, payload) {
+                    (VariantBody::Unit, None) => VariantData::Unit,
+                    (VariantBody::Unit, Some(payload)) => {
+                        return self.error(
+                            payload,
+                            format!("variant `{variant}` of `{name}` takes no payload"),
+                        )
+                    }
+                    (_, None) => {
+                        return self.error(
+                            expr,
+                            format!("variant `{variant}` of `{name}` takes a payload"),
+                        )
+                    }
+                    (VariantBody::Newtype(inner), Some(payload)) => {
+                        VariantData::Newtype(Box::new(self.value(payload, &substituted(inner))?))
+                    }
+                    (VariantBody::Tuple(types), Some(payload)) => {
+                        let types: Vec<_> = types.iter().map(substituted).collect();
+                        match &payload.kind {
+                            ValueKind::List(_) => {
+                                VariantData::Tuple(self.list(payload, ty, &types)?)
+                            }
+                            _ => {
+                                return self.error(
+                                    payload,
+                                    format!(
+                                        "variant `{variant}` of `{name}` takes a list of {} values",
+                                        types.len()
+                                    ),
+                                )
+                            }
+                        }
+                    }
+                    (VariantBody::Struct(fields), Some(payload)) => match &payload.kind {
+                        ValueKind::Record(given) => VariantData::Struct(self.fields(
+                            payload,
+                            name,
+                            fields,
+                            &item.params,
+                            args,
+                            given,
+                        )?),
+                        ValueKind::Map(entries) if fields.is_empty() && entries.is_empty() => {
+                            VariantData::Struct(Vec::new())
+                        }
+                        _ => {
+                            return self.error(
+                                payload,
+                                format!(
+                                    "variant `{variant}` of `{name}` takes `{{field: value ...}}`"
+                                ),
+                            )
+                        }
+                    },
+                };
+                Some(Value::Variant {
+                    ty: declared,
+                    variant: declared_variant.name.text.clone(),
+                    data,
+                })
+            }
+            _ => self.mismatch(expr, ty),
+        }
+    }
+
+    fn primitive(&mut self, expr: &ValueExpr, ty: &TypeRef, primitive: Primitive) -> Option<Value> {
+        let value = match (primitive, &expr.kind) {
+            (Primitive::Bool, ValueKind::Bool(b)) => Value::Bool(*b),
+            (Primitive::String, ValueKind::String(text)) => Value::String(text.clone()),
+            (Primitive::Char, ValueKind::String(text)) => {
+                let mut chars = text.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Value::Char(c),
+                    _ => {
+                        return self.error(
+                            expr,
+                            "a `char` is written as a string of exactly one character".to_string(),
+                        )
+                    }
+                }
+            }
+            (
+                Primitive::Uuid,
+                ValueKind::Tagged {
+                    tag: ValueTag::Uuid,
+                    value,
+                },
+            ) => match &value.kind {
+                ValueKind::String(_) => return self.primitive(value, ty, primitive),
+                _ => return self.mismatch(value, ty),
+            },
+            (Primitive::Uuid, ValueKind::String(text)) => match text.parse::<Uuid>() {
+                Ok(uuid) => Value::Uuid(uuid),
+                Err(_) => {
+                    return self.error(
+                        expr,
+                        "a `uuid` is written as a string like \
+                         \"67e55044-10b1-426f-9247-bb680e5fe0c8\""
+                            .to_string(),
+                    )
+                }
+            },
+            (
+                Primitive::Bytes,
+                ValueKind::Tagged {
+                    tag: ValueTag::Bytes,
+                    value,
+                },
+            ) => match &value.kind {
+                ValueKind::String(text) if hex_bytes(text).is_some() => {
+                    Value::Bytes(hex_bytes(text).expect("checked to be hex"))
+                }
+                _ => {
+                    return self.error(
+                        value,
+                        "`#bytes` takes a string of hex digits, two per byte, like \"00ff\""
+                            .to_string(),
+                    )
+                }
+            },
+            (Primitive::Bytes, ValueKind::List(elements)) => {
+                let byte = TypeRef {
+                    kind: TypeKind::Named {
+                        name: Primitive::U8.name().to_string(),
+                        args: Vec::new(),
+                    },
+                    range: ty.range,
+                };
+                let mut bytes = Vec::with_capacity(elements.len());
+                for each in elements {
+                    bytes.push(match self.primitive(each, &byte, Primitive::U8) {
+                        Some(Value::U8(byte)) => Some(byte),
+                        _ => None,
+                    });
+                }
+                Value::Bytes(bytes.into_iter().collect::<Option<_>>()?)
+            }
+            (Primitive::Unit, ValueKind::List(elements)) if elements.is_empty() => Value::Unit,
+            (Primitive::F32, ValueKind::Int(text) | ValueKind::Float(text)) => {
+                Value::F32(text.parse().expect("numbers lex as Rust reads them"))
+            }
+            (Primitive::F64, ValueKind::Int(text) | ValueKind::Float(text)) => {
+                Value::F64(text.parse().expect("numbers lex as Rust reads them"))
+            }
+            (_, ValueKind::Int(text)) => {
+                let value = match primitive {
+                    Primitive::I8 => text.parse().ok().map(Value::I8),
+                    Primitive::I16 => text.parse().ok().map(Value::I16),
+                    Primitive::I32 => text.parse().ok().map(Value::I32),
+                    Primitive::I64 => text.parse().ok().map(Value::I64),
+                    Primitive::I128 => text.parse().ok().map(Value::I128),
+                    Primitive::Isize => text.parse().ok().map(Value::Isize),
+                    Primitive::U8 => text.parse().ok().map(Value::U8),
+                    Primitive::U16 => text.parse().ok().map(Value::U16),
+                    Primitive::U32 => text.parse().ok().map(Value::U32),
+                    Primitive::U64 => text.parse().ok().map(Value::U64),
+                    Primitive::U128 => text.parse().ok().map(Value::U128),
+                    Primitive::Usize => text.parse().ok().map(Value::Usize),
+                    _ => return self.mismatch(expr, ty),
+                };
+                match value {
+                    Some(value) => value,
+                    None => {
+                        return self
+                            .error(expr, format!("`{text}` is out of range for `{primitive}`"))
+                    }
+                }
+            }
+            _ => return self.mismatch(expr, ty),
+        };
+        Some(value)
+    }
+
+    /// Reads `{field: value ...}` as the fields of the declaration `name` or of one of
+    /// its variants.
+    fn fields(
+        &mut self,
+        expr: &ValueExpr,
+        name: &str,
+        fields: &[Field],
+        params: &[Name],
+        args: &[TypeRef],
+        given: &[(Name, ValueExpr)],
+    ) -> Option<Vec<(String, Value)>> {
+        let mut seen = HashSet::new();
+        let mut values = Vec::with_capacity(given.len());
+        for (field_name, field_value) in given {
+            let Some(field) = fields.iter().find(|f| f.name.text == field_name.text) else {
+                self.diagnostics.push(Diagnostic::error(
+                    format!("`{name}` has no field `{field_name}`"),
+                    field_name.range,
+                ));
+                continue;
+            };
+            if !seen.insert(field_name.text.as_str()) {
+                self.diagnostics.push(Diagnostic::error(
+                    format!("field `{field_name}` is given more than once"),
+                    field_name.range,
+                ));
+                continue;
+            }
+            let value = self.value(field_value, &field.ty.substituted(params, args));
+            values.push(value.map(|value| (field.name.text.clone(), value)));
+        }
+        let mut complete = true;
+        for field in fields {
+            if !seen.contains(field.name.text.as_str()) {
+                self.report(expr, format!("missing field `{}`", field.name));
+                complete = false;
+            }
+        }
+        let values = values.into_iter().collect::<Option<Vec<_>>>()?;
+        complete.then_some(values)
+    }
+
+    fn elements(&mut self, elements: &[ValueExpr], ty: &TypeRef) -> Option<Vec<Value>> {
+        let values: Vec<_> = elements.iter().map(|each| self.value(each, ty)).collect();
+        values.into_iter().collect()
+    }
+
+    /// Reads `[element ...]` with one type per element.
+    fn list(&mut self, expr: &ValueExpr, ty: &TypeRef, types: &[TypeRef]) -> Option<Vec<Value>> {
+        let ValueKind::List(elements) = &expr.kind else {
+            return self.mismatch(expr, ty);
+        };
+        if !self.count(expr, ty, types.len(), elements.len()) {
+            return None;
+        }
+        let values: Vec<_> = elements
+            .iter()
+            .zip(types)
+            .map(|(each, ty)| self.value(each, ty))
+            .collect();
+        values.into_iter().collect()
+    }
+
+    /// Reports each value equal to one before it, at the text it was read from.
+    fn distinct<'e>(
+        &mut self,
+        exprs: impl Iterator<Item = &'e ValueExpr>,
+        values: &[&Value],
+        what: &str,
+    ) {
+        for (index, (expr, value)) in exprs.zip(values).enumerate() {
+            if values[..index].contains(value) {
+                self.report(expr, format!("the {what} is given more than once"));
+            }
+        }
+    }
+
+    /// Whether a list of `given` elements has the `expected` number, reporting it if not.
+    fn count(&mut self, expr: &ValueExpr, ty: &TypeRef, expected: usize, given: usize) -> bool {
+        if expected != given {
+            self.report(
+                expr,
+                format!("a value of `{ty}` has {expected} elements, not {given}"),
+            );
+        }
+        expected == given
+    }
+
+    fn mismatch<T>(&mut self, expr: &ValueExpr, ty: &TypeRef) -> Option<T> {
+        self.error(
+            expr,
+            format!("expected a value of `{ty}`, found {}", described(expr)),
+        )
+    }
+
+    /// Reports `message` about `expr`, from which no value is read.
+    fn error<T>(&mut self, expr: &ValueExpr, message: String) -> Option<T> {
+        self.report(expr, message);
+        None
+    }
+
+    fn report(&mut self, expr: &ValueExpr, message: String) {
+        self.diagnostics
+            .push(Diagnostic::error(message, expr.range));
+    }
+}
+
+fn described(expr: &ValueExpr) -> String {
+    match &expr.kind {
+        ValueKind::Bool(b) => format!("`{b}`"),
+        ValueKind::Int(_) => "an integer".to_string(),
+        ValueKind::Float(_) => "a float".to_string(),
+        ValueKind::String(_) => "a string".to_string(),
+        ValueKind::None => "`none`".to_string(),
+        ValueKind::List(_) => "a list".to_string(),
+        ValueKind::Map(_) => "a map".to_string(),
+        ValueKind::Record(_) => "a record".to_string(),
+        ValueKind::Variant { ty, .. } => format!("a `{ty}` variant"),
+        ValueKind::Tagged { tag, .. } => format!("a `{tag}` value"),
+    }
+}
@@ -0,0 +1,184 @@
+// The dynamic value model.
+//
+// A `Value` has one variant per kind of aski type, so it tells apart everything the
+// generated Rust types tell apart: every integer width, `f32` from `f64`, a `char` from a
+// one-character string, bytes from a vec of `u8`, an array from a vec. Values of declared
+// types carry the name of their declaration, in kebab-case as the schema writes it.
+//
+// Sets and maps keep their elements in a list, in the order they were given, as floats
+// leave values without a total order to keep them sorted by. Telling whether elements
+// repeat is the checker's business. `(box T)` is an indirection only, so its values are
+// the values of `T`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use aski_sema::Primitive;
+
+/// A value of some aski type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    Char(char),
+    String(String),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    I128(i128),
+    Isize(isize),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    Usize(usize),
+    F32(f32),
+    F64(f64),
+    Unit,
+    Uuid(Uuid),
+    Bytes(Vec<u8>),
+    Option(Option<Box<Value>>),
+    Result(Result<Box<Value>, Box<Value>>),
+    Vec(Vec<Value>),
+    Set(Vec<Value>),
+    Map(Vec<(Value, Value)>),
+    Array(Vec<Value>),
+    /// A value of a tuple type, `[i32 string]`.
+    Tuple(Vec<Value>),
+    /// A value of a `newtype` declaration.
+    Newtype {
+        ty: String,
+        value: Box<Value>,
+    },
+    /// A value of a `record` declaration, with its fields by name.
+    Record {
+        ty: String,
+        fields: Vec<(String, Value)>,
+    },
+    /// A value of a `tuple` declaration.
+    TupleStruct {
+        ty: String,
+        fields: Vec<Value>,
+    },
+    /// A variant of an `enum` declaration.
+    Variant {
+        ty: String,
+        variant: String,
+        data: VariantData,
+    },
+}
+
+/// What an enum variant holds, in the shape its declaration gives it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum VariantData {
+    Unit,
+    Newtype(Box<Value>),
+    Tuple(Vec<Value>),
+    Struct(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// The primitive this value is a value of, if it is one.
+    pub fn primitive(&self) -> Option<Primitive> {
+        let primitive = match self {
+            Value::Bool(_) => Primitive::Bool,
+            Value::Char(_) => Primitive::Char,
+            Value::String(_) => Primitive::String,
+            Value::I8(_) => Primitive::I8,
+            Value::I16(_) => Primitive::I16,
+            Value::I32(_) => Primitive::I32,
+            Value::I64(_) => Primitive::I64,
+            Value::I128(_) => Primitive::I128,
+            Value::Isize(_) => Primitive::Isize,
+            Value::U8(_) => Primitive::U8,
+            Value::U16(_) => Primitive::U16,
+            Value::U32(_) => Primitive::U32,
+            Value::U64(_) => Primitive::U64,
+            Value::U128(_) => Primitive::U128,
+            Value::Usize(_) => Primitive::Usize,
+            Value::F32(_) => Primitive::F32,
+            Value::F64(_) => Primitive::F64,
+            Value::Unit => Primitive::Unit,
+            Value::Uuid(_) => Primitive::Uuid,
+            Value::Bytes(_) => Primitive::Bytes,
+            _ => return None,
+        };
+        Some(primitive)
+    }
+
+    /// What this value is, for messages: "a value of `u8`", "a vec", "record `point`".
+    pub fn describe(&self) -> String {
+        if let Some(primitive) = self.primitive() {
+            return format!("a value of `{primitive}`");
+        }
+        let kind = match self {
+            Value::Option(_) => "an option",
+            Value::Result(_) => "a result",
+            Value::Vec(_) => "a vec",
+            Value::Set(_) => "a set",
+            Value::Map(_) => "a map",
+            Value::Array(_) => "an array",
+            Value::Tuple(_) => "a tuple",
+            Value::Newtype { ty, .. } => return format!("newtype `{ty}`"),
+            Value::Record { ty, .. } => return format!("record `{ty}`"),
+            Value::TupleStruct { ty, .. } => return format!("tuple `{ty}`"),
+            Value::Variant { ty, variant, .. } => return format!("variant `{variant}` of `{ty}`"),
+            _ => unreachable!("primitives are described above"),
+        };
+        kind.to_string()
+    }
+}
+
+/// A uuid, as its 128 bits. It reads and displays in the hyphenated form,
+/// `67e55044-10b1-426f-9247-bb680e5fe0c8`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Uuid(pub u128);
+
+/// The groups of hex digits of a hyphenated uuid.
+const UUID_GROUPS: [usize; 5] = [8, 4, 4, 4, 12];
+
+impl fmt::Display for Uuid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let hex = format!("{:032x}", self.0);
+        let mut start = 0;
+        for (index, len) in UUID_GROUPS.into_iter().enumerate() {
+            if index > 0 {
+                f.write_str("-")?;
+            }
+            f.write_str(&hex[start..start + len])?;
+            start += len;
+        }
+        Ok(())
+    }
+}
+
+/// Text that is not a uuid in the hyphenated form.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidUuid(pub String);
+
+impl fmt::Display for InvalidUuid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` is not a uuid", self.0)
+    }
+}
+
+impl std::error::Error for InvalidUuid {}
+
+impl FromStr for Uuid {
+    type Err = InvalidUuid;
+
+    fn from_str(text: &str) -> Result<Uuid, InvalidUuid> {
+        let groups: Vec<&str> = text.split('-').collect();
+        let hyphenated = groups.len() == UUID_GROUPS.len()
+            && groups.iter().zip(UUID_GROUPS).all(|(group, len)| {
+                group.len() == len && group.bytes().all(|b| b.is_ascii_hexdigit())
+            });
+        if !hyphenated {
+            return Err(InvalidUuid(text.to_string()));
+        }
+        u128::from_str_radix(&groups.concat(), 16)
+            .map(Uuid)
+            .map_err(|_| InvalidUuid(text.to_string()))
+    }
+}
@@ -0,0 +1,309 @@
+//! Values are checked against the declarations of a schema, all the way down.
+
+use aski_sema::{lower_source_file, Schema};
+use aski_syntax::parse;
+use aski_value::{check_value, Uuid, Value, VariantData};
+
+const ALL_TYPES: &str = include_str!("../../../encoder/drafts/all-types.aski");
+
+fn all_types_schema() -> Schema {
+    let parse = parse(ALL_TYPES);
+    assert_eq!(parse.errors(), &[]);
+    let (schema, diagnostics) = lower_source_file(&parse.tree());
+    assert_eq!(diagnostics, &[]);
+    schema
+}
+
+fn string(text: &str) -> Value {
+    Value::String(text.to_string())
+}
+
+fn user_id() -> Value {
+    Value::Newtype {
+        ty: "user-id".to_string(),
+        value: Box::new(Value::Uuid(
+            "67e55044-10b1-426f-9247-bb680e5fe0c8".parse().unwrap(),
+        )),
+    }
+}
+
+fn record(ty: &str, fields: Vec<(&str, Value)>) -> Value {
+    Value::Record {
+        ty: ty.to_string(),
+        fields: fields
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect(),
+    }
+}
+
+fn variant(ty: &str, variant: &str, data: VariantData) -> Value {
+    Value::Variant {
+        ty: ty.to_string(),
+        variant: variant.to_string(),
+        data,
+    }
+}
+
+/// A value of `all-types` with every field filled in.
+fn all_types() -> Vec<(&'static str, Value)> {
+    vec![
+        ("bool-value", Value::Bool(true)),
+        ("char-value", Value::Char('λ')),
+        ("string-value", string("hello")),
+        ("i8-value", Value::I8(-8)),
+        ("i16-value", Value::I16(-16)),
+        ("i32-value", Value::I32(-32)),
+        ("i64-value", Value::I64(-64)),
+        ("i128-value", Value::I128(i128::MIN)),
+        ("isize-value", Value::Isize(-1)),
+        ("u8-value", Value::U8(8)),
+        ("u16-value", Value::U16(16)),
+        ("u32-value", Value::U32(32)),
+        ("u64-value", Value::U64(64)),
+        ("u128-value", Value::U128(u128::MAX)),
+        ("usize-value", Value::Usize(1)),
+        ("f32-value", Value::F32(1.5)),
+        ("f64-value", Value::F64(-2.25)),
+        (
+            "maybe-i64-value",
+            Value::Option(Some(Box::new(Value::I64(7)))),
+        ),
+        (
+            "outcome-string-or-error-code",
+            Value::Result(Err(Box::new(variant(
+                "error-code",
+                "invalid",
+                VariantData::Newtype(Box::new(string("bad"))),
+            )))),
+        ),
+        ("string-vector", Value::Vec(vec![string("a"), string("b")])),
+        (
+            "mixed-tuple",
+            Value::Tuple(vec![Value::I32(1), string("one"), Value::Bool(false)]),
+        ),
+        (
+            "u16-array-len-3",
+            Value::Array(vec![Value::U16(1), Value::U16(2), Value::U16(3)]),
+        ),
+        ("string-set", Value::Set(vec![string("x"), string("y")])),
+        (
+            "string-to-u32-map",
+            Value::Map(vec![(string("one"), Value::U32(1))]),
+        ),
+        (
+            "user-id-to-i64-map",
+            Value::Map(vec![(user_id(), Value::I64(-1))]),
+        ),
+        ("user-id", user_id()),
+        (
+            "blob-bytes",
+            Value::Newtype {
+                ty: "blob".to_string(),
+                value: Box::new(Value::Bytes(vec![0, 255])),
+            },
+        ),
+        (
+            "wrapped-pair",
+            Value::Newtype {
+                ty: "wrapped".to_string(),
+                value: Box::new(Value::TupleStruct {
+                    ty: "pair".to_string(),
+                    fields: vec![Value::I32(1), Value::I32(2)],
+                }),
+            },
+        ),
+        (
+            "shape",
+            variant(
+                "shape",
+                "circle",
+                VariantData::Struct(vec![("r".to_string(), Value::F64(1.5))]),
+            ),
+        ),
+        (
+            "message",
+            variant(
+                "message",
+                "batch",
+                VariantData::Newtype(Box::new(Value::Vec(vec![
+                    variant("message", "ping", VariantData::Unit),
+                    variant(
+                        "message",
+                        "kv",
+                        VariantData::Newtype(Box::new(Value::Map(vec![(
+                            string("k"),
+                            string("v"),
+                        )]))),
+                    ),
+                ]))),
+            ),
+        ),
+        (
+            "status",
+            record(
+                "status",
+                vec![
+                    ("ok", Value::Bool(true)),
+                    ("code", Value::Option(None)),
+                    ("note", Value::Option(Some(Box::new(string("fine"))))),
+                ],
+            ),
+        ),
+        ("unit-value", Value::Unit),
+        ("unit-struct-value", record("unit-struct", Vec::new())),
+    ]
+}
+
+/// The messages of checking `all-types` with `change` applied to its fields.
+fn check_all_types(change: impl FnOnce(&mut Vec<(&'static str, Value)>)) -> Vec<String> {
+    let mut fields = all_types();
+    change(&mut fields);
+    check_value(
+        &all_types_schema(),
+        "all-types",
+        &record("all-types", fields),
+    )
+    .iter()
+    .map(ToString::to_string)
+    .collect()
+}
+
+fn set(fields: &mut [(&'static str, Value)], name: &str, value: Value) {
+    let field = fields.iter_mut().find(|(field, _)| *field == name).unwrap();
+    field.1 = value;
+}
+
+#[test]
+fn every_field_of_all_types_checks() {
+    assert_eq!(check_all_types(|_| {}), Vec::<String>::new());
+}
+
+#[test]
+fn primitives_keep_their_width() {
+    let errors = check_all_types(|fields| {
+        set(fields, "u8-value", Value::U16(8));
+        set(fields, "f32-value", Value::F64(1.5));
+        set(fields, "char-value", string("λ"));
+        set(fields, "blob-bytes", Value::Bytes(vec![1]));
+    });
+    assert_eq!(
+        errors,
+        [
+            "all-types.char-value: expected a value of `char`, found a value of `string`",
+            "all-types.u8-value: expected a value of `u8`, found a value of `u16`",
+            "all-types.f32-value: expected a value of `f32`, found a value of `f64`",
+            "all-types.blob-bytes: expected a value of `blob`, found a value of `bytes`",
+        ]
+    );
+}
+
+#[test]
+fn containers_are_checked_element_by_element() {
+    let errors = check_all_types(|fields| {
+        set(
+            fields,
+            "string-vector",
+            Value::Vec(vec![string("a"), Value::I32(1)]),
+        );
+        set(
+            fields,
+            "string-set",
+            Value::Set(vec![string("x"), string("x")]),
+        );
+        set(
+            fields,
+            "user-id-to-i64-map",
+            Value::Map(vec![(string("nope"), Value::I64(1))]),
+        );
+        set(fields, "u16-array-len-3", Value::Array(vec![Value::U16(1)]));
+        set(fields, "mixed-tuple", Value::Vec(Vec::new()));
+    });
+    assert_eq!(
+        errors,
+        [
+            "all-types.string-vector[1]: expected a value of `string`, found a value of `i32`",
+            "all-types.mixed-tuple: expected a value of `[i32 string bool]`, found a vec",
+            "all-types.u16-array-len-3: a value of `(array 3 u16)` has 3 elements, not 1",
+            "all-types.string-set[1]: the element is given more than once",
+            "all-types.user-id-to-i64-map[0]: expected a value of `user-id`, found a value of `string`",
+        ]
+    );
+}
+
+#[test]
+fn declarations_are_checked_by_their_fields_and_variants() {
+    let errors = check_all_types(|fields| {
+        fields.retain(|(name, _)| *name != "unit-value");
+        fields.push(("extra", Value::Unit));
+        set(
+            fields,
+            "shape",
+            variant("shape", "hexagon", VariantData::Unit),
+        );
+        set(
+            fields,
+            "message",
+            variant("message", "text", VariantData::Unit),
+        );
+        set(
+            fields,
+            "wrapped-pair",
+            Value::Newtype {
+                ty: "wrapped".to_string(),
+                value: Box::new(Value::TupleStruct {
+                    ty: "pair".to_string(),
+                    fields: vec![Value::I32(1), Value::I64(2)],
+                }),
+            },
+        );
+        set(
+            fields,
+            "status",
+            record("status", vec![("ok", Value::Bool(true))]),
+        );
+        set(fields, "user-id", record("user-id", Vec::new()));
+    });
+    assert_eq!(
+        errors,
+        [
+            "all-types.user-id: expected a value of `user-id`, found record `user-id`",
+            "all-types.wrapped-pair[1]: expected a value of `i32`, found a value of `i64`",
+            "all-types.shape: `shape` has no variant `hexagon`",
+            "all-types.message: variant `text` of `message` holds one value",
+            "all-types.status: missing field `code`",
+            "all-types.status: missing field `note`",
+            "all-types: `all-types` has no field `extra`",
+            "all-types: missing field `unit-value`",
+        ]
+    );
+}
+
+#[test]
+fn only_declared_non_generic_types_are_checked_against() {
+    let schema = all_types_schema();
+    let messages = |name: &str| -> Vec<String> {
+        check_value(&schema, name, &Value::Unit)
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    };
+    assert_eq!(messages("nope"), ["nope: cannot find type `nope`"]);
+    assert_eq!(
+        messages("wrapped"),
+        ["wrapped: `wrapped` is generic; check values of a declaration that gives it arguments"]
+    );
+}
+
+#[test]
+fn uuids_read_and_display_hyphenated() {
+    let text = "67e55044-10b1-426f-9247-bb680e5fe0c8";
+    let uuid: Uuid = text.parse().unwrap();
+    assert_eq!(uuid, Uuid(0x67e5504410b1426f9247bb680e5fe0c8));
+    assert_eq!(uuid.to_string(), text);
+    assert_eq!(Uuid(1).to_string(), "00000000-0000-0000-0000-000000000001");
+    assert!("67e5504410b1426f9247bb680e5fe0c8".parse::<Uuid>().is_err());
+    assert!("67e55044-10b1-426f-9247-bb680e5fe0cg"
+        .parse::<Uuid>()
+        .is_err());
+}
@@ -0,0 +1,353 @@
+//! Values print in aski's value notation and read back as values of a declaration.
+
+use aski_sema::{lower_source_file, Schema};
+use aski_syntax::parse;
+use aski_value::{read_value, Value, VariantData};
+
+const ALL_TYPES: &str = include_str!("../../../encoder/drafts/all-types.aski");
+
+/// Every field of `all-types`, written as it would be by hand.
+const ALL_TYPES_VALUE: &str = r##"
+{ bool-value: true
+  char-value: "λ"
+  string-value: "hello"
+
+  i8-value: -8
+  i16-value: -16
+  i32-value: -32
+  i64-value: -64
+  i128-value: -170141183460469231731687303715884105728
+  isize-value: -1
+
+  u8-value: 8
+  u16-value: 16
+  u32-value: 32
+  u64-value: 64
+  u128-value: 340282366920938463463374607431768211455
+  usize-value: 1
+
+  f32-value: 1.5
+  f64-value: -2.25
+
+  maybe-i64-value: 7
+  outcome-string-or-error-code: #err (error-code invalid "bad")
+
+  string-vector: ["a" "b"]
+  mixed-tuple: [1 "one" false]
+  u16-array-len-3: [1 2 3]
+
+  string-set: ["x" "y"]
+  string-to-u32-map: {"one" 1}
+  user-id-to-i64-map: {#uuid "67e55044-10b1-426f-9247-bb680e5fe0c8" -1}
+
+  user-id: #uuid "67e55044-10b1-426f-9247-bb680e5fe0c8"
+  blob-bytes: #bytes "00ff"
+  wrapped-pair: [1 2]
+
+  shape: (shape circle {r: 1.5})
+  message: (message batch [(message ping) (message kv {"k" "v"})])
+  status: {ok: true code: none note: "fine"}
+
+  unit-value: []
+  unit-struct-value: {} }
+"##;
+
+fn all_types_schema() -> Schema {
+    let parse = parse(ALL_TYPES);
+    assert_eq!(parse.errors(), &[]);
+    let (schema, diagnostics) = lower_source_file(&parse.tree());
+    assert_eq!(diagnostics, &[]);
+    schema
+}
+
+fn string(text: &str) -> Value {
+    Value::String(text.to_string())
+}
+
+fn user_id() -> Value {
+    Value::Newtype {
+        ty: "user-id".to_string(),
+        value: Box::new(Value::Uuid(
+            "67e55044-10b1-426f-9247-bb680e5fe0c8".parse().unwrap(),
+        )),
+    }
+}
+
+fn record(ty: &str, fields: Vec<(&str, Value)>) -> Value {
+    Value::Record {
+        ty: ty.to_string(),
+        fields: fields
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect(),
+    }
+}
+
+fn variant(ty: &str, variant: &str, data: VariantData) -> Value {
+    Value::Variant {
+        ty: ty.to_string(),
+        variant: variant.to_string(),
+        data,
+    }
+}
+
+/// A value of `all-types` with every field filled in, in declaration order.
+fn all_types() -> Value {
+    record(
+        "all-types",
+        vec![
+            ("bool-value", Value::Bool(true)),
+            ("char-value", Value::Char('λ')),
+            ("string-value", string("hello")),
+            ("i8-value", Value::I8(-8)),
+            ("i16-value", Value::I16(-16)),
+            ("i32-value", Value::I32(-32)),
+            ("i64-value", Value::I64(-64)),
+            ("i128-value", Value::I128(i128::MIN)),
+            ("isize-value", Value::Isize(-1)),
+            ("u8-value", Value::U8(8)),
+            ("u16-value", Value::U16(16)),
+            ("u32-value", Value::U32(32)),
+            ("u64-value", Value::U64(64)),
+            ("u128-value", Value::U128(u128::MAX)),
+            ("usize-value", Value::Usize(1)),
+            ("f32-value", Value::F32(1.5)),
+            ("f64-value", Value::F64(-2.25)),
+            (
+                "maybe-i64-value",
+                Value::Option(Some(Box::new(Value::I64(7)))),
+            ),
+            (
+                "outcome-string-or-error-code",
+                Value::Result(Err(Box::new(variant(
+                    "error-code",
+                    "invalid",
+                    VariantData::Newtype(Box::new(string("bad"))),
+                )))),
+            ),
+            ("string-vector", Value::Vec(vec![string("a"), string("b")])),
+            (
+                "mixed-tuple",
+                Value::Tuple(vec![Value::I32(1), string("one"), Value::Bool(false)]),
+            ),
+            (
+                "u16-array-len-3",
+                Value::Array(vec![Value::U16(1), Value::U16(2), Value::U16(3)]),
+            ),
+            ("string-set", Value::Set(vec![string("x"), string("y")])),
+            (
+                "string-to-u32-map",
+                Value::Map(vec![(string("one"), Value::U32(1))]),
+            ),
+            (
+                "user-id-to-i64-map",
+                Value::Map(vec![(user_id(), Value::I64(-1))]),
+            ),
+            ("user-id", user_id()),
+            (
+                "blob-bytes",
+                Value::Newtype {
+                    ty: "blob".to_string(),
+                    value: Box::new(Value::Bytes(vec![0, 255])),
+                },
+            ),
+            (
+                "wrapped-pair",
+                Value::Newtype {
+                    ty: "wrapped".to_string(),
+                    value: Box::new(Value::TupleStruct {
+                        ty: "pair".to_string(),
+                        fields: vec![Value::I32(1), Value::I32(2)],
+                    }),
+                },
+            ),
+            (
+                "shape",
+                variant(
+                    "shape",
+                    "circle",
+                    VariantData::Struct(vec![("r".to_string(), Value::F64(1.5))]),
+                ),
+            ),
+            (
+                "message",
+                variant(
+                    "message",
+                    "batch",
+                    VariantData::Newtype(Box::new(Value::Vec(vec![
+                        variant("message", "ping", VariantData::Unit),
+                        variant(
+                            "message",
+                            "kv",
+                            VariantData::Newtype(Box::new(Value::Map(vec![(
+                                string("k"),
+                                string("v"),
+                            )]))),
+                        ),
+                    ]))),
+                ),
+            ),
+            (
+                "status",
+                record(
+                    "status",
+                    vec![
+                        ("ok", Value::Bool(true)),
+                        ("code", Value::Option(None)),
+                        ("note", Value::Option(Some(Box::new(string("fine"))))),
+                    ],
+                ),
+            ),
+            ("unit-value", Value::Unit),
+            ("unit-struct-value", record("unit-struct", Vec::new())),
+        ],
+    )
+}
+
+/// The messages of reading `text` as a value of `name`, which must not read.
+fn errors(schema: &Schema, name: &str, text: &str) -> Vec<String> {
+    read_value(schema, name, text)
+        .unwrap_err()
+        .into_iter()
+        .map(|diagnostic| diagnostic.message)
+        .collect()
+}
+
+#[test]
+fn a_written_value_reads_as_every_field_of_all_types() {
+    let schema = all_types_schema();
+    assert_eq!(
+        read_value(&schema, "all-types", ALL_TYPES_VALUE),
+        Ok(all_types())
+    );
+}
+
+#[test]
+fn all_types_round_trips_through_the_notation() {
+    let schema = all_types_schema();
+    let value = all_types();
+    for text in [value.to_string(), format!("{value:#}")] {
+        assert_eq!(read_value(&schema, "all-types", &text), Ok(value.clone()));
+    }
+}
+
+#[test]
+fn values_print_as_they_are_written() {
+    let Value::Record { fields, .. } = all_types() else {
+        unreachable!("all-types is a record");
+    };
+    let printed = |name: &str| {
+        let (_, value) = fields.iter().find(|(field, _)| field == name).unwrap();
+        value.to_string()
+    };
+    assert_eq!(printed("char-value"), "\"λ\"");
+    assert_eq!(printed("maybe-i64-value"), "7");
+    assert_eq!(
+        printed("outcome-string-or-error-code"),
+        "#err (error-code invalid \"bad\")"
+    );
+    assert_eq!(printed("u16-array-len-3"), "[1 2 3]");
+    assert_eq!(
+        printed("user-id-to-i64-map"),
+        "{#uuid \"67e55044-10b1-426f-9247-bb680e5fe0c8\" -1}"
+    );
+    assert_eq!(printed("blob-bytes"), "#bytes \"00ff\"");
+    assert_eq!(printed("wrapped-pair"), "[1 2]");
+    assert_eq!(printed("shape"), "(shape circle {r: 1.5})");
+    assert_eq!(
+        printed("message"),
+        "(message batch [(message ping) (message kv {\"k\" \"v\"})])"
+    );
+    assert_eq!(printed("status"), "{ok: true code: none note: \"fine\"}");
+    assert_eq!(printed("unit-value"), "[]");
+    assert_eq!(printed("unit-struct-value"), "{}");
+    assert_eq!(
+        format!("{:#}", record("status", vec![("ok", Value::Bool(true))])),
+        "{\n  ok: true\n}"
+    );
+    assert_eq!(string("a\"b\\c\nd").to_string(), r#""a\"b\\c\nd""#);
+    assert_eq!(Value::F64(f64::NEG_INFINITY).to_string(), "-inf");
+    assert_eq!(Value::F32(1e-7).to_string(), "1e-7");
+}
+
+#[test]
+fn the_declaration_decides_how_a_value_reads() {
+    let schema = all_types_schema();
+    assert_eq!(
+        read_value(&schema, "shape", "(shape rect [1 2.5])"),
+        Ok(variant(
+            "shape",
+            "rect",
+            VariantData::Tuple(vec![Value::F64(1.0), Value::F64(2.5)])
+        ))
+    );
+    assert_eq!(
+        read_value(
+            &schema,
+            "user-id",
+            r#""67e55044-10b1-426f-9247-bb680e5fe0c8""#
+        ),
+        Ok(user_id())
+    );
+    assert_eq!(
+        read_value(&schema, "blob", "[0 255]"),
+        Ok(Value::Newtype {
+            ty: "blob".to_string(),
+            value: Box::new(Value::Bytes(vec![0, 255])),
+        })
+    );
+    assert_eq!(
+        read_value(&schema, "shape", "(shape circle {r: inf})"),
+        Ok(variant(
+            "shape",
+            "circle",
+            VariantData::Struct(vec![("r".to_string(), Value::F64(f64::INFINITY))])
+        ))
+    );
+}
+
+#[test]
+fn values_that_do_not_fit_are_reported() {
+    let schema = all_types_schema();
+    assert_eq!(
+        errors(&schema, "pair", "[1 3000000000]"),
+        ["`3000000000` is out of range for `i32`"]
+    );
+    assert_eq!(
+        errors(&schema, "status", r#"{ok: 1 note: "x" note: none}"#),
+        [
+            "expected a value of `bool`, found an integer",
+            "field `note` is given more than once",
+            "missing field `code`",
+        ]
+    );
+    assert_eq!(
+        errors(&schema, "shape", "(shape hexagon)"),
+        ["`shape` has no variant `hexagon`"]
+    );
+    assert_eq!(
+        errors(&schema, "message", r#"(message kv {"k" "v" "k" "w"})"#),
+        ["the key is given more than once"]
+    );
+    assert_eq!(
+        errors(&schema, "user-id", r##"#bytes "00""##),
+        ["expected a value of `uuid`, found a `#bytes` value"]
+    );
+    assert_eq!(
+        errors(&schema, "blob", r##"#bytes "0""##),
+        ["`#bytes` takes a string of hex digits, two per byte, like \"00ff\""]
+    );
+    assert_eq!(
+        errors(&schema, "error-code", "(error-code not-found) 1"),
+        ["expected the end of input after the value"]
+    );
+    assert_eq!(
+        errors(&schema, "error-code", "#guid 1"),
+        ["unknown tag `#guid`; the tags are `#uuid`, `#bytes`, `#ok`, `#err`"]
+    );
+    assert_eq!(errors(&schema, "nope", "1"), ["cannot find type `nope`"]);
+    assert_eq!(
+        errors(&schema, "wrapped", "1"),
+        ["`wrapped` is generic; read values of a declaration that gives it arguments"]
+    );
+}
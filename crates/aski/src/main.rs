@@ -0,0 +1,117 @@
+//! `aski fmt [--check] [<file.aski>...]` lays aski documents out canonically, in place, or
+//! from stdin to stdout when no file is given. With `--check` nothing is written, and the
+//! files that are not laid out canonically are listed instead.
+//!
+//! `aski migrate --to <capitalized|aski/v1> [<file.aski>...]` rewrites aski documents in
+//! the given dialect the same way.
+
+use std::io::{Read, Write};
+use std::process::ExitCode;
+
+use aski_syntax::{format, to_aski_v1, to_capitalized, Diagnostic};
+
+const USAGE: &str = "usage: aski fmt [--check] [<file.aski>...]
+       aski migrate --to <capitalized|aski/v1> [<file.aski>...]";
+
+/// What a command does to the text of a document.
+type Rewrite = fn(&str) -> Result<String, Vec<Diagnostic>>;
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    let command = args.next();
+    let migrate = command.as_deref() == Some("migrate");
+    let (mut check, mut dialect, mut paths) = (false, None, Vec::new());
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--check" if !migrate => check = true,
+            "--to" if migrate => dialect = args.next(),
+            flag if flag.starts_with('-') => {
+                eprintln!("unknown option `{flag}`\n{USAGE}");
+                return ExitCode::FAILURE;
+            }
+            _ => paths.push(arg),
+        }
+    }
+    let rewrite: Rewrite = match (command.as_deref(), dialect.as_deref()) {
+        (Some("fmt"), _) => format,
+        (Some("migrate"), Some("capitalized")) => to_capitalized,
+        (Some("migrate"), Some("aski/v1")) => to_aski_v1,
+        (Some("migrate"), Some(dialect)) => {
+            eprintln!("unknown dialect `{dialect}`, expected `capitalized` or `aski/v1`");
+            return ExitCode::FAILURE;
+        }
+        _ => {
+            eprintln!("{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+    if paths.is_empty() {
+        return rewrite_stdin(rewrite, check);
+    }
+    let mut status = ExitCode::SUCCESS;
+    for path in &paths {
+        if !rewrite_file(rewrite, path, check) {
+            status = ExitCode::FAILURE;
+        }
+    }
+    status
+}
+
+fn rewrite_stdin(rewrite: Rewrite, check: bool) -> ExitCode {
+    let mut text = String::new();
+    if let Err(error) = std::io::stdin().read_to_string(&mut text) {
+        eprintln!("<stdin>: {error}");
+        return ExitCode::FAILURE;
+    }
+    let Some(rewritten) = rewritten(rewrite, "<stdin>", &text) else {
+        return ExitCode::FAILURE;
+    };
+    if check {
+        if rewritten != text {
+            println!("<stdin>");
+            return ExitCode::FAILURE;
+        }
+    } else if let Err(error) = std::io::stdout().write_all(rewritten.as_bytes()) {
+        eprintln!("<stdout>: {error}");
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
+
+/// Rewrites the file at `path`, returning whether it is, or now is, written the way
+/// `rewrite` writes it.
+fn rewrite_file(rewrite: Rewrite, path: &str, check: bool) -> bool {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(error) => {
+            eprintln!("{path}: {error}");
+            return false;
+        }
+    };
+    let Some(rewritten) = rewritten(rewrite, path, &text) else {
+        return false;
+    };
+    if rewritten == text {
+        return true;
+    }
+    if check {
+        println!("{path}");
+        return false;
+    }
+    if let Err(error) = std::fs::write(path, rewritten) {
+        eprintln!("{path}: {error}");
+        return false;
+    }
+    true
+}
+
+/// `text` rewritten, or nothing after reporting why it cannot be.
+fn rewritten(rewrite: Rewrite, path: &str, text: &str) -> Option<String> {
+    rewrite(text)
+        .map_err(|errors| {
+            for error in &errors {
+                eprintln!("{}", error.render(path, text));
+            }
+        })
+        .ok()
+}
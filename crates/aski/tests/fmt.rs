@@ -0,0 +1,65 @@
+//! `aski fmt`, run on files the way it is run from a shell.
+
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+const ALL_TYPES: &str = include_str!("../../../encoder/drafts/all-types.aski");
+
+fn aski(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_aski"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+/// A file holding `text` in a directory of its own.
+fn file(dir: &str, text: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("schema.aski");
+    std::fs::write(&path, text).unwrap();
+    path
+}
+
+#[test]
+fn files_are_formatted_in_place() {
+    let path = file("in-place", ALL_TYPES);
+    let path = path.to_str().unwrap();
+    let check = aski(&["fmt", "--check", path]);
+    assert!(!check.status.success());
+    assert_eq!(
+        String::from_utf8(check.stdout).unwrap(),
+        format!("{path}\n")
+    );
+    assert_eq!(std::fs::read_to_string(path).unwrap(), ALL_TYPES);
+
+    assert!(aski(&["fmt", path]).status.success());
+    let formatted = std::fs::read_to_string(path).unwrap();
+    assert_eq!(Ok(formatted.clone()), aski_syntax::format(ALL_TYPES));
+    assert!(aski(&["fmt", "--check", path]).status.success());
+    assert!(aski(&["fmt", path]).status.success());
+    assert_eq!(std::fs::read_to_string(path).unwrap(), formatted);
+}
+
+#[test]
+fn files_with_errors_are_left_alone() {
+    let text = "(aski/v1\n  (record status { ok: bool code: (? }))\n";
+    let path = file("errors", text);
+    let path = path.to_str().unwrap();
+    let output = aski(&["fmt", path]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.starts_with("error: "), "{stderr}");
+    assert!(stderr.contains(&format!("--> {path}:2:")), "{stderr}");
+    assert!(stderr.contains("2 |   (record status"), "{stderr}");
+    assert_eq!(std::fs::read_to_string(path).unwrap(), text);
+}
+
+#[test]
+fn usage_is_explained() {
+    let output = aski(&["format"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .starts_with("usage: aski fmt"));
+}
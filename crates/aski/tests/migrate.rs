@@ -0,0 +1,71 @@
+//! `aski migrate`, run on files the way it is run from a shell.
+
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+const ALL_TYPES: &str = include_str!("../../../encoder/drafts/all-types.aski");
+
+fn aski(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_aski"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+/// A file holding `text` in a directory of its own.
+fn file(dir: &str, text: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("schema.aski");
+    std::fs::write(&path, text).unwrap();
+    path
+}
+
+#[test]
+fn files_are_migrated_in_place_and_back() {
+    let path = file("migrate", ALL_TYPES);
+    let path = path.to_str().unwrap();
+    assert!(aski(&["migrate", "--to", "capitalized", path])
+        .status
+        .success());
+    let capitalized = std::fs::read_to_string(path).unwrap();
+    assert_eq!(
+        Ok(capitalized.clone()),
+        aski_syntax::to_capitalized(ALL_TYPES)
+    );
+
+    assert!(aski(&["migrate", "--to", "aski/v1", path]).status.success());
+    let v1 = std::fs::read_to_string(path).unwrap();
+    assert_eq!(Ok(v1), aski_syntax::format(ALL_TYPES));
+}
+
+#[test]
+fn files_that_cannot_be_migrated_are_left_alone() {
+    let text = "(aski/v1\n  (tuple single [u8]))\n";
+    let path = file("single", text);
+    let path = path.to_str().unwrap();
+    let output = aski(&["migrate", "--to", "capitalized", path]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.starts_with("error: a tuple of one field would read as a newtype"),
+        "{stderr}"
+    );
+    assert!(stderr.contains(&format!("--> {path}:2:")), "{stderr}");
+    assert_eq!(std::fs::read_to_string(path).unwrap(), text);
+}
+
+#[test]
+fn the_dialect_is_required() {
+    let output = aski(&["migrate", "--to", "edn"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .starts_with("unknown dialect `edn`"));
+
+    let output = aski(&["migrate"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .starts_with("usage: aski fmt"));
+}